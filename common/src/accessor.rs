@@ -0,0 +1,23 @@
+// For SDKs generated with sdk_gen's gen_accessors feature, whose accessors
+// find their fields by name at runtime instead of trusting the offsets the
+// SDK was generated with. Those keep working when an update shifts fields
+// around without renaming them.
+
+use crate::{GUObjectArray, UStruct};
+
+// The offset of `property` in `structure`, a full name like
+// "Class /Script/FSD.Item". `fallback` is the offset the SDK was generated
+// with, for when either can't be found.
+pub unsafe fn offset(structure: &'static str, property: &str, fallback: usize) -> usize {
+    let Ok(structure) = (*GUObjectArray).find(structure) else {
+        return fallback;
+    };
+
+    let property = (*structure.cast::<UStruct>()).find_property(property);
+
+    if property.is_null() {
+        fallback
+    } else {
+        (*property).Offset as usize
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    FName, FSoftObjectPath, FSoftObjectPtr, FString, FWeakObjectPtr, GUObjectArray, TArray,
+    TPersistentObjectPtr, TScriptInterface, TSoftClassPtr, TSoftObjectPtr, UClass, UFunction,
+    UObject,
+};
+
+use core::ffi::c_void;
+use core::ptr;
+
+static mut KISMET_SYSTEM_LIBRARY: *mut UObject = ptr::null_mut();
+static mut LOAD_ASSET_BLOCKING: *mut UFunction = ptr::null_mut();
+static mut LOAD_CLASS_ASSET_BLOCKING: *mut UFunction = ptr::null_mut();
+
+static mut ASSET_REGISTRY_HELPERS: *mut UObject = ptr::null_mut();
+static mut GET_ASSET_REGISTRY: *mut UFunction = ptr::null_mut();
+static mut GET_ALL_ASSETS: *mut UFunction = ptr::null_mut();
+static mut GET_ASSETS_BY_CLASS: *mut UFunction = ptr::null_mut();
+
+// Reused between registry queries so that we don't leak a new engine
+// allocation per query.
+static mut ASSETS: TArray<FAssetData> = TArray::new();
+
+impl FSoftObjectPath {
+    pub fn is_null(&self) -> bool {
+        self.AssetPathName.is_none()
+    }
+
+    // Looks for the asset among the loaded objects. Sub-object paths aren't
+    // supported, so this resolves to the top-level asset.
+    pub unsafe fn resolve(&self) -> *mut UObject {
+        if self.is_null() {
+            ptr::null_mut()
+        } else {
+            (*GUObjectArray).find_by_path(self.AssetPathName.text())
+        }
+    }
+}
+
+impl FSoftObjectPtr {
+    // Returns the object if it's already loaded. Never loads.
+    pub unsafe fn get(&self) -> *mut UObject {
+        let object = self.base.WeakPtr.get();
+
+        if object.is_null() {
+            self.base.ObjectID.resolve()
+        } else {
+            object
+        }
+    }
+
+    // Returns the object, synchronously loading it if needed. Must be called
+    // from the game thread.
+    pub unsafe fn load(&self) -> *mut UObject {
+        let object = self.get();
+
+        if object.is_null() && !self.base.ObjectID.is_null() {
+            load_blocking(
+                self,
+                &mut LOAD_ASSET_BLOCKING,
+                "Function /Script/Engine.KismetSystemLibrary.LoadAsset_Blocking",
+            )
+        } else {
+            object
+        }
+    }
+}
+
+// The asset at `path`, like "/Game/Audio/UI/Cue_Click.Cue_Click", loading it
+// if needed. Null if there's no such asset. Must be called from the game
+// thread.
+pub unsafe fn load_by_path(path: &str) -> *mut UObject {
+    let object = (*GUObjectArray).find_by_path(path);
+
+    if !object.is_null() {
+        return object;
+    }
+
+    // Every asset's path is in the name pool, since the asset registry has
+    // it.
+    let Some(name) = FName::from_existing(path) else {
+        return ptr::null_mut();
+    };
+
+    let asset = FSoftObjectPtr {
+        base: TPersistentObjectPtr {
+            WeakPtr: FWeakObjectPtr::NULL,
+            TagAtLastTest: 0,
+            ObjectID: FSoftObjectPath {
+                AssetPathName: name,
+                SubPathString: FString::new(),
+            },
+        },
+    };
+
+    asset.load()
+}
+
+impl<T> TSoftObjectPtr<T> {
+    pub unsafe fn get(&self) -> *mut T {
+        self.SoftObjectPtr.get().cast()
+    }
+
+    pub unsafe fn load(&self) -> *mut T {
+        self.SoftObjectPtr.load().cast()
+    }
+}
+
+impl<T> TSoftClassPtr<T> {
+    pub unsafe fn get(&self) -> *mut UClass {
+        self.SoftObjectPtr.get().cast()
+    }
+
+    pub unsafe fn load(&self) -> *mut UClass {
+        let class = self.get();
+
+        if class.is_null() && !self.SoftObjectPtr.base.ObjectID.is_null() {
+            load_blocking(
+                &self.SoftObjectPtr,
+                &mut LOAD_CLASS_ASSET_BLOCKING,
+                "Function /Script/Engine.KismetSystemLibrary.LoadClassAsset_Blocking",
+            )
+            .cast()
+        } else {
+            class
+        }
+    }
+}
+
+unsafe fn load_blocking(
+    asset: &FSoftObjectPtr,
+    function: &mut *mut UFunction,
+    function_name: &'static str,
+) -> *mut UObject {
+    #[repr(C)]
+    struct Parameters {
+        Asset: FSoftObjectPtr,
+        ReturnValue: *mut UObject,
+    }
+
+    let library = find_cached(
+        &mut KISMET_SYSTEM_LIBRARY,
+        "KismetSystemLibrary /Script/Engine.Default__KismetSystemLibrary",
+    );
+
+    if library.is_null() || find_cached(function, function_name).is_null() {
+        return ptr::null_mut();
+    }
+
+    // The engine only reads the parameter, so a shallow copy that shares the
+    // path's string buffer is fine.
+    let mut parameters = Parameters {
+        Asset: ptr::read(asset),
+        ReturnValue: ptr::null_mut(),
+    };
+
+    UObject::process_event(
+        library,
+        *function,
+        (&mut parameters as *mut Parameters).cast::<c_void>(),
+    );
+
+    parameters.ReturnValue
+}
+
+// FAssetData describes an asset on disk, whether or not it's loaded.
+#[repr(C)]
+pub struct FAssetData {
+    pub ObjectPath: FName,
+    pub PackageName: FName,
+    pub PackagePath: FName,
+    pub AssetName: FName,
+    pub AssetClass: FName,
+    TagsAndValues: *const c_void,
+    ChunkIDs: TArray<i32>,
+    pub PackageFlags: u32,
+}
+
+#[repr(C)]
+struct IAssetRegistry {
+    vtable: *const c_void,
+}
+
+// Every asset the registry knows about. The returned slice is only valid until
+// the next registry query.
+pub unsafe fn all_assets(include_only_on_disk_assets: bool) -> &'static [FAssetData] {
+    #[repr(C)]
+    struct Parameters {
+        OutAssetData: TArray<FAssetData>,
+        bIncludeOnlyOnDiskAssets: bool,
+        ReturnValue: bool,
+    }
+
+    // Forget the previous query's results. The engine appends to the array.
+    ASSETS.len = 0;
+
+    let mut parameters = Parameters {
+        OutAssetData: ptr::read(ptr::addr_of!(ASSETS)),
+        bIncludeOnlyOnDiskAssets: include_only_on_disk_assets,
+        ReturnValue: false,
+    };
+
+    call_registry(
+        &mut GET_ALL_ASSETS,
+        "Function /Script/AssetRegistry.AssetRegistry.GetAllAssets",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    // The engine may have reallocated the array.
+    ASSETS = parameters.OutAssetData;
+    &*ptr::addr_of!(ASSETS)
+}
+
+// Every asset of `class`, without loading any of them. The returned slice is
+// only valid until the next registry query.
+pub unsafe fn assets_by_class(
+    class: *const UClass,
+    search_sub_classes: bool,
+) -> &'static [FAssetData] {
+    #[repr(C)]
+    struct Parameters {
+        ClassName: FName,
+        OutAssetData: TArray<FAssetData>,
+        bSearchSubClasses: bool,
+        ReturnValue: bool,
+    }
+
+    ASSETS.len = 0;
+
+    let mut parameters = Parameters {
+        ClassName: (*class.cast::<UObject>()).NamePrivate,
+        OutAssetData: ptr::read(ptr::addr_of!(ASSETS)),
+        bSearchSubClasses: search_sub_classes,
+        ReturnValue: false,
+    };
+
+    call_registry(
+        &mut GET_ASSETS_BY_CLASS,
+        "Function /Script/AssetRegistry.AssetRegistry.GetAssetsByClass",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    ASSETS = parameters.OutAssetData;
+    &*ptr::addr_of!(ASSETS)
+}
+
+unsafe fn call_registry(
+    function: &mut *mut UFunction,
+    function_name: &'static str,
+    parameters: *mut c_void,
+) {
+    let registry = asset_registry();
+    let function = find_cached(function, function_name);
+
+    if !registry.is_null() && !function.is_null() {
+        UObject::process_event(registry, function, parameters);
+    }
+}
+
+unsafe fn asset_registry() -> *mut UObject {
+    #[repr(C)]
+    struct Parameters {
+        ReturnValue: TScriptInterface<IAssetRegistry>,
+    }
+
+    let helpers = find_cached(
+        &mut ASSET_REGISTRY_HELPERS,
+        "AssetRegistryHelpers /Script/AssetRegistry.Default__AssetRegistryHelpers",
+    );
+
+    let function = find_cached(
+        &mut GET_ASSET_REGISTRY,
+        "Function /Script/AssetRegistry.AssetRegistryHelpers.GetAssetRegistry",
+    );
+
+    if helpers.is_null() || function.is_null() {
+        return ptr::null_mut();
+    }
+
+    let mut parameters = Parameters {
+        ReturnValue: TScriptInterface {
+            ObjectPointer: ptr::null(),
+            InterfacePointer: ptr::null(),
+        },
+    };
+
+    UObject::process_event(
+        helpers,
+        function,
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    parameters.ReturnValue.ObjectPointer.cast_mut()
+}
+
+// Finds `name` the first time and remembers it in `cache`. Logs a warning and
+// returns null if it isn't loaded yet, so that the next call tries again.
+pub unsafe fn find_cached<T>(cache: &mut *mut T, name: &'static str) -> *mut T {
+    if cache.is_null() {
+        *cache = (*GUObjectArray)
+            .find(name)
+            .map(|o| o.cast())
+            .unwrap_or(ptr::null_mut());
+
+        if cache.is_null() {
+            crate::log!("warning: unable to find {}", name);
+        }
+    }
+
+    *cache
+}
@@ -0,0 +1,109 @@
+// Pointer chains, the classic way to find a value that moves around:
+//
+// FSD-Win64-Shipping.exe+0x1234 -> +0x18 -> +0x40 as f32
+//
+// reads the pointer at the module's base plus 0x1234, then the pointer 0x18
+// past where that points, and finds an f32 0x40 past where the last one
+// points. Offsets can be negative. For values we haven't found through
+// reflection or a pattern yet.
+
+use crate::scan::{self, Value, ValueType};
+use crate::win;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    MissingType,
+    BadType,
+    MissingModule,
+    BadOffset,
+}
+
+pub struct Chain {
+    module: String,
+    // From the module's base.
+    base_offset: isize,
+    // From each pointer along the way.
+    offsets: Vec<isize>,
+    value_type: ValueType,
+}
+
+impl Chain {
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let (path, value_type) = text.rsplit_once(" as ").ok_or(Error::MissingType)?;
+        let value_type = ValueType::parse(value_type.trim()).ok_or(Error::BadType)?;
+
+        let mut steps = path.split("->").map(str::trim);
+        let first = steps.next().unwrap_or_default();
+
+        // Module names can have dashes in them, but offsets can't.
+        let (module, base_offset) = match first.rfind(['+', '-']) {
+            Some(0) | None => return Err(Error::MissingModule),
+            Some(i) => first.split_at(i),
+        };
+
+        Ok(Self {
+            module: module.trim().to_owned(),
+            base_offset: parse_offset(base_offset)?,
+            offsets: steps.map(parse_offset).collect::<Result<_, _>>()?,
+            value_type,
+        })
+    }
+
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    // Where the value is right now. None if the module isn't loaded or a
+    // pointer along the way can't be read.
+    pub unsafe fn resolve(&self) -> Option<usize> {
+        let mut address = module_base(&self.module)?.checked_add_signed(self.base_offset)?;
+
+        for &offset in &self.offsets {
+            let Value::I64(pointer) = scan::read(address, ValueType::I64)? else {
+                return None;
+            };
+
+            if pointer == 0 {
+                return None;
+            }
+
+            address = (pointer as usize).checked_add_signed(offset)?;
+        }
+
+        Some(address)
+    }
+}
+
+// "+0x18", "-0x8" or "+24".
+fn parse_offset(text: &str) -> Result<isize, Error> {
+    let (negative, digits) = match text.as_bytes().first() {
+        Some(b'+') => (false, &text[1..]),
+        Some(b'-') => (true, &text[1..]),
+        _ => return Err(Error::BadOffset),
+    };
+
+    let digits = digits.trim();
+
+    let offset = match digits.strip_prefix("0x") {
+        Some(hex) => isize::from_str_radix(hex, 16),
+        None => digits.parse(),
+    }
+    .map_err(|_| Error::BadOffset)?;
+
+    Ok(if negative { -offset } else { offset })
+}
+
+unsafe fn module_base(name: &str) -> Option<usize> {
+    let mut base = None;
+
+    let _ = win::module::for_each_loaded(|module| {
+        if base.is_none() && module.name().eq_ignore_ascii_case(name) {
+            base = Some(module.base());
+        }
+    });
+
+    base
+}
@@ -0,0 +1,190 @@
+// The engine's two color types. FColor is 8-bit sRGB, like colors picked in
+// the editor or written as hex. FLinearColor is floating point linear color,
+// which is what materials and the canvas take.
+
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
+
+// Each component in [0, 1]. Anything else is unclamped HDR color.
+#[derive(Copy, Clone, Default, PartialEq)]
+#[repr(C)]
+pub struct FLinearColor {
+    pub R: f32,
+    pub G: f32,
+    pub B: f32,
+    pub A: f32,
+}
+
+impl FLinearColor {
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
+    pub const GRAY: Self = Self::new(0.5, 0.5, 0.5, 1.0);
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+    pub const RED: Self = Self::new(1.0, 0.0, 0.0, 1.0);
+    pub const GREEN: Self = Self::new(0.0, 1.0, 0.0, 1.0);
+    pub const BLUE: Self = Self::new(0.0, 0.0, 1.0, 1.0);
+    pub const YELLOW: Self = Self::new(1.0, 1.0, 0.0, 1.0);
+
+    pub const fn new(R: f32, G: f32, B: f32, A: f32) -> Self {
+        Self { R, G, B, A }
+    }
+
+    // The same color with a different opacity.
+    pub const fn with_alpha(self, A: f32) -> Self {
+        Self { A, ..self }
+    }
+
+    // Decodes an sRGB color, like FLinearColor(FColor). Alpha is linear in
+    // both.
+    pub fn from_srgb(color: FColor) -> Self {
+        Self::new(
+            srgb_to_linear(color.R),
+            srgb_to_linear(color.G),
+            srgb_to_linear(color.B),
+            f32::from(color.A) / 255.0,
+        )
+    }
+
+    // Takes each component as already linear, for colors that were never
+    // sRGB encoded.
+    pub fn from_linear_bytes(color: FColor) -> Self {
+        Self::new(
+            f32::from(color.R) / 255.0,
+            f32::from(color.G) / 255.0,
+            f32::from(color.B) / 255.0,
+            f32::from(color.A) / 255.0,
+        )
+    }
+
+    // FLinearColor::ToFColor(). Encodes with the sRGB curve if `srgb`.
+    pub fn to_fcolor(self, srgb: bool) -> FColor {
+        let encode = |c: f32| {
+            if srgb {
+                linear_to_srgb(c)
+            } else {
+                to_byte(c)
+            }
+        };
+
+        FColor::new(
+            encode(self.R),
+            encode(self.G),
+            encode(self.B),
+            to_byte(self.A),
+        )
+    }
+
+    // Blends from `self` at 0 to `other` at 1.
+    pub fn lerp(self, other: Self, alpha: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * alpha;
+
+        Self::new(
+            lerp(self.R, other.R),
+            lerp(self.G, other.G),
+            lerp(self.B, other.B),
+            lerp(self.A, other.A),
+        )
+    }
+}
+
+impl From<FColor> for FLinearColor {
+    fn from(color: FColor) -> Self {
+        Self::from_srgb(color)
+    }
+}
+
+// The engine stores the components as BGRA, so that the whole color reads as
+// a little endian 0xAARRGGBB.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct FColor {
+    pub B: u8,
+    pub G: u8,
+    pub R: u8,
+    pub A: u8,
+}
+
+impl FColor {
+    pub const WHITE: Self = Self::new(255, 255, 255, 255);
+    pub const BLACK: Self = Self::new(0, 0, 0, 255);
+    pub const TRANSPARENT: Self = Self::new(0, 0, 0, 0);
+    pub const RED: Self = Self::new(255, 0, 0, 255);
+    pub const GREEN: Self = Self::new(0, 255, 0, 255);
+    pub const BLUE: Self = Self::new(0, 0, 255, 255);
+    pub const YELLOW: Self = Self::new(255, 255, 0, 255);
+    pub const CYAN: Self = Self::new(0, 255, 255, 255);
+    pub const MAGENTA: Self = Self::new(255, 0, 255, 255);
+    pub const ORANGE: Self = Self::new(243, 156, 18, 255);
+    pub const PURPLE: Self = Self::new(169, 7, 228, 255);
+    pub const TURQUOISE: Self = Self::new(26, 188, 156, 255);
+    pub const SILVER: Self = Self::new(189, 195, 199, 255);
+    pub const EMERALD: Self = Self::new(46, 204, 113, 255);
+
+    pub const fn new(R: u8, G: u8, B: u8, A: u8) -> Self {
+        Self { B, G, R, A }
+    }
+
+    // Parses "RGB", "RGBA", "RRGGBB" or "RRGGBBAA", with or without a leading
+    // '#', like FColor::FromHex(). Alpha defaults to opaque.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex).as_bytes();
+
+        let digit = |i: usize| -> Option<u8> {
+            let digit = char::from(*hex.get(i)?).to_digit(16)?;
+            Some(digit as u8)
+        };
+
+        // Single digits repeat, so "F80" is "FF8800".
+        let short = |i: usize| Some(digit(i)? * 0x11);
+        let long = |i: usize| Some((digit(2 * i)? << 4) | digit(2 * i + 1)?);
+
+        match hex.len() {
+            3 => Some(Self::new(short(0)?, short(1)?, short(2)?, 255)),
+            4 => Some(Self::new(short(0)?, short(1)?, short(2)?, short(3)?)),
+            6 => Some(Self::new(long(0)?, long(1)?, long(2)?, 255)),
+            8 => Some(Self::new(long(0)?, long(1)?, long(2)?, long(3)?)),
+            _ => None,
+        }
+    }
+
+    // The same color with a different opacity.
+    pub const fn with_alpha(self, A: u8) -> Self {
+        Self { A, ..self }
+    }
+
+    pub fn linear(self) -> FLinearColor {
+        FLinearColor::from_srgb(self)
+    }
+}
+
+impl From<FLinearColor> for FColor {
+    fn from(color: FLinearColor) -> Self {
+        color.to_fcolor(true)
+    }
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = f32::from(c) / 255.0;
+
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+
+    to_byte(if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    })
+}
+
+// Like the engine, scales by just under 256 so that each byte value covers an
+// equal share of [0, 1].
+fn to_byte(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.999) as u8
+}
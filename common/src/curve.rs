@@ -0,0 +1,393 @@
+// Float curves, where the game keeps things like difficulty scaling and
+// weapon damage falloff. A UCurveFloat holds one FRichCurve. A UCurveTable
+// holds a named curve per row, all rich or all simple.
+//
+// let falloff = curve::float_curve(object)?;
+// let damage = (*falloff).eval(distance);
+
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
+use crate::{live, FName, TArray, TMap, UClass, UObject};
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::ptr;
+
+static mut CURVE_FLOAT: *mut UClass = ptr::null_mut();
+static mut CURVE_TABLE: *mut UClass = ptr::null_mut();
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct ERichCurveInterpMode(pub u8);
+
+impl ERichCurveInterpMode {
+    pub const RCIM_Linear: Self = Self(0);
+    pub const RCIM_Constant: Self = Self(1);
+    pub const RCIM_Cubic: Self = Self(2);
+    pub const RCIM_None: Self = Self(3);
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RCIM_Linear => "linear",
+            Self::RCIM_Constant => "constant",
+            Self::RCIM_Cubic => "cubic",
+            _ => "none",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct ERichCurveExtrapolation(pub u8);
+
+impl ERichCurveExtrapolation {
+    pub const RCCE_Cycle: Self = Self(0);
+    pub const RCCE_CycleWithOffset: Self = Self(1);
+    pub const RCCE_Oscillate: Self = Self(2);
+    pub const RCCE_Linear: Self = Self(3);
+    pub const RCCE_Constant: Self = Self(4);
+    pub const RCCE_None: Self = Self(5);
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RCCE_Cycle => "cycle",
+            Self::RCCE_CycleWithOffset => "cycle with offset",
+            Self::RCCE_Oscillate => "oscillate",
+            Self::RCCE_Linear => "linear",
+            Self::RCCE_Constant => "constant",
+            _ => "none",
+        }
+    }
+
+    fn cycles(self) -> bool {
+        self != Self::RCCE_Linear && self != Self::RCCE_Constant
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct ECurveTableMode(pub u8);
+
+impl ECurveTableMode {
+    pub const Empty: Self = Self(0);
+    pub const SimpleCurves: Self = Self(1);
+    pub const RichCurves: Self = Self(2);
+}
+
+#[repr(C)]
+pub struct FRichCurveKey {
+    pub InterpMode: ERichCurveInterpMode,
+    pub TangentMode: u8,
+    pub TangentWeightMode: u8,
+    pub Time: f32,
+    pub Value: f32,
+    pub ArriveTangent: f32,
+    pub ArriveTangentWeight: f32,
+    pub LeaveTangent: f32,
+    pub LeaveTangentWeight: f32,
+}
+
+#[repr(C)]
+pub struct FSimpleCurveKey {
+    pub Time: f32,
+    pub Value: f32,
+}
+
+// FIndexedCurve's key handle map is only used by the editor.
+#[repr(C)]
+pub struct FRealCurve {
+    vtable: *const c_void,
+    KeyHandlesToIndices: [u8; 0x60],
+    pub DefaultValue: f32,
+    pub PreInfinityExtrap: ERichCurveExtrapolation,
+    pub PostInfinityExtrap: ERichCurveExtrapolation,
+}
+
+#[repr(C)]
+pub struct FRichCurve {
+    pub base: FRealCurve,
+    pub Keys: TArray<FRichCurveKey>,
+}
+
+#[repr(C)]
+pub struct FSimpleCurve {
+    pub base: FRealCurve,
+    pub InterpMode: ERichCurveInterpMode,
+    pub Keys: TArray<FSimpleCurveKey>,
+}
+
+// UCurveTable is also an FCurveOwnerInterface, whose vtable comes after
+// UObject's members.
+#[repr(C)]
+pub struct UCurveTable {
+    base: UObject,
+    curve_owner_vtable: *const c_void,
+    RowMap: TMap<FName, *mut FRealCurve>,
+    pub CurveTableMode: ECurveTableMode,
+}
+
+// One of a curve table's rows.
+#[derive(Copy, Clone)]
+pub enum Curve<'a> {
+    Rich(&'a FRichCurve),
+    Simple(&'a FSimpleCurve),
+}
+
+impl FRichCurve {
+    pub fn eval(&self, time: f32) -> f32 {
+        let keys = &self.Keys;
+
+        eval(&self.base, keys.len(), time, |i| {
+            keys.get(i).map(|key| Point {
+                time: key.Time,
+                value: key.Value,
+                interp: key.InterpMode,
+                arrive: key.ArriveTangent,
+                leave: key.LeaveTangent,
+            })
+        })
+    }
+}
+
+impl FSimpleCurve {
+    pub fn eval(&self, time: f32) -> f32 {
+        let keys = &self.Keys;
+
+        eval(&self.base, keys.len(), time, |i| {
+            keys.get(i).map(|key| Point {
+                time: key.Time,
+                value: key.Value,
+                interp: self.InterpMode,
+                arrive: 0.0,
+                leave: 0.0,
+            })
+        })
+    }
+}
+
+impl Curve<'_> {
+    pub fn eval(&self, time: f32) -> f32 {
+        match self {
+            Self::Rich(curve) => curve.eval(time),
+            Self::Simple(curve) => curve.eval(time),
+        }
+    }
+
+    pub fn base(&self) -> &FRealCurve {
+        match self {
+            Self::Rich(curve) => &curve.base,
+            Self::Simple(curve) => &curve.base,
+        }
+    }
+
+    // Each key's time, value and interpolation.
+    pub fn keys(&self) -> Vec<(f32, f32, ERichCurveInterpMode)> {
+        match self {
+            Self::Rich(curve) => curve
+                .Keys
+                .iter()
+                .map(|key| (key.Time, key.Value, key.InterpMode))
+                .collect(),
+            Self::Simple(curve) => curve
+                .Keys
+                .iter()
+                .map(|key| (key.Time, key.Value, curve.InterpMode))
+                .collect(),
+        }
+    }
+}
+
+impl UCurveTable {
+    // The rows, in the table's order.
+    pub unsafe fn rows(&self) -> impl Iterator<Item = (&FName, Curve<'_>)> {
+        let mode = self.CurveTableMode;
+
+        self.RowMap
+            .iter()
+            .filter(|(_, curve)| !curve.is_null())
+            .filter_map(move |(name, &curve)| match mode {
+                ECurveTableMode::RichCurves => Some((name, Curve::Rich(&*curve.cast()))),
+                ECurveTableMode::SimpleCurves => Some((name, Curve::Simple(&*curve.cast()))),
+                _ => None,
+            })
+    }
+}
+
+// Every loaded UCurveFloat.
+pub unsafe fn float_curves() -> Vec<*mut UObject> {
+    objects(&mut CURVE_FLOAT, "Class /Script/Engine.CurveFloat")
+}
+
+// Every loaded UCurveTable.
+pub unsafe fn tables() -> Vec<*mut UCurveTable> {
+    objects(&mut CURVE_TABLE, "Class /Script/Engine.CurveTable")
+        .into_iter()
+        .map(|table| table.cast())
+        .collect()
+}
+
+// A UCurveFloat's curve. FloatCurve is reflected, so we don't need the rest of
+// the class's layout.
+pub unsafe fn float_curve(object: *const UObject) -> Option<*const FRichCurve> {
+    let property = (*(*object).class()).find_property("FloatCurve");
+
+    if property.is_null() {
+        None
+    } else {
+        Some((*property).value_ptr(object.cast(), 0))
+    }
+}
+
+unsafe fn objects(class: &mut *mut UClass, name: &'static str) -> Vec<*mut UObject> {
+    let class = crate::asset::find_cached(class, name);
+
+    if class.is_null() {
+        return Vec::new();
+    }
+
+    live::objects_of(class)
+        .into_iter()
+        .filter(|&object| !(*object).is_default_object())
+        .collect()
+}
+
+struct Point {
+    time: f32,
+    value: f32,
+    interp: ERichCurveInterpMode,
+    arrive: f32,
+    leave: f32,
+}
+
+// FRichCurve::Eval(), for keys of either kind. Weighted tangents are
+// evaluated as if they weren't.
+fn eval(curve: &FRealCurve, len: usize, time: f32, key: impl Fn(usize) -> Option<Point>) -> f32 {
+    let (Some(first), Some(last)) = (key(0), len.checked_sub(1).and_then(&key)) else {
+        return curve.DefaultValue;
+    };
+
+    let (time, offset) = remap(curve, &first, &last, len, time);
+
+    let value = if len < 2 || time <= first.time {
+        match key(1) {
+            Some(second) if curve.PreInfinityExtrap == ERichCurveExtrapolation::RCCE_Linear => {
+                extrapolate(&first, &second, time)
+            }
+            _ => first.value,
+        }
+    } else if time < last.time {
+        // The first key after `time`. There's one, since `time` is before the
+        // last key.
+        let after = (1..len).find(|&i| key(i).is_some_and(|k| k.time > time));
+
+        match after.and_then(|i| Some((key(i - 1)?, key(i)?))) {
+            Some((before, after)) => interpolate(&before, &after, time),
+            None => last.value,
+        }
+    } else {
+        match len.checked_sub(2).and_then(&key) {
+            Some(before) if curve.PostInfinityExtrap == ERichCurveExtrapolation::RCCE_Linear => {
+                extrapolate(&last, &before, time)
+            }
+            _ => last.value,
+        }
+    };
+
+    value + offset
+}
+
+// FRealCurve::RemapTimeValue(). Brings a time outside the keys back inside
+// them for cycling extrapolation, with the value to add for each cycle.
+fn remap(curve: &FRealCurve, first: &Point, last: &Point, len: usize, time: f32) -> (f32, f32) {
+    let extrapolation = if time <= first.time {
+        curve.PreInfinityExtrap
+    } else if time >= last.time {
+        curve.PostInfinityExtrap
+    } else {
+        return (time, 0.0);
+    };
+
+    let duration = last.time - first.time;
+
+    if len < 2 || !extrapolation.cycles() || duration <= 0.0 {
+        return (time, 0.0);
+    }
+
+    let mut cycles = 0.0;
+    let mut remapped = time;
+
+    if time > last.time {
+        cycles = ((last.time - time) / duration).floor();
+        remapped = time + duration * cycles;
+    } else if time < first.time {
+        cycles = ((time - first.time) / duration).floor();
+        remapped = time - duration * cycles;
+    }
+
+    if remapped == last.time && time < first.time {
+        remapped = first.time;
+    }
+
+    if remapped == first.time && time > last.time {
+        remapped = last.time;
+    }
+
+    let cycles = cycles.abs();
+
+    match extrapolation {
+        ERichCurveExtrapolation::RCCE_CycleWithOffset => {
+            let change = if time <= first.time {
+                first.value - last.value
+            } else {
+                last.value - first.value
+            };
+
+            (remapped, change * cycles)
+        }
+        ERichCurveExtrapolation::RCCE_Oscillate if cycles % 2.0 == 1.0 => {
+            (first.time + (last.time - remapped), 0.0)
+        }
+        _ => (remapped, 0.0),
+    }
+}
+
+// Continues the line through `from` and `other` past `from`.
+fn extrapolate(from: &Point, other: &Point, time: f32) -> f32 {
+    let dt = other.time - from.time;
+
+    if dt.abs() <= f32::EPSILON {
+        from.value
+    } else {
+        (other.value - from.value) / dt * (time - from.time) + from.value
+    }
+}
+
+fn interpolate(before: &Point, after: &Point, time: f32) -> f32 {
+    let duration = after.time - before.time;
+
+    if duration <= 0.0 {
+        return before.value;
+    }
+
+    let alpha = (time - before.time) / duration;
+
+    match before.interp {
+        ERichCurveInterpMode::RCIM_Linear => before.value + (after.value - before.value) * alpha,
+        ERichCurveInterpMode::RCIM_Cubic => {
+            let p0 = before.value;
+            let p3 = after.value;
+            let p1 = p0 + before.leave * duration / 3.0;
+            let p2 = p3 - after.arrive * duration / 3.0;
+            bezier(p0, p1, p2, p3, alpha)
+        }
+        _ => before.value,
+    }
+}
+
+fn bezier(p0: f32, p1: f32, p2: f32, p3: f32, alpha: f32) -> f32 {
+    let p01 = p0 + (p1 - p0) * alpha;
+    let p12 = p1 + (p2 - p1) * alpha;
+    let p23 = p2 + (p3 - p2) * alpha;
+    let p012 = p01 + (p12 - p01) * alpha;
+    let p123 = p12 + (p23 - p12) * alpha;
+    p012 + (p123 - p012) * alpha
+}
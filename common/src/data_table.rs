@@ -0,0 +1,74 @@
+// Reads the game's data tables, where DRG keeps things like enemy stats,
+// upgrade values and drop rates. Each row is a struct of the table's
+// RowStruct, read through reflection like a snapshot:
+//
+// for table in data_table::tables() {
+//     for row in data_table::rows(table) {
+//         // row.object is the row's name, row.values its properties.
+//     }
+// }
+
+use crate::snapshot::Snapshot;
+use crate::{live, FName, TMap, UClass, UObject, UStruct};
+use alloc::format;
+use alloc::vec::Vec;
+use core::mem;
+use core::ptr;
+
+static mut DATA_TABLE: *mut UClass = ptr::null_mut();
+
+// Every loaded data table, skipping the class's default object.
+pub unsafe fn tables() -> Vec<*mut UObject> {
+    let class = crate::asset::find_cached(&mut DATA_TABLE, "Class /Script/Engine.DataTable");
+
+    if class.is_null() {
+        return Vec::new();
+    }
+
+    live::objects_of(class)
+        .into_iter()
+        .filter(|&table| !(*table).is_default_object())
+        .collect()
+}
+
+// The table's rows, in the table's order. Empty if `table` isn't a data table.
+pub unsafe fn rows(table: *const UObject) -> Vec<Snapshot> {
+    let Some((row_struct, row_map)) = parts(table) else {
+        return Vec::new();
+    };
+
+    (*row_map)
+        .iter()
+        .filter(|(_, &row)| !row.is_null())
+        .map(|(name, &row)| Snapshot::of_struct(format!("{}", name), row_struct, row.cast()))
+        .collect()
+}
+
+// How many rows the table has, without reading them. Zero if `table` isn't a
+// data table.
+pub unsafe fn row_count(table: *const UObject) -> usize {
+    parts(table).map_or(0, |(_, row_map)| (*row_map).len())
+}
+
+// UDataTable's RowStruct is reflected, but RowMap, a TMap<FName, uint8*>,
+// isn't. It comes right after RowStruct.
+unsafe fn parts(table: *const UObject) -> Option<(*const UStruct, *const TMap<FName, *mut u8>)> {
+    let property = (*(*table).class()).find_property("RowStruct");
+
+    if property.is_null() {
+        return None;
+    }
+
+    let row_struct = (*table).object_property("RowStruct");
+
+    if row_struct.is_null() {
+        return None;
+    }
+
+    let row_map = table
+        .cast::<u8>()
+        .add((*property).offset() + mem::size_of::<*mut UObject>())
+        .cast();
+
+    Some((row_struct.cast(), row_map))
+}
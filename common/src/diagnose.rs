@@ -0,0 +1,83 @@
+// Logs what a maintainer needs to fix a signature that stopped matching after
+// a game update: which build it was, the pattern, and the code where the
+// pattern comes closest to matching, which is usually the same instruction
+// with a new register or offset.
+
+use crate::win::{self, module::pe};
+use crate::{hexdump, Hex};
+use alloc::string::String;
+use core::fmt::Write as _;
+
+// Bytes to dump on each side of the closest match.
+const CONTEXT: usize = 32;
+
+// While waiting for the game to start, when signatures aren't expected to
+// match yet.
+static mut QUIET: bool = false;
+
+pub unsafe fn set_quiet(quiet: bool) {
+    QUIET = quiet;
+}
+
+pub unsafe fn is_quiet() -> bool {
+    QUIET
+}
+
+pub unsafe fn pattern_not_found(module: &win::Module, name: &str, pattern: &[Option<u8>]) {
+    if QUIET {
+        return;
+    }
+
+    crate::log!("diagnose: no match for {}'s signature", name);
+    build(module);
+
+    let mut text = String::new();
+
+    for byte in pattern {
+        match byte {
+            Some(byte) => {
+                let _ = write!(text, "{:02x} ", byte);
+            }
+            None => text.push_str("?? "),
+        }
+    }
+
+    crate::log!("diagnose: pattern {}", text.trim_end());
+
+    let Some((closest, matches)) = module.best_match(pattern) else {
+        return;
+    };
+
+    let wanted = pattern.iter().filter(|byte| byte.is_some()).count();
+    let closest = closest as usize;
+
+    crate::log!(
+        "diagnose: closest match is {} of {} bytes at exe+{}",
+        matches,
+        wanted,
+        Hex(closest - module.base())
+    );
+
+    let start = closest.saturating_sub(CONTEXT).max(module.start());
+    let end = (closest + pattern.len() + CONTEXT).min(module.start() + module.size());
+
+    crate::log!(
+        "diagnose: from exe+{}:\n{}",
+        Hex(start - module.base()),
+        hexdump(start as *const u8, end - start)
+    );
+}
+
+// Which build of the game this is, for reports.
+pub unsafe fn build(module: &win::Module) {
+    match pe::nt_headers(module.base()) {
+        Some(nt_headers) => crate::log!(
+            "diagnose: build {:08x}, image size {}, code at exe+{} ({} bytes)",
+            nt_headers.file_header.time_date_stamp,
+            Hex(nt_headers.optional_header.size_of_image as usize),
+            Hex(module.start() - module.base()),
+            module.size()
+        ),
+        None => crate::log!("diagnose: unable to read the executable's headers"),
+    }
+}
@@ -0,0 +1,27 @@
+// Checked casts from UObject down to the SDK's classes, which
+// #[derive(macros::UObjectCast)] implements Downcast for.
+//
+// if let Some(character) = (*object).try_downcast_mut::<PlayerCharacter>() {
+//     ..
+// }
+//
+// Casts up need no check. Each class derefs to its base, and `.into()` turns
+// a reference to it into a reference to any of its ancestors.
+
+use crate::UObject;
+
+pub trait Downcast: Sized {
+    // `object` as Self, if its class is Self's class or one derived from it.
+    // None for a null object.
+    unsafe fn from_object(object: *mut UObject) -> Option<*mut Self>;
+}
+
+impl UObject {
+    pub unsafe fn try_downcast<T: Downcast>(&self) -> Option<&T> {
+        T::from_object(self as *const Self as *mut Self).map(|object| &*object)
+    }
+
+    pub unsafe fn try_downcast_mut<T: Downcast>(&mut self) -> Option<&mut T> {
+        T::from_object(self).map(|object| &mut *object)
+    }
+}
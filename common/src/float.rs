@@ -0,0 +1,82 @@
+// The float functions that std has and core doesn't, for building without
+// std. They call the C runtime, like std does for most of them.
+
+#[cfg(not(feature = "std"))]
+pub trait Float {
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn tan(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn floor(self) -> Self;
+}
+
+#[cfg(not(feature = "std"))]
+mod crt {
+    extern "C" {
+        pub fn sqrtf(x: f32) -> f32;
+        pub fn sqrt(x: f64) -> f64;
+        pub fn tanf(x: f32) -> f32;
+        pub fn tan(x: f64) -> f64;
+        pub fn atan2f(y: f32, x: f32) -> f32;
+        pub fn atan2(y: f64, x: f64) -> f64;
+        pub fn powf(x: f32, y: f32) -> f32;
+        pub fn pow(x: f64, y: f64) -> f64;
+        pub fn floorf(x: f32) -> f32;
+        pub fn floor(x: f64) -> f64;
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Float for f32 {
+    fn abs(self) -> Self {
+        Self::from_bits(self.to_bits() & !(1 << 31))
+    }
+
+    fn sqrt(self) -> Self {
+        unsafe { crt::sqrtf(self) }
+    }
+
+    fn tan(self) -> Self {
+        unsafe { crt::tanf(self) }
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        unsafe { crt::atan2f(self, other) }
+    }
+
+    fn powf(self, n: Self) -> Self {
+        unsafe { crt::powf(self, n) }
+    }
+
+    fn floor(self) -> Self {
+        unsafe { crt::floorf(self) }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Float for f64 {
+    fn abs(self) -> Self {
+        Self::from_bits(self.to_bits() & !(1 << 63))
+    }
+
+    fn sqrt(self) -> Self {
+        unsafe { crt::sqrt(self) }
+    }
+
+    fn tan(self) -> Self {
+        unsafe { crt::tan(self) }
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        unsafe { crt::atan2(self, other) }
+    }
+
+    fn powf(self, n: Self) -> Self {
+        unsafe { crt::pow(self, n) }
+    }
+
+    fn floor(self) -> Self {
+        unsafe { crt::floor(self) }
+    }
+}
@@ -1,5 +1,6 @@
-use core::fmt::{self, Display, Formatter};
+use core::fmt::{self, Display, Formatter, Write as _};
 use core::ops::{DivAssign, Rem};
+use core::slice;
 use core::str;
 
 pub trait Hexable: Copy + Rem<Output = Self> + DivAssign + From<u8> + PartialEq {
@@ -74,3 +75,152 @@ impl<T> Display for Hex<*const T> {
         Hex(self.0 as usize).fmt(f)
     }
 }
+
+const BYTES_PER_ROW: usize = 16;
+
+// Formats memory as rows of offset, bytes, and an ASCII gutter:
+// 0x0000  48 8b 05 f7 84 5c 04 48  8b 0c c8 4c 8d 04 d1 00  |H....\.H...L....|
+pub struct HexDump {
+    address: *const u8,
+    len: usize,
+}
+
+// The caller must ensure that `len` bytes starting at `address` are readable
+// for as long as the dump is formatted.
+pub unsafe fn hexdump<T>(address: *const T, len: usize) -> HexDump {
+    HexDump {
+        address: address.cast(),
+        len,
+    }
+}
+
+impl HexDump {
+    unsafe fn bytes(&self) -> &[u8] {
+        slice::from_raw_parts(self.address, self.len)
+    }
+}
+
+impl Display for HexDump {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let bytes = unsafe { self.bytes() };
+
+        for (row, chunk) in bytes.chunks(BYTES_PER_ROW).enumerate() {
+            if row > 0 {
+                f.write_str("\n")?;
+            }
+
+            write!(f, "{:#06x} ", row * BYTES_PER_ROW)?;
+            write_row(f, chunk)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_row(f: &mut Formatter, chunk: &[u8]) -> Result<(), fmt::Error> {
+    for i in 0..BYTES_PER_ROW {
+        if i == BYTES_PER_ROW / 2 {
+            f.write_str(" ")?;
+        }
+
+        match chunk.get(i) {
+            Some(byte) => write!(f, " {:02x}", byte)?,
+            None => f.write_str("   ")?,
+        }
+    }
+
+    f.write_str("  |")?;
+
+    for &byte in chunk {
+        let c = if byte.is_ascii_graphic() || byte == b' ' {
+            char::from(byte)
+        } else {
+            '.'
+        };
+
+        c.fmt(f)?;
+    }
+
+    f.write_str("|")
+}
+
+// A named range of bytes within a struct, for annotating dumps.
+pub struct DumpField {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+}
+
+// Formats a struct field by field, so that a layout mismatch shows up as a
+// field holding obviously wrong bytes:
+// 0x0010 ClassPrivate (8 bytes)
+//        80 3a 1c 4e f6 7f 00 00                           |.:.N....|
+pub struct StructDump<'fields> {
+    address: *const u8,
+    fields: &'fields [DumpField],
+}
+
+// The caller must ensure that every field is readable for as long as the dump
+// is formatted.
+pub unsafe fn struct_dump<T>(address: *const T, fields: &[DumpField]) -> StructDump {
+    StructDump {
+        address: address.cast(),
+        fields,
+    }
+}
+
+impl Display for StructDump<'_> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+
+            write!(
+                f,
+                "{:#06x} {} ({} bytes)",
+                field.offset, field.name, field.size
+            )?;
+
+            let bytes =
+                unsafe { slice::from_raw_parts(self.address.add(field.offset), field.size) };
+
+            for chunk in bytes.chunks(BYTES_PER_ROW) {
+                f.write_str("\n      ")?;
+                write_row(f, chunk)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Formats `T` as the inside of a JSON string, escaping quotes, backslashes and
+// control characters.
+pub struct JsonString<T>(pub T);
+
+impl<T: Display> Display for JsonString<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(JsonEscaper(f), "{}", self.0)
+    }
+}
+
+struct JsonEscaper<'a, 'b>(&'a mut Formatter<'b>);
+
+impl fmt::Write for JsonEscaper<'_, '_> {
+    fn write_str(&mut self, s: &str) -> Result<(), fmt::Error> {
+        for c in s.chars() {
+            match c {
+                '"' => self.0.write_str("\\\"")?,
+                '\\' => self.0.write_str("\\\\")?,
+                '\n' => self.0.write_str("\\n")?,
+                '\r' => self.0.write_str("\\r")?,
+                '\t' => self.0.write_str("\\t")?,
+                c if c < ' ' => write!(self.0, "\\u{:04x}", u32::from(c))?,
+                c => fmt::Write::write_char(self.0, c)?,
+            }
+        }
+
+        Ok(())
+    }
+}
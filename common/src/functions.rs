@@ -0,0 +1,89 @@
+// Every loaded UFunction by full name, so that find_function is a map lookup
+// instead of a walk of GUObjectArray. The hook builds it when it attaches.
+// Blueprint functions come and go with their levels, so it's rebuilt when the
+// world changes. A function that isn't in it, like one loaded since, is found
+// the slow way and added.
+//
+// Keys hash the entry ids of the function's class, name and outers, in that
+// order, the same pieces FUObjectArray::find compares. An entry is only used
+// while its function is alive and still has the name it was added under.
+
+use crate::{EClassCastFlags, FNameEntryId, GUObjectArray, ObjectHandle, UFunction, UObject};
+use alloc::collections::BTreeMap;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+static mut FUNCTIONS: BTreeMap<u64, ObjectHandle<UFunction>> = BTreeMap::new();
+
+// Held by whichever thread is using FUNCTIONS. Anyone else does without.
+static BUSY: AtomicBool = AtomicBool::new(false);
+
+// Replaces the map with the functions loaded now.
+pub unsafe fn build() {
+    let mut functions = BTreeMap::new();
+
+    for object in (*GUObjectArray).iter().filter(|o| !o.is_null()) {
+        if (*object).fast_is(EClassCastFlags::CASTCLASS_UFunction) {
+            functions.insert(key_of(object), ObjectHandle::new(object.cast()));
+        }
+    }
+
+    with(|map| *map = functions);
+}
+
+pub unsafe fn clear() {
+    with(BTreeMap::clear);
+}
+
+// The function with `key`, if it's been added and is still the same function.
+pub unsafe fn get(key: u64) -> Option<*mut UFunction> {
+    with(|map| map.get(&key).copied())
+        .flatten()
+        .and_then(|handle| handle.resolve())
+        .filter(|&function| key_of(function.cast()) == key)
+}
+
+pub unsafe fn insert(key: u64, function: *mut UFunction) {
+    with(|map| map.insert(key, ObjectHandle::new(function)));
+}
+
+// The key for a name made of these entry ids: the class, the name, then each
+// outer from the innermost.
+pub fn key(ids: impl IntoIterator<Item = FNameEntryId>) -> u64 {
+    // FNV-1a over each id's bytes.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for id in ids {
+        for byte in id.value().to_le_bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    hash
+}
+
+unsafe fn key_of(object: *const UObject) -> u64 {
+    let outer = |object: *const UObject| Some((*object).outer()).filter(|o| !o.is_null());
+
+    let outers = core::iter::successors(outer(object), |&o| outer(o))
+        .map(|o| (*o).NamePrivate.comparison_index());
+
+    let class = (*object).class().cast::<UObject>();
+
+    key([
+        (*class).NamePrivate.comparison_index(),
+        (*object).NamePrivate.comparison_index(),
+    ]
+    .into_iter()
+    .chain(outers))
+}
+
+unsafe fn with<T>(f: impl FnOnce(&mut BTreeMap<u64, ObjectHandle<UFunction>>) -> T) -> Option<T> {
+    if BUSY.swap(true, Ordering::Acquire) {
+        return None;
+    }
+
+    let result = f(&mut *core::ptr::addr_of_mut!(FUNCTIONS));
+    BUSY.store(false, Ordering::Release);
+    Some(result)
+}
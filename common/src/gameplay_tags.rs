@@ -0,0 +1,162 @@
+// Gameplay tags, the dotted names like "Enemy.Bug.Glyphid" that much of FSD
+// keys its behavior off. A tag is only an FName. A container also keeps the
+// parents of its tags, e.g. "Enemy.Bug" for "Enemy.Bug.Glyphid".
+//
+// There's no list of every tag outside of the editor, so `collect` gathers
+// the tags that loaded objects and tag tables use instead.
+
+use crate::{
+    data_table, inspect, EClassCastFlags, FArrayProperty, FName, FProperty, FStructProperty,
+    GUObjectArray, TArray, UStruct,
+};
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use core::ffi::c_void;
+use core::fmt::{self, Display, Formatter};
+
+// How far `collect` descends into structs.
+const MAX_DEPTH: usize = 4;
+
+// Longer arrays only have their first elements looked through.
+const MAX_ELEMENTS: i32 = 64;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct FGameplayTag {
+    pub TagName: FName,
+}
+
+impl Display for FGameplayTag {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        self.TagName.fmt(f)
+    }
+}
+
+#[repr(C)]
+pub struct FGameplayTagContainer {
+    pub GameplayTags: TArray<FGameplayTag>,
+    // Every parent of GameplayTags that isn't in it itself.
+    pub ParentTags: TArray<FGameplayTag>,
+}
+
+impl Display for FGameplayTagContainer {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.write_str("(")?;
+
+        for (i, tag) in self.GameplayTags.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+
+            tag.fmt(f)?;
+        }
+
+        f.write_str(")")
+    }
+}
+
+// Writes the value of a tag or tag container struct. Returns None for any
+// other struct.
+pub unsafe fn fmt_struct(
+    structure: *const UStruct,
+    value: *const c_void,
+    f: &mut Formatter,
+) -> Option<Result<(), fmt::Error>> {
+    match (*structure).name() {
+        "GameplayTag" => Some((*value.cast::<FGameplayTag>()).fmt(f)),
+        "GameplayTagContainer" => Some((*value.cast::<FGameplayTagContainer>()).fmt(f)),
+        _ => None,
+    }
+}
+
+// Every tag that loaded objects, and the data tables that define tags, refer
+// to, along with their parents, in order.
+pub unsafe fn collect() -> BTreeSet<String> {
+    let mut tags = BTreeSet::new();
+
+    for object in (*GUObjectArray).iter().filter(|o| !o.is_null()) {
+        add_struct(&mut tags, (*object).class().cast(), object.cast(), 0);
+    }
+
+    for table in data_table::tables() {
+        for row in data_table::rows(table) {
+            // GameplayTagTableRow's Tag, which is what defines a tag.
+            if let Some(tag) = row.get("Tag").filter(|tag| tag.contains('.')) {
+                add(&mut tags, tag);
+            }
+        }
+    }
+
+    tags
+}
+
+fn add(tags: &mut BTreeSet<String>, tag: &str) {
+    if tag.is_empty() || tag == "None" {
+        return;
+    }
+
+    let mut end = 0;
+
+    for part in tag.split('.') {
+        end += part.len();
+        tags.insert(String::from(tag.get(..end).unwrap_or(tag)));
+        end += 1;
+    }
+}
+
+unsafe fn add_struct(
+    tags: &mut BTreeSet<String>,
+    structure: *const UStruct,
+    container: *const c_void,
+    depth: usize,
+) {
+    if depth == MAX_DEPTH {
+        return;
+    }
+
+    inspect::for_each_property(structure, |property| {
+        for i in 0..(*property).array_dim() {
+            add_value(tags, property, (*property).value_ptr(container, i), depth);
+        }
+    });
+}
+
+// `value` is where `property`'s value is, rather than its container.
+unsafe fn add_value(
+    tags: &mut BTreeSet<String>,
+    property: *const FProperty,
+    value: *const c_void,
+    depth: usize,
+) {
+    if (*property).is(EClassCastFlags::CASTCLASS_FStructProperty) {
+        let structure = (*property.cast::<FStructProperty>()).Structure;
+
+        match (*structure).name() {
+            "GameplayTag" => add(tags, &format!("{}", *value.cast::<FGameplayTag>())),
+            "GameplayTagContainer" => {
+                for tag in (*value.cast::<FGameplayTagContainer>()).GameplayTags.iter() {
+                    add(tags, &format!("{}", tag));
+                }
+            }
+            _ => add_struct(tags, structure, value, depth + 1),
+        }
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FArrayProperty) {
+        let inner = (*property.cast::<FArrayProperty>()).Inner;
+
+        if !(*inner).is(EClassCastFlags::CASTCLASS_FStructProperty) {
+            return;
+        }
+
+        let array = &*value.cast::<TArray<u8>>();
+
+        for i in 0..array.len.min(MAX_ELEMENTS) {
+            let element = array
+                .as_ptr()
+                .add(i as usize * (*inner).element_size())
+                .cast::<c_void>();
+
+            add_value(tags, inner, element, depth + 1);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// A reference to an object that can be kept across frames. The object's slot
+// in GUObjectArray gets a new serial number when it's reused, so a handle to
+// a destroyed object resolves to None instead of to whatever took its place.
+// It's FWeakObjectPtr, typed and Copy.
+//
+// let handle = ObjectHandle::new(pawn);
+// ...
+// if let Some(pawn) = handle.resolve() {
+//     common::log!("{}", *pawn.cast::<UObject>());
+// }
+
+use crate::FWeakObjectPtr;
+use core::marker::PhantomData;
+
+pub struct ObjectHandle<T> {
+    weak: FWeakObjectPtr,
+    _marker: PhantomData<*mut T>,
+}
+
+impl<T> ObjectHandle<T> {
+    pub const NULL: Self = Self {
+        weak: FWeakObjectPtr::NULL,
+        _marker: PhantomData,
+    };
+
+    // `object` must be a UObject or null.
+    pub unsafe fn new(object: *mut T) -> Self {
+        Self {
+            weak: FWeakObjectPtr::from_object(object.cast()),
+            _marker: PhantomData,
+        }
+    }
+
+    // The object, if it still exists.
+    pub unsafe fn resolve(&self) -> Option<*mut T> {
+        let object = self.weak.get();
+        (!object.is_null()).then_some(object.cast())
+    }
+}
+
+// Derives would require the same of T.
+impl<T> Copy for ObjectHandle<T> {}
+
+impl<T> Clone for ObjectHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> PartialEq for ObjectHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.weak == other.weak
+    }
+}
+
+impl<T> Eq for ObjectHandle<T> {}
@@ -0,0 +1,72 @@
+// Keeps the most recent log lines and traced events in memory so that the
+// crash handler and the overlay can show what happened leading up to now.
+
+use crate::{List, Ring};
+
+use core::fmt::{self, Write};
+use core::hint;
+use core::ptr;
+use core::str;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+const MAX_LINES: usize = 256;
+const MAX_LINE_LEN: usize = 256;
+
+type Line = List<u8, MAX_LINE_LEN>;
+
+static LOCKED: AtomicBool = AtomicBool::new(false);
+static mut LINES: Ring<Line, MAX_LINES> = Ring::new();
+
+// Lines are recorded from any thread, so guard the ring with a spin lock.
+// Critical sections only copy a few hundred bytes.
+fn with_lines<R>(f: impl FnOnce(&mut Ring<Line, MAX_LINES>) -> R) -> R {
+    while LOCKED
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        hint::spin_loop();
+    }
+
+    let result = f(unsafe { &mut *ptr::addr_of_mut!(LINES) });
+    LOCKED.store(false, Ordering::Release);
+    result
+}
+
+// Writes as much of the text as fits, dropping the rest.
+struct Truncate<'line>(&'line mut Line);
+
+impl Write for Truncate<'_> {
+    fn write_str(&mut self, text: &str) -> Result<(), fmt::Error> {
+        let remaining = self.0.capacity() - self.0.len();
+        let mut end = text.len().min(remaining);
+
+        // Don't cut a multibyte character in half.
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let _ = self.0.write_bytes(&text.as_bytes()[..end]);
+        Ok(())
+    }
+}
+
+pub fn record(args: fmt::Arguments) {
+    let mut line = Line::new();
+    let _ = Truncate(&mut line).write_fmt(args);
+    with_lines(|lines| lines.push(line));
+}
+
+// Calls `f` with each retained line, from oldest to newest. `f` must not log,
+// since the lines stay locked until it returns.
+pub fn for_each(mut f: impl FnMut(&str)) {
+    with_lines(|lines| {
+        for line in lines.iter() {
+            // SAFETY: Truncate only writes whole UTF-8 characters.
+            f(unsafe { str::from_utf8_unchecked(line.as_slice()) });
+        }
+    });
+}
+
+pub fn clear() {
+    with_lines(|lines| lines.clear());
+}
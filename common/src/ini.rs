@@ -0,0 +1,132 @@
+// INI text the way the engine writes its config files: a [Section] header,
+// then Key=Value lines. A key can repeat to make an array, written as +Key=.
+// Sections and keys are matched ignoring case, like the engine does. Editing
+// keeps every other line as it was, comments and blank lines included.
+//
+// let mut ini = Ini::parse(&text);
+// let fov = ini.get("/Script/FSD.FSDGameUserSettings", "FieldOfView");
+// ini.set("/Script/FSD.FSDGameUserSettings", "FieldOfView", "100");
+// let text = ini.to_string();
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::ops::Range;
+
+pub struct Ini {
+    lines: Vec<String>,
+}
+
+impl Ini {
+    pub fn parse(text: &str) -> Self {
+        Self {
+            lines: text.lines().map(String::from).collect(),
+        }
+    }
+
+    // The key's last value in the section, which is the one the engine uses.
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.get_all(section, key).pop()
+    }
+
+    // Every value of the key in the section, for arrays.
+    pub fn get_all(&self, section: &str, key: &str) -> Vec<&str> {
+        let Some(range) = self.section(section) else {
+            return Vec::new();
+        };
+
+        self.lines
+            .get(range)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|line| value(line, key))
+            .collect()
+    }
+
+    // Replaces the key's last value in the section, the one get() returns, or
+    // adds the key to the end of the section, or the section to the end of
+    // the text.
+    pub fn set(&mut self, section: &str, key: &str, new_value: &str) {
+        let line = format!("{}={}", key, new_value);
+
+        let Some(range) = self.section(section) else {
+            if self
+                .lines
+                .last()
+                .is_some_and(|last| !last.trim().is_empty())
+            {
+                self.lines.push(String::new());
+            }
+
+            self.lines.push(format!("[{}]", section));
+            self.lines.push(line);
+            return;
+        };
+
+        let existing = range
+            .clone()
+            .rev()
+            .find(|&i| self.lines.get(i).is_some_and(|l| value(l, key).is_some()));
+
+        if let Some(existing) = existing.and_then(|i| self.lines.get_mut(i)) {
+            *existing = line;
+            return;
+        }
+
+        // Before the blank lines that separate it from the next section.
+        let start = range.start;
+
+        let end = range
+            .rev()
+            .find(|&i| self.lines.get(i).is_some_and(|l| !l.trim().is_empty()))
+            .map_or(start, |i| i + 1);
+
+        self.lines.insert(end, line);
+    }
+
+    // Every section's name, in order.
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().filter_map(|line| header(line))
+    }
+
+    // The lines in `section`, after its header.
+    fn section(&self, section: &str) -> Option<Range<usize>> {
+        let start = self
+            .lines
+            .iter()
+            .position(|line| header(line).is_some_and(|h| h.eq_ignore_ascii_case(section)))?
+            + 1;
+
+        let end = self
+            .lines
+            .iter()
+            .skip(start)
+            .position(|line| header(line).is_some())
+            .map_or(self.lines.len(), |len| start + len);
+
+        Some(start..end)
+    }
+}
+
+impl Display for Ini {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn header(line: &str) -> Option<&str> {
+    line.trim().strip_prefix('[')?.strip_suffix(']')
+}
+
+// The value `line` gives `key`, if it gives it one.
+fn value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let (k, value) = line.split_once('=')?;
+    let k = k.trim();
+    let k = k.strip_prefix('+').unwrap_or(k);
+    k.eq_ignore_ascii_case(key).then(|| value.trim())
+}
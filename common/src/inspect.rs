@@ -0,0 +1,337 @@
+// Reads and tweaks any object through reflection, for finding out what an
+// object holds without generated types.
+
+use crate::{
+    EClassCastFlags, FArrayProperty, FBoolProperty, FEnumProperty, FNumericProperty,
+    FObjectPropertyBase, FProperty, FStructProperty, GUObjectArray, PropertyValue, TArray, UObject,
+    UStruct,
+};
+
+use crate::list::List;
+use core::ffi::c_void;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::mem;
+use core::ptr;
+
+// How far `dump` descends into structs, arrays and subobjects.
+const MAX_DEPTH: usize = 8;
+
+// Deeper than any class hierarchy in the game.
+const MAX_SUPER_STRUCTS: usize = 32;
+
+// Longer arrays only have their first elements listed.
+const MAX_LISTED_ELEMENTS: i32 = 32;
+
+// Logs every property of `object`, descending into structs, arrays, and the
+// objects it owns, like an actor's components:
+// Credits = 52310
+// Resources = {ResourcesSave}
+//   OwnedResources = [4 elements]
+//     [0] = {ResourceAmount}
+// HealthComponent = PlayerHealthComponent /Game/...BP_PlayerCharacter_C_0.Health
+//   MaxHealth = 100
+pub unsafe fn dump(object: *const UObject) {
+    dump_struct((*object).class().cast(), object.cast(), object, 0);
+}
+
+// `owner` is the object that `container` is part of.
+unsafe fn dump_struct(
+    structure: *const UStruct,
+    container: *const c_void,
+    owner: *const UObject,
+    depth: usize,
+) {
+    if depth == MAX_DEPTH {
+        return;
+    }
+
+    for_each_property(structure, |property| {
+        crate::log!(
+            "{:indent$}{} = {}",
+            "",
+            (*property).name(),
+            PropertyValue {
+                property,
+                container
+            },
+            indent = depth * 2
+        );
+
+        dump_children(property, container, owner, depth + 1);
+    });
+}
+
+unsafe fn dump_children(
+    property: *const FProperty,
+    container: *const c_void,
+    owner: *const UObject,
+    depth: usize,
+) {
+    if (*property).array_dim() > 1 {
+        return;
+    }
+
+    if (*property).is(EClassCastFlags::CASTCLASS_FStructProperty) {
+        let structure = (*property.cast::<FStructProperty>()).Structure;
+        dump_struct(structure, (*property).value_ptr(container, 0), owner, depth);
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FArrayProperty) {
+        let inner = (*property.cast::<FArrayProperty>()).Inner;
+        let array = (*property).read::<TArray<u8>>(container);
+
+        // An element's inner property has offset 0, so each element is its
+        // own container.
+        for i in 0..array.len.min(MAX_LISTED_ELEMENTS) {
+            let element = array
+                .as_ptr()
+                .add(i as usize * (*inner).element_size())
+                .cast::<c_void>();
+
+            crate::log!(
+                "{:indent$}[{}] = {}",
+                "",
+                i,
+                PropertyValue {
+                    property: inner,
+                    container: element
+                },
+                indent = depth * 2
+            );
+
+            dump_children(inner, element, owner, depth + 1);
+        }
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FObjectProperty) {
+        let object = (*property.cast::<FObjectPropertyBase>()).read_object(container);
+
+        // Only objects that `owner` owns, like components. Other references
+        // can lead back up the tree.
+        if !object.is_null() && (*object).outer() == owner.cast_mut() {
+            dump_struct((*object).class().cast(), object.cast(), object, depth);
+        }
+    }
+}
+
+// Calls `f` with every property of `structure`, inherited properties first,
+// in declaration order.
+pub unsafe fn for_each_property(structure: *const UStruct, mut f: impl FnMut(*const FProperty)) {
+    let mut structures = List::<*const UStruct, MAX_SUPER_STRUCTS>::new();
+    let mut current = structure;
+
+    while !current.is_null() && structures.push(current).is_ok() {
+        current = (*current).SuperStruct;
+    }
+
+    for &structure in structures.iter().rev() {
+        let mut property = (*structure).ChildProperties.cast::<FProperty>();
+
+        while !property.is_null() {
+            f(property);
+            property = (*property).base.Next.cast();
+        }
+    }
+}
+
+// Writes every property of `object` and its value, the way `{:?}` writes a
+// struct, for the Debug impls of the SDK's classes. Uses the object's own
+// class, which may be a subclass of the type it's formatted as.
+pub unsafe fn fmt_object(object: *const UObject, f: &mut Formatter) -> Result<(), fmt::Error> {
+    fmt_properties((*object).class().cast(), object.cast(), f)
+}
+
+// Like `fmt_object`, for the SDK's structs. `structure` is the struct's full
+// name, like "ScriptStruct /Script/CoreUObject.Vector".
+pub unsafe fn fmt_struct(
+    structure: &'static str,
+    container: *const c_void,
+    f: &mut Formatter,
+) -> Result<(), fmt::Error> {
+    match (*GUObjectArray).find(structure) {
+        Ok(structure) => fmt_properties(structure.cast(), container, f),
+        Err(_) => f.write_str(structure),
+    }
+}
+
+unsafe fn fmt_properties(
+    structure: *const UStruct,
+    container: *const c_void,
+    f: &mut Formatter,
+) -> Result<(), fmt::Error> {
+    let mut out = f.debug_struct((*structure).name());
+
+    for_each_property(structure, |property| {
+        out.field(
+            (*property).name(),
+            &AsDebug(PropertyValue {
+                property,
+                container,
+            }),
+        );
+    });
+
+    out.finish()
+}
+
+// Shows a Display value, like PropertyValue, through Debug.
+struct AsDebug<T>(T);
+
+impl<T: Display> Debug for AsDebug<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        Display::fmt(&self.0, f)
+    }
+}
+
+// Whether `object`'s class, or any class it inherits from, is called `name`,
+// like "PlayerCharacter".
+pub unsafe fn is_a(object: *const UObject, name: &str) -> bool {
+    let mut structure: *const UStruct = (*object).class().cast();
+
+    while !structure.is_null() {
+        if (*structure).name().eq_ignore_ascii_case(name) {
+            return true;
+        }
+
+        structure = (*structure).SuperStruct;
+    }
+
+    false
+}
+
+// A pointer to the property called `name` on `object`, for reading or
+// tweaking it. Returns `None` if there's no such property or if it isn't the
+// size of a `T`.
+pub unsafe fn property_mut<T>(object: *mut UObject, name: &str) -> Option<*mut T> {
+    let property = (*(*object).class()).find_property(name);
+
+    if property.is_null() || (*property).size() != mem::size_of::<T>() {
+        None
+    } else {
+        Some((*property).value_ptr_mut(object.cast(), 0))
+    }
+}
+
+// A property value that scripts and tools can work with directly. Anything
+// else they only get to see through `PropertyValue`.
+#[derive(Copy, Clone)]
+pub enum Primitive {
+    Bool(bool),
+    // Integers, and enums by value.
+    Integer(i64),
+    Float(f64),
+    // Null for an unset reference.
+    Object(*mut UObject),
+}
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum WriteError {
+    // The value can't go in this type of property.
+    Mismatch,
+    // The object isn't of the property's class.
+    WrongClass,
+    StaticArray,
+    Unsupported,
+}
+
+pub unsafe fn read_primitive(
+    property: *const FProperty,
+    container: *const c_void,
+) -> Option<Primitive> {
+    if (*property).array_dim() > 1 {
+        return None;
+    }
+
+    if (*property).is(EClassCastFlags::CASTCLASS_FBoolProperty) {
+        let value = (*property.cast::<FBoolProperty>()).read_bool(container);
+        Some(Primitive::Bool(value))
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FEnumProperty) {
+        let value = (*property.cast::<FEnumProperty>()).read_value(container)?;
+        Some(Primitive::Integer(value))
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FNumericProperty) {
+        let numeric = &*property.cast::<FNumericProperty>();
+
+        match numeric.read_integer(container) {
+            Some(value) => Some(Primitive::Integer(value)),
+            None => numeric.read_float(container).map(Primitive::Float),
+        }
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FObjectProperty) {
+        let object = (*property.cast::<FObjectPropertyBase>()).read_object(container);
+        Some(Primitive::Object(object))
+    } else {
+        None
+    }
+}
+
+// The first object that one of `object`'s properties holds and that is_a()
+// `class`, whichever property that is. Null if there's none.
+pub unsafe fn find_object_of(object: *mut UObject, class: &str) -> *mut UObject {
+    let mut found: *mut UObject = ptr::null_mut();
+
+    for_each_property((*object).class().cast(), |property| {
+        if let Some(Primitive::Object(value)) = read_primitive(property, object.cast()) {
+            if found.is_null() && !value.is_null() && is_a(value, class) {
+                found = value;
+            }
+        }
+    });
+
+    found
+}
+
+// Integers and floats convert to whichever the property holds.
+pub unsafe fn write_primitive(
+    property: *const FProperty,
+    container: *mut c_void,
+    value: Primitive,
+) -> Result<(), WriteError> {
+    if (*property).array_dim() > 1 {
+        return Err(WriteError::StaticArray);
+    }
+
+    if (*property).is(EClassCastFlags::CASTCLASS_FBoolProperty) {
+        let Primitive::Bool(value) = value else {
+            return Err(WriteError::Mismatch);
+        };
+
+        (*property.cast::<FBoolProperty>()).write_bool(container, value);
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FEnumProperty) {
+        let value = match value {
+            Primitive::Integer(n) => n,
+            Primitive::Float(n) => n as i64,
+            _ => return Err(WriteError::Mismatch),
+        };
+
+        // The underlying property has offset 0 within the enum's value.
+        let underlying = (*property.cast::<FEnumProperty>()).UnderlyingProp;
+        (*underlying).write_integer((*property).value_ptr_mut(container, 0), value);
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FNumericProperty) {
+        let numeric = &*property.cast::<FNumericProperty>();
+
+        match value {
+            Primitive::Integer(n) => {
+                if !numeric.write_integer(container, n) {
+                    numeric.write_float(container, n as f64);
+                }
+            }
+            Primitive::Float(n) => {
+                if !numeric.write_float(container, n) {
+                    numeric.write_integer(container, n as i64);
+                }
+            }
+            _ => return Err(WriteError::Mismatch),
+        }
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FObjectProperty) {
+        let Primitive::Object(object) = value else {
+            return Err(WriteError::Mismatch);
+        };
+
+        let property = &*property.cast::<FObjectPropertyBase>();
+
+        if !object.is_null() && !(*object).is(property.PropertyClass) {
+            return Err(WriteError::WrongClass);
+        }
+
+        property.write_object(container, object);
+    } else {
+        return Err(WriteError::Unsupported);
+    }
+
+    Ok(())
+}
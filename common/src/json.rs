@@ -0,0 +1,343 @@
+// A small JSON reader for messages from external tools. Writing is left to
+// `write!` and `JsonString`, which is all our responses need.
+
+use crate::JsonString;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::str;
+
+// Deeper than any message we expect. Keeps a hostile message from
+// overflowing the stack.
+const MAX_DEPTH: usize = 32;
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    UnexpectedEnd,
+    Unexpected(u8, usize),
+    BadNumber(usize),
+    BadEscape(usize),
+    TooDeep,
+    TrailingCharacters(usize),
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    // In the order they were written.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            position: 0,
+        };
+
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+
+        if parser.position == parser.text.len() {
+            Ok(value)
+        } else {
+            Err(Error::TrailingCharacters(parser.position))
+        }
+    }
+
+    // The member called `key`, if this is an object that has one.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => b.fmt(f),
+
+            // JSON has no NaN or infinity.
+            Self::Number(n) if !n.is_finite() => f.write_str("null"),
+            Self::Number(n) => n.fmt(f),
+
+            Self::String(s) => write!(f, "\"{}\"", JsonString(s)),
+            Self::Array(values) => {
+                f.write_str("[")?;
+
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+
+                    value.fmt(f)?;
+                }
+
+                f.write_str("]")
+            }
+            Self::Object(members) => {
+                f.write_str("{")?;
+
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+
+                    write!(f, "\"{}\":{}", JsonString(key), value)?;
+                }
+
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth == MAX_DEPTH {
+            return Err(Error::TooDeep);
+        }
+
+        self.skip_whitespace();
+
+        match self.peek()? {
+            b'n' => self.literal("null", Value::Null),
+            b't' => self.literal("true", Value::Bool(true)),
+            b'f' => self.literal("false", Value::Bool(false)),
+            b'"' => Ok(Value::String(self.string()?)),
+            b'[' => self.array(depth),
+            b'{' => self.object(depth),
+            b'-' | b'0'..=b'9' => self.number(),
+            c => Err(Error::Unexpected(c, self.position)),
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, Error> {
+        let mut values = Vec::new();
+        self.position += 1;
+        self.skip_whitespace();
+
+        if self.peek()? == b']' {
+            self.position += 1;
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            values.push(self.value(depth + 1)?);
+
+            if self.separator(b']')? {
+                return Ok(Value::Array(values));
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, Error> {
+        let mut members = Vec::new();
+        self.position += 1;
+        self.skip_whitespace();
+
+        if self.peek()? == b'}' {
+            self.position += 1;
+            return Ok(Value::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            self.expect(b'"')?;
+            let key = self.string()?;
+
+            self.skip_whitespace();
+            self.expect(b':')?;
+            self.position += 1;
+
+            members.push((key, self.value(depth + 1)?));
+
+            if self.separator(b'}')? {
+                return Ok(Value::Object(members));
+            }
+        }
+    }
+
+    // Consumes a ',' or `end`. Returns whether it was `end`.
+    fn separator(&mut self, end: u8) -> Result<bool, Error> {
+        self.skip_whitespace();
+
+        match self.peek()? {
+            b',' => {
+                self.position += 1;
+                Ok(false)
+            }
+            c if c == end => {
+                self.position += 1;
+                Ok(true)
+            }
+            c => Err(Error::Unexpected(c, self.position)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let mut s = String::new();
+
+        // Skip the opening quote.
+        self.position += 1;
+
+        loop {
+            let start = self.position;
+
+            // Copy runs of plain characters at once. Both stops are ASCII,
+            // so the run is whole UTF-8 characters.
+            while !matches!(self.peek()?, b'"' | b'\\') {
+                self.position += 1;
+            }
+
+            s.push_str(&String::from_utf8_lossy(&self.text[start..self.position]));
+
+            let c = self.next()?;
+
+            if c == b'"' {
+                return Ok(s);
+            }
+
+            let escape = self.position;
+
+            let unescaped = match self.next()? {
+                b'"' => '"',
+                b'\\' => '\\',
+                b'/' => '/',
+                b'b' => '\u{8}',
+                b'f' => '\u{c}',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'u' => self.unicode_escape(escape)?,
+                _ => return Err(Error::BadEscape(escape)),
+            };
+
+            s.push(unescaped);
+        }
+    }
+
+    // The character after "\u", including a following low surrogate for
+    // characters outside the BMP.
+    fn unicode_escape(&mut self, escape: usize) -> Result<char, Error> {
+        let high = self.hex4(escape)?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.next()? != b'\\' || self.next()? != b'u' {
+                return Err(Error::BadEscape(escape));
+            }
+
+            let low = self.hex4(escape)?;
+
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(Error::BadEscape(escape));
+            }
+
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or(Error::BadEscape(escape))
+    }
+
+    fn hex4(&mut self, escape: usize) -> Result<u32, Error> {
+        let digits = self
+            .text
+            .get(self.position..self.position + 4)
+            .ok_or(Error::UnexpectedEnd)?;
+
+        let digits = str::from_utf8(digits).map_err(|_| Error::BadEscape(escape))?;
+        let code = u32::from_str_radix(digits, 16).map_err(|_| Error::BadEscape(escape))?;
+
+        self.position += 4;
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Value, Error> {
+        let start = self.position;
+
+        while let Some(&c) = self.text.get(self.position) {
+            if !matches!(c, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') {
+                break;
+            }
+
+            self.position += 1;
+        }
+
+        // Only ASCII was consumed.
+        str::from_utf8(&self.text[start..self.position])
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .map(Value::Number)
+            .ok_or(Error::BadNumber(start))
+    }
+
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Value, Error> {
+        if self.text[self.position..].starts_with(literal.as_bytes()) {
+            self.position += literal.len();
+            Ok(value)
+        } else {
+            Err(Error::Unexpected(self.text[self.position], self.position))
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), Error> {
+        match self.peek()? {
+            found if found == c => Ok(()),
+            found => Err(Error::Unexpected(found, self.position)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.text.get(self.position) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Result<u8, Error> {
+        self.text
+            .get(self.position)
+            .copied()
+            .ok_or(Error::UnexpectedEnd)
+    }
+
+    fn next(&mut self) -> Result<u8, Error> {
+        let c = self.peek()?;
+        self.position += 1;
+        Ok(c)
+    }
+}
@@ -0,0 +1,394 @@
+// Wrappers for the blueprint library functions that hooks reach for most.
+// Each one calls the function on its library's default object, like a
+// blueprint would. `world_context` is any object in the world, such as an
+// actor.
+
+use crate::asset::find_cached;
+use crate::{
+    FLinearColor, FName, FString, FText, FVector, FVector2D, TArray, TWeakObjectPtr, UClass,
+    UFunction, UObject,
+};
+
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::mem;
+use core::ptr;
+
+static mut GAMEPLAY_STATICS: *mut UObject = ptr::null_mut();
+static mut GET_PLAYER_CONTROLLER: *mut UFunction = ptr::null_mut();
+static mut GET_ALL_ACTORS_OF_CLASS: *mut UFunction = ptr::null_mut();
+static mut PROJECT_WORLD_TO_SCREEN: *mut UFunction = ptr::null_mut();
+static mut PLAY_SOUND_2D: *mut UFunction = ptr::null_mut();
+
+static mut KISMET_SYSTEM_LIBRARY: *mut UObject = ptr::null_mut();
+static mut LINE_TRACE_SINGLE: *mut UFunction = ptr::null_mut();
+static mut GET_GAME_TIME_IN_SECONDS: *mut UFunction = ptr::null_mut();
+static mut IS_SERVER: *mut UFunction = ptr::null_mut();
+
+static mut KISMET_TEXT_LIBRARY: *mut UObject = ptr::null_mut();
+static mut CONV_STRING_TO_TEXT: *mut UFunction = ptr::null_mut();
+
+// Reused between queries so that we don't leak a new engine allocation per
+// query.
+static mut ACTORS: TArray<*mut UObject> = TArray::new();
+
+#[repr(C)]
+pub struct FHitResult {
+    pub FaceIndex: i32,
+    pub Time: f32,
+    pub Distance: f32,
+    pub Location: FVector,
+    pub ImpactPoint: FVector,
+    pub Normal: FVector,
+    pub ImpactNormal: FVector,
+    pub TraceStart: FVector,
+    pub TraceEnd: FVector,
+    pub PenetrationDepth: f32,
+    pub Item: i32,
+    pub ElementIndex: u8,
+    flags: u8,
+    pub PhysMaterial: TWeakObjectPtr<UObject>,
+    pub Actor: TWeakObjectPtr<UObject>,
+    pub Component: TWeakObjectPtr<UObject>,
+    pub BoneName: FName,
+    pub MyBoneName: FName,
+}
+
+impl FHitResult {
+    pub fn blocking_hit(&self) -> bool {
+        self.flags & 0b01 != 0
+    }
+
+    pub fn start_penetrating(&self) -> bool {
+        self.flags & 0b10 != 0
+    }
+}
+
+// Returns whether the function was found and called.
+unsafe fn call(
+    library: &mut *mut UObject,
+    library_name: &'static str,
+    function: &mut *mut UFunction,
+    function_name: &'static str,
+    parameters: *mut c_void,
+) -> bool {
+    let library = find_cached(library, library_name);
+    let function = find_cached(function, function_name);
+
+    if library.is_null() || function.is_null() {
+        return false;
+    }
+
+    UObject::process_event(library, function, parameters);
+    true
+}
+
+pub(crate) unsafe fn call_gameplay_statics(
+    function: &mut *mut UFunction,
+    function_name: &'static str,
+    parameters: *mut c_void,
+) -> bool {
+    call(
+        &mut GAMEPLAY_STATICS,
+        "GameplayStatics /Script/Engine.Default__GameplayStatics",
+        function,
+        function_name,
+        parameters,
+    )
+}
+
+unsafe fn call_kismet_system_library(
+    function: &mut *mut UFunction,
+    function_name: &'static str,
+    parameters: *mut c_void,
+) -> bool {
+    call(
+        &mut KISMET_SYSTEM_LIBRARY,
+        "KismetSystemLibrary /Script/Engine.Default__KismetSystemLibrary",
+        function,
+        function_name,
+        parameters,
+    )
+}
+
+pub(crate) unsafe fn call_kismet_text_library(
+    function: &mut *mut UFunction,
+    function_name: &'static str,
+    parameters: *mut c_void,
+) -> bool {
+    call(
+        &mut KISMET_TEXT_LIBRARY,
+        "KismetTextLibrary /Script/Engine.Default__KismetTextLibrary",
+        function,
+        function_name,
+        parameters,
+    )
+}
+
+// `text` as culture invariant FText, or None if the function couldn't be
+// called.
+pub unsafe fn string_to_text(text: &str) -> Option<FText> {
+    #[repr(C)]
+    struct Parameters {
+        InString: FString,
+        ReturnValue: FText,
+    }
+
+    // The engine copies the string, so it can borrow ours. FString's length
+    // counts the null terminator.
+    let wide: Vec<u16> = text.encode_utf16().chain([0]).collect();
+
+    // A zeroed FText has no text data to release when the engine assigns
+    // over it.
+    let mut parameters = Parameters {
+        InString: FString::from(wide.as_slice()),
+        ReturnValue: mem::zeroed(),
+    };
+
+    let called = call_kismet_text_library(
+        &mut CONV_STRING_TO_TEXT,
+        "Function /Script/Engine.KismetTextLibrary.Conv_StringToText",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    called.then_some(parameters.ReturnValue)
+}
+
+// The local player's controller, or null.
+pub unsafe fn player_controller(world_context: *mut UObject, player_index: i32) -> *mut UObject {
+    #[repr(C)]
+    struct Parameters {
+        WorldContextObject: *mut UObject,
+        PlayerIndex: i32,
+        ReturnValue: *mut UObject,
+    }
+
+    let mut parameters = Parameters {
+        WorldContextObject: world_context,
+        PlayerIndex: player_index,
+        ReturnValue: ptr::null_mut(),
+    };
+
+    call_gameplay_statics(
+        &mut GET_PLAYER_CONTROLLER,
+        "Function /Script/Engine.GameplayStatics.GetPlayerController",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    parameters.ReturnValue
+}
+
+// Every actor of `class` in the world. The returned slice is only valid until
+// the next call.
+pub unsafe fn all_actors_of_class(
+    world_context: *mut UObject,
+    class: *const UClass,
+) -> &'static [*mut UObject] {
+    #[repr(C)]
+    struct Parameters {
+        WorldContextObject: *mut UObject,
+        ActorClass: *const UClass,
+        OutActors: TArray<*mut UObject>,
+    }
+
+    // The engine empties the array before filling it, keeping the buffer.
+    let mut parameters = Parameters {
+        WorldContextObject: world_context,
+        ActorClass: class,
+        OutActors: ACTORS,
+    };
+
+    call_gameplay_statics(
+        &mut GET_ALL_ACTORS_OF_CLASS,
+        "Function /Script/Engine.GameplayStatics.GetAllActorsOfClass",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    // The engine may have reallocated the array.
+    ACTORS = parameters.OutActors;
+    &*ptr::addr_of!(ACTORS)
+}
+
+// Traces along `channel`, an ETraceTypeQuery, and returns the first blocking
+// hit.
+pub unsafe fn line_trace_single(
+    world_context: *mut UObject,
+    start: FVector,
+    end: FVector,
+    channel: u8,
+    trace_complex: bool,
+    actors_to_ignore: &[*mut UObject],
+) -> Option<FHitResult> {
+    #[repr(C)]
+    struct Parameters {
+        WorldContextObject: *mut UObject,
+        Start: FVector,
+        End: FVector,
+        TraceChannel: u8,
+        bTraceComplex: bool,
+        ActorsToIgnore: TArray<*mut UObject>,
+        DrawDebugType: u8,
+        OutHit: FHitResult,
+        bIgnoreSelf: bool,
+        TraceColor: FLinearColor,
+        TraceHitColor: FLinearColor,
+        DrawTime: f32,
+        ReturnValue: bool,
+    }
+
+    // EDrawDebugTrace::None
+    const NO_DEBUG_DRAW: u8 = 0;
+
+    // The engine only reads the array, so it can borrow the slice.
+    let mut parameters = Parameters {
+        WorldContextObject: world_context,
+        Start: start,
+        End: end,
+        TraceChannel: channel,
+        bTraceComplex: trace_complex,
+        ActorsToIgnore: TArray {
+            data: actors_to_ignore.as_ptr().cast_mut(),
+            len: actors_to_ignore.len() as i32,
+            capacity: actors_to_ignore.len() as i32,
+        },
+        DrawDebugType: NO_DEBUG_DRAW,
+        OutHit: mem::zeroed(),
+        bIgnoreSelf: true,
+        TraceColor: FLinearColor::default(),
+        TraceHitColor: FLinearColor::default(),
+        DrawTime: 0.0,
+        ReturnValue: false,
+    };
+
+    call_kismet_system_library(
+        &mut LINE_TRACE_SINGLE,
+        "Function /Script/Engine.KismetSystemLibrary.LineTraceSingle",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    if parameters.ReturnValue {
+        Some(parameters.OutHit)
+    } else {
+        None
+    }
+}
+
+// Seconds since the world started, adjusted for pauses and time dilation.
+pub unsafe fn game_time_in_seconds(world_context: *mut UObject) -> f32 {
+    #[repr(C)]
+    struct Parameters {
+        WorldContextObject: *mut UObject,
+        ReturnValue: f32,
+    }
+
+    let mut parameters = Parameters {
+        WorldContextObject: world_context,
+        ReturnValue: 0.0,
+    };
+
+    call_kismet_system_library(
+        &mut GET_GAME_TIME_IN_SECONDS,
+        "Function /Script/Engine.KismetSystemLibrary.GetGameTimeInSeconds",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    parameters.ReturnValue
+}
+
+// Whether the world is ours rather than a server's we joined. True for a
+// game that isn't networked too. False if the function couldn't be called.
+pub unsafe fn is_server(world_context: *mut UObject) -> bool {
+    #[repr(C)]
+    struct Parameters {
+        WorldContextObject: *mut UObject,
+        ReturnValue: bool,
+    }
+
+    let mut parameters = Parameters {
+        WorldContextObject: world_context,
+        ReturnValue: false,
+    };
+
+    call_kismet_system_library(
+        &mut IS_SERVER,
+        "Function /Script/Engine.KismetSystemLibrary.IsServer",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    parameters.ReturnValue
+}
+
+// Where `world_position` appears on `player`'s screen, or None if it's behind
+// the camera.
+pub unsafe fn project_world_to_screen(
+    player: *mut UObject,
+    world_position: FVector,
+    player_viewport_relative: bool,
+) -> Option<FVector2D> {
+    #[repr(C)]
+    struct Parameters {
+        Player: *mut UObject,
+        WorldPosition: FVector,
+        ScreenPosition: FVector2D,
+        bPlayerViewportRelative: bool,
+        ReturnValue: bool,
+    }
+
+    let mut parameters = Parameters {
+        Player: player,
+        WorldPosition: world_position,
+        ScreenPosition: FVector2D::default(),
+        bPlayerViewportRelative: player_viewport_relative,
+        ReturnValue: false,
+    };
+
+    call_gameplay_statics(
+        &mut PROJECT_WORLD_TO_SCREEN,
+        "Function /Script/Engine.GameplayStatics.ProjectWorldToScreen",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    if parameters.ReturnValue {
+        Some(parameters.ScreenPosition)
+    } else {
+        None
+    }
+}
+
+// Plays `sound`, a SoundBase like a SoundCue or SoundWave, without a position
+// in the world, like a UI sound. A `pitch` over 1 is higher.
+pub unsafe fn play_sound_2d(
+    world_context: *mut UObject,
+    sound: *mut UObject,
+    volume: f32,
+    pitch: f32,
+) {
+    #[repr(C)]
+    struct Parameters {
+        WorldContextObject: *mut UObject,
+        Sound: *mut UObject,
+        VolumeMultiplier: f32,
+        PitchMultiplier: f32,
+        StartTime: f32,
+        ConcurrencySettings: *mut UObject,
+        OwningActor: *mut UObject,
+        bIsUISound: bool,
+    }
+
+    let mut parameters = Parameters {
+        WorldContextObject: world_context,
+        Sound: sound,
+        VolumeMultiplier: volume,
+        PitchMultiplier: pitch,
+        StartTime: 0.0,
+        ConcurrencySettings: ptr::null_mut(),
+        OwningActor: ptr::null_mut(),
+        bIsUISound: true,
+    };
+
+    call_gameplay_statics(
+        &mut PLAY_SOUND_2D,
+        "Function /Script/Engine.GameplayStatics.PlaySound2D",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+}
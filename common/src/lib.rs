@@ -1,37 +1,131 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(non_snake_case, non_upper_case_globals, non_camel_case_types)]
 #![allow(clippy::missing_safety_doc)]
 
+extern crate alloc;
+
 use core::ffi::c_void;
+use core::fmt::{Display, Formatter, Write as _};
 use core::marker::PhantomData;
+use core::mem;
 use core::ops::{Deref, DerefMut};
 use core::ptr::{self, NonNull};
 use core::slice;
 
+pub mod accessor;
+
+pub mod asset;
+
+pub mod chain;
+
+pub mod curve;
+
+pub mod data_table;
+
+pub mod diagnose;
+
+mod downcast;
+pub use downcast::Downcast;
+
+mod color;
+pub use color::*;
+
+pub mod float;
+
+pub mod functions;
+
+pub mod gameplay_tags;
+
 mod fmt;
 pub use fmt::*;
 
+mod handle;
+pub use handle::ObjectHandle;
+
+pub mod history;
+
+pub mod ini;
+
+pub mod inspect;
+
+pub mod json;
+
+pub mod kismet;
+
 mod name;
 pub use name::*;
 
 mod object;
 pub use object::*;
 
+pub mod params;
+
+pub mod player;
+
+pub mod plugin;
+
+pub mod prompt;
+
+mod property;
+pub use property::*;
+
 pub mod list;
 pub use list::*;
 
+pub mod live;
+
+mod map;
+pub use map::*;
+
+mod math;
+pub use math::*;
+
+pub mod memory;
+
+pub mod offsets;
+
+pub mod queue;
+pub use queue::Queue;
+
+pub mod ready;
+
+mod ring;
+pub use ring::*;
+
+pub mod save;
+
+pub mod scan;
+
+pub mod snapshot;
+
+mod static_class;
+pub use static_class::StaticClass;
+
+pub mod string_table;
+
 mod split;
 pub use split::*;
 
 pub mod timer;
 pub use timer::Timer;
 
-mod util;
+pub mod trace;
+
+#[doc(hidden)]
+pub mod util;
+
+pub mod view;
+
+pub mod vm;
+
+pub mod widget;
 
 pub mod win;
 
 #[derive(macros::NoPanicErrorDebug)]
 pub enum Error {
     FindNamePoolData,
+    Memory(#[from] memory::Error),
     Object(#[from] object::Error),
 }
 
@@ -43,6 +137,65 @@ pub struct TArray<T> {
     pub capacity: i32,
 }
 
+impl<T> TArray<T> {
+    pub const fn new() -> Self {
+        Self {
+            data: ptr::null_mut(),
+            len: 0,
+            capacity: 0,
+        }
+    }
+
+    // Grows the array through the engine's allocator, since the engine owns
+    // the buffer and may grow or free it after us.
+    pub unsafe fn push(&mut self, value: T) {
+        if self.len == self.capacity {
+            let capacity = (self.capacity * 2).max(4);
+
+            self.data = memory::realloc(
+                self.data.cast(),
+                capacity as usize * mem::size_of::<T>(),
+                memory::DEFAULT_ALIGNMENT,
+            )
+            .cast();
+
+            self.capacity = capacity;
+        }
+
+        self.data.add(self.len as usize).write(value);
+        self.len += 1;
+    }
+
+    // Frees the buffer of an array the engine handed over to us, like a
+    // UFunction's return value. Doesn't drop the elements.
+    pub unsafe fn free(&mut self) {
+        memory::free(self.data.cast());
+        *self = Self::new();
+    }
+
+    // Removes the element at `index`, shifting the rest down like
+    // TArray::RemoveAt().
+    pub unsafe fn remove(&mut self, index: usize) -> Option<T> {
+        let len = self.len as usize;
+
+        if index >= len {
+            return None;
+        }
+
+        let element = self.data.add(index);
+        let value = element.read();
+        ptr::copy(element.add(1), element, len - index - 1);
+        self.len -= 1;
+        Some(value)
+    }
+}
+
+impl<T> Default for TArray<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Deref for TArray<T> {
     type Target = [T];
 
@@ -69,6 +222,12 @@ impl<T> DerefMut for TArray<T> {
     }
 }
 
+#[repr(C)]
+pub struct TPair<K, V> {
+    pub Key: K,
+    pub Value: V,
+}
+
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub struct FString {
@@ -77,6 +236,77 @@ pub struct FString {
     pub capacity: i32,
 }
 
+impl FString {
+    pub const fn new() -> Self {
+        Self {
+            data: ptr::null(),
+            len: 0,
+            capacity: 0,
+        }
+    }
+
+    // The characters, without the null terminator that `len` counts.
+    pub fn as_slice(&self) -> &[u16] {
+        if self.data.is_null() || self.len <= 1 {
+            &[]
+        } else {
+            unsafe { slice::from_raw_parts(self.data, self.len as usize - 1) }
+        }
+    }
+
+    // Replaces the characters, growing the buffer with the engine's allocator.
+    // Only for strings the engine owns, since it may free the buffer later.
+    pub unsafe fn set(&mut self, text: &str) {
+        let len = text.encode_utf16().count() + 1;
+
+        if (self.capacity as usize) < len {
+            let data = memory::realloc(
+                self.data.cast_mut().cast(),
+                len * mem::size_of::<u16>(),
+                memory::DEFAULT_ALIGNMENT,
+            );
+
+            if data.is_null() {
+                return;
+            }
+
+            self.data = data.cast();
+            self.capacity = len as i32;
+        }
+
+        let data = self.data.cast_mut();
+
+        for (i, c) in text.encode_utf16().chain([0]).enumerate() {
+            data.add(i).write(c);
+        }
+
+        self.len = len as i32;
+    }
+
+    // Frees a string the engine handed over to us, like a UFunction's return
+    // value.
+    pub unsafe fn free(&mut self) {
+        memory::free(self.data.cast_mut().cast());
+        *self = Self::new();
+    }
+}
+
+impl Default for FString {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for FString {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), core::fmt::Error> {
+        for c in char::decode_utf16(self.as_slice().iter().copied()) {
+            f.write_char(c.unwrap_or(char::REPLACEMENT_CHARACTER))?;
+        }
+
+        Ok(())
+    }
+}
+
 impl<'a> From<&'a [u16]> for FString {
     fn from(s: &[u16]) -> FString {
         FString {
@@ -93,9 +323,14 @@ struct TSharedRef<T> {
     SharedReferenceCount: *const c_void,
 }
 
+// Every ITextData in the game is a TTextData, which starts with the string
+// it shows.
 #[repr(C)]
 struct ITextData {
     vtable: *const *const usize,
+    // Null for text that builds its string when it's shown, like formatted
+    // text.
+    LocalizedString: TSharedRef<FString>,
 }
 
 #[repr(C)]
@@ -104,6 +339,22 @@ pub struct FText {
     Flags: u32,
 }
 
+impl FText {
+    // The string this text shows, or null. Localized text shares it with
+    // every other text for the same key, like the entry in a string table,
+    // so changing it changes them all.
+    pub unsafe fn display_string(&self) -> *mut FString {
+        let data = self.TextData.Object;
+
+        if data.is_null() {
+            ptr::null_mut()
+        } else {
+            (*data).LocalizedString.Object.cast_mut()
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
 #[repr(C)]
 pub struct FWeakObjectPtr {
     ObjectIndex: i32,
@@ -111,6 +362,24 @@ pub struct FWeakObjectPtr {
 }
 
 impl FWeakObjectPtr {
+    pub const NULL: Self = Self {
+        ObjectIndex: -1,
+        ObjectSerialNumber: 0,
+    };
+
+    pub unsafe fn from_object(object: *mut UObject) -> Self {
+        if object.is_null() {
+            return Self::NULL;
+        }
+
+        let index = (*object).InternalIndex;
+
+        Self {
+            ObjectIndex: index,
+            ObjectSerialNumber: (*GUObjectArray).allocate_serial_number(index),
+        }
+    }
+
     pub unsafe fn get(&self) -> *mut UObject {
         if self.ObjectSerialNumber == 0 || self.ObjectIndex < 0 {
             ptr::null_mut()
@@ -136,28 +405,98 @@ pub struct TWeakObjectPtr<T> {
 }
 
 impl<T> TWeakObjectPtr<T> {
+    pub unsafe fn from_object(object: *mut T) -> Self {
+        Self {
+            base: FWeakObjectPtr::from_object(object.cast()),
+            _marker: PhantomData,
+        }
+    }
+
     pub unsafe fn get(&self) -> *mut T {
         self.base.get().cast()
     }
 }
 
+#[derive(Copy, Clone)]
 #[repr(C)]
 pub struct FScriptDelegate {
     Object: FWeakObjectPtr,
     FunctionName: FName,
 }
 
+impl FScriptDelegate {
+    // A delegate that calls `object`'s UFunction named `function_name`.
+    pub unsafe fn new(object: *mut UObject, function_name: FName) -> Self {
+        Self {
+            Object: FWeakObjectPtr::from_object(object),
+            FunctionName: function_name,
+        }
+    }
+
+    pub unsafe fn object(&self) -> *mut UObject {
+        self.Object.get()
+    }
+
+    pub fn function_name(&self) -> FName {
+        self.FunctionName
+    }
+}
+
 #[repr(C)]
 pub struct TScriptInterface<T> {
     ObjectPointer: *const UObject,
     InterfacePointer: *const T,
 }
 
+impl<T> TScriptInterface<T> {
+    pub fn object(&self) -> *mut UObject {
+        self.ObjectPointer.cast_mut()
+    }
+
+    // The object, typed as the interface so that the interface's generated
+    // methods can be called on it. Null if the interface is empty.
+    pub fn get(&self) -> *mut T {
+        self.object().cast()
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.ObjectPointer.is_null()
+    }
+}
+
 #[repr(C)]
 pub struct FMulticastScriptDelegate {
     InvocationList: TArray<FScriptDelegate>,
 }
 
+impl FMulticastScriptDelegate {
+    pub unsafe fn contains(&self, object: *mut UObject, function_name: FName) -> bool {
+        self.position(object, function_name).is_some()
+    }
+
+    // Binds `object`'s function, like AddUnique().
+    pub unsafe fn add(&mut self, object: *mut UObject, function_name: FName) {
+        if !self.contains(object, function_name) {
+            self.InvocationList
+                .push(FScriptDelegate::new(object, function_name));
+        }
+    }
+
+    pub unsafe fn remove(&mut self, object: *mut UObject, function_name: FName) {
+        if let Some(index) = self.position(object, function_name) {
+            self.InvocationList.remove(index);
+        }
+    }
+
+    unsafe fn position(&self, object: *mut UObject, function_name: FName) -> Option<usize> {
+        let index = (*object).InternalIndex;
+
+        self.InvocationList.iter().position(|delegate| {
+            delegate.FunctionName == function_name && delegate.Object.ObjectIndex == index
+        })
+    }
+}
+
 #[repr(C)]
 pub struct FSparseDelegate {
     bIsBound: bool,
@@ -230,13 +569,15 @@ pub struct TLazyObjectPtr<T> {
 //     _marker: PhantomData<*const T>,
 // }
 
+// A prompt with only help, continue and eject. See prompt.
 pub unsafe fn idle() {
-    log!("Idling. Press enter to continue.");
-    win::idle();
+    prompt::run(&[]);
 }
 
 pub unsafe fn init_globals(module: &win::Module) -> Result<(), Error> {
     FNamePool::init(module)?;
+    memory::init(module)?;
     FUObjectArray::init(module)?;
+    UObject::init(module);
     Ok(())
 }
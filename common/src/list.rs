@@ -4,8 +4,18 @@ use core::ptr;
 use core::slice::{self, Iter};
 use core::str;
 
+mod atomic;
+pub use atomic::AtomicList;
+
+mod chunked;
+pub use chunked::{ChunkedIter, ChunkedList};
+
+mod listeners;
+pub use listeners::Listeners;
+
 #[derive(macros::NoPanicErrorDebug)]
 pub enum Error {
+    AllocationFailed,
     CapacityReached,
     BadSwapRemoveIndex(usize, usize),
     BadGetIndex(usize, usize),
@@ -121,6 +131,33 @@ impl<T, const N: usize> List<T, N> {
             Err(Error::BadSwapRemoveIndex(index, len))
         }
     }
+
+    // Keeps the values that `keep` returns true for, in order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&mut T) -> bool) {
+        let len = self.len;
+        let mut kept = 0;
+
+        // If `keep` panics, the values it hasn't seen leak instead of being
+        // dropped twice.
+        self.len = 0;
+
+        unsafe {
+            let data = self.data.as_mut_ptr().cast::<T>();
+
+            for i in 0..len {
+                let value = data.add(i);
+
+                if keep(&mut *value) {
+                    ptr::copy(value, data.add(kept), 1);
+                    kept += 1;
+                } else {
+                    ptr::drop_in_place(value);
+                }
+            }
+        }
+
+        self.len = kept;
+    }
 }
 
 impl<T, const N: usize> Drop for List<T, N> {
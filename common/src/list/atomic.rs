@@ -0,0 +1,130 @@
+use super::Error;
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+struct Slot<T> {
+    ready: AtomicBool,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+    const fn empty() -> Self {
+        Self {
+            ready: AtomicBool::new(false),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+// A fixed-capacity list that any number of threads can push onto and exactly
+// one thread drains. Unlike Queue, slots aren't recycled one at a time: the
+// list starts over from the front once a drain empties it.
+pub struct AtomicList<T, const N: usize> {
+    slots: [Slot<T>; N],
+
+    // Slots claimed by producers, whether or not they've been written yet.
+    len: AtomicUsize,
+
+    // The next slot to drain. Only touched by the consumer.
+    head: UnsafeCell<usize>,
+}
+
+unsafe impl<T: Send, const N: usize> Sync for AtomicList<T, N> {}
+
+impl<T, const N: usize> AtomicList<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { Slot::empty() }; N],
+            len: AtomicUsize::new(0),
+            head: UnsafeCell::new(0),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    // The number of claimed slots. Other threads may change it at any moment.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push(&self, value: T) -> Result<(), Error> {
+        let mut len = self.len.load(Ordering::Relaxed);
+
+        loop {
+            if len >= N {
+                return Err(Error::CapacityReached);
+            }
+
+            match self
+                .len
+                .compare_exchange_weak(len, len + 1, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(current) => len = current,
+            }
+        }
+
+        // Use .get() to elide the bounds check panic branch.
+        let slot = self.slots.get(len).ok_or(Error::CapacityReached)?;
+
+        unsafe {
+            // SAFETY: Winning the exchange gives us exclusive access to this
+            // slot until we publish it.
+            (*slot.value.get()).write(value);
+        }
+
+        slot.ready.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    // Hands every published value to `f`, in push order. Values that are still
+    // being written are left for the next drain.
+    // Only one thread may drain a list.
+    pub unsafe fn drain(&self, mut f: impl FnMut(T)) {
+        let head = &mut *self.head.get();
+
+        while let Some(slot) = self.slots.get(*head) {
+            if !slot.ready.load(Ordering::Acquire) {
+                break;
+            }
+
+            let value = (*slot.value.get()).assume_init_read();
+            slot.ready.store(false, Ordering::Relaxed);
+            *head += 1;
+            f(value);
+        }
+
+        // If no producer claimed a slot past what we drained, start over from
+        // the front. Otherwise, pick up where we left off next time.
+        if self
+            .len
+            .compare_exchange(*head, 0, Ordering::Release, Ordering::Relaxed)
+            .is_ok()
+        {
+            *head = 0;
+        }
+    }
+}
+
+impl<T, const N: usize> Default for AtomicList<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for AtomicList<T, N> {
+    fn drop(&mut self) {
+        unsafe {
+            // Drop values that were pushed but never drained.
+            self.drain(drop);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use super::Error;
+
+use core::mem;
+use core::ptr;
+use windows::Win32::System::Memory::{
+    VirtualAlloc, VirtualFree, MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_READWRITE,
+};
+
+// The most chunks a list can hold. With 4096-element chunks, that's room for a
+// million elements before pushes start failing.
+const MAX_CHUNKS: usize = 256;
+
+// A growable list that allocates storage in chunks of `CHUNK` elements.
+// Chunks are never moved or freed until the list is dropped, so references to
+// elements stay valid as the list grows.
+pub struct ChunkedList<T, const CHUNK: usize> {
+    chunks: [*mut T; MAX_CHUNKS],
+    len: usize,
+}
+
+impl<T, const CHUNK: usize> ChunkedList<T, CHUNK> {
+    pub const fn new() -> Self {
+        Self {
+            chunks: [ptr::null_mut(); MAX_CHUNKS],
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn capacity(&self) -> usize {
+        CHUNK * MAX_CHUNKS
+    }
+
+    pub fn push(&mut self, value: T) -> Result<(), Error> {
+        let chunk = self.len / CHUNK;
+        let offset = self.len % CHUNK;
+
+        let data = *self.chunks.get(chunk).ok_or(Error::CapacityReached)?;
+
+        let data = if data.is_null() {
+            let data = unsafe { allocate::<T>(CHUNK) };
+
+            if data.is_null() {
+                return Err(Error::AllocationFailed);
+            }
+
+            self.chunks[chunk] = data;
+            data
+        } else {
+            data
+        };
+
+        unsafe {
+            data.add(offset).write(value);
+        }
+
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn get(&self, index: usize) -> Result<&T, Error> {
+        if index < self.len {
+            unsafe { Ok(self.get_unchecked(index)) }
+        } else {
+            Err(Error::BadGetIndex(index, self.len))
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Result<&mut T, Error> {
+        if index < self.len {
+            unsafe { Ok(self.get_unchecked_mut(index)) }
+        } else {
+            Err(Error::BadGetIndex(index, self.len))
+        }
+    }
+
+    pub unsafe fn get_unchecked(&self, index: usize) -> &T {
+        &*self.chunks.get_unchecked(index / CHUNK).add(index % CHUNK)
+    }
+
+    pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut T {
+        &mut *self.chunks.get_unchecked(index / CHUNK).add(index % CHUNK)
+    }
+
+    // Drops every element, but keeps the chunks around for reuse.
+    pub fn clear(&mut self) {
+        let len = self.len;
+        self.len = 0;
+
+        if mem::needs_drop::<T>() {
+            for index in 0..len {
+                unsafe {
+                    ptr::drop_in_place(self.chunks.get_unchecked(index / CHUNK).add(index % CHUNK));
+                }
+            }
+        }
+    }
+
+    pub fn iter(&self) -> ChunkedIter<T, CHUNK> {
+        ChunkedIter {
+            list: self,
+            index: 0,
+        }
+    }
+}
+
+impl<T, const CHUNK: usize> Drop for ChunkedList<T, CHUNK> {
+    fn drop(&mut self) {
+        self.clear();
+
+        for &chunk in self.chunks.iter().take_while(|chunk| !chunk.is_null()) {
+            unsafe {
+                VirtualFree(chunk.cast(), 0, MEM_RELEASE);
+            }
+        }
+    }
+}
+
+impl<T, const CHUNK: usize> Default for ChunkedList<T, CHUNK> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct ChunkedIter<'list, T, const CHUNK: usize> {
+    list: &'list ChunkedList<T, CHUNK>,
+    index: usize,
+}
+
+impl<'list, T, const CHUNK: usize> Iterator for ChunkedIter<'list, T, CHUNK> {
+    type Item = &'list T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.list.get(self.index).ok()?;
+        self.index += 1;
+        Some(element)
+    }
+}
+
+unsafe fn allocate<T>(count: usize) -> *mut T {
+    VirtualAlloc(
+        None,
+        count * mem::size_of::<T>(),
+        MEM_COMMIT | MEM_RESERVE,
+        PAGE_READWRITE,
+    )
+    .cast()
+}
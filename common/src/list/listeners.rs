@@ -0,0 +1,105 @@
+// Callbacks that may add and remove callbacks, themselves included, while
+// they're being called. Removing one mid-dispatch only empties its entry, so
+// the rest keep their places and none is skipped or called twice. Emptied
+// entries are dropped once the outermost dispatch is over. Entries added
+// mid-dispatch are first called by the next one.
+//
+// Dispatches can nest, like ProcessEvent listeners whose own calls go back
+// through ProcessEvent. Everything happens on one thread.
+//
+// static mut LISTENERS: Listeners<Listener, 32> = Listeners::new();
+//
+// Listeners::for_each(ptr::addr_of_mut!(LISTENERS), |listener| listener(object));
+
+use super::{Error, List};
+
+pub struct Listeners<T, const N: usize> {
+    entries: List<Option<T>, N>,
+    // How many dispatches are under way.
+    depth: usize,
+}
+
+impl<T: Clone, const N: usize> Listeners<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: List::new(),
+            depth: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(Option::is_none)
+    }
+
+    // Only for looking. Use for_each to call them.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter().flatten()
+    }
+
+    pub fn add(&mut self, entry: T) -> Result<(), Error> {
+        self.entries.push(Some(entry))
+    }
+
+    // Removes the first entry that `matches`. Returns whether there was one.
+    pub fn remove(&mut self, mut matches: impl FnMut(&T) -> bool) -> bool {
+        let slot = self
+            .entries
+            .as_mut_slice()
+            .iter_mut()
+            .find(|slot| slot.as_ref().is_some_and(&mut matches));
+
+        let Some(slot) = slot else {
+            return false;
+        };
+
+        *slot = None;
+        self.compact();
+        true
+    }
+
+    // Removes every entry that `matches`.
+    pub fn remove_all(&mut self, mut matches: impl FnMut(&T) -> bool) {
+        for slot in self.entries.as_mut_slice() {
+            if slot.as_ref().is_some_and(&mut matches) {
+                *slot = None;
+            }
+        }
+
+        self.compact();
+    }
+
+    pub fn clear(&mut self) {
+        self.remove_all(|_| true);
+    }
+
+    // Calls `f` with a copy of each entry. Takes a pointer, not a reference,
+    // since `f` may add and remove entries through the same static.
+    pub unsafe fn for_each(this: *mut Self, mut f: impl FnMut(T)) {
+        (*this).depth += 1;
+        let len = (*this).entries.len();
+
+        for i in 0..len {
+            let entry = match (*this).entries.get(i) {
+                Ok(Some(entry)) => entry.clone(),
+                _ => continue,
+            };
+
+            f(entry);
+        }
+
+        (*this).depth -= 1;
+        (*this).compact();
+    }
+
+    fn compact(&mut self) {
+        if self.depth == 0 {
+            self.entries.retain(|slot| slot.is_some());
+        }
+    }
+}
+
+impl<T: Clone, const N: usize> Default for Listeners<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,424 @@
+// Which objects exist, by class and by name, kept up to date as the engine
+// creates and destroys them, so that finding every object of a class or
+// looking one up by name doesn't mean walking all of GUObjectArray.
+//
+// We add ourselves to FUObjectArray's create and delete listeners, which the
+// engine calls from whichever thread made or destroyed the object. They only
+// queue the object's index. The queue is applied by whoever asks next. If it
+// ever fills up, like during a big level load, the next query rebuilds
+// everything from GUObjectArray instead. A query made while another thread is
+// using the index walks GUObjectArray too.
+//
+// Objects are indexed by the name they were created with. The engine doesn't
+// tell listeners about renames, which cooked games rarely do.
+//
+// Classes are keyed by address, so each key keeps a handle to its class too.
+// A key goes when its last object does, or when the class itself is deleted,
+// and one whose class is gone is skipped rather than read.
+//
+// The listener arrays' place in FUObjectArray isn't reflected anywhere, so
+// they're checked to look like arrays before we write to them, and attaching
+// fails if they don't.
+//
+// let _listeners = live::Listeners::new();
+// ...
+// for pawn in live::objects_of(pawn_class) { ... }
+
+use crate::queue::Queue;
+use crate::{memory, FNameEntryId, GUObjectArray, ObjectHandle, TArray, UClass, UObject};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::mem;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, Ordering};
+use windows::Win32::System::Threading::{EnterCriticalSection, LeaveCriticalSection};
+
+// Enough for everything a level usually creates between two queries.
+const MAX_CHANGES: usize = 1 << 16;
+
+// More listeners than any engine registers. A bigger array isn't one.
+const MAX_LISTENERS: i32 = 1024;
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    // The listener arrays aren't where FUObjectArray's layout says.
+    UnexpectedCreateListeners,
+    UnexpectedDeleteListeners,
+}
+
+#[derive(Copy, Clone)]
+enum Change {
+    Created(i32),
+    Deleted(i32),
+}
+
+static CHANGES: Queue<Change, MAX_CHANGES> = Queue::new();
+static OVERFLOWED: AtomicBool = AtomicBool::new(true);
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
+// Held by whichever thread is using the maps below.
+static BUSY: AtomicBool = AtomicBool::new(false);
+
+static mut BY_CLASS: BTreeMap<*const UClass, Instances> = BTreeMap::new();
+static mut BY_NAME: BTreeMap<FNameEntryId, BTreeSet<i32>> = BTreeMap::new();
+
+// What each indexed object was indexed under.
+static mut ENTRIES: BTreeMap<i32, Entry> = BTreeMap::new();
+
+struct Instances {
+    class: ObjectHandle<UClass>,
+    indices: BTreeSet<i32>,
+}
+
+#[derive(Copy, Clone)]
+struct Entry {
+    object: *const UObject,
+    class: *const UClass,
+    name: FNameEntryId,
+}
+
+// FUObjectCreateListener and FUObjectDeleteListener. Their only data is the
+// vtable, whose first slot is MSVC's scalar deleting destructor.
+#[repr(C)]
+struct Listener<V: 'static> {
+    vtable: &'static V,
+}
+
+#[repr(C)]
+struct CreateVTable {
+    destructor: unsafe extern "C" fn(this: *mut c_void, flags: u32) -> *mut c_void,
+    notify_created: unsafe extern "C" fn(this: *mut c_void, object: *const UObject, index: i32),
+    on_shutdown: unsafe extern "C" fn(this: *mut c_void),
+}
+
+#[repr(C)]
+struct DeleteVTable {
+    destructor: unsafe extern "C" fn(this: *mut c_void, flags: u32) -> *mut c_void,
+    notify_deleted: unsafe extern "C" fn(this: *mut c_void, object: *const UObject, index: i32),
+    on_shutdown: unsafe extern "C" fn(this: *mut c_void),
+    allocated_size: unsafe extern "C" fn(this: *const c_void) -> usize,
+}
+
+static CREATE_VTABLE: CreateVTable = CreateVTable {
+    destructor,
+    notify_created,
+    on_shutdown,
+};
+
+static DELETE_VTABLE: DeleteVTable = DeleteVTable {
+    destructor,
+    notify_deleted,
+    on_shutdown,
+    allocated_size,
+};
+
+static mut CREATE_LISTENER: Listener<CreateVTable> = Listener {
+    vtable: &CREATE_VTABLE,
+};
+
+static mut DELETE_LISTENER: Listener<DeleteVTable> = Listener {
+    vtable: &DELETE_VTABLE,
+};
+
+// Listens while alive. Must be dropped before we unload, or the engine calls
+// into freed code.
+pub struct Listeners;
+
+impl Listeners {
+    pub unsafe fn new() -> Result<Self, Error> {
+        let array = &mut *GUObjectArray.cast_mut();
+
+        if !is_plausible(&array.UObjectCreateListeners) {
+            return Err(Error::UnexpectedCreateListeners);
+        }
+
+        if !is_plausible(&array.UObjectDeleteListeners) {
+            return Err(Error::UnexpectedDeleteListeners);
+        }
+
+        // The engine only locks it for the delete listeners, but holding it
+        // for both keeps us from racing another listener being added.
+        let critical = ptr::addr_of_mut!(array.UObjectDeleteListenersCritical).cast();
+
+        OVERFLOWED.store(true, Ordering::Release);
+
+        EnterCriticalSection(critical);
+        add(&mut array.UObjectCreateListeners, create_listener());
+        add(&mut array.UObjectDeleteListeners, delete_listener());
+        LeaveCriticalSection(critical);
+
+        LISTENING.store(true, Ordering::Release);
+        Ok(Self)
+    }
+}
+
+impl Drop for Listeners {
+    fn drop(&mut self) {
+        unsafe {
+            let array = &mut *GUObjectArray.cast_mut();
+            let critical = ptr::addr_of_mut!(array.UObjectDeleteListenersCritical).cast();
+
+            LISTENING.store(false, Ordering::Release);
+
+            EnterCriticalSection(critical);
+            remove(&mut array.UObjectCreateListeners, create_listener());
+            remove(&mut array.UObjectDeleteListeners, delete_listener());
+            LeaveCriticalSection(critical);
+
+            while BUSY.swap(true, Ordering::Acquire) {
+                core::hint::spin_loop();
+            }
+
+            clear();
+            BUSY.store(false, Ordering::Release);
+        }
+    }
+}
+
+// Every live object that is a `class`, or a subclass of it, including default
+// objects. Empty for a null class.
+pub unsafe fn objects_of(class: *const UClass) -> Vec<*mut UObject> {
+    if class.is_null() {
+        return Vec::new();
+    }
+
+    let indexed = with_index(|| {
+        BY_CLASS
+            .values()
+            .filter(|instances| {
+                instances
+                    .class
+                    .resolve()
+                    .is_some_and(|c| (*c).is(class.cast()))
+            })
+            .flat_map(|instances| instances.indices.iter())
+            .filter_map(|&index| object(index))
+            .collect()
+    });
+
+    indexed.unwrap_or_else(|| {
+        (*GUObjectArray)
+            .iter()
+            .filter(|&object| !object.is_null() && (*object).is(class))
+            .collect()
+    })
+}
+
+// Whether the listeners are in, so that there's an index to ask.
+pub fn is_listening() -> bool {
+    LISTENING.load(Ordering::Acquire)
+}
+
+// The live objects called `name`, in GUObjectArray's order. None if there's no
+// index to ask, in which case look through GUObjectArray instead.
+pub unsafe fn named(name: FNameEntryId) -> Option<Vec<*mut UObject>> {
+    with_index(|| {
+        BY_NAME
+            .get(&name)
+            .into_iter()
+            .flatten()
+            .filter_map(|&index| object(index))
+            .collect()
+    })
+}
+
+unsafe fn with_index<R>(f: impl FnOnce() -> R) -> Option<R> {
+    if !LISTENING.load(Ordering::Acquire) || BUSY.swap(true, Ordering::Acquire) {
+        return None;
+    }
+
+    // Dropped while we waited for BUSY.
+    let result = LISTENING.load(Ordering::Acquire).then(|| {
+        update();
+        f()
+    });
+
+    BUSY.store(false, Ordering::Release);
+    result
+}
+
+// Applies the queued changes, or rebuilds if some were lost.
+unsafe fn update() {
+    if OVERFLOWED.swap(false, Ordering::AcqRel) {
+        // The queue only has changes we're about to see anyway.
+        while CHANGES.pop().is_some() {}
+
+        clear();
+
+        for (index, object) in (*GUObjectArray).iter().enumerate() {
+            if !object.is_null() {
+                insert(index as i32, object);
+            }
+        }
+
+        return;
+    }
+
+    while let Some(change) = CHANGES.pop() {
+        match change {
+            Change::Created(index) => {
+                // It may have been destroyed since. Its Deleted is queued.
+                if let Some(object) = object(index) {
+                    erase(index);
+                    insert(index, object);
+                }
+            }
+            Change::Deleted(index) => erase(index),
+        }
+    }
+}
+
+unsafe fn insert(index: i32, object: *mut UObject) {
+    let class = (*object).class();
+    let name = (*object).NamePrivate.comparison_index();
+    let handle = ObjectHandle::new(class.cast_mut());
+
+    let instances = BY_CLASS.entry(class).or_insert_with(|| Instances {
+        class: handle,
+        indices: BTreeSet::new(),
+    });
+
+    // A new class at the address of one that's gone.
+    if instances.class != handle {
+        instances.class = handle;
+        instances.indices.clear();
+    }
+
+    instances.indices.insert(index);
+    BY_NAME.entry(name).or_default().insert(index);
+
+    ENTRIES.insert(
+        index,
+        Entry {
+            object,
+            class,
+            name,
+        },
+    );
+}
+
+unsafe fn erase(index: i32) {
+    let Some(entry) = ENTRIES.remove(&index) else {
+        return;
+    };
+
+    if let Some(instances) = BY_CLASS.get_mut(&entry.class) {
+        instances.indices.remove(&index);
+
+        if instances.indices.is_empty() {
+            BY_CLASS.remove(&entry.class);
+        }
+    }
+
+    // If it was a class, its key is about to dangle.
+    BY_CLASS.remove(&entry.object.cast::<UClass>());
+
+    if let Some(indices) = BY_NAME.get_mut(&entry.name) {
+        indices.remove(&index);
+
+        if indices.is_empty() {
+            BY_NAME.remove(&entry.name);
+        }
+    }
+}
+
+unsafe fn clear() {
+    BY_CLASS.clear();
+    BY_NAME.clear();
+    ENTRIES.clear();
+}
+
+unsafe fn object(index: i32) -> Option<*mut UObject> {
+    let item = (*GUObjectArray).index_to_object(index);
+
+    if item.is_null() || (*item).Object.is_null() || !(*item).is_valid() {
+        None
+    } else {
+        Some((*item).Object)
+    }
+}
+
+unsafe fn create_listener() -> *mut c_void {
+    ptr::addr_of_mut!(CREATE_LISTENER).cast()
+}
+
+unsafe fn delete_listener() -> *mut c_void {
+    ptr::addr_of_mut!(DELETE_LISTENER).cast()
+}
+
+fn push(change: Change) {
+    if CHANGES.push(change).is_err() {
+        OVERFLOWED.store(true, Ordering::Release);
+    }
+}
+
+unsafe extern "C" fn notify_created(_: *mut c_void, _: *const UObject, index: i32) {
+    push(Change::Created(index));
+}
+
+unsafe extern "C" fn notify_deleted(_: *mut c_void, _: *const UObject, index: i32) {
+    push(Change::Deleted(index));
+}
+
+unsafe extern "C" fn on_shutdown(_: *mut c_void) {
+    LISTENING.store(false, Ordering::Release);
+}
+
+// Our listeners are statics, so there's nothing to destroy.
+unsafe extern "C" fn destructor(this: *mut c_void, _: u32) -> *mut c_void {
+    this
+}
+
+unsafe extern "C" fn allocated_size(_: *const c_void) -> usize {
+    0
+}
+
+// Whether `array` looks like a TArray of listeners: a sane length and
+// capacity, and a buffer if it has room for any.
+unsafe fn is_plausible(array: &TArray<*mut c_void>) -> bool {
+    (0..=array.capacity).contains(&array.len)
+        && array.capacity <= MAX_LISTENERS
+        && (array.capacity == 0 || !array.data.is_null())
+        && (array.data as usize).is_multiple_of(mem::align_of::<*mut c_void>())
+        && array.iter().all(|listener| !listener.is_null())
+}
+
+// Other threads may be walking `array` as we add to it. Rather than let the
+// engine's realloc free the buffer out from under them, we copy into a new one
+// and leak the old.
+unsafe fn add(array: &mut TArray<*mut c_void>, listener: *mut c_void) {
+    if array.contains(&listener) {
+        return;
+    }
+
+    if array.len == array.capacity {
+        let capacity = (array.capacity * 2).max(4);
+
+        let data = memory::malloc(
+            capacity as usize * mem::size_of::<*mut c_void>(),
+            memory::DEFAULT_ALIGNMENT,
+        )
+        .cast::<*mut c_void>();
+
+        if data.is_null() {
+            return;
+        }
+
+        if array.len > 0 {
+            ptr::copy_nonoverlapping(array.data, data, array.len as usize);
+        }
+
+        array.data = data;
+        array.capacity = capacity;
+    }
+
+    array.data.add(array.len as usize).write(listener);
+    array.len += 1;
+}
+
+unsafe fn remove(array: &mut TArray<*mut c_void>, listener: *mut c_void) {
+    if let Some(index) = array.iter().position(|&l| l == listener) {
+        array.remove(index);
+    }
+}
@@ -0,0 +1,265 @@
+// TMap<K, V> as laid out by the engine: a TSet of pairs stored in a sparse
+// array, with a separate hash of element ids. Implements lookup, insertion and
+// iteration so that we can register things without calling engine code.
+
+use crate::{memory, FName, TArray, TPair};
+
+use core::mem;
+use core::ptr;
+
+// An index into the sparse array, or -1.
+const INDEX_NONE: i32 = -1;
+
+// TBitArray's default allocator keeps this many bits inline.
+const INLINE_BITS: i32 = 4 * 32;
+
+pub trait TypeHash {
+    fn type_hash(&self) -> u32;
+}
+
+impl TypeHash for FName {
+    // GetTypeHash(FName)
+    fn type_hash(&self) -> u32 {
+        self.comparison_index().value().wrapping_add(self.number())
+    }
+}
+
+#[repr(C)]
+struct TSetElement<T> {
+    Value: T,
+    HashNextId: i32,
+    HashIndex: i32,
+}
+
+// Unused slots in the sparse array hold a link in its free list instead of an
+// element.
+#[derive(Copy, Clone)]
+#[repr(C)]
+struct FreeListLink {
+    PrevFreeIndex: i32,
+    NextFreeIndex: i32,
+}
+
+#[repr(C)]
+struct TBitArray {
+    InlineData: [u32; 4],
+    SecondaryData: *mut u32,
+    NumBits: i32,
+    MaxBits: i32,
+}
+
+impl TBitArray {
+    fn words(&mut self) -> *mut u32 {
+        if self.SecondaryData.is_null() {
+            self.InlineData.as_mut_ptr()
+        } else {
+            self.SecondaryData
+        }
+    }
+
+    unsafe fn get(&self, index: i32) -> bool {
+        let words = if self.SecondaryData.is_null() {
+            self.InlineData.as_ptr()
+        } else {
+            self.SecondaryData.cast_const()
+        };
+
+        index < self.NumBits && *words.add(index as usize / 32) & (1 << (index % 32)) != 0
+    }
+
+    unsafe fn set(&mut self, index: i32) {
+        let words = self.words();
+        *words.add(index as usize / 32) |= 1 << (index % 32);
+    }
+
+    unsafe fn push(&mut self, value: bool) {
+        if self.NumBits == self.MaxBits {
+            let max_bits = (self.MaxBits * 2).max(INLINE_BITS);
+
+            if max_bits > INLINE_BITS {
+                let words = max_bits as usize / 32;
+                let bytes = words * mem::size_of::<u32>();
+                let was_inline = self.SecondaryData.is_null();
+
+                self.SecondaryData =
+                    memory::realloc(self.SecondaryData.cast(), bytes, memory::DEFAULT_ALIGNMENT)
+                        .cast();
+
+                if was_inline {
+                    ptr::copy_nonoverlapping(self.InlineData.as_ptr(), self.SecondaryData, 4);
+                }
+            }
+
+            self.MaxBits = max_bits;
+        }
+
+        let index = self.NumBits;
+        self.NumBits += 1;
+
+        let words = self.words();
+        *words.add(index as usize / 32) &= !(1 << (index % 32));
+
+        if value {
+            self.set(index);
+        }
+    }
+}
+
+#[repr(C)]
+struct TSparseArray<T> {
+    Data: TArray<T>,
+    AllocationFlags: TBitArray,
+    FirstFreeIndex: i32,
+    NumFreeIndices: i32,
+}
+
+impl<T> TSparseArray<T> {
+    fn len(&self) -> usize {
+        (self.Data.len - self.NumFreeIndices).max(0) as usize
+    }
+
+    // The allocated elements, skipping the free list's slots.
+    unsafe fn iter(&self) -> impl Iterator<Item = &T> {
+        self.Data
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| self.AllocationFlags.get(index as i32))
+            .map(|(_, element)| element)
+    }
+
+    // Returns the index of the new element.
+    unsafe fn add(&mut self, value: T) -> i32 {
+        if self.NumFreeIndices > 0 {
+            let index = self.FirstFreeIndex;
+            let slot = self.Data.as_mut_ptr().add(index as usize);
+            let link = slot.cast::<FreeListLink>().read();
+
+            self.FirstFreeIndex = link.NextFreeIndex;
+            self.NumFreeIndices -= 1;
+
+            if self.NumFreeIndices > 0 {
+                let next = self.Data.as_mut_ptr().add(link.NextFreeIndex as usize);
+                (*next.cast::<FreeListLink>()).PrevFreeIndex = INDEX_NONE;
+            }
+
+            slot.write(value);
+            self.AllocationFlags.set(index);
+            index
+        } else {
+            let index = self.Data.len;
+            self.Data.push(value);
+            self.AllocationFlags.push(true);
+            index
+        }
+    }
+}
+
+#[repr(C)]
+struct TSet<T> {
+    Elements: TSparseArray<TSetElement<T>>,
+    InlineHash: [i32; 1],
+    SecondaryHash: *mut i32,
+    HashSize: i32,
+}
+
+impl<T> TSet<T> {
+    fn hash(&mut self) -> *mut i32 {
+        if self.SecondaryHash.is_null() {
+            self.InlineHash.as_mut_ptr()
+        } else {
+            self.SecondaryHash
+        }
+    }
+
+    unsafe fn find(&mut self, hash: u32, mut matches: impl FnMut(&T) -> bool) -> *mut T {
+        if self.HashSize == 0 {
+            return ptr::null_mut();
+        }
+
+        let bucket = hash & (self.HashSize as u32 - 1);
+        let mut id = *self.hash().add(bucket as usize);
+
+        while id != INDEX_NONE {
+            let element = self.Elements.Data.as_mut_ptr().add(id as usize);
+
+            if matches(&(*element).Value) {
+                return ptr::addr_of_mut!((*element).Value);
+            }
+
+            id = (*element).HashNextId;
+        }
+
+        ptr::null_mut()
+    }
+
+    // Doesn't check for an existing element, and never rehashes. The engine
+    // rehashes on its own when it next adds to the set.
+    unsafe fn add(&mut self, hash: u32, value: T) {
+        if self.HashSize == 0 {
+            self.HashSize = 1;
+            self.InlineHash[0] = INDEX_NONE;
+        }
+
+        let bucket = (hash & (self.HashSize as u32 - 1)) as i32;
+        let head = self.hash().add(bucket as usize);
+
+        let id = self.Elements.add(TSetElement {
+            Value: value,
+            HashNextId: *head,
+            HashIndex: bucket,
+        });
+
+        *head = id;
+    }
+}
+
+#[repr(C)]
+pub struct TMap<K, V> {
+    Pairs: TSet<TPair<K, V>>,
+}
+
+impl<K, V> TMap<K, V> {
+    // In the order the engine iterates them.
+    pub unsafe fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.Pairs
+            .Elements
+            .iter()
+            .map(|element| (&element.Value.Key, &element.Value.Value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.Pairs.Elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: TypeHash + PartialEq, V> TMap<K, V> {
+    pub unsafe fn get(&mut self, key: &K) -> Option<&mut V> {
+        let pair = self.Pairs.find(key.type_hash(), |pair| pair.Key == *key);
+
+        if pair.is_null() {
+            None
+        } else {
+            Some(&mut (*pair).Value)
+        }
+    }
+
+    // Inserts `value`, or replaces the value already under `key`.
+    pub unsafe fn insert(&mut self, key: K, value: V) {
+        if let Some(existing) = self.get(&key) {
+            *existing = value;
+        } else {
+            let hash = key.type_hash();
+            self.Pairs.add(
+                hash,
+                TPair {
+                    Key: key,
+                    Value: value,
+                },
+            );
+        }
+    }
+}
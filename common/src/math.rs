@@ -0,0 +1,587 @@
+// The engine's core math types, laid out like their reflected structs so that
+// generated SDK fields can use them directly. Follows the engine's
+// conventions: X is forward, Y is right, Z is up, angles are in degrees, and
+// matrices are row-major for row vectors on the left.
+
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// Below this, a length counts as zero.
+const SMALL_NUMBER: f32 = 1.0e-8;
+
+// How close FQuat::rotator() lets the pitch get to straight up or down before
+// it picks a yaw and roll itself.
+const SINGULARITY_THRESHOLD: f32 = 0.4999995;
+
+#[derive(Copy, Clone, Default, PartialEq)]
+#[repr(C)]
+pub struct FVector {
+    pub X: f32,
+    pub Y: f32,
+    pub Z: f32,
+}
+
+impl FVector {
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0);
+    pub const ONE: Self = Self::new(1.0, 1.0, 1.0);
+    pub const FORWARD: Self = Self::new(1.0, 0.0, 0.0);
+    pub const RIGHT: Self = Self::new(0.0, 1.0, 0.0);
+    pub const UP: Self = Self::new(0.0, 0.0, 1.0);
+
+    pub const fn new(X: f32, Y: f32, Z: f32) -> Self {
+        Self { X, Y, Z }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.X * other.X + self.Y * other.Y + self.Z * other.Z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.Y * other.Z - self.Z * other.Y,
+            self.Z * other.X - self.X * other.Z,
+            self.X * other.Y - self.Y * other.X,
+        )
+    }
+
+    pub fn size_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    pub fn size(self) -> f32 {
+        self.size_squared().sqrt()
+    }
+
+    pub fn distance(self, other: Self) -> f32 {
+        (other - self).size()
+    }
+
+    // The same direction with length 1, or zero if too short to have a
+    // direction.
+    pub fn normal(self) -> Self {
+        let size = self.size();
+
+        if size < SMALL_NUMBER {
+            Self::ZERO
+        } else {
+            self * (1.0 / size)
+        }
+    }
+
+    // Multiplies each component separately.
+    pub fn scale(self, other: Self) -> Self {
+        Self::new(self.X * other.X, self.Y * other.Y, self.Z * other.Z)
+    }
+
+    // The rotation that points forward along this vector, without roll.
+    pub fn rotation(self) -> FRotator {
+        FRotator {
+            Pitch: self
+                .Z
+                .atan2((self.X * self.X + self.Y * self.Y).sqrt())
+                .to_degrees(),
+            Yaw: self.Y.atan2(self.X).to_degrees(),
+            Roll: 0.0,
+        }
+    }
+}
+
+impl Add for FVector {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.X + other.X, self.Y + other.Y, self.Z + other.Z)
+    }
+}
+
+impl AddAssign for FVector {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for FVector {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.X - other.X, self.Y - other.Y, self.Z - other.Z)
+    }
+}
+
+impl SubAssign for FVector {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<f32> for FVector {
+    type Output = Self;
+
+    fn mul(self, scale: f32) -> Self {
+        Self::new(self.X * scale, self.Y * scale, self.Z * scale)
+    }
+}
+
+impl MulAssign<f32> for FVector {
+    fn mul_assign(&mut self, scale: f32) {
+        *self = *self * scale;
+    }
+}
+
+impl Div<f32> for FVector {
+    type Output = Self;
+
+    fn div(self, scale: f32) -> Self {
+        Self::new(self.X / scale, self.Y / scale, self.Z / scale)
+    }
+}
+
+impl Neg for FVector {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.X, -self.Y, -self.Z)
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq)]
+#[repr(C)]
+pub struct FVector2D {
+    pub X: f32,
+    pub Y: f32,
+}
+
+impl FVector2D {
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+
+    pub const fn new(X: f32, Y: f32) -> Self {
+        Self { X, Y }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.X * other.X + self.Y * other.Y
+    }
+
+    pub fn size(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn distance(self, other: Self) -> f32 {
+        (other - self).size()
+    }
+}
+
+impl Add for FVector2D {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.X + other.X, self.Y + other.Y)
+    }
+}
+
+impl Sub for FVector2D {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.X - other.X, self.Y - other.Y)
+    }
+}
+
+impl Mul<f32> for FVector2D {
+    type Output = Self;
+
+    fn mul(self, scale: f32) -> Self {
+        Self::new(self.X * scale, self.Y * scale)
+    }
+}
+
+// In degrees.
+#[derive(Copy, Clone, Default, PartialEq)]
+#[repr(C)]
+pub struct FRotator {
+    pub Pitch: f32,
+    pub Yaw: f32,
+    pub Roll: f32,
+}
+
+impl FRotator {
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0);
+
+    pub const fn new(Pitch: f32, Yaw: f32, Roll: f32) -> Self {
+        Self { Pitch, Yaw, Roll }
+    }
+
+    // The same rotation with each angle in (-180, 180].
+    pub fn normalize(self) -> Self {
+        Self::new(
+            normalize_axis(self.Pitch),
+            normalize_axis(self.Yaw),
+            normalize_axis(self.Roll),
+        )
+    }
+
+    // The direction this rotation faces.
+    pub fn vector(self) -> FVector {
+        self.matrix().axes().0
+    }
+
+    // FRotator::Quaternion()
+    pub fn quaternion(self) -> FQuat {
+        let (sp, cp) = (self.Pitch.to_radians() / 2.0).sin_cos();
+        let (sy, cy) = (self.Yaw.to_radians() / 2.0).sin_cos();
+        let (sr, cr) = (self.Roll.to_radians() / 2.0).sin_cos();
+
+        FQuat {
+            X: cr * sp * sy - sr * cp * cy,
+            Y: -cr * sp * cy - sr * cp * sy,
+            Z: cr * cp * sy - sr * sp * cy,
+            W: cr * cp * cy + sr * sp * sy,
+        }
+    }
+
+    // FRotationMatrix: the rows are the forward, right and up axes.
+    pub fn matrix(self) -> FMatrix {
+        let (sp, cp) = self.Pitch.to_radians().sin_cos();
+        let (sy, cy) = self.Yaw.to_radians().sin_cos();
+        let (sr, cr) = self.Roll.to_radians().sin_cos();
+
+        FMatrix {
+            M: [
+                [cp * cy, cp * sy, sp, 0.0],
+                [
+                    sr * sp * cy - cr * sy,
+                    sr * sp * sy + cr * cy,
+                    -sr * cp,
+                    0.0,
+                ],
+                [
+                    -(cr * sp * cy + sr * sy),
+                    cy * sr - cr * sp * sy,
+                    cr * cp,
+                    0.0,
+                ],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn rotate_vector(self, v: FVector) -> FVector {
+        self.matrix().transform_vector(v)
+    }
+}
+
+impl Add for FRotator {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.Pitch + other.Pitch,
+            self.Yaw + other.Yaw,
+            self.Roll + other.Roll,
+        )
+    }
+}
+
+impl Sub for FRotator {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(
+            self.Pitch - other.Pitch,
+            self.Yaw - other.Yaw,
+            self.Roll - other.Roll,
+        )
+    }
+}
+
+fn normalize_axis(angle: f32) -> f32 {
+    let angle = angle.rem_euclid(360.0);
+
+    if angle > 180.0 {
+        angle - 360.0
+    } else {
+        angle
+    }
+}
+
+// A rotation. The engine aligns it for SIMD.
+#[derive(Copy, Clone, PartialEq)]
+#[repr(C, align(16))]
+pub struct FQuat {
+    pub X: f32,
+    pub Y: f32,
+    pub Z: f32,
+    pub W: f32,
+}
+
+impl Default for FQuat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl FQuat {
+    pub const IDENTITY: Self = Self {
+        X: 0.0,
+        Y: 0.0,
+        Z: 0.0,
+        W: 1.0,
+    };
+
+    // A rotation of `angle` radians around `axis`, which must be normalized.
+    pub fn from_axis_angle(axis: FVector, angle: f32) -> Self {
+        let (s, c) = (angle / 2.0).sin_cos();
+
+        Self {
+            X: axis.X * s,
+            Y: axis.Y * s,
+            Z: axis.Z * s,
+            W: c,
+        }
+    }
+
+    fn xyz(self) -> FVector {
+        FVector::new(self.X, self.Y, self.Z)
+    }
+
+    // The opposite rotation. Only valid for normalized quaternions.
+    pub fn inverse(self) -> Self {
+        Self {
+            X: -self.X,
+            Y: -self.Y,
+            Z: -self.Z,
+            W: self.W,
+        }
+    }
+
+    pub fn normalize(self) -> Self {
+        let size = (self.X * self.X + self.Y * self.Y + self.Z * self.Z + self.W * self.W).sqrt();
+
+        if size < SMALL_NUMBER {
+            Self::IDENTITY
+        } else {
+            Self {
+                X: self.X / size,
+                Y: self.Y / size,
+                Z: self.Z / size,
+                W: self.W / size,
+            }
+        }
+    }
+
+    pub fn rotate_vector(self, v: FVector) -> FVector {
+        let q = self.xyz();
+        let t = q.cross(v) * 2.0;
+        v + t * self.W + q.cross(t)
+    }
+
+    pub fn unrotate_vector(self, v: FVector) -> FVector {
+        self.inverse().rotate_vector(v)
+    }
+
+    // FQuat::Rotator()
+    pub fn rotator(self) -> FRotator {
+        let singularity_test = self.Z * self.X - self.W * self.Y;
+        let yaw_y = 2.0 * (self.W * self.Z + self.X * self.Y);
+        let yaw_x = 1.0 - 2.0 * (self.Y * self.Y + self.Z * self.Z);
+        let yaw = yaw_y.atan2(yaw_x).to_degrees();
+        let x_angle = 2.0 * self.X.atan2(self.W).to_degrees();
+
+        if singularity_test < -SINGULARITY_THRESHOLD {
+            FRotator::new(-90.0, yaw, normalize_axis(-yaw - x_angle))
+        } else if singularity_test > SINGULARITY_THRESHOLD {
+            FRotator::new(90.0, yaw, normalize_axis(yaw - x_angle))
+        } else {
+            FRotator::new(
+                (2.0 * singularity_test).asin().to_degrees(),
+                yaw,
+                (-2.0 * (self.W * self.X + self.Y * self.Z))
+                    .atan2(1.0 - 2.0 * (self.X * self.X + self.Y * self.Y))
+                    .to_degrees(),
+            )
+        }
+    }
+
+    // FQuatRotationTranslationMatrix with no translation.
+    pub fn matrix(self) -> FMatrix {
+        let (x2, y2, z2) = (self.X + self.X, self.Y + self.Y, self.Z + self.Z);
+        let (xx, xy, xz) = (self.X * x2, self.X * y2, self.X * z2);
+        let (yy, yz, zz) = (self.Y * y2, self.Y * z2, self.Z * z2);
+        let (wx, wy, wz) = (self.W * x2, self.W * y2, self.W * z2);
+
+        FMatrix {
+            M: [
+                [1.0 - (yy + zz), xy + wz, xz - wy, 0.0],
+                [xy - wz, 1.0 - (xx + zz), yz + wx, 0.0],
+                [xz + wy, yz - wx, 1.0 - (xx + yy), 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+}
+
+// `a * b` rotates by `b` first, then by `a`.
+impl Mul for FQuat {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let (a, b) = (self, other);
+
+        Self {
+            X: a.W * b.X + a.X * b.W + a.Y * b.Z - a.Z * b.Y,
+            Y: a.W * b.Y - a.X * b.Z + a.Y * b.W + a.Z * b.X,
+            Z: a.W * b.Z + a.X * b.Y - a.Y * b.X + a.Z * b.W,
+            W: a.W * b.W - a.X * b.X - a.Y * b.Y - a.Z * b.Z,
+        }
+    }
+}
+
+// Scales, then rotates, then translates. The engine keeps each part in its
+// own SIMD register.
+#[derive(Copy, Clone, PartialEq)]
+#[repr(C, align(16))]
+pub struct FTransform {
+    pub Rotation: FQuat,
+    pub Translation: FVector,
+    pad0: f32,
+    pub Scale3D: FVector,
+    pad1: f32,
+}
+
+impl Default for FTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl FTransform {
+    pub const IDENTITY: Self = Self::new(FQuat::IDENTITY, FVector::ZERO, FVector::ONE);
+
+    pub const fn new(rotation: FQuat, translation: FVector, scale: FVector) -> Self {
+        Self {
+            Rotation: rotation,
+            Translation: translation,
+            pad0: 0.0,
+            Scale3D: scale,
+            pad1: 0.0,
+        }
+    }
+
+    pub fn transform_position(&self, v: FVector) -> FVector {
+        self.transform_vector(v) + self.Translation
+    }
+
+    // Ignores the translation, for directions and offsets.
+    pub fn transform_vector(&self, v: FVector) -> FVector {
+        self.Rotation.rotate_vector(v.scale(self.Scale3D))
+    }
+
+    pub fn inverse_transform_position(&self, v: FVector) -> FVector {
+        self.inverse_transform_vector(v - self.Translation)
+    }
+
+    pub fn inverse_transform_vector(&self, v: FVector) -> FVector {
+        let v = self.Rotation.unrotate_vector(v);
+        let s = self.Scale3D;
+
+        // A zero scale squashes everything onto the same point, so there's no
+        // way back.
+        let inverse = |scale: f32| {
+            if scale.abs() < SMALL_NUMBER {
+                0.0
+            } else {
+                1.0 / scale
+            }
+        };
+
+        v.scale(FVector::new(inverse(s.X), inverse(s.Y), inverse(s.Z)))
+    }
+
+    // FTransform::ToMatrixWithScale()
+    pub fn matrix(&self) -> FMatrix {
+        let mut matrix = self.Rotation.matrix();
+        let scale = [self.Scale3D.X, self.Scale3D.Y, self.Scale3D.Z];
+
+        for (row, scale) in matrix.M.iter_mut().zip(scale) {
+            for value in &mut row[..3] {
+                *value *= scale;
+            }
+        }
+
+        matrix.M[3] = [
+            self.Translation.X,
+            self.Translation.Y,
+            self.Translation.Z,
+            1.0,
+        ];
+        matrix
+    }
+}
+
+// `a * b` applies `a` first, then `b`, like the engine's FTransform.
+impl Mul for FTransform {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            other.Rotation * self.Rotation,
+            other.transform_position(self.Translation),
+            self.Scale3D.scale(other.Scale3D),
+        )
+    }
+}
+
+// Row-major, for row vectors on the left, like the engine's FMatrix.
+#[derive(Copy, Clone, PartialEq)]
+#[repr(C, align(16))]
+pub struct FMatrix {
+    pub M: [[f32; 4]; 4],
+}
+
+impl FMatrix {
+    pub const IDENTITY: Self = Self {
+        M: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub fn multiply(&self, other: &Self) -> Self {
+        let mut M = [[0.0; 4]; 4];
+
+        for (i, row) in M.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.M[i][k] * other.M[k][j]).sum();
+            }
+        }
+
+        Self { M }
+    }
+
+    // The first three rows. For a rotation matrix, the forward, right and up
+    // axes.
+    pub fn axes(&self) -> (FVector, FVector, FVector) {
+        let row = |[x, y, z, _]: [f32; 4]| FVector::new(x, y, z);
+        (row(self.M[0]), row(self.M[1]), row(self.M[2]))
+    }
+
+    // Returns all four components, since a projection divides by W.
+    pub fn transform_position(&self, v: FVector) -> [f32; 4] {
+        let mut out = [0.0; 4];
+
+        for (j, value) in out.iter_mut().enumerate() {
+            *value = v.X * self.M[0][j] + v.Y * self.M[1][j] + v.Z * self.M[2][j] + self.M[3][j];
+        }
+
+        out
+    }
+
+    // Ignores the translation, for directions and offsets.
+    pub fn transform_vector(&self, v: FVector) -> FVector {
+        let (x, y, z) = self.axes();
+        x * v.X + y * v.Y + z * v.Z
+    }
+}
@@ -0,0 +1,106 @@
+// The engine's allocator. Memory that the engine may later grow or free, like
+// the buffer behind a TArray it owns, has to come from here rather than from
+// Rust's heap.
+
+use crate::diagnose;
+use crate::offsets;
+use crate::win;
+
+use core::ffi::c_void;
+use core::ptr;
+
+pub static mut GMalloc: *const *mut FMalloc = ptr::null();
+
+// FMemory's default. Lets the allocator pick an alignment for the size.
+pub const DEFAULT_ALIGNMENT: u32 = 0;
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    FindGMalloc,
+}
+
+#[repr(C)]
+pub struct FMalloc {
+    vtable: *const FMallocVTable,
+}
+
+// FMalloc derives from FExec, whose virtuals come first.
+#[repr(C)]
+struct FMallocVTable {
+    destructor: *const c_void,
+    exec: *const c_void,
+    malloc: unsafe extern "C" fn(this: *mut FMalloc, count: usize, alignment: u32) -> *mut c_void,
+    try_malloc: *const c_void,
+    realloc: unsafe extern "C" fn(
+        this: *mut FMalloc,
+        original: *mut c_void,
+        count: usize,
+        alignment: u32,
+    ) -> *mut c_void,
+    try_realloc: *const c_void,
+    free: unsafe extern "C" fn(this: *mut FMalloc, original: *mut c_void),
+}
+
+pub unsafe fn init(module: &win::Module) -> Result<(), Error> {
+    if let Some(address) = offsets::get("GMalloc") {
+        GMalloc = address as *const _;
+        return Ok(());
+    }
+
+    // FMemory::Free():
+    // 00007FF63707A1F0 | 48:85C9                  | test rcx,rcx                            |
+    // 00007FF63707A1F3 | 74 1F                    | je fsd-win64-shipping.7FF63707A214      |
+    // 00007FF63707A1F5 | 4C:8B05 5C8A9804         | mov r8,qword ptr ds:[7FF63BA02C58]      | <<<< GMalloc
+    // 00007FF63707A1FC | 48:8BD1                  | mov rdx,rcx                             |
+    // 00007FF63707A1FF | 4D:85C0                  | test r8,r8                              |
+    // 00007FF63707A202 | 75 05                    | jne fsd-win64-shipping.7FF63707A209     |
+    const FREE_PATTERN: [Option<u8>; 18] = [
+        Some(0x48),
+        Some(0x85),
+        Some(0xC9),
+        Some(0x74),
+        None,
+        Some(0x4C),
+        Some(0x8B),
+        Some(0x05),
+        None,
+        None,
+        None,
+        None,
+        Some(0x48),
+        Some(0x8B),
+        Some(0xD1),
+        Some(0x4D),
+        Some(0x85),
+        Some(0xC0),
+    ];
+
+    let free: *const u8 = module.find(&FREE_PATTERN).ok_or_else(|| {
+        diagnose::pattern_not_found(module, "GMalloc", &FREE_PATTERN);
+        Error::FindGMalloc
+    })?;
+
+    let mov_immediate = free.add(8);
+    let instruction_after_mov = mov_immediate.add(4);
+    let mov_immediate = mov_immediate.cast::<u32>().read_unaligned();
+
+    GMalloc = instruction_after_mov.add(mov_immediate as usize).cast();
+    Ok(())
+}
+
+pub unsafe fn malloc(count: usize, alignment: u32) -> *mut c_void {
+    let malloc = *GMalloc;
+    ((*(*malloc).vtable).malloc)(malloc, count, alignment)
+}
+
+pub unsafe fn realloc(original: *mut c_void, count: usize, alignment: u32) -> *mut c_void {
+    let malloc = *GMalloc;
+    ((*(*malloc).vtable).realloc)(malloc, original, count, alignment)
+}
+
+pub unsafe fn free(original: *mut c_void) {
+    if !original.is_null() {
+        let malloc = *GMalloc;
+        ((*(*malloc).vtable).free)(malloc, original)
+    }
+}
@@ -1,14 +1,22 @@
+use crate::diagnose;
+use crate::offsets;
 use crate::util;
 use crate::win;
 use crate::Error;
 
+use alloc::vec::Vec;
 use core::cmp::Ordering;
 use core::ffi::c_void;
 use core::fmt::{self, Display, Formatter};
 use core::mem;
 use core::ptr;
+use core::slice;
 use core::str;
 
+mod city_hash;
+pub use city_hash::city_hash_64;
+mod index;
+
 pub static mut NamePoolData: *const FNamePool = ptr::null();
 
 const FNameMaxBlockBits: u8 = 13;
@@ -17,6 +25,13 @@ const FNameMaxBlocks: usize = 1 << FNameMaxBlockBits;
 const FNameBlockOffsets: usize = 1 << FNameBlockOffsetBits;
 const Stride: usize = mem::align_of::<FNameEntry>();
 const BlockSizeBytes: usize = Stride * FNameBlockOffsets;
+const FNamePoolShardBits: u8 = 10;
+const FNamePoolShards: usize = 1 << FNamePoolShardBits;
+
+// A name slot packs an entry id into its low bits and some hash bits above.
+const EntryIdBits: u8 = FNameMaxBlockBits + FNameBlockOffsetBits;
+const EntryIdMask: u32 = (1 << EntryIdBits) - 1;
+const ProbeHashMask: u32 = !EntryIdMask;
 
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -37,8 +52,49 @@ impl FName {
     pub fn number(&self) -> u32 {
         self.Number
     }
+
+    pub fn is_none(&self) -> bool {
+        self.ComparisonIndex.Value == 0 && self.Number == 0
+    }
+
+    pub fn comparison_index(&self) -> FNameEntryId {
+        self.ComparisonIndex
+    }
+
+    // The same name with a different number, e.g. "Foo_3" for "Foo". The
+    // number is stored one higher than it's displayed, with 0 meaning none.
+    // Doesn't add anything to the name pool.
+    pub fn with_number(&self, number: u32) -> FName {
+        FName {
+            ComparisonIndex: self.ComparisonIndex,
+            Number: number,
+        }
+    }
+
+    // Looks up a name that is already in the name pool, ignoring case like the
+    // engine does. `text` is matched whole: a numeric suffix is not split off
+    // into the name's number.
+    pub unsafe fn from_existing(text: &str) -> Option<FName> {
+        Some(FName {
+            ComparisonIndex: (*NamePoolData).find(text)?,
+            Number: 0,
+        })
+    }
+
+    pub unsafe fn eq_ignore_case(&self, text: &str) -> bool {
+        self.text().eq_ignore_ascii_case(text)
+    }
 }
 
+impl PartialEq for FName {
+    fn eq(&self, other: &Self) -> bool {
+        // Comparison indices are shared by names that differ only by case.
+        self.ComparisonIndex == other.ComparisonIndex && self.Number == other.Number
+    }
+}
+
+impl Eq for FName {}
+
 impl Display for FName {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
         unsafe {
@@ -51,7 +107,7 @@ impl Display for FName {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(C)]
 pub struct FNameEntryId {
     Value: u32,
@@ -64,11 +120,13 @@ impl FNameEntryId {
         }
     }
 
-    fn block(&self) -> u32 {
+    // The name pool block that the entry is in.
+    pub fn block(&self) -> u32 {
         self.Value >> FNameBlockOffsetBits
     }
 
-    fn offset(&self) -> u32 {
+    // Where the entry is in its block, in strides.
+    pub fn offset(&self) -> u32 {
         self.Value & (FNameBlockOffsets - 1) as u32
     }
 
@@ -93,10 +151,27 @@ pub struct FNamePool {
     CurrentBlock: u32,
     CurrentByteCursor: u32,
     Blocks: [*const u8; FNameMaxBlocks],
+    ComparisonShards: [FNamePoolShard; FNamePoolShards],
+}
+
+#[repr(C, align(64))]
+struct FNamePoolShard {
+    Lock: *mut c_void,
+    UsedSlots: u32,
+    CapacityMask: u32,
+    Slots: *const u32,
+    Entries: *const FNamePool,
+    NumCreatedEntries: u32,
+    NumCreatedWideEntries: u32,
 }
 
 impl FNamePool {
     pub unsafe fn init(module: &win::Module) -> Result<(), Error> {
+        if let Some(address) = offsets::get("NamePoolData") {
+            NamePoolData = address as *const _;
+            return Ok(());
+        }
+
         // 00007FF7F9DC1F96 | 897424 30                | mov dword ptr ss:[rsp+30],esi                           |
         // 00007FF7F9DC1F9A | 894424 34                | mov dword ptr ss:[rsp+34],eax                           |
         // 00007FF7F9DC1F9E | 74 09                    | je fsd-win64-shipping.7FF7F9DC1FA9                      |
@@ -124,9 +199,10 @@ impl FNamePool {
         ];
 
         // 00007FF7F9DC1F96 | 897424 30                | mov dword ptr ss:[rsp+30],esi                           |
-        let mov: *const u8 = module
-            .find(&NAME_POOL_DATA_PATTERN)
-            .ok_or(Error::FindNamePoolData)?;
+        let mov: *const u8 = module.find(&NAME_POOL_DATA_PATTERN).ok_or_else(|| {
+            diagnose::pattern_not_found(module, "NamePoolData", &NAME_POOL_DATA_PATTERN);
+            Error::FindNamePoolData
+        })?;
 
         // 00007FF7F9DC1FA7 | EB 16                    | jmp fsd-win64-shipping.7FF7F9DC1FBF                     |
         let instruction_after_lea = mov.add(NAME_POOL_DATA_PATTERN.len());
@@ -143,19 +219,84 @@ impl FNamePool {
         Ok(())
     }
 
+    pub unsafe fn find(&self, text: &str) -> Option<FNameEntryId> {
+        let text = text.as_bytes();
+
+        if text.is_empty() || text.len() > NAME_SIZE {
+            return None;
+        }
+
+        // The hashed lookup reads the pool's slots without taking the engine's
+        // lock, so fall back to our own index if it comes up empty.
+        self.find_hashed(text).or_else(|| index::find(self, text))
+    }
+
+    unsafe fn find_hashed(&self, text: &[u8]) -> Option<FNameEntryId> {
+        let hash = lowercase_hash(text)?;
+        let hi = (hash >> 32) as u32;
+        let lo = hash as u32;
+
+        // "None" is entry id 0, so the engine sets a hash bit to tell its slot
+        // apart from an unused one.
+        let is_none_bit = u32::from(text.eq_ignore_ascii_case(b"None")) << EntryIdBits;
+        let probe_hash = (hi & ProbeHashMask) | is_none_bit;
+
+        let shard = self
+            .ComparisonShards
+            .get(hi as usize & (FNamePoolShards - 1))?;
+
+        if shard.Slots.is_null() {
+            return None;
+        }
+
+        let mut index = lo & shard.CapacityMask;
+
+        for _ in 0..=shard.CapacityMask {
+            let slot = *shard.Slots.add(index as usize);
+
+            if slot == 0 {
+                // Hit an unused slot. The name isn't in this shard.
+                return None;
+            }
+
+            if slot & ProbeHashMask == probe_hash {
+                let id = FNameEntryId {
+                    Value: slot & EntryIdMask,
+                };
+
+                if (*id.entry()).eq_ignore_case(text) {
+                    return Some(id);
+                }
+            }
+
+            index = (index + 1) & shard.CapacityMask;
+        }
+
+        None
+    }
+
     pub unsafe fn iter(&self) -> NameIterator {
-        let first_block_size = if self.CurrentBlock > 0 {
+        self.iter_from(0, 0)
+    }
+
+    // Resumes iteration at a position previously returned by
+    // NameIterator::position(). Entries are only ever appended, so this picks
+    // up any names added since.
+    unsafe fn iter_from(&self, block: u32, byte_offset: usize) -> NameIterator {
+        let block_size = if block < self.CurrentBlock {
             BlockSizeBytes
         } else {
             self.CurrentByteCursor as usize
         };
 
+        let block_start = *self.Blocks.get_unchecked(block as usize);
+
         NameIterator {
             pool: self,
-            block: 0,
-            block_start: self.Blocks[0],
-            cursor_within_block: self.Blocks[0],
-            block_end: self.Blocks[0].add(first_block_size - mem::size_of::<FNameEntryHeader>()),
+            block,
+            block_start,
+            cursor_within_block: block_start.add(byte_offset),
+            block_end: block_start.add(block_size - mem::size_of::<FNameEntryHeader>()),
         }
     }
 }
@@ -168,6 +309,16 @@ pub struct NameIterator<'pool> {
     block_end: *const u8,
 }
 
+impl NameIterator<'_> {
+    // Where the next entry would be, as a block and a byte offset into it.
+    fn position(&self) -> (u32, usize) {
+        (
+            self.block,
+            self.cursor_within_block as usize - self.block_start as usize,
+        )
+    }
+}
+
 impl Iterator for NameIterator<'_> {
     type Item = (FNameEntryId, *const FNameEntry);
 
@@ -227,6 +378,19 @@ impl Iterator for NameIterator<'_> {
     }
 }
 
+// CityHash64 of the text in lowercase, which is how the engine hashes names.
+// None if the text is too long to be a name.
+fn lowercase_hash(text: &[u8]) -> Option<u64> {
+    let mut buffer = [0; NAME_SIZE];
+    let lowercase = buffer.get_mut(..text.len())?;
+
+    for (l, c) in lowercase.iter_mut().zip(text) {
+        *l = c.to_ascii_lowercase();
+    }
+
+    Some(city_hash::city_hash_64(lowercase))
+}
+
 #[repr(C)]
 struct FNameEntryHeader {
     bitfield: u16,
@@ -259,6 +423,34 @@ impl FNameEntry {
         usize::from(self.Header.len())
     }
 
+    pub fn is_wide(&self) -> bool {
+        self.Header.is_wide()
+    }
+
+    // The hash the name pool files the entry under, like FNameHash. Its high
+    // half picks the shard and its low half the slot. Wide names only have
+    // their ASCII letters lowercased, where the engine lowercases every letter.
+    pub unsafe fn hash(&self) -> u64 {
+        if !self.Header.is_wide() {
+            return lowercase_hash(self.AnsiName.get(..self.len()).unwrap_or_default())
+                .unwrap_or_default();
+        }
+
+        // Wide names are stored as UTF-16, past the end of AnsiName if long.
+        let wide = slice::from_raw_parts(self.AnsiName.as_ptr().cast::<u16>(), self.len());
+
+        let lowercase: Vec<u8> = wide
+            .iter()
+            .map(|&c| match u8::try_from(c) {
+                Ok(c) => u16::from(c.to_ascii_lowercase()),
+                Err(_) => c,
+            })
+            .flat_map(u16::to_le_bytes)
+            .collect();
+
+        city_hash::city_hash_64(&lowercase)
+    }
+
     pub unsafe fn text(&self) -> &str {
         if self.Header.is_wide() {
             "__[UNSUPPORTED WIDE TEXT]__"
@@ -267,6 +459,14 @@ impl FNameEntry {
         }
     }
 
+    fn eq_ignore_case(&self, text: &[u8]) -> bool {
+        !self.Header.is_wide()
+            && self
+                .AnsiName
+                .get(..self.len())
+                .is_some_and(|name| name.eq_ignore_ascii_case(text))
+    }
+
     fn get_size(&self) -> usize {
         let num_text_bytes = if self.Header.is_wide() {
             2 * self.len()
@@ -0,0 +1,212 @@
+// CityHash64 (v1.1), which Unreal Engine uses to hash names into the name pool.
+// Engine\Source\Runtime\Core\Private\Hash\CityHash.cpp
+
+const k0: u64 = 0xc3a5c85c97cb3127;
+const k1: u64 = 0xb492b66fbe98f273;
+const k2: u64 = 0x9ae16a3b2f90404f;
+const kMul: u64 = 0x9ddfea08eb382d69;
+
+fn fetch64(s: &[u8], i: usize) -> u64 {
+    let mut bytes = [0; 8];
+
+    if let Some(source) = s.get(i..i + 8) {
+        bytes.copy_from_slice(source);
+    }
+
+    u64::from_le_bytes(bytes)
+}
+
+fn fetch32(s: &[u8], i: usize) -> u64 {
+    let mut bytes = [0; 4];
+
+    if let Some(source) = s.get(i..i + 4) {
+        bytes.copy_from_slice(source);
+    }
+
+    u64::from(u32::from_le_bytes(bytes))
+}
+
+fn byte(s: &[u8], i: usize) -> u32 {
+    s.get(i).copied().map_or(0, u32::from)
+}
+
+fn shift_mix(v: u64) -> u64 {
+    v ^ (v >> 47)
+}
+
+fn hash_len_16(u: u64, v: u64, mul: u64) -> u64 {
+    let a = shift_mix((u ^ v).wrapping_mul(mul));
+    let b = shift_mix((v ^ a).wrapping_mul(mul));
+    b.wrapping_mul(mul)
+}
+
+fn hash_len_0_to_16(s: &[u8]) -> u64 {
+    let len = s.len();
+
+    if len >= 8 {
+        let mul = k2.wrapping_add(len as u64 * 2);
+        let a = fetch64(s, 0).wrapping_add(k2);
+        let b = fetch64(s, len - 8);
+        let c = b.rotate_right(37).wrapping_mul(mul).wrapping_add(a);
+        let d = a.rotate_right(25).wrapping_add(b).wrapping_mul(mul);
+        hash_len_16(c, d, mul)
+    } else if len >= 4 {
+        let mul = k2.wrapping_add(len as u64 * 2);
+        let a = fetch32(s, 0);
+        hash_len_16((len as u64).wrapping_add(a << 3), fetch32(s, len - 4), mul)
+    } else if len > 0 {
+        let a = byte(s, 0);
+        let b = byte(s, len >> 1);
+        let c = byte(s, len - 1);
+        let y = a.wrapping_add(b << 8);
+        let z = (len as u32).wrapping_add(c << 2);
+        shift_mix(u64::from(y).wrapping_mul(k2) ^ u64::from(z).wrapping_mul(k0)).wrapping_mul(k2)
+    } else {
+        k2
+    }
+}
+
+fn hash_len_17_to_32(s: &[u8]) -> u64 {
+    let len = s.len();
+    let mul = k2.wrapping_add(len as u64 * 2);
+    let a = fetch64(s, 0).wrapping_mul(k1);
+    let b = fetch64(s, 8);
+    let c = fetch64(s, len - 8).wrapping_mul(mul);
+    let d = fetch64(s, len - 16).wrapping_mul(k2);
+
+    hash_len_16(
+        a.wrapping_add(b)
+            .rotate_right(43)
+            .wrapping_add(c.rotate_right(30))
+            .wrapping_add(d),
+        a.wrapping_add(b.wrapping_add(k2).rotate_right(18))
+            .wrapping_add(c),
+        mul,
+    )
+}
+
+fn hash_len_33_to_64(s: &[u8]) -> u64 {
+    let len = s.len();
+    let mul = k2.wrapping_add(len as u64 * 2);
+    let a = fetch64(s, 0).wrapping_mul(k2);
+    let b = fetch64(s, 8);
+    let c = fetch64(s, len - 24);
+    let d = fetch64(s, len - 32);
+    let e = fetch64(s, 16).wrapping_mul(k2);
+    let f = fetch64(s, 24).wrapping_mul(9);
+    let g = fetch64(s, len - 8);
+    let h = fetch64(s, len - 16).wrapping_mul(mul);
+
+    let u = a
+        .wrapping_add(g)
+        .rotate_right(43)
+        .wrapping_add(b.rotate_right(30).wrapping_add(c).wrapping_mul(9));
+    let v = (a.wrapping_add(g) ^ d).wrapping_add(f).wrapping_add(1);
+    let w = u
+        .wrapping_add(v)
+        .wrapping_mul(mul)
+        .swap_bytes()
+        .wrapping_add(h);
+    let x = e.wrapping_add(f).rotate_right(42).wrapping_add(c);
+    let y = v
+        .wrapping_add(w)
+        .wrapping_mul(mul)
+        .swap_bytes()
+        .wrapping_add(g)
+        .wrapping_mul(mul);
+    let z = e.wrapping_add(f).wrapping_add(c);
+    let a = x
+        .wrapping_add(z)
+        .wrapping_mul(mul)
+        .wrapping_add(y)
+        .swap_bytes()
+        .wrapping_add(b);
+    let b = shift_mix(
+        z.wrapping_add(a)
+            .wrapping_mul(mul)
+            .wrapping_add(d)
+            .wrapping_add(h),
+    )
+    .wrapping_mul(mul);
+
+    b.wrapping_add(x)
+}
+
+fn weak_hash_len_32_with_seeds(s: &[u8], i: usize, a: u64, b: u64) -> (u64, u64) {
+    let w = fetch64(s, i);
+    let x = fetch64(s, i + 8);
+    let y = fetch64(s, i + 16);
+    let z = fetch64(s, i + 24);
+
+    let mut a = a.wrapping_add(w);
+    let mut b = b.wrapping_add(a).wrapping_add(z).rotate_right(21);
+    let c = a;
+    a = a.wrapping_add(x).wrapping_add(y);
+    b = b.wrapping_add(a.rotate_right(44));
+
+    (a.wrapping_add(z), b.wrapping_add(c))
+}
+
+pub fn city_hash_64(s: &[u8]) -> u64 {
+    let len = s.len();
+
+    if len <= 16 {
+        return hash_len_0_to_16(s);
+    } else if len <= 32 {
+        return hash_len_17_to_32(s);
+    } else if len <= 64 {
+        return hash_len_33_to_64(s);
+    }
+
+    // For strings over 64 bytes we hash the end first, and then as we loop we
+    // keep 56 bytes of state: v, w, x, y, and z.
+    let mut x = fetch64(s, len - 40);
+    let mut y = fetch64(s, len - 16).wrapping_add(fetch64(s, len - 56));
+    let mut z = hash_len_16(
+        fetch64(s, len - 48).wrapping_add(len as u64),
+        fetch64(s, len - 24),
+        kMul,
+    );
+    let mut v = weak_hash_len_32_with_seeds(s, len - 64, len as u64, z);
+    let mut w = weak_hash_len_32_with_seeds(s, len - 32, y.wrapping_add(k1), x);
+    x = x.wrapping_mul(k1).wrapping_add(fetch64(s, 0));
+
+    // Decrease len to the nearest multiple of 64, and operate on 64-byte chunks.
+    let mut remaining = (len - 1) & !63;
+    let mut i = 0;
+
+    while remaining != 0 {
+        x = x
+            .wrapping_add(y)
+            .wrapping_add(v.0)
+            .wrapping_add(fetch64(s, i + 8))
+            .rotate_right(37)
+            .wrapping_mul(k1);
+        y = y
+            .wrapping_add(v.1)
+            .wrapping_add(fetch64(s, i + 48))
+            .rotate_right(42)
+            .wrapping_mul(k1);
+        x ^= w.1;
+        y = y.wrapping_add(v.0).wrapping_add(fetch64(s, i + 40));
+        z = z.wrapping_add(w.0).rotate_right(33).wrapping_mul(k1);
+        v = weak_hash_len_32_with_seeds(s, i, v.1.wrapping_mul(k1), x.wrapping_add(w.0));
+        w = weak_hash_len_32_with_seeds(
+            s,
+            i + 32,
+            z.wrapping_add(w.1),
+            y.wrapping_add(fetch64(s, i + 16)),
+        );
+        core::mem::swap(&mut z, &mut x);
+        i += 64;
+        remaining -= 64;
+    }
+
+    hash_len_16(
+        hash_len_16(v.0, w.0, kMul)
+            .wrapping_add(shift_mix(y).wrapping_mul(k1))
+            .wrapping_add(z),
+        hash_len_16(v.1, w.1, kMul).wrapping_add(x),
+        kMul,
+    )
+}
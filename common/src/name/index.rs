@@ -0,0 +1,111 @@
+// A case-insensitive index from name text to entry id, built on demand.
+// Without it, a name that misses the engine's own hash table costs a scan of
+// every entry in the name pool.
+
+use super::{FNameEntryId, FNamePool};
+
+use core::ptr;
+
+const CAPACITY: usize = 1 << 21;
+const MASK: usize = CAPACITY - 1;
+
+// Keep the table at most 3/4 full so that probe sequences stay short. Names
+// past this point are found by scanning only the entries we couldn't index.
+const MAX_LEN: usize = CAPACITY / 4 * 3;
+
+struct Index {
+    // Entry id + 1, so that 0 marks an unused slot.
+    slots: [u32; CAPACITY],
+    len: usize,
+
+    // Where to resume indexing the name pool.
+    block: u32,
+    byte_offset: usize,
+}
+
+static mut INDEX: Index = Index {
+    slots: [0; CAPACITY],
+    len: 0,
+    block: 0,
+    byte_offset: 0,
+};
+
+pub unsafe fn find(pool: &FNamePool, text: &[u8]) -> Option<FNameEntryId> {
+    let index = &mut *ptr::addr_of_mut!(INDEX);
+
+    if let Some(id) = index.lookup(text) {
+        return Some(id);
+    }
+
+    // The name may have been added since we last looked.
+    index.update(pool);
+
+    index.lookup(text).or_else(|| {
+        pool.iter_from(index.block, index.byte_offset)
+            .find(|(_, entry)| (**entry).eq_ignore_case(text))
+            .map(|(id, _)| id)
+    })
+}
+
+impl Index {
+    unsafe fn lookup(&self, text: &[u8]) -> Option<FNameEntryId> {
+        let mut i = hash(text) & MASK;
+
+        loop {
+            let slot = *self.slots.get_unchecked(i);
+
+            if slot == 0 {
+                return None;
+            }
+
+            let id = FNameEntryId { Value: slot - 1 };
+
+            if (*id.entry()).eq_ignore_case(text) {
+                return Some(id);
+            }
+
+            i = (i + 1) & MASK;
+        }
+    }
+
+    unsafe fn update(&mut self, pool: &FNamePool) {
+        let mut names = pool.iter_from(self.block, self.byte_offset);
+
+        while self.len < MAX_LEN {
+            let (id, entry) = match names.next() {
+                Some(name) => name,
+                None => break,
+            };
+
+            (self.block, self.byte_offset) = names.position();
+
+            // Wide names never compare equal to our ASCII lookups.
+            if !(*entry).Header.is_wide() {
+                self.insert(id, (*entry).AnsiName.get_unchecked(..(*entry).len()));
+            }
+        }
+    }
+
+    unsafe fn insert(&mut self, id: FNameEntryId, text: &[u8]) {
+        let mut i = hash(text) & MASK;
+
+        while *self.slots.get_unchecked(i) != 0 {
+            i = (i + 1) & MASK;
+        }
+
+        *self.slots.get_unchecked_mut(i) = id.Value + 1;
+        self.len += 1;
+    }
+}
+
+// FNV-1a over the lowercased text.
+fn hash(text: &[u8]) -> usize {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for c in text {
+        hash ^= u64::from(c.to_ascii_lowercase());
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+
+    hash as usize
+}
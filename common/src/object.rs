@@ -1,7 +1,12 @@
+use crate::diagnose;
+use crate::functions;
+use crate::live;
+use crate::offsets;
 use crate::split::ReverseSplitIterator;
 use crate::win;
-use crate::FName;
 use crate::List;
+use crate::{FName, FNameEntryId, FObjectPropertyBase, FProperty, FString, TArray, TMap, TPair};
+use alloc::boxed::Box;
 
 use core::convert::TryFrom;
 use core::ffi::c_void;
@@ -10,12 +15,20 @@ use core::mem;
 use core::ops::BitOr;
 use core::ptr;
 use core::str;
+use core::sync::atomic::{AtomicI32, Ordering};
 
 mod full_name;
 use full_name::FullName;
 
+mod hierarchy;
+pub use hierarchy::{ClassHierarchy, ClassName, ClassTree};
+
 pub static mut GUObjectArray: *const FUObjectArray = ptr::null();
 
+// Overwritten by UObject::init() with the offset table's index, or else the
+// one found in the game's code.
+static mut PROCESS_EVENT_VTABLE_INDEX: usize = 68;
+
 const NumElementsPerChunk: usize = 64 * 1024;
 
 // The maximum number of outers we can store in an array.
@@ -31,6 +44,104 @@ pub enum Error {
     UnableToFind(&'static str),
 }
 
+// A full name, like "Function /Script/FSD.Item.GetItemName", resolved to the
+// entry ids that FUObjectArray::find compares.
+struct Target {
+    name: FNameEntryId,
+    class: FNameEntryId,
+    outers: List<FNameEntryId, MAX_OUTERS>,
+}
+
+impl Target {
+    unsafe fn new(name: &'static str) -> Result<Self, Error> {
+        let target = FullName::<MAX_OUTERS>::try_from(name)?;
+
+        // Names are interned, so look up each piece of the target once and
+        // compare entry ids instead of strings. Entry ids ignore case, just like
+        // the engine's own name comparisons. If a piece isn't in the name pool,
+        // then no object can have it.
+        let entry = |text: &[u8]| {
+            FName::from_existing(str::from_utf8_unchecked(text))
+                .map(|n| n.comparison_index())
+                .ok_or(Error::UnableToFind(name))
+        };
+
+        let mut outers = List::<FNameEntryId, MAX_OUTERS>::new();
+
+        for &outer in target.outers.iter() {
+            outers
+                .push(entry(outer)?)
+                .map_err(|_| Error::UnableToFind(name))?;
+        }
+
+        Ok(Self {
+            name: entry(target.name)?,
+            class: entry(target.class)?,
+            outers,
+        })
+    }
+
+    fn key(&self) -> u64 {
+        functions::key(
+            [self.class, self.name]
+                .into_iter()
+                .chain(self.outers.iter().copied()),
+        )
+    }
+
+    // Do a short-circuiting name comparison.
+
+    // Compare the class from the target against the class of `object`.
+    // Then compare the outers in the target against the outers of `object`.
+
+    // This way, we don't have to construct the full name of `object` if we
+    // can rule out non-matching classes and outers sooner.
+    unsafe fn is_match(&self, object: *mut UObject) -> bool {
+        if object.is_null() {
+            // We're not looking for a null object.
+            return false;
+        }
+
+        if (*object).NamePrivate.comparison_index() != self.name {
+            // Object names don't match.
+            // No need to check the class. Let's bail.
+            return false;
+        }
+
+        let class = (*object).ClassPrivate.cast::<UObject>();
+
+        if (*class).NamePrivate.comparison_index() != self.class {
+            // Classes don't match.
+            // No need to check the outers. Let's bail.
+            return false;
+        }
+
+        let mut my_outer = (*object).OuterPrivate;
+
+        for &target_outer in self.outers.iter() {
+            if my_outer.is_null() {
+                // We have no more outers left to check for this object, but
+                // we still have target outers. So this object can't be what
+                // we're looking for.
+                return false;
+            }
+
+            if (*my_outer).NamePrivate.comparison_index() != target_outer {
+                // This outer doesn't match the target outer we're looking for.
+                // No need to check the remaining outers. Let's bail.
+                return false;
+            }
+
+            // Advance up to the next outer.
+            my_outer = (*my_outer).OuterPrivate;
+        }
+
+        // We got here because the name, class, and outers all match the
+        // input name.
+        true
+    }
+}
+
 #[repr(C)]
 pub struct FUObjectArray {
     ObjFirstGCIndex: i32,
@@ -38,10 +149,25 @@ pub struct FUObjectArray {
     MaxObjectsNotConsideredByGC: i32,
     OpenForDisregardForGC: bool,
     pub ObjObjects: TUObjectArray,
+
+    // ObjObjectsCritical and ObjAvailableList.
+    pad0: [u8; 176],
+
+    // Called with each object as it's created or destroyed. See live.rs.
+    pub(crate) UObjectCreateListeners: TArray<*mut c_void>,
+    pub(crate) UObjectDeleteListeners: TArray<*mut c_void>,
+    pub(crate) UObjectDeleteListenersCritical: [u8; 40],
+
+    MasterSerialNumber: AtomicI32,
 }
 
 impl FUObjectArray {
     pub unsafe fn init(module: &win::Module) -> Result<(), Error> {
+        if let Some(address) = offsets::get("GUObjectArray") {
+            GUObjectArray = address as *const _;
+            return Ok(());
+        }
+
         // https://github.com/rkr35/drg/issues/3
 
         // 00007FF75CAF6D32 | 48:8B05 F7845C04         | mov rax,qword ptr ds:[7FF7610BF230]     |
@@ -66,9 +192,10 @@ impl FUObjectArray {
             Some(0xD1),
         ];
 
-        let mov_rax: *const u8 = module
-            .find(&GU_OBJECT_ARRAY_PATTERN)
-            .ok_or(Error::FindGUObjectArray)?;
+        let mov_rax: *const u8 = module.find(&GU_OBJECT_ARRAY_PATTERN).ok_or_else(|| {
+            diagnose::pattern_not_found(module, "GUObjectArray", &GU_OBJECT_ARRAY_PATTERN);
+            Error::FindGUObjectArray
+        })?;
 
         let mov_immediate = mov_rax.add(3);
         let instruction_after_mov = mov_immediate.add(4);
@@ -82,75 +209,96 @@ impl FUObjectArray {
         Ok(())
     }
 
+    // Goes through the map in functions.rs before looking the slow way.
     #[inline(never)]
     pub unsafe fn find_function(&self, name: &'static str) -> *mut UFunction {
-        self.find(name)
-            .map(|f| f.cast())
-            .unwrap_or(core::ptr::null_mut())
+        let Ok(target) = Target::new(name) else {
+            return ptr::null_mut();
+        };
+
+        let key = target.key();
+
+        if let Some(function) = functions::get(key) {
+            return function;
+        }
+
+        match self.find_target(&target) {
+            Some(function) => {
+                functions::insert(key, function.cast());
+                function.cast()
+            }
+            None => ptr::null_mut(),
+        }
     }
 
     pub unsafe fn find(&self, name: &'static str) -> Result<*mut UObject, Error> {
-        // Do a short-circuiting name comparison.
+        let target = Target::new(name)?;
+        self.find_target(&target).ok_or(Error::UnableToFind(name))
+    }
 
-        // Compare the class from `name` against the class in `self`.
-        // Then compare the outers in `name` against the outers in `self`.
+    // find() without the name index, for measuring what the index saves.
+    pub unsafe fn find_unindexed(&self, name: &'static str) -> Result<*mut UObject, Error> {
+        let target = Target::new(name)?;
 
-        // This way, we don't have to construct the full name of `self` if we
-        // can rule out non-matching classes and outers sooner.
+        self.iter()
+            .find(|&object| target.is_match(object))
+            .ok_or(Error::UnableToFind(name))
+    }
 
-        let target = FullName::<MAX_OUTERS>::try_from(name)?;
+    unsafe fn find_target(&self, target: &Target) -> Option<*mut UObject> {
+        let is_match = |object: &*mut UObject| target.is_match(*object);
 
-        'outer: for object in self.iter() {
-            if object.is_null() {
-                // We're not looking for a null object.
-                continue;
-            }
+        // Only the objects with the target's name, if they're indexed.
+        match live::named(target.name) {
+            Some(candidates) => candidates.into_iter().find(is_match),
+            None => self.iter().find(is_match),
+        }
+    }
 
-            let my_name = (*object).name().as_bytes();
+    // Find an object by its path name alone, e.g. "/Game/GameData.GameData".
+    pub unsafe fn find_by_path(&self, path: &str) -> *mut UObject {
+        // Resolve the path's names up front so that we compare entry ids below.
+        let mut names = List::<FNameEntryId, MAX_OUTERS>::new();
 
-            if my_name != target.name {
-                // Object names don't match.
-                // No need to check the class. Let's bail.
-                continue;
-            }
+        for name in ReverseSplitIterator::new(path.as_bytes(), b'.') {
+            let name = match FName::from_existing(str::from_utf8_unchecked(name)) {
+                Some(name) => name.comparison_index(),
 
-            let my_class = (*(*object).ClassPrivate).name().as_bytes();
+                // No object can have a name that isn't in the name pool.
+                None => return ptr::null_mut(),
+            };
 
-            if my_class != target.class {
-                // Classes don't match.
-                // No need to check the outers. Let's bail.
-                continue;
+            if names.push(name).is_err() {
+                return ptr::null_mut();
             }
+        }
 
-            let mut my_outer = (*object).OuterPrivate;
-
-            for target_outer in target.outers.iter() {
-                if my_outer.is_null() {
-                    // We have no more outers left to check for this object, but
-                    // we still have target outers. So this object can't be what
-                    // we're looking for. Let's check out the next object.
-                    continue 'outer;
-                }
+        let is_match = |object: &*mut UObject| {
+            let mut current = *object;
 
-                let my_outer_name = (*my_outer).name().as_bytes();
+            if current.is_null() {
+                return false;
+            }
 
-                if my_outer_name != *target_outer {
-                    // This outer doesn't match the target outer we're looking for.
-                    // No need to check the remaining outers. Let's bail.
-                    continue 'outer;
+            // Walk the path inside-out alongside the object's outer chain.
+            for &name in names.iter() {
+                if current.is_null() || (*current).NamePrivate.comparison_index() != name {
+                    return false;
                 }
 
-                // Advance up to the next outer.
-                my_outer = (*my_outer).OuterPrivate;
+                current = (*current).OuterPrivate;
             }
 
-            // We got here because the name, class, and outers all match the
-            // input name. So our search is over.
-            return Ok(object);
-        }
+            // Only a match if the path accounted for every outer.
+            current.is_null()
+        };
 
-        // No object matched our search.
-        Err(Error::UnableToFind(name))
+        let found = match names.iter().next().and_then(|&name| live::named(name)) {
+            Some(candidates) => candidates.into_iter().find(is_match),
+            None => self.iter().find(is_match),
+        };
+
+        found.unwrap_or(ptr::null_mut())
     }
 
     pub unsafe fn index_to_object(&self, index: i32) -> *const FUObjectItem {
@@ -163,27 +311,71 @@ impl FUObjectArray {
         }
     }
 
+    // The object's serial number, giving it one if nothing has taken a weak
+    // reference to it yet. Mirrors FUObjectArray::AllocateSerialNumber().
+    pub unsafe fn allocate_serial_number(&self, index: i32) -> i32 {
+        let item = self.index_to_object(index).cast_mut();
+
+        if item.is_null() {
+            return 0;
+        }
+
+        let serial_number = AtomicI32::from_ptr(ptr::addr_of_mut!((*item).SerialNumber));
+        let current = serial_number.load(Ordering::Acquire);
+
+        if current != 0 {
+            return current;
+        }
+
+        let new = self.MasterSerialNumber.fetch_add(1, Ordering::SeqCst) + 1;
+
+        // Another thread may have beaten us to it. Use theirs if so.
+        match serial_number.compare_exchange(0, new, Ordering::SeqCst, Ordering::SeqCst) {
+            Ok(_) => new,
+            Err(existing) => existing,
+        }
+    }
+
     pub fn iter(&self) -> ObjectIterator {
         ObjectIterator {
             chunks: self.ObjObjects.Objects,
-            num_objects: self.ObjObjects.NumElements as usize,
+            end: self.ObjObjects.NumElements as usize,
             index: 0,
         }
     }
+
+    // The objects of each of the array's chunks, in order, for splitting work
+    // between threads.
+    pub fn chunks(&self) -> impl Iterator<Item = ObjectIterator> {
+        let chunks = self.ObjObjects.Objects;
+        let num_objects = self.ObjObjects.NumElements as usize;
+
+        (0..num_objects)
+            .step_by(NumElementsPerChunk)
+            .map(move |start| ObjectIterator {
+                chunks,
+                end: (start + NumElementsPerChunk).min(num_objects),
+                index: start,
+            })
+    }
 }
 
 pub struct ObjectIterator {
     chunks: *const *mut FUObjectItem,
-    num_objects: usize,
+    end: usize,
     index: usize,
 }
 
+// The engine never frees or moves a chunk once it's allocated, so another
+// thread can walk it.
+unsafe impl Send for ObjectIterator {}
+
 impl Iterator for ObjectIterator {
     type Item = *mut UObject;
 
     fn next(&mut self) -> Option<Self::Item> {
         unsafe {
-            if self.index < self.num_objects {
+            if self.index < self.end {
                 let chunk = *self.chunks.add(self.index / NumElementsPerChunk);
                 let object = chunk.add(self.index % NumElementsPerChunk);
                 let object = (*object).Object;
@@ -228,11 +420,45 @@ impl FUObjectItem {
     pub fn is_valid(&self) -> bool {
         !self.is_unreachable() && !self.is_pending_kill()
     }
+
+    // The index of the root of the cluster the object is in, if it's in one.
+    pub fn cluster_root_index(&self) -> i32 {
+        self.ClusterRootIndex
+    }
+
+    const ROOT_SET: i32 = 1 << 30;
+
+    pub fn is_root_set(&self) -> bool {
+        self.Flags & Self::ROOT_SET == Self::ROOT_SET
+    }
+
+    // The garbage collector reads these flags on other threads, so they're
+    // only changed atomically, like FUObjectItem::ThisThreadAtomicallySetFlag().
+    unsafe fn set_flags(item: *mut Self, flags: i32, set: bool) {
+        let current = AtomicI32::from_ptr(ptr::addr_of_mut!((*item).Flags));
+
+        if set {
+            current.fetch_or(flags, Ordering::SeqCst);
+        } else {
+            current.fetch_and(!flags, Ordering::SeqCst);
+        }
+    }
 }
 
 #[macro_export]
 macro_rules! impl_deref {
     ($Derived:ty as $Base:ty) => {
+        $crate::impl_deref! { $Derived as $Base, no_display }
+
+        impl core::fmt::Display for $Derived {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+                let object: &UObject = self;
+                object.fmt(f)
+            }
+        }
+    };
+
+    ($Derived:ty as $Base:ty, no_display) => {
         impl core::ops::Deref for $Derived {
             type Target = $Base;
 
@@ -246,20 +472,13 @@ macro_rules! impl_deref {
                 &mut self.base
             }
         }
-
-        impl core::fmt::Display for $Derived {
-            fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
-                let object: &UObject = self;
-                object.fmt(f)
-            }
-        }
     };
 }
 
 #[repr(C)]
 pub struct UObject {
     pub vtable: *mut *const c_void,
-    ObjectFlags: u32, //EObjectFlags
+    pub ObjectFlags: EObjectFlags,
     pub InternalIndex: i32,
     ClassPrivate: *const UClass,
     pub NamePrivate: FName,
@@ -287,8 +506,24 @@ impl UObject {
         top.cast()
     }
 
+    // False for a null class, like a static_class() that isn't loaded.
     pub unsafe fn is(&self, class: *const UClass) -> bool {
-        (*self.ClassPrivate).is(class.cast())
+        !class.is_null() && (*self.ClassPrivate).is(class.cast())
+    }
+
+    pub fn has_any_flags(&self, flags: EObjectFlags) -> bool {
+        self.ObjectFlags.any(flags)
+    }
+
+    pub fn is_default_object(&self) -> bool {
+        self.has_any_flags(EObjectFlags::RF_ClassDefaultObject)
+    }
+
+    pub unsafe fn is_native(&self) -> bool {
+        self.has_any_flags(EObjectFlags::RF_MarkAsNative)
+            || (*self.ClassPrivate)
+                .ClassFlags
+                .any(EClassFlags::CLASS_Native)
     }
 
     pub unsafe fn fast_is(&self, class: EClassCastFlags) -> bool {
@@ -299,6 +534,112 @@ impl UObject {
         self.NamePrivate.text()
     }
 
+    // Writes "Class /Script/FSD.PlayerCharacter" without allocating, so that
+    // it works from hooks and into fixed-size buffers.
+    pub unsafe fn write_full_name(&self, out: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        write!(out, "{} ", (*self.ClassPrivate).name())?;
+        self.write_path(out)
+    }
+
+    // Writes "/Script/FSD.PlayerCharacter", the full name without the class.
+    pub unsafe fn write_path(&self, out: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        let mut outers = List::<&str, MAX_OUTERS>::new();
+        let mut outer = self.OuterPrivate;
+
+        while !outer.is_null() {
+            if outers.push((*outer).name()).is_err() {
+                crate::log!(
+                    "warning: reached outers capacity of {} for {}. outer name will be truncated.",
+                    outers.capacity(),
+                    self as *const _ as usize
+                );
+                break;
+            }
+
+            outer = (*outer).OuterPrivate;
+        }
+
+        for outer in outers.iter().rev() {
+            write!(out, "{}.", outer)?;
+        }
+
+        write!(out, "{}", self.name())?;
+
+        if self.NamePrivate.number() > 0 {
+            write!(out, "_{}", self.NamePrivate.number() - 1)?;
+        }
+
+        Ok(())
+    }
+
+    // Reads ProcessEvent's vtable index out of a function that calls it, so
+    // that we survive engine updates that shuffle virtuals. See the listing in
+    // process_event() below. The call has to be the only match, and the index
+    // has to lead to a function in UObject's vtable.
+    pub unsafe fn init(module: &win::Module) {
+        if let Some(index) = offsets::index("ProcessEventVTableIndex") {
+            PROCESS_EVENT_VTABLE_INDEX = index;
+            return;
+        }
+
+        // 00007FF6389DDFC2 | 48:8BD0                  | mov rdx,rax                             |
+        // 00007FF6389DDFC5 | 4C:8D4424 38             | lea r8,qword ptr ss:[rsp+38]            |
+        // 00007FF6389DDFCA | 48:8BCF                  | mov rcx,rdi                             |
+        // 00007FF6389DDFCD | FF93 20020000            | call qword ptr ds:[rbx+220]             |
+        const PROCESS_EVENT_CALL_PATTERN: [Option<u8>; 17] = [
+            Some(0x48),
+            Some(0x8B),
+            Some(0xD0),
+            Some(0x4C),
+            Some(0x8D),
+            Some(0x44),
+            Some(0x24),
+            None,
+            Some(0x48),
+            Some(0x8B),
+            Some(0xCF),
+            Some(0xFF),
+            Some(0x93),
+            None,
+            None,
+            None,
+            None,
+        ];
+
+        let index = module
+            .find_unique_mut::<u8>(&PROCESS_EVENT_CALL_PATTERN)
+            .map(|call| {
+                call.add(PROCESS_EVENT_CALL_PATTERN.len() - 4)
+                    .cast::<u32>()
+                    .read_unaligned() as usize
+            })
+            .filter(|offset| offset % mem::size_of::<usize>() == 0)
+            .map(|offset| offset / mem::size_of::<usize>())
+            .filter(|&index| Self::is_function_in_vtable(module, index));
+
+        match index {
+            Some(index) => PROCESS_EVENT_VTABLE_INDEX = index,
+
+            None if diagnose::is_quiet() => {}
+
+            None => crate::log!(
+                "warning: unable to find ProcessEvent's vtable index. Assuming {}.",
+                PROCESS_EVENT_VTABLE_INDEX
+            ),
+        }
+    }
+
+    unsafe fn is_function_in_vtable(module: &win::Module, index: usize) -> bool {
+        // UObject has fewer virtual functions than this.
+        const MAX_SLOTS: usize = 256;
+
+        let Ok(vtable) = module.find_vtable("UObject") else {
+            return false;
+        };
+
+        index < MAX_SLOTS && (0..=index).all(|i| module.is_function_start(*vtable.add(i) as usize))
+    }
+
     pub unsafe fn process_event(
         this: *mut UObject,
         function: *mut UFunction,
@@ -320,48 +661,271 @@ impl UObject {
         // 00007FF6389DDFD8 | 48:83C4 20               | add rsp,20                              |
         // 00007FF6389DDFDC | 5F                       | pop rdi                                 |
         // 00007FF6389DDFDD | C3                       | ret                                     |
-        const PROCESS_EVENT_VTABLE_INDEX: usize = 68;
-
         type ProcessEvent = unsafe extern "C" fn(*mut UObject, *mut UFunction, *mut c_void);
         let process_event = mem::transmute::<*const c_void, ProcessEvent>(
             *(*this).vtable.add(PROCESS_EVENT_VTABLE_INDEX),
         );
         process_event(this, function, parameters);
     }
+
+    // Which of an object's virtual functions is ProcessEvent.
+    pub unsafe fn process_event_vtable_index() -> usize {
+        PROCESS_EVENT_VTABLE_INDEX
+    }
+
+    // The ProcessEvent in this object's vtable, for hooking it. Classes that
+    // override it, like AActor, still end up in UObject::ProcessEvent.
+    pub unsafe fn process_event_address(&self) -> *mut c_void {
+        (*self.vtable.add(PROCESS_EVENT_VTABLE_INDEX)).cast_mut()
+    }
+
+    // Calls an interface function on an object that implements the interface.
+    // Blueprints implement interface functions with their own UFunction of
+    // the same name, so look for that first.
+    pub unsafe fn process_interface_event(
+        this: *mut UObject,
+        interface_function: *mut UFunction,
+        parameters: *mut c_void,
+    ) {
+        let function = (*(*this).ClassPrivate).find_function((*interface_function).name());
+
+        let function = if function.is_null() {
+            interface_function
+        } else {
+            function
+        };
+
+        Self::process_event(this, function, parameters);
+    }
+
+    pub fn class(&self) -> *const UClass {
+        self.ClassPrivate
+    }
+
+    pub fn outer(&self) -> *mut UObject {
+        self.OuterPrivate
+    }
+
+    // Keeps the object from being garbage collected, even if nothing
+    // references it, until `remove_from_root`. The hook goes through its gc
+    // module, which remembers what it rooted so it can unroot it on unload.
+    pub unsafe fn add_to_root(&self) {
+        self.set_root_set(true);
+    }
+
+    pub unsafe fn remove_from_root(&self) {
+        self.set_root_set(false);
+    }
+
+    pub unsafe fn is_rooted(&self) -> bool {
+        let item = (*GUObjectArray).index_to_object(self.InternalIndex);
+        !item.is_null() && (*item).is_root_set()
+    }
+
+    unsafe fn set_root_set(&self, set: bool) {
+        let item = (*GUObjectArray)
+            .index_to_object(self.InternalIndex)
+            .cast_mut();
+
+        if !item.is_null() {
+            FUObjectItem::set_flags(item, FUObjectItem::ROOT_SET, set);
+        }
+    }
+
+    // What the object property `name` points to. Returns null if it's unset
+    // or if there's no such property.
+    pub unsafe fn object_property(&self, name: &str) -> *mut UObject {
+        let property = (*self.ClassPrivate).find_property(name);
+
+        if property.is_null() || !(*property).is(EClassCastFlags::CASTCLASS_FObjectProperty) {
+            ptr::null_mut()
+        } else {
+            (*property.cast::<FObjectPropertyBase>()).read_object((self as *const Self).cast())
+        }
+    }
 }
 
-impl Display for UObject {
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct EObjectFlags(pub u32);
+
+impl EObjectFlags {
+    pub const RF_NoFlags: Self = Self(0x0);
+    pub const RF_Public: Self = Self(0x1);
+    pub const RF_Standalone: Self = Self(0x2);
+    pub const RF_MarkAsNative: Self = Self(0x4);
+    pub const RF_Transactional: Self = Self(0x8);
+    pub const RF_ClassDefaultObject: Self = Self(0x10);
+    pub const RF_ArchetypeObject: Self = Self(0x20);
+    pub const RF_Transient: Self = Self(0x40);
+    pub const RF_MarkAsRootSet: Self = Self(0x80);
+    pub const RF_TagGarbageTemp: Self = Self(0x100);
+    pub const RF_NeedInitialization: Self = Self(0x200);
+    pub const RF_NeedLoad: Self = Self(0x400);
+    pub const RF_KeepForCooker: Self = Self(0x800);
+    pub const RF_NeedPostLoad: Self = Self(0x1000);
+    pub const RF_NeedPostLoadSubobjects: Self = Self(0x2000);
+    pub const RF_NewerVersionExists: Self = Self(0x4000);
+    pub const RF_BeginDestroyed: Self = Self(0x8000);
+    pub const RF_FinishDestroyed: Self = Self(0x10000);
+    pub const RF_BeingRegenerated: Self = Self(0x20000);
+    pub const RF_DefaultSubObject: Self = Self(0x40000);
+    pub const RF_WasLoaded: Self = Self(0x80000);
+    pub const RF_TextExportTransient: Self = Self(0x100000);
+    pub const RF_LoadCompleted: Self = Self(0x200000);
+    pub const RF_InheritableComponentTemplate: Self = Self(0x400000);
+    pub const RF_DuplicateTransient: Self = Self(0x800000);
+    pub const RF_StrongRefOnFrame: Self = Self(0x1000000);
+    pub const RF_NonPIEDuplicateTransient: Self = Self(0x2000000);
+    pub const RF_Dynamic: Self = Self(0x4000000);
+    pub const RF_WillBeLoaded: Self = Self(0x8000000);
+    pub const RF_HasExternalPackage: Self = Self(0x10000000);
+
+    pub fn any(&self, Self(flags): Self) -> bool {
+        self.0 & flags != 0
+    }
+}
+
+impl BitOr for EObjectFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl Display for EObjectFlags {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        unsafe {
-            write!(f, "{} ", (*self.ClassPrivate).name())?;
+        let flags = self.0;
 
-            let mut outers = List::<&str, MAX_OUTERS>::new();
-            let mut outer = self.OuterPrivate;
+        if flags & Self::RF_Public.0 == Self::RF_Public.0 {
+            write!(f, "RF_Public, ")?;
+        }
 
-            while !outer.is_null() {
-                if outers.push((*outer).name()).is_err() {
-                    crate::log!("warning: reached outers capacity of {} for {}. outer name will be truncated.", outers.capacity(), self as *const _ as usize);
-                    break;
-                }
+        if flags & Self::RF_Standalone.0 == Self::RF_Standalone.0 {
+            write!(f, "RF_Standalone, ")?;
+        }
 
-                outer = (*outer).OuterPrivate;
-            }
+        if flags & Self::RF_MarkAsNative.0 == Self::RF_MarkAsNative.0 {
+            write!(f, "RF_MarkAsNative, ")?;
+        }
 
-            for outer in outers.iter().rev() {
-                write!(f, "{}.", outer)?;
-            }
+        if flags & Self::RF_Transactional.0 == Self::RF_Transactional.0 {
+            write!(f, "RF_Transactional, ")?;
+        }
 
-            write!(f, "{}", self.name())?;
+        if flags & Self::RF_ClassDefaultObject.0 == Self::RF_ClassDefaultObject.0 {
+            write!(f, "RF_ClassDefaultObject, ")?;
+        }
 
-            if self.NamePrivate.number() > 0 {
-                write!(f, "_{}", self.NamePrivate.number() - 1)?;
-            }
+        if flags & Self::RF_ArchetypeObject.0 == Self::RF_ArchetypeObject.0 {
+            write!(f, "RF_ArchetypeObject, ")?;
+        }
+
+        if flags & Self::RF_Transient.0 == Self::RF_Transient.0 {
+            write!(f, "RF_Transient, ")?;
+        }
+
+        if flags & Self::RF_MarkAsRootSet.0 == Self::RF_MarkAsRootSet.0 {
+            write!(f, "RF_MarkAsRootSet, ")?;
+        }
+
+        if flags & Self::RF_TagGarbageTemp.0 == Self::RF_TagGarbageTemp.0 {
+            write!(f, "RF_TagGarbageTemp, ")?;
+        }
+
+        if flags & Self::RF_NeedInitialization.0 == Self::RF_NeedInitialization.0 {
+            write!(f, "RF_NeedInitialization, ")?;
+        }
+
+        if flags & Self::RF_NeedLoad.0 == Self::RF_NeedLoad.0 {
+            write!(f, "RF_NeedLoad, ")?;
+        }
+
+        if flags & Self::RF_KeepForCooker.0 == Self::RF_KeepForCooker.0 {
+            write!(f, "RF_KeepForCooker, ")?;
+        }
+
+        if flags & Self::RF_NeedPostLoad.0 == Self::RF_NeedPostLoad.0 {
+            write!(f, "RF_NeedPostLoad, ")?;
+        }
+
+        if flags & Self::RF_NeedPostLoadSubobjects.0 == Self::RF_NeedPostLoadSubobjects.0 {
+            write!(f, "RF_NeedPostLoadSubobjects, ")?;
+        }
+
+        if flags & Self::RF_NewerVersionExists.0 == Self::RF_NewerVersionExists.0 {
+            write!(f, "RF_NewerVersionExists, ")?;
+        }
+
+        if flags & Self::RF_BeginDestroyed.0 == Self::RF_BeginDestroyed.0 {
+            write!(f, "RF_BeginDestroyed, ")?;
+        }
+
+        if flags & Self::RF_FinishDestroyed.0 == Self::RF_FinishDestroyed.0 {
+            write!(f, "RF_FinishDestroyed, ")?;
+        }
+
+        if flags & Self::RF_BeingRegenerated.0 == Self::RF_BeingRegenerated.0 {
+            write!(f, "RF_BeingRegenerated, ")?;
+        }
+
+        if flags & Self::RF_DefaultSubObject.0 == Self::RF_DefaultSubObject.0 {
+            write!(f, "RF_DefaultSubObject, ")?;
+        }
+
+        if flags & Self::RF_WasLoaded.0 == Self::RF_WasLoaded.0 {
+            write!(f, "RF_WasLoaded, ")?;
+        }
+
+        if flags & Self::RF_TextExportTransient.0 == Self::RF_TextExportTransient.0 {
+            write!(f, "RF_TextExportTransient, ")?;
+        }
+
+        if flags & Self::RF_LoadCompleted.0 == Self::RF_LoadCompleted.0 {
+            write!(f, "RF_LoadCompleted, ")?;
+        }
+
+        if flags & Self::RF_InheritableComponentTemplate.0
+            == Self::RF_InheritableComponentTemplate.0
+        {
+            write!(f, "RF_InheritableComponentTemplate, ")?;
+        }
+
+        if flags & Self::RF_DuplicateTransient.0 == Self::RF_DuplicateTransient.0 {
+            write!(f, "RF_DuplicateTransient, ")?;
+        }
+
+        if flags & Self::RF_StrongRefOnFrame.0 == Self::RF_StrongRefOnFrame.0 {
+            write!(f, "RF_StrongRefOnFrame, ")?;
+        }
+
+        if flags & Self::RF_NonPIEDuplicateTransient.0 == Self::RF_NonPIEDuplicateTransient.0 {
+            write!(f, "RF_NonPIEDuplicateTransient, ")?;
+        }
+
+        if flags & Self::RF_Dynamic.0 == Self::RF_Dynamic.0 {
+            write!(f, "RF_Dynamic, ")?;
+        }
+
+        if flags & Self::RF_WillBeLoaded.0 == Self::RF_WillBeLoaded.0 {
+            write!(f, "RF_WillBeLoaded, ")?;
+        }
+
+        if flags & Self::RF_HasExternalPackage.0 == Self::RF_HasExternalPackage.0 {
+            write!(f, "RF_HasExternalPackage, ")?;
         }
 
         Ok(())
     }
 }
 
+impl Display for UObject {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        unsafe { self.write_full_name(f) }
+    }
+}
+
 #[repr(C)]
 pub struct UField {
     base: UObject,
@@ -370,6 +934,28 @@ pub struct UField {
 
 impl_deref! { UField as UObject }
 
+#[repr(C)]
+pub struct UEnum {
+    base: UField,
+    CppType: FString,
+    pub Names: TArray<TPair<FName, i64>>,
+    CppForm: i32,
+    EnumDisplayNameFn: usize,
+}
+
+impl_deref! { UEnum as UField }
+
+impl UEnum {
+    // Returns the full variant name, which includes the enum name for
+    // namespaced enums (e.g. "EItemState::Equipped").
+    pub unsafe fn name_for_value(&self, value: i64) -> Option<&str> {
+        self.Names
+            .iter()
+            .find(|variant| variant.Value == value)
+            .map(|variant| variant.Key.text())
+    }
+}
+
 #[repr(C)]
 pub struct FStructBaseChain {
     StructBaseChainArray: *const *const FStructBaseChain,
@@ -401,6 +987,28 @@ impl UStruct {
     pub unsafe fn is(&self, parent: *const Self) -> bool {
         self.struct_base_chain.is(&(*parent).struct_base_chain)
     }
+
+    // Finds a property by name in this struct or its super structs. Returns
+    // null if there isn't one.
+    pub unsafe fn find_property(&self, name: &str) -> *const FProperty {
+        let mut structure: *const UStruct = self;
+
+        while !structure.is_null() {
+            let mut property = (*structure).ChildProperties.cast::<FProperty>();
+
+            while !property.is_null() {
+                if (*property).name() == name {
+                    return property;
+                }
+
+                property = (*property).base.Next.cast();
+            }
+
+            structure = (*structure).SuperStruct;
+        }
+
+        ptr::null()
+    }
 }
 
 impl_deref! { UStruct as UField }
@@ -411,7 +1019,21 @@ pub struct UClass {
     pad0: [u8; 28],
     pub ClassFlags: EClassFlags,
     pub ClassCastFlags: EClassCastFlags,
-    pad1: [u8; 344],
+    pad1: [u8; 88],
+    pub FuncMap: TMap<FName, *mut UFunction>,
+
+    // SuperFuncMap and its lock.
+    pad2: [u8; 88],
+
+    pub Interfaces: TArray<FImplementedInterface>,
+    pad3: [u8; 72],
+}
+
+#[repr(C)]
+pub struct FImplementedInterface {
+    pub Class: *const UClass,
+    pub PointerOffset: i32,
+    pub bImplementedByK2: bool,
 }
 
 impl_deref! { UClass as UStruct }
@@ -421,37 +1043,107 @@ impl UClass {
         self.ClassFlags
             .any(EClassFlags::CLASS_CompiledFromBlueprint)
     }
+
+    pub fn is_interface(&self) -> bool {
+        self.ClassFlags.any(EClassFlags::CLASS_Interface)
+    }
+
+    // Finds a function by name in this class or its super classes. Returns
+    // null if there isn't one.
+    pub unsafe fn find_function(&self, name: &str) -> *mut UFunction {
+        let mut structure: *const UStruct = &self.base;
+
+        while !structure.is_null() {
+            let mut field = (*structure).Children;
+
+            while !field.is_null() {
+                if (*field).fast_is(EClassCastFlags::CASTCLASS_UFunction) && (*field).name() == name
+                {
+                    return field.cast_mut().cast();
+                }
+
+                field = (*field).Next;
+            }
+
+            structure = (*structure).SuperStruct;
+        }
+
+        ptr::null_mut()
+    }
 }
 
-// struct FFrame : public FOutputDevice
-// TODO: fill in from UnrealEngine\Engine\Source\Runtime\CoreUObject\Public\UObject\Stack.h
+// A blueprint function's execution state, from Stack.h. `Code` points at the
+// next bytecode, which GNatives dispatches on. See vm.rs.
 
 #[repr(C)]
 pub struct FOutputDevice {
+    vtable: *const c_void,
     bSuppressEventTag: bool,
     bAutoEmitLineTerminator: bool,
 }
 
+// An out parameter of the function being called, linked in declaration
+// order.
+#[repr(C)]
+pub struct FOutParmRec {
+    pub Property: *mut FProperty,
+    pub PropAddr: *mut u8,
+    pub NextOutParm: *mut FOutParmRec,
+}
+
+// TArray<CodeSkipSizeType, TInlineAllocator<8>>
+#[repr(C)]
+struct FlowStackType {
+    InlineData: [u32; 8],
+    SecondaryData: *mut u32,
+    ArrayNum: i32,
+    ArrayMax: i32,
+}
+
 #[repr(C)]
 pub struct FFrame {
     base: FOutputDevice,
 
-    Node: *mut UFunction,
-    Object: *mut UObject,
+    pub Node: *mut UFunction,
+    pub Object: *mut UObject,
 
-    Code: *mut u8,
+    pub Code: *mut u8,
     pub Locals: *mut u8,
 
-    MostRecentProperty: *mut c_void,
-    MostRecentPropertyAddress: *mut c_void,
-    FlowStack: crate::TArray<u32>,
-    PreviousFrame: *mut c_void,
-    OutParms: *mut c_void,
-    PropertyChainForCompiledIn: *mut c_void,
-    CurrentNativeFunction: *mut c_void,
+    pub MostRecentProperty: *mut FProperty,
+    pub MostRecentPropertyAddress: *mut u8,
+    FlowStack: FlowStackType,
+    // The caller's frame, or null if it was called from native code.
+    pub PreviousFrame: *mut FFrame,
+    pub OutParms: *mut FOutParmRec,
+    PropertyChainForCompiledIn: *mut FField,
+    pub CurrentNativeFunction: *mut UFunction,
     bArrayContextFailed: bool,
 }
 
+impl FFrame {
+    // The opcode that runs next, one of the EX_ constants in vm.rs.
+    pub unsafe fn next_opcode(&self) -> Option<u8> {
+        (!self.Code.is_null()).then(|| *self.Code)
+    }
+
+    // This frame and its callers', innermost first.
+    pub unsafe fn frames(&self) -> impl Iterator<Item = *const FFrame> {
+        let mut frame: *const FFrame = self;
+
+        core::iter::from_fn(move || {
+            let current = frame;
+
+            if current.is_null() {
+                None
+            } else {
+                frame = (*current).PreviousFrame;
+                Some(current)
+            }
+        })
+    }
+}
+
 pub type FNativeFuncPtr =
     unsafe extern "C" fn(Context: *mut UObject, TheStack: *mut FFrame, Result: *mut c_void);
 
@@ -514,6 +1206,7 @@ pub struct UFunction {
     pub Func: FNativeFuncPtr,
 }
 
+#[derive(Copy, Clone)]
 #[repr(transparent)]
 pub struct EFunctionFlags(u32);
 
@@ -548,6 +1241,10 @@ impl EFunctionFlags {
     pub const FUNC_EditorOnly: Self = Self(0x20000000);
     pub const FUNC_Const: Self = Self(0x40000000);
     pub const FUNC_NetValidate: Self = Self(0x80000000);
+
+    pub fn contains(&self, flag: Self) -> bool {
+        self.0 & flag.0 == flag.0
+    }
 }
 
 impl Display for EFunctionFlags {
@@ -678,6 +1375,31 @@ impl Display for EFunctionFlags {
     }
 }
 
+impl UFunction {
+    pub fn parms_size(&self) -> usize {
+        usize::from(self.ParmsSize)
+    }
+
+    // A copy of this function, named `name` and owned by `outer`, that runs
+    // `func` natively. Copying keeps the parameter properties, so the engine
+    // builds the same frame for it. The copy is never freed since the engine
+    // doesn't know how to.
+    pub unsafe fn clone_native(
+        &self,
+        name: FName,
+        outer: *mut UObject,
+        func: FNativeFuncPtr,
+    ) -> *mut UFunction {
+        let function = Box::into_raw(Box::new(ptr::read(self)));
+        let object = function.cast::<UObject>();
+        (*object).NamePrivate = name;
+        (*object).OuterPrivate = outer;
+        (*function).FunctionFlags.0 |= EFunctionFlags::FUNC_Native.0;
+        (*function).Func = func;
+        function
+    }
+}
+
 impl_deref! { UFunction as UStruct }
 
 #[repr(C)]
@@ -782,6 +1504,8 @@ impl BitOr for EClassCastFlags {
 pub struct EClassFlags(u32);
 
 impl EClassFlags {
+    pub const CLASS_Native: Self = Self(0x1);
+    pub const CLASS_Interface: Self = Self(0x4000);
     pub const CLASS_CompiledFromBlueprint: Self = Self(0x40000);
 
     pub fn any(&self, Self(flags): Self) -> bool {
@@ -0,0 +1,121 @@
+// Prints a class's inheritance. UObject's Display only shows the outer chain.
+
+use crate::{EClassCastFlags, GUObjectArray, UClass, UStruct};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+// Deeper than any class tree in the game. Guards against cycles in a corrupt
+// SuperStruct chain.
+const MAX_DEPTH: usize = 64;
+
+// The class's name as it's spelled in C++, e.g. "APawn" for "Class
+// /Script/Engine.Pawn".
+pub struct ClassName(pub *const UClass);
+
+impl Display for ClassName {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        unsafe {
+            let class = &*self.0;
+
+            let prefix = if class.is_interface() {
+                "I"
+            } else if class.ClassCastFlags.any(EClassCastFlags::CASTCLASS_AActor) {
+                "A"
+            } else {
+                "U"
+            };
+
+            write!(f, "{}{}", prefix, class.name())
+        }
+    }
+}
+
+// "ADeepPathfinderCharacter : APawn : AActor : UObject", followed by the
+// interfaces implemented anywhere in the chain.
+pub struct ClassHierarchy(pub *const UClass);
+
+impl Display for ClassHierarchy {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        unsafe {
+            write!(f, "{}", ClassName(self.0))?;
+
+            let mut class = super_class(self.0);
+            let mut depth = 0;
+
+            while !class.is_null() && depth < MAX_DEPTH {
+                write!(f, " : {}", ClassName(class))?;
+                class = super_class(class);
+                depth += 1;
+            }
+
+            let mut separator = " implements ";
+            let mut class = self.0;
+            let mut depth = 0;
+
+            while !class.is_null() && depth < MAX_DEPTH {
+                for interface in (*class).Interfaces.iter() {
+                    write!(f, "{}{}", separator, ClassName(interface.Class))?;
+                    separator = ", ";
+                }
+
+                class = super_class(class);
+                depth += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// The class and every class derived from it, one per line and indented by
+// depth. Finding children means walking the whole object array once, so this
+// is for dumps and one-off logging, not hot paths.
+pub struct ClassTree(pub *const UClass);
+
+impl Display for ClassTree {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        unsafe { write_tree(f, &children_by_parent(), self.0, 0) }
+    }
+}
+
+// Every class, keyed by its SuperStruct, in object array order.
+unsafe fn children_by_parent() -> BTreeMap<*const UClass, Vec<*const UClass>> {
+    let mut children: BTreeMap<_, Vec<_>> = BTreeMap::new();
+
+    for object in (*GUObjectArray).iter() {
+        if !object.is_null() && (*object).fast_is(EClassCastFlags::CASTCLASS_UClass) {
+            let class = object.cast::<UClass>().cast_const();
+            let parent = super_class(class);
+            children.entry(parent).or_default().push(class);
+        }
+    }
+
+    children
+}
+
+unsafe fn write_tree(
+    f: &mut Formatter,
+    children: &BTreeMap<*const UClass, Vec<*const UClass>>,
+    class: *const UClass,
+    depth: usize,
+) -> Result<(), fmt::Error> {
+    writeln!(f, "{:indent$}{}", "", ClassName(class), indent = depth * 4)?;
+
+    if depth + 1 >= MAX_DEPTH {
+        return Ok(());
+    }
+
+    for &child in children.get(&class).into_iter().flatten() {
+        write_tree(f, children, child, depth + 1)?;
+    }
+
+    Ok(())
+}
+
+unsafe fn super_class(class: *const UClass) -> *const UClass {
+    (*class.cast::<UStruct>())
+        .SuperStruct
+        .cast::<UClass>()
+        .cast_const()
+}
@@ -0,0 +1,132 @@
+// Addresses for specific builds of the game, for when an update breaks a
+// signature. A build is identified by the timestamp the linker writes into the
+// executable's header, which changes with every build. Whatever the selected
+// table doesn't have is still found by signature.
+//
+// Tables are JSON, keyed by build ID in hex, with offsets from the
+// executable's base, and vtable indices as plain numbers:
+//
+// {
+//     "6512ab3f": {
+//         "GUObjectArray": "0x5c1f220",
+//         "GEngine": "0x5b57de60",
+//         "ProcessEventVTableIndex": 68
+//     }
+// }
+//
+// The names are those of the globals and functions the finders look for.
+
+use crate::json::{self, Value};
+use crate::win::{self, module::pe};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+static mut BUILD_ID: Option<u32> = None;
+
+// Absolute addresses, by name.
+static mut ADDRESSES: Vec<(String, usize)> = Vec::new();
+
+// Virtual function slots, by name.
+static mut INDICES: Vec<(String, usize)> = Vec::new();
+
+// More slots than any class we hook has.
+const MAX_VTABLE_INDEX: usize = 1024;
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Json(#[from] json::Error),
+    // Not a hex string, or outside the executable.
+    BadOffset,
+    // Not a whole number, or past any vtable's end.
+    BadIndex,
+}
+
+// Uses the first of `tables` with an entry for this build. Each table is a
+// name, for logging, and its JSON. Returns whether one had this build.
+pub unsafe fn select(module: &win::Module, tables: &[(&str, &str)]) -> bool {
+    let Some(nt_headers) = pe::nt_headers(module.base()) else {
+        crate::log!("offsets: unable to read the build ID");
+        return false;
+    };
+
+    let build_id = nt_headers.file_header.time_date_stamp;
+    let image_size = nt_headers.optional_header.size_of_image as usize;
+    BUILD_ID = Some(build_id);
+    let key = format!("{:08x}", build_id);
+
+    for &(name, table) in tables {
+        match parse(module.base(), image_size, table, &key) {
+            Ok(Some(Table { addresses, indices })) => {
+                crate::log!("offsets: using {}'s table for build {}", name, key);
+                ADDRESSES = addresses;
+                INDICES = indices;
+                return true;
+            }
+            Ok(None) => {}
+            Err(e) => crate::log!("offsets: {}: {:?}", name, e),
+        }
+    }
+
+    crate::log!("offsets: no table for build {}. scanning instead", key);
+    false
+}
+
+// Where the selected table says `name` is, if it has it.
+pub unsafe fn get(name: &str) -> Option<usize> {
+    ADDRESSES
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|&(_, address)| address)
+}
+
+// The vtable index the selected table gives for `name`, if it has it.
+pub unsafe fn index(name: &str) -> Option<usize> {
+    INDICES
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|&(_, index)| index)
+}
+
+// The build ID select() found, if it's been called.
+pub unsafe fn current_build_id() -> Option<u32> {
+    BUILD_ID
+}
+
+struct Table {
+    addresses: Vec<(String, usize)>,
+    indices: Vec<(String, usize)>,
+}
+
+fn parse(base: usize, image_size: usize, table: &str, key: &str) -> Result<Option<Table>, Error> {
+    let table = Value::parse(table)?;
+
+    let Some(Value::Object(entries)) = table.get(key) else {
+        return Ok(None);
+    };
+
+    let mut addresses = Vec::with_capacity(entries.len());
+    let mut indices = Vec::new();
+
+    for (name, offset) in entries {
+        if let Value::Number(index) = offset {
+            let index = Some(*index as usize)
+                .filter(|&i| i as f64 == *index && i < MAX_VTABLE_INDEX)
+                .ok_or(Error::BadIndex)?;
+
+            indices.push((name.clone(), index));
+            continue;
+        }
+
+        let offset = offset
+            .as_str()
+            .and_then(|offset| offset.strip_prefix("0x"))
+            .and_then(|offset| usize::from_str_radix(offset, 16).ok())
+            .filter(|&offset| offset < image_size)
+            .ok_or(Error::BadOffset)?;
+
+        addresses.push((name.clone(), base + offset));
+    }
+
+    Ok(Some(Table { addresses, indices }))
+}
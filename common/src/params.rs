@@ -0,0 +1,80 @@
+// Checks hand-written ProcessEvent parameter structs against the function's
+// reflected properties, so that a game update that moves a parameter fails
+// loudly instead of corrupting the stack. #[derive(macros::UParams)] fills in
+// the fields.
+
+use crate::{FProperty, GUObjectArray, UFunction, UStruct};
+use core::mem;
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    FindFunction(&'static str),
+    UnknownParameter(&'static str),
+    Offset(&'static str, usize, usize),
+    Size(&'static str, usize, usize),
+    StructSize(usize, usize),
+}
+
+pub struct ParamField {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+}
+
+// Lets the derive get a field's size without naming its type.
+pub fn field_size<S, F>(_: fn(&S) -> &F) -> usize {
+    mem::size_of::<F>()
+}
+
+pub unsafe fn verify_layout(
+    function: &'static str,
+    struct_size: usize,
+    fields: &[ParamField],
+) -> Result<(), Error> {
+    let function = (*GUObjectArray)
+        .find(function)
+        .map_err(|_| Error::FindFunction(function))?
+        .cast::<UFunction>();
+
+    for field in fields {
+        let property = find_property(function, field.name)?;
+
+        let offset = (*property).offset();
+
+        if offset != field.offset {
+            return Err(Error::Offset(field.name, field.offset, offset));
+        }
+
+        let size = ((*property).ElementSize * (*property).ArrayDim) as usize;
+
+        if size != field.size {
+            return Err(Error::Size(field.name, field.size, size));
+        }
+    }
+
+    // ProcessEvent copies ParmsSize bytes in and out of the struct.
+    if struct_size < (*function).parms_size() {
+        return Err(Error::StructSize(struct_size, (*function).parms_size()));
+    }
+
+    Ok(())
+}
+
+unsafe fn find_property(
+    function: *const UFunction,
+    name: &'static str,
+) -> Result<*const FProperty, Error> {
+    let mut property = (*function.cast::<UStruct>())
+        .ChildProperties
+        .cast::<FProperty>();
+
+    while !property.is_null() {
+        if (*property).name() == name {
+            return Ok(property);
+        }
+
+        property = (*property).base.Next.cast();
+    }
+
+    Err(Error::UnknownParameter(name))
+}
@@ -0,0 +1,100 @@
+// The world being played and the local player's controller, pawn and camera
+// manager, where most features start. They're found through reflection, as GEngine->GameInstance->
+// LocalPlayers[0]->PlayerController, so that common doesn't need the SDK. The
+// hook gives us GEngine when it attaches.
+//
+// Each level has its own controller, so the hook refreshes it when the world
+// changes, and one that's been destroyed is looked up again. The pawn and the
+// camera manager are read from the controller every time, since they change
+// within a level, like when the player dies.
+//
+// The world is GEngine->GameViewport->World, read every time, since it's
+// replaced whenever a level loads.
+//
+// let pawn = common::player::local_pawn();
+//
+// if !pawn.is_null() {
+//     common::log!("{}", *pawn);
+// }
+
+use crate::{EClassCastFlags, FArrayProperty, ObjectHandle, UObject};
+use core::ptr;
+
+static mut ENGINE: *mut UObject = ptr::null_mut();
+static mut CONTROLLER: ObjectHandle<UObject> = ObjectHandle::NULL;
+
+// `engine` is GEngine.
+pub unsafe fn init(engine: *mut UObject) {
+    ENGINE = engine;
+    refresh();
+}
+
+// Looks the controller up again, for when the level changes.
+pub unsafe fn refresh() {
+    CONTROLLER = ObjectHandle::new(find_controller());
+}
+
+pub unsafe fn clear() {
+    ENGINE = ptr::null_mut();
+    CONTROLLER = ObjectHandle::NULL;
+}
+
+// The world being played, or null between levels.
+pub unsafe fn world() -> *mut UObject {
+    object_property(object_property(ENGINE, "GameViewport"), "World")
+}
+
+// The world's game state, or null, like while a level loads.
+pub unsafe fn game_state() -> *mut UObject {
+    object_property(world(), "GameState")
+}
+
+// The first local player's controller, or null if there isn't one yet, like
+// during loading screens.
+pub unsafe fn local_controller() -> *mut UObject {
+    if let Some(controller) = CONTROLLER.resolve() {
+        return controller;
+    }
+
+    refresh();
+    CONTROLLER.resolve().unwrap_or(ptr::null_mut())
+}
+
+// What the local player controls, or null, like while dead.
+pub unsafe fn local_pawn() -> *mut UObject {
+    object_property(local_controller(), "Pawn")
+}
+
+pub unsafe fn camera_manager() -> *mut UObject {
+    object_property(local_controller(), "PlayerCameraManager")
+}
+
+unsafe fn find_controller() -> *mut UObject {
+    let game_instance = object_property(ENGINE, "GameInstance");
+    object_property(first_local_player(game_instance), "PlayerController")
+}
+
+// LocalPlayers[0].
+unsafe fn first_local_player(game_instance: *mut UObject) -> *mut UObject {
+    if game_instance.is_null() {
+        return ptr::null_mut();
+    }
+
+    let property = (*(*game_instance).class()).find_property("LocalPlayers");
+
+    if property.is_null() || !(*property).is(EClassCastFlags::CASTCLASS_FArrayProperty) {
+        return ptr::null_mut();
+    }
+
+    let players = (*property.cast::<FArrayProperty>()).array::<*mut UObject>(game_instance.cast());
+    (*players).first().copied().unwrap_or(ptr::null_mut())
+}
+
+// Null if `object` is.
+unsafe fn object_property(object: *mut UObject, name: &str) -> *mut UObject {
+    if object.is_null() {
+        ptr::null_mut()
+    } else {
+        (*object).object_property(name)
+    }
+}
@@ -0,0 +1,117 @@
+// The C ABI between the hook and plugin DLLs. The hook loads every DLL in the
+// "plugins" folder next to the game's executable, calls its INIT_SYMBOL export
+// with a Context, and calls its SHUTDOWN_SYMBOL export, if it has one, before
+// unloading it.
+//
+// #[no_mangle]
+// unsafe extern "C" fn drg_plugin_init(context: *const Context) -> bool {
+//     if (*context).abi_version < ABI_VERSION {
+//         return false;
+//     }
+//
+//     CONTEXT = context;
+//     ((*context).register_tick)((*context).plugin, on_tick)
+// }
+//
+// Strings are UTF-8, passed as a pointer and a length, and don't need to be
+// null-terminated. Callbacks run on the game thread, and everything but
+// logging must only be called from init, shutdown or a callback.
+
+use crate::{FFrame, FProperty, UFunction, UObject};
+use core::ffi::c_void;
+
+// Bumped whenever a field is added to Context. Fields are only ever added to
+// the end, so a plugin works with any version at least as new as the one it
+// was built against.
+pub const ABI_VERSION: u32 = 2;
+
+// Null-terminated for GetProcAddress.
+pub const INIT_SYMBOL: &[u8] = b"drg_plugin_init\0";
+pub const SHUTDOWN_SYMBOL: &[u8] = b"drg_plugin_shutdown\0";
+
+// Returns whether the plugin loaded. The context outlives the plugin.
+pub type Init = unsafe extern "C" fn(context: *const Context) -> bool;
+pub type Shutdown = unsafe extern "C" fn();
+
+pub type TickCallback = unsafe extern "C" fn(delta_seconds: f32);
+
+// Runs before the function does, so it sees the parameters but not the
+// outputs.
+pub type ProcessEventListener =
+    unsafe extern "C" fn(object: *mut UObject, function: *mut UFunction, parameters: *mut c_void);
+
+// Runs for every UFunction called through UFunction::Invoke, which includes
+// blueprint-to-blueprint calls that ProcessEvent never sees. For a blueprint
+// function, `stack` is its own frame. For a native one, it's the caller's.
+pub type InvokeListener =
+    unsafe extern "C" fn(object: *mut UObject, function: *mut UFunction, stack: *mut FFrame);
+
+#[repr(u32)]
+#[derive(Copy, Clone)]
+pub enum EventKind {
+    // `object` is the mission's hazard setting, or null.
+    MissionStart,
+    // `object` is the character that went down.
+    PlayerDowned,
+    // `object` is our player controller.
+    MissionEnd,
+    // `object` is the new world. Drop any pointers into the old one.
+    WorldChanged,
+}
+
+// A game event, like the mission starting. `seed` is only set for
+// MissionStart.
+pub type EventListener = unsafe extern "C" fn(kind: EventKind, object: *mut UObject, seed: u32);
+
+// Identifies the calling plugin, so that its log lines are labeled and its
+// callbacks are removed when it unloads.
+pub type Plugin = usize;
+
+#[repr(C)]
+pub struct Context {
+    pub abi_version: u32,
+    pub plugin: Plugin,
+
+    pub log: unsafe extern "C" fn(plugin: Plugin, text: *const u8, len: usize),
+
+    // Each returns false if there's no room for another callback.
+    pub register_tick: unsafe extern "C" fn(plugin: Plugin, callback: TickCallback) -> bool,
+    pub unregister_tick: unsafe extern "C" fn(plugin: Plugin, callback: TickCallback),
+    pub register_process_event:
+        unsafe extern "C" fn(plugin: Plugin, listener: ProcessEventListener) -> bool,
+    pub unregister_process_event:
+        unsafe extern "C" fn(plugin: Plugin, listener: ProcessEventListener),
+
+    // Runs a line as if it was typed into the console, like "drg.outline on".
+    // Returns whether one of our commands handled it. False in someone else's
+    // game, where plugins may not change anything.
+    pub run_command: unsafe extern "C" fn(line: *const u8, len: usize) -> bool,
+
+    // Reflection. Paths are like "/Game/GameData.GameData" and class names
+    // like "PlayerCharacter". Lookups return null if there's no such object or
+    // property.
+    pub find_object: unsafe extern "C" fn(path: *const u8, len: usize) -> *mut UObject,
+    pub is_a: unsafe extern "C" fn(object: *const UObject, class: *const u8, len: usize) -> bool,
+    pub find_property: unsafe extern "C" fn(
+        object: *const UObject,
+        name: *const u8,
+        len: usize,
+    ) -> *const FProperty,
+
+    // Where the named property's value lives in the object. Null in someone
+    // else's game, like run_command.
+    pub property_address:
+        unsafe extern "C" fn(object: *mut UObject, name: *const u8, len: usize) -> *mut c_void,
+
+    // Writes as much of the object's full name as fits in the buffer, without
+    // a null terminator. Returns the full name's length.
+    pub object_name:
+        unsafe extern "C" fn(object: *const UObject, buffer: *mut u8, capacity: usize) -> usize,
+
+    // Since version 2. Like register_tick, these return false if there's no
+    // room.
+    pub register_invoke: unsafe extern "C" fn(plugin: Plugin, listener: InvokeListener) -> bool,
+    pub unregister_invoke: unsafe extern "C" fn(plugin: Plugin, listener: InvokeListener),
+    pub register_event: unsafe extern "C" fn(plugin: Plugin, listener: EventListener) -> bool,
+    pub unregister_event: unsafe extern "C" fn(plugin: Plugin, listener: EventListener),
+}
@@ -0,0 +1,118 @@
+// A prompt on the console the DLL was attached with, for holding its thread
+// until whoever's at the console is done. Each line is a command. Callers add
+// their own to these:
+//
+// help       list the commands
+// continue   stop reading the console, but stay loaded until ejected
+// eject      leave the prompt and unload
+//
+// Without a console to read, or once it's closed, it's as if by continue.
+// Eject stays reachable after that through request_eject(), e.g. from a game
+// console command.
+
+use crate::log;
+use alloc::string::String;
+use core::sync::atomic::{AtomicBool, Ordering};
+use windows::Win32::System::Threading::Sleep;
+
+const POLL_INTERVAL_MS: u32 = 250;
+
+pub struct Command {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub run: unsafe fn(args: &str),
+}
+
+static EJECT: AtomicBool = AtomicBool::new(false);
+
+// Makes run() return, from any thread. Takes effect within a poll interval
+// while the prompt is waiting, or after the line being typed otherwise.
+pub fn request_eject() {
+    EJECT.store(true, Ordering::Relaxed);
+}
+
+// Returns once eject is typed or requested.
+pub unsafe fn run(commands: &[Command]) {
+    log!("Type a command, or help to list them.");
+
+    let mut line = String::new();
+
+    while !EJECT.load(Ordering::Relaxed) {
+        if !wait_for_input() {
+            continue;
+        }
+
+        line.clear();
+
+        if !read_line(&mut line) {
+            log!("The console was closed. Eject from the game's console to unload.");
+            break;
+        }
+
+        let text = line.trim();
+        let (name, args) = text.split_once(' ').unwrap_or((text, ""));
+
+        if name.is_empty() {
+            continue;
+        } else if name.eq_ignore_ascii_case("continue") {
+            log!("Left the prompt. Eject from the game's console to unload.");
+            break;
+        } else if name.eq_ignore_ascii_case("eject") {
+            return;
+        } else if name.eq_ignore_ascii_case("help") {
+            help(commands);
+        } else {
+            match commands.iter().find(|c| c.name.eq_ignore_ascii_case(name)) {
+                Some(command) => (command.run)(args.trim()),
+                None => log!("Unknown command {}. Type help to list them.", name),
+            }
+        }
+    }
+
+    while !EJECT.load(Ordering::Relaxed) {
+        Sleep(POLL_INTERVAL_MS);
+    }
+}
+
+fn help(commands: &[Command]) {
+    log!("help: list these commands");
+    log!("continue: stop reading the console, but stay loaded until ejected");
+    log!("eject: leave the prompt and unload");
+
+    for command in commands {
+        log!("{}: {}", command.name, command.help);
+    }
+}
+
+// Whether there's console input to read, after waiting up to a poll interval
+// for it. Also true once the console's gone, so read_line() finds that out.
+#[cfg(feature = "std")]
+unsafe fn wait_for_input() -> bool {
+    use windows::Win32::Foundation::WAIT_TIMEOUT;
+    use windows::Win32::System::Console::{GetStdHandle, STD_INPUT_HANDLE};
+    use windows::Win32::System::Threading::WaitForSingleObject;
+
+    match GetStdHandle(STD_INPUT_HANDLE) {
+        Ok(stdin) if !stdin.is_invalid() => {
+            WaitForSingleObject(stdin, POLL_INTERVAL_MS) != WAIT_TIMEOUT
+        }
+        _ => true,
+    }
+}
+
+#[cfg(not(feature = "std"))]
+unsafe fn wait_for_input() -> bool {
+    true
+}
+
+// Whether a line was read.
+#[cfg(feature = "std")]
+fn read_line(line: &mut String) -> bool {
+    matches!(std::io::stdin().read_line(line), Ok(len) if len > 0)
+}
+
+// There's no stdin without std.
+#[cfg(not(feature = "std"))]
+fn read_line(_: &mut String) -> bool {
+    false
+}
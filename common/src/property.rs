@@ -0,0 +1,536 @@
+use crate::{
+    EClassCastFlags, FField, FName, FString, TArray, UClass, UEnum, UFunction, UObject, UStruct,
+};
+
+use core::ffi::c_void;
+use core::fmt::{self, Display, Formatter};
+
+#[repr(C)]
+pub struct FProperty {
+    pub base: FField,
+    pub ArrayDim: i32,
+    pub ElementSize: i32,
+    pub PropertyFlags: EPropertyFlags,
+    pad0: [u8; 4],
+    pub Offset: i32,
+    pad1: [u8; 40],
+}
+
+impl FProperty {
+    pub unsafe fn is(&self, property: EClassCastFlags) -> bool {
+        (*self.base.ClassPrivate).CastFlags.any(property)
+    }
+
+    pub unsafe fn id(&self) -> EClassCastFlags {
+        (*self.base.ClassPrivate).Id
+    }
+
+    pub unsafe fn name(&self) -> &str {
+        self.base.name()
+    }
+
+    pub fn offset(&self) -> usize {
+        self.Offset as usize
+    }
+
+    pub fn flags(&self) -> EPropertyFlags {
+        self.PropertyFlags
+    }
+
+    pub fn element_size(&self) -> usize {
+        self.ElementSize as usize
+    }
+
+    pub fn array_dim(&self) -> usize {
+        self.ArrayDim as usize
+    }
+
+    // The size of the whole property, including every element of a static array.
+    pub fn size(&self) -> usize {
+        self.element_size() * self.array_dim()
+    }
+
+    pub unsafe fn value_ptr<T>(&self, container: *const c_void, index: usize) -> *const T {
+        container
+            .cast::<u8>()
+            .add(self.offset() + index * self.element_size())
+            .cast()
+    }
+
+    pub unsafe fn value_ptr_mut<T>(&self, container: *mut c_void, index: usize) -> *mut T {
+        container
+            .cast::<u8>()
+            .add(self.offset() + index * self.element_size())
+            .cast()
+    }
+
+    // The caller must make sure `T` matches the property's type.
+    pub unsafe fn read<T: Copy>(&self, container: *const c_void) -> T {
+        self.value_ptr::<T>(container, 0).read_unaligned()
+    }
+
+    // The caller must make sure `T` matches the property's type.
+    pub unsafe fn write<T: Copy>(&self, container: *mut c_void, value: T) {
+        self.value_ptr_mut::<T>(container, 0).write_unaligned(value);
+    }
+}
+
+// Formats the value of a property in `container` for logging, e.g. "42",
+// "EResourceType::Gold", or "[3 elements]" for an array.
+pub struct PropertyValue {
+    pub property: *const FProperty,
+    pub container: *const c_void,
+}
+
+impl Display for PropertyValue {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        unsafe {
+            let property = &*self.property;
+            let container = self.container;
+
+            if property.array_dim() > 1 {
+                return write!(f, "[{} elements]", property.array_dim());
+            }
+
+            if property.is(EClassCastFlags::CASTCLASS_FBoolProperty) {
+                let property = &*self.property.cast::<FBoolProperty>();
+                write!(f, "{}", property.read_bool(container))
+            } else if property.is(EClassCastFlags::CASTCLASS_FEnumProperty) {
+                let property = &*self.property.cast::<FEnumProperty>();
+
+                if let Some(name) = property.read_name(container) {
+                    f.write_str(name)
+                } else if let Some(value) = property.read_value(container) {
+                    write!(f, "{}", value)
+                } else {
+                    f.write_str("?")
+                }
+            } else if property.is(EClassCastFlags::CASTCLASS_FNumericProperty) {
+                let property = &*self.property.cast::<FNumericProperty>();
+
+                if property.is(EClassCastFlags::CASTCLASS_FByteProperty) {
+                    let enumeration = (*self.property.cast::<FByteProperty>()).Enumeration;
+                    let value = property.read::<u8>(container);
+
+                    if !enumeration.is_null() {
+                        if let Some(name) = (*enumeration).name_for_value(i64::from(value)) {
+                            return f.write_str(name);
+                        }
+                    }
+                }
+
+                if let Some(value) = property.read_integer(container) {
+                    write!(f, "{}", value)
+                } else if let Some(value) = property.read_float(container) {
+                    write!(f, "{}", value)
+                } else {
+                    f.write_str("?")
+                }
+            } else if property.is(EClassCastFlags::CASTCLASS_FNameProperty) {
+                write!(f, "{}", property.read::<FName>(container))
+            } else if property.is(EClassCastFlags::CASTCLASS_FStrProperty) {
+                write!(f, "\"{}\"", property.read::<FString>(container))
+            } else if property.is(EClassCastFlags::CASTCLASS_FObjectProperty) {
+                let object = (*self.property.cast::<FObjectPropertyBase>()).read_object(container);
+
+                if object.is_null() {
+                    f.write_str("null")
+                } else {
+                    write!(f, "{}", *object)
+                }
+            } else if property.is(EClassCastFlags::CASTCLASS_FArrayProperty) {
+                let array = property.read::<TArray<u8>>(container);
+                write!(f, "[{} elements]", array.len)
+            } else if property.is(EClassCastFlags::CASTCLASS_FStructProperty) {
+                let structure = (*self.property.cast::<FStructProperty>()).Structure;
+                let value = property.value_ptr(container, 0);
+
+                crate::gameplay_tags::fmt_struct(structure, value, f)
+                    .unwrap_or_else(|| write!(f, "{{{}}}", (*structure).name()))
+            } else {
+                write!(f, "<{} bytes>", property.size())
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct EPropertyFlags(pub u64);
+
+impl EPropertyFlags {
+    // Engine\Source\Runtime\CoreUObject\Public\UObject\ObjectMacros.h
+    pub const CPF_None: Self = Self(0);
+    pub const CPF_Edit: Self = Self(0x1); // < Property is user-settable in the editor.
+    pub const CPF_ConstParm: Self = Self(0x2); // < This is a constant function parameter
+    pub const CPF_BlueprintVisible: Self = Self(0x4); // < This property can be read by blueprint code
+    pub const CPF_ExportObject: Self = Self(0x8); // < Object can be exported with actor.
+    pub const CPF_BlueprintReadOnly: Self = Self(0x10); // < This property cannot be modified by blueprint code
+    pub const CPF_Net: Self = Self(0x20); // < Property is relevant to network replication.
+    pub const CPF_EditFixedSize: Self = Self(0x40); // < Indicates that elements of an array can be modified, but its size cannot be changed.
+    pub const CPF_Parm: Self = Self(0x80); // < Function/When call parameter.
+    pub const CPF_OutParm: Self = Self(0x100); // < Value is copied out after function call.
+    pub const CPF_ZeroConstructor: Self = Self(0x200); // < memset is fine for construction
+    pub const CPF_ReturnParm: Self = Self(0x400); // < Return value.
+    pub const CPF_DisableEditOnTemplate: Self = Self(0x800); // < Disable editing of this property on an archetype/sub-blueprint
+    pub const CPF_Transient: Self = Self(0x2000); // < Property is transient: shouldn't be saved or loaded, except for Blueprint CDOs.
+    pub const CPF_Config: Self = Self(0x4000); // < Property should be loaded/saved as permanent profile.
+    pub const CPF_DisableEditOnInstance: Self = Self(0x10000); // < Disable editing on an instance of this class
+    pub const CPF_EditConst: Self = Self(0x20000); // < Property is uneditable in the editor.
+    pub const CPF_GlobalConfig: Self = Self(0x40000); // < Load config from base class, not subclass.
+    pub const CPF_InstancedReference: Self = Self(0x80000); // < Property is a component references.
+    pub const CPF_DuplicateTransient: Self = Self(0x200000); // < Property should always be reset to the default value during any type of duplication (copy/paste, binary duplication, etc.)
+    pub const CPF_SubobjectReference: Self = Self(0x400000); // < Property contains subobject references (TSubobjectPtr)
+    pub const CPF_SaveGame: Self = Self(0x1000000); // < Property should be serialized for save games, this is only checked for game-specific archives with ArIsSaveGame
+    pub const CPF_NoClear: Self = Self(0x2000000); // < Hide clear (and browse) button.
+    pub const CPF_ReferenceParm: Self = Self(0x8000000); // < Value is passed by reference; CPF_OutParam and CPF_Param should also be set.
+    pub const CPF_BlueprintAssignable: Self = Self(0x10000000); // < MC Delegates only.  Property should be exposed for assigning in blueprint code
+    pub const CPF_Deprecated: Self = Self(0x20000000); // < Property is deprecated.  Read it from an archive, but don't save it.
+    pub const CPF_IsPlainOldData: Self = Self(0x40000000); // < If this is set, then the property can be memcopied instead of CopyCompleteValue / CopySingleValue
+    pub const CPF_RepSkip: Self = Self(0x80000000); // < Not replicated. For non replicated properties in replicated structs
+    pub const CPF_RepNotify: Self = Self(0x100000000); // < Notify actors when a property is replicated
+    pub const CPF_Interp: Self = Self(0x200000000); // < interpolatable property for use with matinee
+    pub const CPF_NonTransactional: Self = Self(0x400000000); // < Property isn't transacted
+    pub const CPF_EditorOnly: Self = Self(0x800000000); // < Property should only be loaded in the editor
+    pub const CPF_NoDestructor: Self = Self(0x1000000000); // < No destructor
+    pub const CPF_AutoWeak: Self = Self(0x4000000000); // < Only used for weak pointers, means the export type is autoweak
+    pub const CPF_ContainsInstancedReference: Self = Self(0x8000000000); // < Property contains component references.
+    pub const CPF_AssetRegistrySearchable: Self = Self(0x10000000000); // < asset instances will add properties with this flag to the asset registry automatically
+    pub const CPF_SimpleDisplay: Self = Self(0x20000000000); // < The property is visible by default in the editor details view
+    pub const CPF_AdvancedDisplay: Self = Self(0x40000000000); // < The property is advanced and not visible by default in the editor details view
+    pub const CPF_Protected: Self = Self(0x80000000000); // < property is protected from the perspective of script
+    pub const CPF_BlueprintCallable: Self = Self(0x100000000000); // < MC Delegates only.  Property should be exposed for calling in blueprint code
+    pub const CPF_BlueprintAuthorityOnly: Self = Self(0x200000000000); // < MC Delegates only.  This delegate accepts (only in blueprint) only events with BlueprintAuthorityOnly.
+    pub const CPF_TextExportTransient: Self = Self(0x400000000000); // < Property shouldn't be exported to text format (e.g. copy/paste)
+    pub const CPF_NonPIEDuplicateTransient: Self = Self(0x800000000000); // < Property should only be copied in PIE
+    pub const CPF_ExposeOnSpawn: Self = Self(0x1000000000000); // < Property is exposed on spawn
+    pub const CPF_PersistentInstance: Self = Self(0x2000000000000); // < A object referenced by the property is duplicated like a component. (Each actor should have an own instance.)
+    pub const CPF_UObjectWrapper: Self = Self(0x4000000000000); // < Property was parsed as a wrapper class like TSubclassOf<T>, FScriptInterface etc., rather than a USomething*
+    pub const CPF_HasGetValueTypeHash: Self = Self(0x8000000000000); // < This property can generate a meaningful hash value.
+    pub const CPF_NativeAccessSpecifierPublic: Self = Self(0x10000000000000); // < Public native access specifier
+    pub const CPF_NativeAccessSpecifierProtected: Self = Self(0x20000000000000); // < Protected native access specifier
+    pub const CPF_NativeAccessSpecifierPrivate: Self = Self(0x40000000000000); // < Private native access specifier
+    pub const CPF_SkipSerialization: Self = Self(0x80000000000000); // < Property shouldn't be serialized, can still be exported to text
+
+    pub fn contains(&self, flag: Self) -> bool {
+        self.0 & flag.0 == flag.0
+    }
+
+    const NAMES: [(Self, &'static str); 50] = [
+        (Self::CPF_Edit, "CPF_Edit"),
+        (Self::CPF_ConstParm, "CPF_ConstParm"),
+        (Self::CPF_BlueprintVisible, "CPF_BlueprintVisible"),
+        (Self::CPF_ExportObject, "CPF_ExportObject"),
+        (Self::CPF_BlueprintReadOnly, "CPF_BlueprintReadOnly"),
+        (Self::CPF_Net, "CPF_Net"),
+        (Self::CPF_EditFixedSize, "CPF_EditFixedSize"),
+        (Self::CPF_Parm, "CPF_Parm"),
+        (Self::CPF_OutParm, "CPF_OutParm"),
+        (Self::CPF_ZeroConstructor, "CPF_ZeroConstructor"),
+        (Self::CPF_ReturnParm, "CPF_ReturnParm"),
+        (Self::CPF_DisableEditOnTemplate, "CPF_DisableEditOnTemplate"),
+        (Self::CPF_Transient, "CPF_Transient"),
+        (Self::CPF_Config, "CPF_Config"),
+        (Self::CPF_DisableEditOnInstance, "CPF_DisableEditOnInstance"),
+        (Self::CPF_EditConst, "CPF_EditConst"),
+        (Self::CPF_GlobalConfig, "CPF_GlobalConfig"),
+        (Self::CPF_InstancedReference, "CPF_InstancedReference"),
+        (Self::CPF_DuplicateTransient, "CPF_DuplicateTransient"),
+        (Self::CPF_SubobjectReference, "CPF_SubobjectReference"),
+        (Self::CPF_SaveGame, "CPF_SaveGame"),
+        (Self::CPF_NoClear, "CPF_NoClear"),
+        (Self::CPF_ReferenceParm, "CPF_ReferenceParm"),
+        (Self::CPF_BlueprintAssignable, "CPF_BlueprintAssignable"),
+        (Self::CPF_Deprecated, "CPF_Deprecated"),
+        (Self::CPF_IsPlainOldData, "CPF_IsPlainOldData"),
+        (Self::CPF_RepSkip, "CPF_RepSkip"),
+        (Self::CPF_RepNotify, "CPF_RepNotify"),
+        (Self::CPF_Interp, "CPF_Interp"),
+        (Self::CPF_NonTransactional, "CPF_NonTransactional"),
+        (Self::CPF_EditorOnly, "CPF_EditorOnly"),
+        (Self::CPF_NoDestructor, "CPF_NoDestructor"),
+        (Self::CPF_AutoWeak, "CPF_AutoWeak"),
+        (
+            Self::CPF_ContainsInstancedReference,
+            "CPF_ContainsInstancedReference",
+        ),
+        (
+            Self::CPF_AssetRegistrySearchable,
+            "CPF_AssetRegistrySearchable",
+        ),
+        (Self::CPF_SimpleDisplay, "CPF_SimpleDisplay"),
+        (Self::CPF_AdvancedDisplay, "CPF_AdvancedDisplay"),
+        (Self::CPF_Protected, "CPF_Protected"),
+        (Self::CPF_BlueprintCallable, "CPF_BlueprintCallable"),
+        (
+            Self::CPF_BlueprintAuthorityOnly,
+            "CPF_BlueprintAuthorityOnly",
+        ),
+        (Self::CPF_TextExportTransient, "CPF_TextExportTransient"),
+        (
+            Self::CPF_NonPIEDuplicateTransient,
+            "CPF_NonPIEDuplicateTransient",
+        ),
+        (Self::CPF_ExposeOnSpawn, "CPF_ExposeOnSpawn"),
+        (Self::CPF_PersistentInstance, "CPF_PersistentInstance"),
+        (Self::CPF_UObjectWrapper, "CPF_UObjectWrapper"),
+        (Self::CPF_HasGetValueTypeHash, "CPF_HasGetValueTypeHash"),
+        (
+            Self::CPF_NativeAccessSpecifierPublic,
+            "CPF_NativeAccessSpecifierPublic",
+        ),
+        (
+            Self::CPF_NativeAccessSpecifierProtected,
+            "CPF_NativeAccessSpecifierProtected",
+        ),
+        (
+            Self::CPF_NativeAccessSpecifierPrivate,
+            "CPF_NativeAccessSpecifierPrivate",
+        ),
+        (Self::CPF_SkipSerialization, "CPF_SkipSerialization"),
+    ];
+}
+
+impl Display for EPropertyFlags {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        for (flag, name) in Self::NAMES {
+            if self.contains(flag) {
+                write!(f, "{}, ", name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[repr(C)]
+pub struct FNumericProperty {
+    pub base: FProperty,
+}
+
+crate::impl_deref! { FNumericProperty as FProperty, no_display }
+
+impl FNumericProperty {
+    // Reads any integral property, widened to an i64. Returns `None` for
+    // floating point properties.
+    pub unsafe fn read_integer(&self, container: *const c_void) -> Option<i64> {
+        let value = match self.id() {
+            EClassCastFlags::CASTCLASS_FInt8Property => i64::from(self.read::<i8>(container)),
+            EClassCastFlags::CASTCLASS_FInt16Property => i64::from(self.read::<i16>(container)),
+            EClassCastFlags::CASTCLASS_FIntProperty => i64::from(self.read::<i32>(container)),
+            EClassCastFlags::CASTCLASS_FInt64Property => self.read::<i64>(container),
+            EClassCastFlags::CASTCLASS_FByteProperty => i64::from(self.read::<u8>(container)),
+            EClassCastFlags::CASTCLASS_FUInt16Property => i64::from(self.read::<u16>(container)),
+            EClassCastFlags::CASTCLASS_FUInt32Property => i64::from(self.read::<u32>(container)),
+            EClassCastFlags::CASTCLASS_FUInt64Property => self.read::<u64>(container) as i64,
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    // Reads any floating point property, widened to an f64. Returns `None`
+    // for integral properties.
+    pub unsafe fn read_float(&self, container: *const c_void) -> Option<f64> {
+        match self.id() {
+            EClassCastFlags::CASTCLASS_FFloatProperty => {
+                Some(f64::from(self.read::<f32>(container)))
+            }
+            EClassCastFlags::CASTCLASS_FDoubleProperty => Some(self.read::<f64>(container)),
+            _ => None,
+        }
+    }
+
+    // Writes any integral property, truncating `value` to its size. Returns
+    // false for floating point properties.
+    pub unsafe fn write_integer(&self, container: *mut c_void, value: i64) -> bool {
+        match self.id() {
+            EClassCastFlags::CASTCLASS_FInt8Property => self.write(container, value as i8),
+            EClassCastFlags::CASTCLASS_FInt16Property => self.write(container, value as i16),
+            EClassCastFlags::CASTCLASS_FIntProperty => self.write(container, value as i32),
+            EClassCastFlags::CASTCLASS_FInt64Property => self.write(container, value),
+            EClassCastFlags::CASTCLASS_FByteProperty => self.write(container, value as u8),
+            EClassCastFlags::CASTCLASS_FUInt16Property => self.write(container, value as u16),
+            EClassCastFlags::CASTCLASS_FUInt32Property => self.write(container, value as u32),
+            EClassCastFlags::CASTCLASS_FUInt64Property => self.write(container, value as u64),
+            _ => return false,
+        }
+
+        true
+    }
+
+    // Writes any floating point property. Returns false for integral
+    // properties.
+    pub unsafe fn write_float(&self, container: *mut c_void, value: f64) -> bool {
+        match self.id() {
+            EClassCastFlags::CASTCLASS_FFloatProperty => self.write(container, value as f32),
+            EClassCastFlags::CASTCLASS_FDoubleProperty => self.write(container, value),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+#[repr(C)]
+pub struct FBoolProperty {
+    pub base: FProperty,
+    pub FieldSize: u8,
+    pub ByteOffset: u8,
+    pub ByteMask: u8,
+    pub FieldMask: u8,
+    pad: [u8; 4],
+}
+
+crate::impl_deref! { FBoolProperty as FProperty, no_display }
+
+impl FBoolProperty {
+    pub fn is_bitfield(&self) -> bool {
+        self.FieldMask != 255
+    }
+
+    pub unsafe fn read_bool(&self, container: *const c_void) -> bool {
+        let byte = self
+            .value_ptr::<u8>(container, 0)
+            .add(usize::from(self.ByteOffset));
+        *byte & self.FieldMask != 0
+    }
+
+    pub unsafe fn write_bool(&self, container: *mut c_void, value: bool) {
+        let byte = self
+            .value_ptr_mut::<u8>(container, 0)
+            .add(usize::from(self.ByteOffset));
+
+        let bit = if value { self.ByteMask } else { 0 };
+        *byte = (*byte & !self.FieldMask) | bit;
+    }
+}
+
+#[repr(C)]
+pub struct FByteProperty {
+    pub base: FNumericProperty,
+    pub Enumeration: *const UEnum,
+}
+
+crate::impl_deref! { FByteProperty as FNumericProperty, no_display }
+
+#[repr(C)]
+pub struct FStructProperty {
+    pub base: FProperty,
+    pub Structure: *const UStruct,
+}
+
+crate::impl_deref! { FStructProperty as FProperty, no_display }
+
+#[repr(C)]
+pub struct FObjectPropertyBase {
+    pub base: FProperty,
+    pub PropertyClass: *const UClass,
+}
+
+crate::impl_deref! { FObjectPropertyBase as FProperty, no_display }
+
+impl FObjectPropertyBase {
+    // Only valid for hard object references (FObjectProperty, FClassProperty).
+    pub unsafe fn read_object(&self, container: *const c_void) -> *mut UObject {
+        self.read(container)
+    }
+
+    pub unsafe fn write_object(&self, container: *mut c_void, object: *mut UObject) {
+        self.write(container, object);
+    }
+}
+
+#[repr(C)]
+pub struct FClassProperty {
+    pub base: FObjectPropertyBase,
+    pub MetaClass: *const UClass,
+}
+
+crate::impl_deref! { FClassProperty as FObjectPropertyBase, no_display }
+
+#[repr(C)]
+pub struct FArrayProperty {
+    pub base: FProperty,
+    pub Inner: *const FProperty,
+    pad: [u8; 8],
+}
+
+crate::impl_deref! { FArrayProperty as FProperty, no_display }
+
+impl FArrayProperty {
+    // The caller must make sure `T` matches the type of `self.Inner`.
+    pub unsafe fn array<T>(&self, container: *mut c_void) -> *mut TArray<T> {
+        self.value_ptr_mut(container, 0)
+    }
+}
+
+#[repr(C)]
+pub struct FEnumProperty {
+    pub base: FProperty,
+    pub UnderlyingProp: *const FNumericProperty,
+    pub Enumeration: *const UEnum,
+}
+
+crate::impl_deref! { FEnumProperty as FProperty, no_display }
+
+impl FEnumProperty {
+    // The underlying property has offset 0 within the enum's value.
+    pub unsafe fn read_value(&self, container: *const c_void) -> Option<i64> {
+        (*self.UnderlyingProp).read_integer(self.value_ptr(container, 0))
+    }
+
+    pub unsafe fn read_name(&self, container: *const c_void) -> Option<&str> {
+        (*self.Enumeration).name_for_value(self.read_value(container)?)
+    }
+}
+
+#[repr(C)]
+pub struct FInterfaceProperty {
+    pub base: FProperty,
+    pub InterfaceClass: *const UClass,
+}
+
+crate::impl_deref! { FInterfaceProperty as FProperty, no_display }
+
+#[repr(C)]
+pub struct FMapProperty {
+    pub base: FProperty,
+    pub KeyProp: *const FProperty,
+    pub ValueProp: *const FProperty,
+    pad: [u8; 32],
+}
+
+crate::impl_deref! { FMapProperty as FProperty, no_display }
+
+// Both inline and sparse multicast delegate properties.
+#[repr(C)]
+pub struct FMulticastDelegateProperty {
+    pub base: FProperty,
+    pub SignatureFunction: *const UFunction,
+}
+
+crate::impl_deref! { FMulticastDelegateProperty as FProperty, no_display }
+
+#[repr(C)]
+pub struct FSetProperty {
+    pub base: FProperty,
+    pub ElementProp: *const FProperty,
+    pad: [u8; 24],
+}
+
+crate::impl_deref! { FSetProperty as FProperty, no_display }
+
+#[repr(C)]
+pub struct FSoftClassProperty {
+    pub base: FObjectPropertyBase,
+    pub MetaClass: *const UClass,
+}
+
+crate::impl_deref! { FSoftClassProperty as FObjectPropertyBase, no_display }
+
+// #[repr(C)]
+// pub struct FFieldPathProperty {
+//     pub base: FProperty,
+//     PropertyClass: *const FFieldClass,
+// }
@@ -0,0 +1,118 @@
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Full,
+}
+
+struct Slot<T> {
+    // Even stamps mean the slot is empty for lap `stamp / 2`. Odd stamps mean
+    // the slot holds a value written during lap `stamp / 2`.
+    stamp: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+    const fn empty() -> Self {
+        Self {
+            stamp: AtomicUsize::new(0),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+// A fixed-capacity, lock-free queue that any number of threads can push onto
+// and exactly one thread pops from.
+pub struct Queue<T, const N: usize> {
+    slots: [Slot<T>; N],
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+unsafe impl<T: Send, const N: usize> Sync for Queue<T, N> {}
+
+impl<T, const N: usize> Queue<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { Slot::empty() }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn push(&self, value: T) -> Result<(), Error> {
+        let mut tail = self.tail.load(Ordering::Relaxed);
+
+        loop {
+            // Use .get() to elide the bounds check panic branch.
+            let slot = self.slots.get(tail % N).ok_or(Error::Full)?;
+            let empty = 2 * (tail / N);
+            let stamp = slot.stamp.load(Ordering::Acquire);
+
+            if stamp == empty {
+                // The slot is free for this lap. Try to claim it.
+                match self.tail.compare_exchange_weak(
+                    tail,
+                    tail + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe {
+                            // SAFETY: Winning the exchange gives us exclusive
+                            // access to this slot until we publish the stamp.
+                            (*slot.value.get()).write(value);
+                        }
+                        slot.stamp.store(empty + 1, Ordering::Release);
+                        return Ok(());
+                    }
+
+                    Err(current) => tail = current,
+                }
+            } else if stamp < empty {
+                // The consumer hasn't drained this slot from the previous lap.
+                return Err(Error::Full);
+            } else {
+                // Another producer claimed this slot. Catch up.
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    // Only one thread may pop from a queue.
+    pub unsafe fn pop(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        let slot = self.slots.get(head % N)?;
+        let full = 2 * (head / N) + 1;
+
+        if slot.stamp.load(Ordering::Acquire) == full {
+            let value = (*slot.value.get()).assume_init_read();
+            slot.stamp.store(full + 1, Ordering::Release);
+            self.head.store(head + 1, Ordering::Relaxed);
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, const N: usize> Default for Queue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for Queue<T, N> {
+    fn drop(&mut self) {
+        unsafe {
+            // Drop values that were pushed but never popped.
+            while self.pop().is_some() {}
+        }
+    }
+}
@@ -0,0 +1,104 @@
+// Waits for the game to get far enough into startup for us to attach. Each
+// poll asks the caller what it's still waiting for, e.g. globals whose
+// signatures only match once the executable has been unpacked, or an engine
+// that hasn't been created yet. That's logged each time it changes. Signature
+// failures aren't logged until the last try.
+//
+// DRG_NATIVE_ATTACH_TIMEOUT=<seconds>  how long to poll before giving up.
+//                                      120 by default
+// DRG_NATIVE_ATTACH_DELAY=<seconds>    wait that long instead of polling, for
+//                                      when polling attaches too early
+
+use crate::{diagnose, inspect, GUObjectArray};
+use windows::core::PCSTR;
+use windows::Win32::System::Environment::GetEnvironmentVariableA;
+use windows::Win32::System::Threading::Sleep;
+
+const POLL_INTERVAL_MS: u32 = 250;
+const DEFAULT_TIMEOUT_SECONDS: u32 = 120;
+
+const TIMEOUT_VARIABLE: &str = "DRG_NATIVE_ATTACH_TIMEOUT\0";
+const DELAY_VARIABLE: &str = "DRG_NATIVE_ATTACH_DELAY\0";
+
+// `waiting_for` names what isn't ready yet, or returns None once everything
+// is. Returns whether that happened before the timeout.
+pub unsafe fn wait(mut waiting_for: impl FnMut() -> Option<&'static str>) -> bool {
+    if let Some(delay) = seconds(DELAY_VARIABLE) {
+        crate::log!("ready: waiting {}s", delay);
+        Sleep(delay.saturating_mul(1000));
+        return true;
+    }
+
+    let timeout_ms = seconds(TIMEOUT_VARIABLE)
+        .unwrap_or(DEFAULT_TIMEOUT_SECONDS)
+        .saturating_mul(1000);
+
+    let mut waited_ms = 0;
+    let mut last = None;
+    diagnose::set_quiet(true);
+
+    let is_ready = loop {
+        let Some(what) = waiting_for() else {
+            break true;
+        };
+
+        if last != Some(what) {
+            crate::log!("ready: waiting for {} after {}ms", what, waited_ms);
+            last = Some(what);
+        }
+
+        if waited_ms >= timeout_ms {
+            break false;
+        }
+
+        Sleep(POLL_INTERVAL_MS);
+        waited_ms += POLL_INTERVAL_MS;
+    };
+
+    diagnose::set_quiet(false);
+
+    if is_ready {
+        crate::log!("ready: the game was ready after {}ms", waited_ms);
+    } else {
+        crate::log!(
+            "ready: gave up waiting for {} after {}ms",
+            last.unwrap_or("the game"),
+            waited_ms
+        );
+    }
+
+    is_ready
+}
+
+// Whether the engine has created a world yet, which it does once it's set up.
+pub unsafe fn world_exists() -> bool {
+    !GUObjectArray.is_null()
+        && (*GUObjectArray).iter().any(|object| {
+            !object.is_null() && !(*object).is_default_object() && inspect::is_a(object, "World")
+        })
+}
+
+// The whole seconds in the environment variable `name`, which must end in a
+// nul, if it's set.
+unsafe fn seconds(name: &str) -> Option<u32> {
+    let mut value = [0; 16];
+    let len = GetEnvironmentVariableA(PCSTR::from_raw(name.as_ptr()), Some(&mut value));
+
+    if len == 0 {
+        return None;
+    }
+
+    let seconds = value
+        .get(..len as usize)
+        .and_then(|value| core::str::from_utf8(value).ok())
+        .and_then(|value| value.trim().parse().ok());
+
+    if seconds.is_none() {
+        crate::log!(
+            "ready: ignoring {}, which isn't whole seconds",
+            name.trim_end_matches('\0')
+        );
+    }
+
+    seconds
+}
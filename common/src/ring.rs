@@ -0,0 +1,109 @@
+use core::mem::MaybeUninit;
+use core::ptr;
+
+// A fixed-capacity buffer that keeps the last N values pushed onto it. Pushing
+// onto a full ring drops the oldest value.
+pub struct Ring<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    start: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> Ring<T, N> {
+    const UNINITIALIZED_VALUE: MaybeUninit<T> = MaybeUninit::uninit();
+
+    pub const fn new() -> Self {
+        Self {
+            data: [Self::UNINITIALIZED_VALUE; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn push(&mut self, value: T) {
+        if N == 0 {
+            return;
+        }
+
+        if self.len < N {
+            let end = (self.start + self.len) % N;
+            self.data[end] = MaybeUninit::new(value);
+            self.len += 1;
+        } else {
+            // Overwrite the oldest value.
+            unsafe {
+                let oldest = self.data[self.start].as_mut_ptr();
+                ptr::drop_in_place(oldest);
+                oldest.write(value);
+            }
+
+            self.start = (self.start + 1) % N;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        let len = self.len;
+        self.len = 0;
+
+        for i in 0..len {
+            unsafe {
+                ptr::drop_in_place(self.data[(self.start + i) % N].as_mut_ptr());
+            }
+        }
+
+        self.start = 0;
+    }
+
+    // Iterates from the oldest value to the newest.
+    pub fn iter(&self) -> RingIter<T, N> {
+        RingIter {
+            ring: self,
+            index: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for Ring<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for Ring<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct RingIter<'ring, T, const N: usize> {
+    ring: &'ring Ring<T, N>,
+    index: usize,
+}
+
+impl<'ring, T, const N: usize> Iterator for RingIter<'ring, T, N> {
+    type Item = &'ring T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.ring.len {
+            let i = (self.ring.start + self.index) % N;
+            self.index += 1;
+
+            // SAFETY: The `len` values after `start` are initialized.
+            Some(unsafe { self.ring.data.get(i)?.assume_init_ref() })
+        } else {
+            None
+        }
+    }
+}
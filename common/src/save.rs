@@ -0,0 +1,116 @@
+// Loading and saving save games. A save game is an ordinary object whose
+// class declares the saved data as properties, so crate::inspect can read and
+// tweak it without generated types.
+
+use crate::kismet::call_gameplay_statics;
+use crate::list::{self, List};
+use crate::{FString, UFunction, UObject};
+
+use core::ptr;
+
+// Slot names are file names, so they're short.
+const MAX_SLOT_NAME_LEN: usize = 128;
+
+static mut LOAD_GAME_FROM_SLOT: *mut UFunction = ptr::null_mut();
+static mut SAVE_GAME_TO_SLOT: *mut UFunction = ptr::null_mut();
+static mut DOES_SAVE_GAME_EXIST: *mut UFunction = ptr::null_mut();
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    SlotName(#[from] list::Error),
+}
+
+// A null-terminated copy of `slot`, for an FString to borrow.
+fn encode_slot_name(slot: &str) -> Result<List<u16, MAX_SLOT_NAME_LEN>, Error> {
+    let mut name = List::new();
+
+    for unit in slot.encode_utf16().chain([0]) {
+        name.push(unit)?;
+    }
+
+    Ok(name)
+}
+
+// The save game in `slot`, or null if there isn't one or it failed to load.
+pub unsafe fn load_game_from_slot(slot: &str, user_index: i32) -> Result<*mut UObject, Error> {
+    #[repr(C)]
+    struct Parameters {
+        SlotName: FString,
+        UserIndex: i32,
+        ReturnValue: *mut UObject,
+    }
+
+    let slot = encode_slot_name(slot)?;
+
+    let mut parameters = Parameters {
+        SlotName: FString::from(slot.as_slice()),
+        UserIndex: user_index,
+        ReturnValue: ptr::null_mut(),
+    };
+
+    call_gameplay_statics(
+        &mut LOAD_GAME_FROM_SLOT,
+        "Function /Script/Engine.GameplayStatics.LoadGameFromSlot",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    Ok(parameters.ReturnValue)
+}
+
+// Writes `save` to `slot`, replacing what's there. Returns whether it was
+// written.
+pub unsafe fn save_game_to_slot(
+    save: *mut UObject,
+    slot: &str,
+    user_index: i32,
+) -> Result<bool, Error> {
+    #[repr(C)]
+    struct Parameters {
+        SaveGameObject: *mut UObject,
+        SlotName: FString,
+        UserIndex: i32,
+        ReturnValue: bool,
+    }
+
+    let slot = encode_slot_name(slot)?;
+
+    let mut parameters = Parameters {
+        SaveGameObject: save,
+        SlotName: FString::from(slot.as_slice()),
+        UserIndex: user_index,
+        ReturnValue: false,
+    };
+
+    call_gameplay_statics(
+        &mut SAVE_GAME_TO_SLOT,
+        "Function /Script/Engine.GameplayStatics.SaveGameToSlot",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    Ok(parameters.ReturnValue)
+}
+
+pub unsafe fn does_save_game_exist(slot: &str, user_index: i32) -> Result<bool, Error> {
+    #[repr(C)]
+    struct Parameters {
+        SlotName: FString,
+        UserIndex: i32,
+        ReturnValue: bool,
+    }
+
+    let slot = encode_slot_name(slot)?;
+
+    let mut parameters = Parameters {
+        SlotName: FString::from(slot.as_slice()),
+        UserIndex: user_index,
+        ReturnValue: false,
+    };
+
+    call_gameplay_statics(
+        &mut DOES_SAVE_GAME_EXIST,
+        "Function /Script/Engine.GameplayStatics.DoesSaveGameExist",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    Ok(parameters.ReturnValue)
+}
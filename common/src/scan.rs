@@ -0,0 +1,328 @@
+// Scans the game's writable memory for a value, like a cheat engine. Useful
+// for native state that reflection can't see. Start with `first_scan` for a
+// known value, then narrow the candidates with `refine` as the value changes
+// in game.
+//
+// Memory is read and written with ReadProcessMemory and WriteProcessMemory on
+// our own process, which fail instead of crashing if the game frees the
+// memory under us.
+
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::fmt::{self, Display, Formatter};
+use core::mem;
+use windows::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
+use windows::Win32::System::Memory::{
+    VirtualQuery, MEMORY_BASIC_INFORMATION, MEM_COMMIT, PAGE_EXECUTE_READWRITE,
+    PAGE_EXECUTE_WRITECOPY, PAGE_GUARD, PAGE_READWRITE, PAGE_WRITECOPY,
+};
+use windows::Win32::System::Threading::GetCurrentProcess;
+
+// Regions are read this much at a time.
+const CHUNK_LEN: usize = 1024 * 1024;
+
+// Floats match values this close, since the game rarely shows every digit.
+const FLOAT_TOLERANCE: f64 = 0.001;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ValueType {
+    U8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+impl ValueType {
+    // "u8", "i16", "i32", "i64", "f32" or "f64".
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => Self::U8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            _ => return None,
+        })
+    }
+
+    pub fn size(self) -> usize {
+        match self {
+            Self::U8 => 1,
+            Self::I16 => 2,
+            Self::I32 | Self::F32 => 4,
+            Self::I64 | Self::F64 => 8,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum Value {
+    U8(u8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+impl Value {
+    pub fn parse(value_type: ValueType, text: &str) -> Option<Self> {
+        Some(match value_type {
+            ValueType::U8 => Self::U8(text.parse().ok()?),
+            ValueType::I16 => Self::I16(text.parse().ok()?),
+            ValueType::I32 => Self::I32(text.parse().ok()?),
+            ValueType::I64 => Self::I64(text.parse().ok()?),
+            ValueType::F32 => Self::F32(text.parse().ok()?),
+            ValueType::F64 => Self::F64(text.parse().ok()?),
+        })
+    }
+
+    pub fn value_type(self) -> ValueType {
+        match self {
+            Self::U8(_) => ValueType::U8,
+            Self::I16(_) => ValueType::I16,
+            Self::I32(_) => ValueType::I32,
+            Self::I64(_) => ValueType::I64,
+            Self::F32(_) => ValueType::F32,
+            Self::F64(_) => ValueType::F64,
+        }
+    }
+
+    // Reads a value of the type from the front of `bytes`.
+    pub fn from_bytes(value_type: ValueType, bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..value_type.size())?;
+        let mut buffer = [0; 8];
+        buffer[..bytes.len()].copy_from_slice(bytes);
+
+        let [a, b, c, d, ..] = buffer;
+
+        Some(match value_type {
+            ValueType::U8 => Self::U8(a),
+            ValueType::I16 => Self::I16(i16::from_le_bytes([a, b])),
+            ValueType::I32 => Self::I32(i32::from_le_bytes([a, b, c, d])),
+            ValueType::I64 => Self::I64(i64::from_le_bytes(buffer)),
+            ValueType::F32 => Self::F32(f32::from_le_bytes([a, b, c, d])),
+            ValueType::F64 => Self::F64(f64::from_le_bytes(buffer)),
+        })
+    }
+
+    fn to_bytes(self) -> ([u8; 8], usize) {
+        let mut buffer = [0; 8];
+        let len = self.value_type().size();
+
+        match self {
+            Self::U8(n) => buffer[0] = n,
+            Self::I16(n) => buffer[..len].copy_from_slice(&n.to_le_bytes()),
+            Self::I32(n) => buffer[..len].copy_from_slice(&n.to_le_bytes()),
+            Self::I64(n) => buffer = n.to_le_bytes(),
+            Self::F32(n) => buffer[..len].copy_from_slice(&n.to_le_bytes()),
+            Self::F64(n) => buffer = n.to_le_bytes(),
+        }
+
+        (buffer, len)
+    }
+
+    // Whether two values are the same, allowing for float rounding. Values
+    // of different types never match.
+    pub fn matches(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::U8(a), Self::U8(b)) => a == b,
+            (Self::I16(a), Self::I16(b)) => a == b,
+            (Self::I32(a), Self::I32(b)) => a == b,
+            (Self::I64(a), Self::I64(b)) => a == b,
+            (Self::F32(a), Self::F32(b)) => (f64::from(a) - f64::from(b)).abs() <= FLOAT_TOLERANCE,
+            (Self::F64(a), Self::F64(b)) => (a - b).abs() <= FLOAT_TOLERANCE,
+            _ => false,
+        }
+    }
+
+    // Exact, for noticing that a value changed at all.
+    fn same_bits(self, other: Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Self::U8(n) => n.fmt(f),
+            Self::I16(n) => n.fmt(f),
+            Self::I32(n) => n.fmt(f),
+            Self::I64(n) => n.fmt(f),
+            Self::F32(n) => n.fmt(f),
+            Self::F64(n) => n.fmt(f),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Candidate {
+    pub address: usize,
+    // As of the last scan.
+    pub value: Value,
+}
+
+#[derive(Copy, Clone)]
+pub enum Filter {
+    // The value is now this.
+    Exact(Value),
+    Changed,
+    Unchanged,
+}
+
+pub unsafe fn read(address: usize, value_type: ValueType) -> Option<Value> {
+    let mut buffer = [0u8; 8];
+
+    if read_bytes(address, &mut buffer[..value_type.size()]) {
+        Value::from_bytes(value_type, &buffer)
+    } else {
+        None
+    }
+}
+
+// Returns whether the whole value was written.
+pub unsafe fn write(address: usize, value: Value) -> bool {
+    let (bytes, len) = value.to_bytes();
+    let mut written = 0;
+
+    WriteProcessMemory(
+        GetCurrentProcess(),
+        address as *const c_void,
+        bytes.as_ptr().cast(),
+        len,
+        Some(&mut written),
+    )
+    .as_bool()
+        && written == len
+}
+
+// Every aligned address in writable memory that holds `value`, up to `limit`
+// of them.
+pub unsafe fn first_scan(value: Value, limit: usize) -> Vec<Candidate> {
+    let value_type = value.value_type();
+    let size = value_type.size();
+    let mut candidates = Vec::new();
+    let mut chunk = vec![0u8; CHUNK_LEN];
+
+    // The chunk holds copies of what it read, so it'd match itself.
+    let chunk_start = chunk.as_ptr() as usize;
+    let chunk_end = chunk_start + CHUNK_LEN;
+
+    for_each_writable_region(|start, len| {
+        let mut offset = 0;
+
+        while offset < len && candidates.len() < limit {
+            let chunk_address = start + offset;
+            let chunk_len = (len - offset).min(CHUNK_LEN);
+            offset += chunk_len;
+
+            if chunk_address < chunk_end && chunk_start < chunk_address + chunk_len {
+                continue;
+            }
+
+            if !read_bytes(chunk_address, &mut chunk[..chunk_len]) {
+                continue;
+            }
+
+            // Regions are page aligned, so chunks are too.
+            for (i, bytes) in chunk[..chunk_len].chunks_exact(size).enumerate() {
+                let Some(found) = Value::from_bytes(value_type, bytes) else {
+                    continue;
+                };
+
+                if found.matches(value) {
+                    candidates.push(Candidate {
+                        address: chunk_address + i * size,
+                        value: found,
+                    });
+
+                    if candidates.len() == limit {
+                        break;
+                    }
+                }
+            }
+        }
+
+        candidates.len() < limit
+    });
+
+    candidates
+}
+
+// Keeps the candidates that pass `filter`, with their values updated. Drops
+// any that can no longer be read.
+pub unsafe fn refine(candidates: &mut Vec<Candidate>, filter: Filter) {
+    candidates.retain_mut(|candidate| {
+        let Some(now) = read(candidate.address, candidate.value.value_type()) else {
+            return false;
+        };
+
+        let keep = match filter {
+            Filter::Exact(value) => now.matches(value),
+            Filter::Changed => !now.same_bits(candidate.value),
+            Filter::Unchanged => now.same_bits(candidate.value),
+        };
+
+        candidate.value = now;
+        keep
+    });
+}
+
+unsafe fn read_bytes(address: usize, buffer: &mut [u8]) -> bool {
+    let mut read = 0;
+
+    ReadProcessMemory(
+        GetCurrentProcess(),
+        address as *const c_void,
+        buffer.as_mut_ptr().cast(),
+        buffer.len(),
+        Some(&mut read),
+    )
+    .as_bool()
+        && read == buffer.len()
+}
+
+// Calls `f` with the start and length of each committed, writable region
+// until it returns false.
+unsafe fn for_each_writable_region(mut f: impl FnMut(usize, usize) -> bool) {
+    const WRITABLE: u32 =
+        PAGE_READWRITE.0 | PAGE_WRITECOPY.0 | PAGE_EXECUTE_READWRITE.0 | PAGE_EXECUTE_WRITECOPY.0;
+
+    let mut address = 0usize;
+
+    loop {
+        let mut information = MEMORY_BASIC_INFORMATION::default();
+
+        let len = VirtualQuery(
+            Some(address as *const c_void),
+            &mut information,
+            mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+        );
+
+        if len == 0 {
+            return;
+        }
+
+        let start = information.BaseAddress as usize;
+        let size = information.RegionSize;
+
+        let is_writable = information.State == MEM_COMMIT
+            && information.Protect.0 & WRITABLE != 0
+            && information.Protect.0 & PAGE_GUARD.0 == 0;
+
+        if is_writable && !f(start, size) {
+            return;
+        }
+
+        match start.checked_add(size) {
+            Some(next) if next > address => address = next,
+            _ => return,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// Every reflected value of an object at one point in time, for answering
+// "what changed?". Take a snapshot, do something in game, take another, and
+// diff them. Snapshots of two objects of the same class diff just as well.
+//
+// Values are flattened into paths, descending like inspect::dump does:
+// Resources.OwnedResources[0].Amount = 12
+// HealthComponent.MaxHealth = 100
+
+use crate::inspect;
+use crate::{
+    EClassCastFlags, FArrayProperty, FObjectPropertyBase, FProperty, FStructProperty,
+    PropertyValue, TArray, UObject, UStruct,
+};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::fmt::Write as _;
+use core::ptr;
+
+// How far a snapshot descends into structs, arrays and subobjects.
+const MAX_DEPTH: usize = 8;
+
+// Longer arrays only have their first elements recorded.
+const MAX_ELEMENTS: i32 = 256;
+
+pub struct Snapshot {
+    // The object's name when it was taken, e.g. "BP_PlayerCharacter_C
+    // /Game/Maps/...BP_PlayerCharacter_C_0".
+    pub object: String,
+    // In declaration order.
+    pub values: Vec<(String, String)>,
+}
+
+// A value that differs between two snapshots. None if only one of them has
+// it, like an element past the end of a shorter array.
+pub struct Change<'a> {
+    pub path: &'a str,
+    pub before: Option<&'a str>,
+    pub after: Option<&'a str>,
+}
+
+impl Snapshot {
+    pub unsafe fn take(object: *const UObject) -> Self {
+        let mut snapshot = Self {
+            object: format!("{}", *object),
+            values: Vec::new(),
+        };
+
+        let mut path = String::new();
+        snapshot.add_struct(
+            (*object).class().cast(),
+            object.cast(),
+            object,
+            &mut path,
+            0,
+        );
+        snapshot
+    }
+
+    // The values of a struct on its own, like a data table's row, under
+    // `name`.
+    pub unsafe fn of_struct(
+        name: String,
+        structure: *const UStruct,
+        container: *const c_void,
+    ) -> Self {
+        let mut snapshot = Self {
+            object: name,
+            values: Vec::new(),
+        };
+
+        let mut path = String::new();
+        snapshot.add_struct(structure, container, ptr::null(), &mut path, 0);
+        snapshot
+    }
+
+    // What's different in `after`, in `self`'s order, then anything only
+    // `after` has.
+    pub fn diff<'a>(&'a self, after: &'a Self) -> Vec<Change<'a>> {
+        let before_values = self.index();
+        let after_values = after.index();
+        let mut changes = Vec::new();
+
+        for (path, before) in &self.values {
+            let now = after_values.get(path.as_str()).copied();
+
+            if now != Some(before.as_str()) {
+                changes.push(Change {
+                    path,
+                    before: Some(before),
+                    after: now,
+                });
+            }
+        }
+
+        for (path, now) in &after.values {
+            if !before_values.contains_key(path.as_str()) {
+                changes.push(Change {
+                    path,
+                    before: None,
+                    after: Some(now),
+                });
+            }
+        }
+
+        changes
+    }
+
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(p, _)| p == path)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // Snapshots have thousands of values, too many to search one by one.
+    fn index(&self) -> BTreeMap<&str, &str> {
+        self.values
+            .iter()
+            .map(|(path, value)| (path.as_str(), value.as_str()))
+            .collect()
+    }
+
+    // `owner` is the object that `container` is part of. `path` leads to
+    // `container`, and is left as it was.
+    unsafe fn add_struct(
+        &mut self,
+        structure: *const UStruct,
+        container: *const c_void,
+        owner: *const UObject,
+        path: &mut String,
+        depth: usize,
+    ) {
+        if depth == MAX_DEPTH {
+            return;
+        }
+
+        let len = path.len();
+
+        inspect::for_each_property(structure, |property| {
+            if len > 0 {
+                path.push('.');
+            }
+
+            path.push_str((*property).name());
+            self.add(property, container, owner, path, depth);
+            path.truncate(len);
+        });
+    }
+
+    unsafe fn add(
+        &mut self,
+        property: *const FProperty,
+        container: *const c_void,
+        owner: *const UObject,
+        path: &mut String,
+        depth: usize,
+    ) {
+        self.values.push((
+            path.clone(),
+            format!(
+                "{}",
+                PropertyValue {
+                    property,
+                    container
+                }
+            ),
+        ));
+
+        if (*property).array_dim() > 1 {
+            return;
+        }
+
+        if (*property).is(EClassCastFlags::CASTCLASS_FStructProperty) {
+            let structure = (*property.cast::<FStructProperty>()).Structure;
+            let value = (*property).value_ptr(container, 0);
+            self.add_struct(structure, value, owner, path, depth + 1);
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FArrayProperty) {
+            let inner = (*property.cast::<FArrayProperty>()).Inner;
+            let array = (*property).read::<TArray<u8>>(container);
+            let len = path.len();
+
+            // An element's inner property has offset 0, so each element is its
+            // own container.
+            for i in 0..array.len.min(MAX_ELEMENTS) {
+                let element = array
+                    .as_ptr()
+                    .add(i as usize * (*inner).element_size())
+                    .cast::<c_void>();
+
+                let _ = write!(path, "[{}]", i);
+                self.add(inner, element, owner, path, depth + 1);
+                path.truncate(len);
+            }
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FObjectProperty) {
+            let object = (*property.cast::<FObjectPropertyBase>()).read_object(container);
+
+            // Only objects that `owner` owns, like components. Other references
+            // can lead back up the tree.
+            if !object.is_null() && (*object).outer() == owner.cast_mut() {
+                self.add_struct(
+                    (*object).class().cast(),
+                    object.cast(),
+                    object,
+                    path,
+                    depth + 1,
+                );
+            }
+        }
+    }
+}
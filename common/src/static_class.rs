@@ -0,0 +1,76 @@
+// The class behind each SDK type's static_class(), looked up by name the
+// first time it's needed. Blueprint classes are unloaded along with their
+// levels, so the class is kept as a handle, and looked up again once it's
+// gone instead of leaving a dangling pointer.
+//
+// A class that isn't loaded is looked for again at most once a second, since
+// static_class() is called on hot paths and each look walks GUObjectArray.
+// The first miss is logged.
+//
+// pub unsafe fn static_class() -> *const UClass {
+//     static mut CLASS: StaticClass = StaticClass::new("Class /Script/FSD.Item");
+//     CLASS.get()
+// }
+
+use crate::{GUObjectArray, ObjectHandle, UClass};
+use core::ptr;
+use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
+
+pub struct StaticClass {
+    name: &'static str,
+    class: ObjectHandle<UClass>,
+    // The performance counter's value once it's worth looking again, or zero
+    // if the class has never been missing.
+    retry_at: i64,
+}
+
+impl StaticClass {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            class: ObjectHandle::NULL,
+            retry_at: 0,
+        }
+    }
+
+    // The class, or null if it isn't loaded.
+    pub unsafe fn get(&mut self) -> *const UClass {
+        if let Some(class) = self.class.resolve() {
+            return class;
+        }
+
+        let now = counter();
+
+        if now < self.retry_at {
+            return ptr::null();
+        }
+
+        let class: *mut UClass = (*GUObjectArray)
+            .find(self.name)
+            .map_or(ptr::null_mut(), |class| class.cast());
+
+        if class.is_null() {
+            if self.retry_at == 0 {
+                crate::log!("static_class: {} isn't loaded", self.name);
+            }
+
+            self.retry_at = now.saturating_add(frequency());
+        }
+
+        self.class = ObjectHandle::new(class);
+        class
+    }
+}
+
+unsafe fn counter() -> i64 {
+    let mut counter = 0;
+    QueryPerformanceCounter(&mut counter);
+    counter
+}
+
+// Counts per second.
+unsafe fn frequency() -> i64 {
+    let mut frequency = 0;
+    QueryPerformanceFrequency(&mut frequency);
+    frequency
+}
@@ -0,0 +1,236 @@
+// Reads the game's string tables, and overrides what their entries show, for
+// relabeling HUD text or marking which entry a piece of UI comes from.
+//
+// An override changes the localized string that every text for the entry
+// shares, so it shows everywhere at once. Widgets already showing the entry
+// may keep the old string until they're next set. Overrides last until the
+// game reloads its localization, e.g. on a language change.
+
+use crate::asset::find_cached;
+use crate::kismet;
+use crate::{FName, FString, FText, TArray, UFunction, UObject};
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use core::ffi::c_void;
+use core::mem;
+use core::ptr;
+
+static mut KISMET_STRING_TABLE_LIBRARY: *mut UObject = ptr::null_mut();
+static mut GET_REGISTERED_STRING_TABLES: *mut UFunction = ptr::null_mut();
+static mut GET_KEYS_FROM_STRING_TABLE: *mut UFunction = ptr::null_mut();
+static mut GET_TABLE_ENTRY_SOURCE_STRING: *mut UFunction = ptr::null_mut();
+static mut IS_REGISTERED_TABLE_ENTRY: *mut UFunction = ptr::null_mut();
+
+static mut TEXT_FROM_STRING_TABLE: *mut UFunction = ptr::null_mut();
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    UnknownTable,
+    UnknownEntry,
+    CallFailed,
+    // The entry's text has no string of its own to override.
+    NoDisplayString,
+}
+
+unsafe fn call_string_table_library(
+    function: &mut *mut UFunction,
+    function_name: &'static str,
+    parameters: *mut c_void,
+) -> Result<(), Error> {
+    let library = find_cached(
+        &mut KISMET_STRING_TABLE_LIBRARY,
+        "KismetStringTableLibrary /Script/Engine.Default__KismetStringTableLibrary",
+    );
+
+    let function = find_cached(function, function_name);
+
+    if library.is_null() || function.is_null() {
+        return Err(Error::CallFailed);
+    }
+
+    UObject::process_event(library, function, parameters);
+    Ok(())
+}
+
+// The IDs of every registered string table, e.g. "/Game/UI/ST_HUD.ST_HUD".
+pub unsafe fn tables() -> Result<Vec<String>, Error> {
+    #[repr(C)]
+    struct Parameters {
+        ReturnValue: TArray<FName>,
+    }
+
+    let mut parameters = Parameters {
+        ReturnValue: TArray::new(),
+    };
+
+    call_string_table_library(
+        &mut GET_REGISTERED_STRING_TABLES,
+        "Function /Script/Engine.KismetStringTableLibrary.GetRegisteredStringTables",
+        (&mut parameters as *mut Parameters).cast(),
+    )?;
+
+    let tables = parameters
+        .ReturnValue
+        .iter()
+        .map(|name| name.text().to_owned())
+        .collect();
+
+    parameters.ReturnValue.free();
+    Ok(tables)
+}
+
+pub unsafe fn keys(table: &str) -> Result<Vec<String>, Error> {
+    #[repr(C)]
+    struct Parameters {
+        TableId: FName,
+        ReturnValue: TArray<FString>,
+    }
+
+    let mut parameters = Parameters {
+        TableId: table_id(table)?,
+        ReturnValue: TArray::new(),
+    };
+
+    call_string_table_library(
+        &mut GET_KEYS_FROM_STRING_TABLE,
+        "Function /Script/Engine.KismetStringTableLibrary.GetKeysFromStringTable",
+        (&mut parameters as *mut Parameters).cast(),
+    )?;
+
+    let keys = parameters
+        .ReturnValue
+        .iter()
+        .map(|key| format!("{}", key))
+        .collect();
+
+    for key in parameters.ReturnValue.iter_mut() {
+        key.free();
+    }
+
+    parameters.ReturnValue.free();
+    Ok(keys)
+}
+
+// The entry as written in the table, before localization or overrides.
+pub unsafe fn source_string(table: &str, key: &str) -> Result<String, Error> {
+    #[repr(C)]
+    struct Parameters {
+        TableId: FName,
+        Key: FString,
+        ReturnValue: FString,
+    }
+
+    let key = wide(key);
+
+    let mut parameters = Parameters {
+        TableId: table_id(table)?,
+        Key: FString::from(key.as_slice()),
+        ReturnValue: FString::new(),
+    };
+
+    call_string_table_library(
+        &mut GET_TABLE_ENTRY_SOURCE_STRING,
+        "Function /Script/Engine.KismetStringTableLibrary.GetTableEntrySourceString",
+        (&mut parameters as *mut Parameters).cast(),
+    )?;
+
+    let source = format!("{}", parameters.ReturnValue);
+    parameters.ReturnValue.free();
+    Ok(source)
+}
+
+pub unsafe fn has_entry(table: &str, key: &str) -> Result<bool, Error> {
+    #[repr(C)]
+    struct Parameters {
+        TableId: FName,
+        Key: FString,
+        ReturnValue: bool,
+    }
+
+    let key = wide(key);
+
+    let mut parameters = Parameters {
+        TableId: table_id(table)?,
+        Key: FString::from(key.as_slice()),
+        ReturnValue: false,
+    };
+
+    call_string_table_library(
+        &mut IS_REGISTERED_TABLE_ENTRY,
+        "Function /Script/Engine.KismetStringTableLibrary.IsRegisteredTableEntry",
+        (&mut parameters as *mut Parameters).cast(),
+    )?;
+
+    Ok(parameters.ReturnValue)
+}
+
+// What the entry shows right now, with any override.
+pub unsafe fn display_string(table: &str, key: &str) -> Result<String, Error> {
+    Ok(format!("{}", *entry_display_string(table, key)?))
+}
+
+// Makes the entry show `text` instead. Returns what it showed before, to put
+// back with another override.
+pub unsafe fn set(table: &str, key: &str, text: &str) -> Result<String, Error> {
+    let display_string = entry_display_string(table, key)?;
+    let previous = format!("{}", *display_string);
+    (*display_string).set(text);
+    Ok(previous)
+}
+
+unsafe fn entry_display_string(table: &str, key: &str) -> Result<*mut FString, Error> {
+    // A missing entry's text shows a placeholder, which isn't ours to change.
+    if !has_entry(table, key)? {
+        return Err(Error::UnknownEntry);
+    }
+
+    #[repr(C)]
+    struct Parameters {
+        TableId: FName,
+        Key: FString,
+        ReturnValue: FText,
+    }
+
+    let key = wide(key);
+
+    // A zeroed FText has no text data to release when the engine assigns
+    // over it.
+    let mut parameters = Parameters {
+        TableId: table_id(table)?,
+        Key: FString::from(key.as_slice()),
+        ReturnValue: mem::zeroed(),
+    };
+
+    let called = kismet::call_kismet_text_library(
+        &mut TEXT_FROM_STRING_TABLE,
+        "Function /Script/Engine.KismetTextLibrary.TextFromStringTable",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    if !called {
+        return Err(Error::CallFailed);
+    }
+
+    // We can't release the text, so its reference keeps the string alive,
+    // which is what we want.
+    let display_string = parameters.ReturnValue.display_string();
+
+    if display_string.is_null() {
+        Err(Error::NoDisplayString)
+    } else {
+        Ok(display_string)
+    }
+}
+
+// Every table ID is already a name, so there's no need to make one.
+unsafe fn table_id(table: &str) -> Result<FName, Error> {
+    FName::from_existing(table).ok_or(Error::UnknownTable)
+}
+
+// FString's length counts the null terminator.
+fn wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain([0]).collect()
+}
@@ -1,4 +1,6 @@
 use core::fmt::Display;
+
+#[cfg(feature = "std")]
 use std::time::Instant;
 
 pub struct Timer<A: Display> {
@@ -22,3 +24,40 @@ impl<A: Display> Timer<A> {
         crate::log!("END: {} ({:?} elapsed)", self.action, elapsed);
     }
 }
+
+// Just enough of Instant for Timer, from the performance counter.
+#[cfg(not(feature = "std"))]
+#[derive(Copy, Clone)]
+struct Instant(i64);
+
+#[cfg(not(feature = "std"))]
+impl Instant {
+    fn now() -> Self {
+        use windows::Win32::System::Performance::QueryPerformanceCounter;
+
+        let mut counter = 0;
+
+        unsafe {
+            QueryPerformanceCounter(&mut counter);
+        }
+
+        Self(counter)
+    }
+
+    fn duration_since(self, earlier: Self) -> core::time::Duration {
+        use windows::Win32::System::Performance::QueryPerformanceFrequency;
+
+        let mut frequency = 0;
+
+        unsafe {
+            QueryPerformanceFrequency(&mut frequency);
+        }
+
+        if frequency <= 0 {
+            return core::time::Duration::ZERO;
+        }
+
+        let ticks = self.0.saturating_sub(earlier.0).max(0);
+        core::time::Duration::from_secs_f64(ticks as f64 / frequency as f64)
+    }
+}
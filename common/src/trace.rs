@@ -0,0 +1,41 @@
+// Line traces for finding what the player is looking at.
+
+use crate::kismet::{self, FHitResult};
+use crate::{view, FVector, UObject};
+
+// ETraceTypeQuery::TraceTypeQuery1, which the engine maps to the Visibility
+// channel.
+const VISIBILITY: u8 = 0;
+
+// Farther than any cave is long.
+pub const DEFAULT_DISTANCE: f32 = 100_000.0;
+
+// The first thing visible along the line from `start` to `end`, skipping
+// `ignore`.
+pub unsafe fn line(
+    world_context: *mut UObject,
+    start: FVector,
+    end: FVector,
+    ignore: &[*mut UObject],
+) -> Option<FHitResult> {
+    kismet::line_trace_single(world_context, start, end, VISIBILITY, false, ignore)
+}
+
+// What's in the middle of `controller`'s screen, up to `distance` away. The
+// controller's own pawn doesn't count, since the camera may be inside it.
+pub unsafe fn under_crosshair(controller: *mut UObject, distance: f32) -> Option<FHitResult> {
+    let camera_manager = (*controller).object_property("PlayerCameraManager");
+
+    if camera_manager.is_null() {
+        return None;
+    }
+
+    let pov = view::camera_pov(camera_manager)?;
+    let start = pov.Location;
+    let end = start + pov.Rotation.vector() * distance;
+
+    // The engine skips a null pawn.
+    let pawn = (*controller).object_property("Pawn");
+
+    line(controller, start, end, &[pawn])
+}
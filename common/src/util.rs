@@ -23,10 +23,44 @@ macro_rules! log {
 
         let _ = writeln!(&mut Stdout, $($arg)*);
         */
-        println!($($arg)*);
+        match format_args!($($arg)*) {
+            args => {
+                $crate::history::record(args);
+                $crate::util::print(args);
+            }
+        }
     }}
 }
 
+// Where log!() lines go besides the history: the console with std, and the
+// debugger's output without it.
+#[cfg(feature = "std")]
+pub fn print(args: core::fmt::Arguments) {
+    println!("{}", args);
+}
+
+#[cfg(not(feature = "std"))]
+pub fn print(args: core::fmt::Arguments) {
+    use core::fmt::Write;
+    use windows::core::PCSTR;
+    use windows::Win32::System::Diagnostics::Debug::OutputDebugStringA;
+
+    let mut line = crate::List::<u8, 512>::new();
+    let _ = writeln!(line, "{}", args);
+
+    // Cut a long line short to make room for the terminator.
+    if line.push(0).is_err() {
+        match line.last_mut() {
+            Some(last) => *last = 0,
+            None => return,
+        }
+    }
+
+    unsafe {
+        OutputDebugStringA(PCSTR(line.as_slice().as_ptr()));
+    }
+}
+
 pub fn align(x: usize, alignment: usize) -> usize {
     (x + alignment - 1) & !(alignment - 1)
 }
@@ -0,0 +1,135 @@
+// Projects world positions onto the screen the way the renderer does, from a
+// snapshot of the camera. Take one snapshot per frame and project as many
+// points as needed without calling into the engine.
+
+#[cfg(not(feature = "std"))]
+use crate::float::Float;
+use crate::{FMatrix, FRotator, FStructProperty, FVector, FVector2D, UObject};
+
+// The renderer's near clipping plane. Anything closer is behind the camera.
+const NEAR_CLIP_PLANE: f32 = 10.0;
+
+// The start of the engine's FMinimalViewInfo.
+#[repr(C)]
+pub struct FMinimalViewInfo {
+    pub Location: FVector,
+    pub Rotation: FRotator,
+
+    // Horizontal, in degrees.
+    pub FOV: f32,
+}
+
+pub struct View {
+    view_projection: FMatrix,
+    width: f32,
+    height: f32,
+}
+
+impl View {
+    pub fn new(location: FVector, rotation: FRotator, fov: f32, width: f32, height: f32) -> Self {
+        let (forward, right, up) = rotation.matrix().axes();
+
+        // Moves the camera to the origin and turns the world so that X is
+        // right, Y is up and Z is into the screen.
+        let view = FMatrix {
+            M: [
+                [right.X, up.X, forward.X, 0.0],
+                [right.Y, up.Y, forward.Y, 0.0],
+                [right.Z, up.Z, forward.Z, 0.0],
+                [
+                    -location.dot(right),
+                    -location.dot(up),
+                    -location.dot(forward),
+                    1.0,
+                ],
+            ],
+        };
+
+        // The engine's reversed Z perspective matrix with an infinite far
+        // plane. W ends up as the distance into the screen.
+        let x_scale = 1.0 / (fov.to_radians() / 2.0).tan();
+        let y_scale = x_scale * width / height;
+
+        let projection = FMatrix {
+            M: [
+                [x_scale, 0.0, 0.0, 0.0],
+                [0.0, y_scale, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+                [0.0, 0.0, NEAR_CLIP_PLANE, 0.0],
+            ],
+        };
+
+        Self {
+            view_projection: view.multiply(&projection),
+            width,
+            height,
+        }
+    }
+
+    // The view that `camera_manager`, an APlayerCameraManager, last rendered
+    // with, for a screen of `width` by `height` pixels.
+    pub unsafe fn from_camera_manager(
+        camera_manager: *const UObject,
+        width: f32,
+        height: f32,
+    ) -> Option<Self> {
+        let pov = camera_pov(camera_manager)?;
+        Some(Self::new(
+            pov.Location,
+            pov.Rotation,
+            pov.FOV,
+            width,
+            height,
+        ))
+    }
+
+    // The view of `controller`'s camera manager.
+    pub unsafe fn from_player_controller(
+        controller: *const UObject,
+        width: f32,
+        height: f32,
+    ) -> Option<Self> {
+        let camera_manager = (*controller).object_property("PlayerCameraManager");
+
+        if camera_manager.is_null() {
+            None
+        } else {
+            Self::from_camera_manager(camera_manager, width, height)
+        }
+    }
+
+    // Where `point` appears on the screen, in pixels from the top left, or
+    // None if it's behind the camera.
+    pub fn project(&self, point: FVector) -> Option<FVector2D> {
+        let [x, y, _, w] = self.view_projection.transform_position(point);
+
+        if w < NEAR_CLIP_PLANE {
+            return None;
+        }
+
+        Some(FVector2D {
+            X: (1.0 + x / w) * 0.5 * self.width,
+            Y: (1.0 - y / w) * 0.5 * self.height,
+        })
+    }
+}
+
+// APlayerCameraManager::CameraCachePrivate.POV. Found through reflection,
+// since the POV's offset in FCameraCacheEntry depends on its alignment.
+pub unsafe fn camera_pov(camera_manager: *const UObject) -> Option<&'static FMinimalViewInfo> {
+    let cache = (*(*camera_manager).class()).find_property("CameraCachePrivate");
+
+    if cache.is_null() {
+        return None;
+    }
+
+    let entry = (*cache.cast::<FStructProperty>()).Structure;
+    let pov = (*entry).find_property("POV");
+
+    if pov.is_null() {
+        return None;
+    }
+
+    let entry = (*cache).value_ptr::<u8>(camera_manager.cast(), 0);
+    Some(&*(*pov).value_ptr(entry.cast(), 0))
+}
@@ -0,0 +1,164 @@
+// The blueprint VM. FFrame::Step() runs a function's bytecode one opcode at a
+// time by calling the opcode's handler in GNatives, a table of 256
+// FNativeFuncPtrs indexed by opcode. Replacing a handler sees every use of
+// that opcode, including calls between blueprint functions that never go
+// through ProcessEvent.
+//
+// A handler is called with the frame's `Code` just past its opcode, so it can
+// read the opcode's operands. EX_FinalFunction's is the UFunction it calls:
+//
+// unsafe extern "C" fn on_final_function(
+//     context: *mut UObject,
+//     stack: *mut FFrame,
+//     result: *mut c_void,
+// ) {
+//     let function = (*stack).Code.cast::<*mut UFunction>().read_unaligned();
+//     common::log!("{} calls {}", *(*stack).Node, *function);
+//     ORIGINAL(context, stack, result);
+// }
+//
+// Native functions called from blueprints, like KismetMathLibrary's, are
+// dispatched through their UFunction's Func instead, which can be hooked
+// directly.
+
+use crate::{diagnose, offsets, win, FNativeFuncPtr};
+use core::ptr;
+
+// Opcodes from Script.h's EExprToken.
+pub const EX_LocalVariable: u8 = 0x00;
+pub const EX_InstanceVariable: u8 = 0x01;
+pub const EX_Return: u8 = 0x04;
+pub const EX_Jump: u8 = 0x06;
+pub const EX_JumpIfNot: u8 = 0x07;
+pub const EX_Let: u8 = 0x0F;
+pub const EX_Context: u8 = 0x19;
+pub const EX_VirtualFunction: u8 = 0x1B;
+pub const EX_FinalFunction: u8 = 0x1C;
+pub const EX_LocalVirtualFunction: u8 = 0x45;
+pub const EX_LocalFinalFunction: u8 = 0x46;
+pub const EX_PushExecutionFlow: u8 = 0x4C;
+pub const EX_PopExecutionFlow: u8 = 0x4D;
+pub const EX_ComputedJump: u8 = 0x4E;
+pub const EX_EndOfScript: u8 = 0x53;
+pub const EX_CallMulticastDelegate: u8 = 0x63;
+pub const EX_CallMath: u8 = 0x68;
+
+const EX_MAX: usize = 0x100;
+
+static mut GNatives: *mut [FNativeFuncPtr; EX_MAX] = ptr::null_mut();
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Module(#[from] win::module::Error),
+    FindGNatives,
+}
+
+// The game's handler for `opcode` while alive, replaced by `hook`.
+pub struct OpcodeHook {
+    opcode: u8,
+    original: FNativeFuncPtr,
+}
+
+impl OpcodeHook {
+    pub unsafe fn new(
+        opcode: u8,
+        where_to_place_original: *mut FNativeFuncPtr,
+        hook: FNativeFuncPtr,
+    ) -> Result<Self, Error> {
+        let handler = handler(natives()?, opcode);
+        let original = *handler;
+        *where_to_place_original = original;
+        *handler = hook;
+        Ok(Self { opcode, original })
+    }
+}
+
+impl Drop for OpcodeHook {
+    fn drop(&mut self) {
+        unsafe {
+            if !GNatives.is_null() {
+                *handler(GNatives, self.opcode) = self.original;
+            }
+        }
+    }
+}
+
+// An opcode always indexes inside the table.
+unsafe fn handler(natives: *mut [FNativeFuncPtr; EX_MAX], opcode: u8) -> *mut FNativeFuncPtr {
+    natives.cast::<FNativeFuncPtr>().add(usize::from(opcode))
+}
+
+// Found the first time it's needed, since most sessions don't.
+unsafe fn natives() -> Result<*mut [FNativeFuncPtr; EX_MAX], Error> {
+    if GNatives.is_null() {
+        GNatives = find(&win::Module::current()?)?;
+    }
+
+    Ok(GNatives)
+}
+
+unsafe fn find(module: &win::Module) -> Result<*mut [FNativeFuncPtr; EX_MAX], Error> {
+    if let Some(address) = offsets::get("GNatives") {
+        return Ok(address as *mut _);
+    }
+
+    // FFrame::Step():
+    // 48:8B41 20                | mov rax,qword ptr ds:[rcx+20]          |
+    // 4C:8BD2                   | mov r10,rdx                            |
+    // 48:8BD1                   | mov rdx,rcx                            |
+    // 44:0FB608                 | movzx r9d,byte ptr ds:[rax]            |
+    // 48:FFC0                   | inc rax                                |
+    // 48:8941 20                | mov qword ptr ds:[rcx+20],rax          |
+    // 48:8D05 ????????          | lea rax,qword ptr ds:[GNatives]        |
+    // 49:8BCA                   | mov rcx,r10                            |
+    const PATTERN: [Option<u8>; 31] = [
+        Some(0x48),
+        Some(0x8B),
+        Some(0x41),
+        Some(0x20),
+        Some(0x4C),
+        Some(0x8B),
+        Some(0xD2),
+        Some(0x48),
+        Some(0x8B),
+        Some(0xD1),
+        Some(0x44),
+        Some(0x0F),
+        Some(0xB6),
+        Some(0x08),
+        Some(0x48),
+        Some(0xFF),
+        Some(0xC0),
+        Some(0x48),
+        Some(0x89),
+        Some(0x41),
+        Some(0x20),
+        Some(0x48),
+        Some(0x8D),
+        Some(0x05),
+        None,
+        None,
+        None,
+        None,
+        Some(0x49),
+        Some(0x8B),
+        Some(0xCA),
+    ];
+
+    // Where the lea's displacement is, and the instruction after it.
+    const DISPLACEMENT: usize = 24;
+    const NEXT_INSTRUCTION: usize = 28;
+
+    let step: *const u8 = module.find(&PATTERN).ok_or_else(|| {
+        diagnose::pattern_not_found(module, "GNatives", &PATTERN);
+        Error::FindGNatives
+    })?;
+
+    let displacement = step.add(DISPLACEMENT).cast::<i32>().read_unaligned();
+
+    Ok(step
+        .add(NEXT_INSTRUCTION)
+        .offset(displacement as isize)
+        .cast_mut()
+        .cast())
+}
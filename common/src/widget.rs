@@ -0,0 +1,216 @@
+// Creates UMG widgets and puts them on screen through the game's own UI, for
+// status text, HUD elements and menus that look like the rest of the game.
+// A widget class is a UserWidget blueprint, e.g. one loaded from a mod's pak.
+//
+// A widget blueprint's named children, like "TextBlock_FPS", are object
+// properties of the widget when they're marked as variables, so
+// UObject::object_property finds them.
+//
+// Nothing keeps a widget that isn't on screen from being garbage collected,
+// so the hook roots the ones it holds on to with gc::keep, which lets them go
+// again when it unloads.
+
+use crate::asset::find_cached;
+use crate::kismet;
+use crate::{FText, FVector2D, UClass, UFunction, UObject};
+
+use core::ffi::c_void;
+use core::ptr;
+
+static mut WIDGET_BLUEPRINT_LIBRARY: *mut UObject = ptr::null_mut();
+static mut CREATE: *mut UFunction = ptr::null_mut();
+
+static mut ADD_TO_VIEWPORT: *mut UFunction = ptr::null_mut();
+static mut SET_POSITION_IN_VIEWPORT: *mut UFunction = ptr::null_mut();
+static mut SET_DESIRED_SIZE_IN_VIEWPORT: *mut UFunction = ptr::null_mut();
+static mut REMOVE_FROM_PARENT: *mut UFunction = ptr::null_mut();
+static mut SET_VISIBILITY: *mut UFunction = ptr::null_mut();
+static mut SET_RENDER_OPACITY: *mut UFunction = ptr::null_mut();
+static mut SET_TEXT: *mut UFunction = ptr::null_mut();
+
+// ESlateVisibility
+#[derive(Copy, Clone)]
+#[repr(u8)]
+pub enum Visibility {
+    Visible,
+    Collapsed,
+    Hidden,
+    HitTestInvisible,
+    SelfHitTestInvisible,
+}
+
+unsafe fn call(
+    object: *mut UObject,
+    function: &mut *mut UFunction,
+    function_name: &'static str,
+    parameters: *mut c_void,
+) -> bool {
+    let function = find_cached(function, function_name);
+
+    if object.is_null() || function.is_null() {
+        return false;
+    }
+
+    UObject::process_event(object, function, parameters);
+    true
+}
+
+// A new widget of `class`, a UserWidget subclass, or null. `owning_player` is
+// the player controller it belongs to.
+pub unsafe fn create(
+    world_context: *mut UObject,
+    class: *const UClass,
+    owning_player: *mut UObject,
+) -> *mut UObject {
+    #[repr(C)]
+    struct Parameters {
+        WorldContextObject: *mut UObject,
+        WidgetType: *const UClass,
+        OwningPlayer: *mut UObject,
+        ReturnValue: *mut UObject,
+    }
+
+    let mut parameters = Parameters {
+        WorldContextObject: world_context,
+        WidgetType: class,
+        OwningPlayer: owning_player,
+        ReturnValue: ptr::null_mut(),
+    };
+
+    let library = find_cached(
+        &mut WIDGET_BLUEPRINT_LIBRARY,
+        "WidgetBlueprintLibrary /Script/UMG.Default__WidgetBlueprintLibrary",
+    );
+
+    call(
+        library,
+        &mut CREATE,
+        "Function /Script/UMG.WidgetBlueprintLibrary.Create",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+
+    parameters.ReturnValue
+}
+
+// Widgets with a higher `z_order` are drawn over those with a lower one.
+pub unsafe fn add_to_viewport(widget: *mut UObject, z_order: i32) {
+    #[repr(C)]
+    struct Parameters {
+        ZOrder: i32,
+    }
+
+    let mut parameters = Parameters { ZOrder: z_order };
+
+    call(
+        widget,
+        &mut ADD_TO_VIEWPORT,
+        "Function /Script/UMG.UserWidget.AddToViewport",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+}
+
+// In pixels from the top left of the viewport.
+pub unsafe fn set_position_in_viewport(widget: *mut UObject, position: FVector2D) {
+    #[repr(C)]
+    struct Parameters {
+        Position: FVector2D,
+        bRemoveDPIScale: bool,
+    }
+
+    let mut parameters = Parameters {
+        Position: position,
+        bRemoveDPIScale: true,
+    };
+
+    call(
+        widget,
+        &mut SET_POSITION_IN_VIEWPORT,
+        "Function /Script/UMG.UserWidget.SetPositionInViewport",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+}
+
+pub unsafe fn set_desired_size_in_viewport(widget: *mut UObject, size: FVector2D) {
+    #[repr(C)]
+    struct Parameters {
+        Size: FVector2D,
+    }
+
+    let mut parameters = Parameters { Size: size };
+
+    call(
+        widget,
+        &mut SET_DESIRED_SIZE_IN_VIEWPORT,
+        "Function /Script/UMG.UserWidget.SetDesiredSizeInViewport",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+}
+
+// Takes the widget off the screen, or out of the panel it's in.
+pub unsafe fn remove_from_parent(widget: *mut UObject) {
+    call(
+        widget,
+        &mut REMOVE_FROM_PARENT,
+        "Function /Script/UMG.Widget.RemoveFromParent",
+        ptr::null_mut(),
+    );
+}
+
+pub unsafe fn set_visibility(widget: *mut UObject, visibility: Visibility) {
+    #[repr(C)]
+    struct Parameters {
+        InVisibility: Visibility,
+    }
+
+    let mut parameters = Parameters {
+        InVisibility: visibility,
+    };
+
+    call(
+        widget,
+        &mut SET_VISIBILITY,
+        "Function /Script/UMG.Widget.SetVisibility",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+}
+
+// From 0, invisible, to 1, opaque.
+pub unsafe fn set_render_opacity(widget: *mut UObject, opacity: f32) {
+    #[repr(C)]
+    struct Parameters {
+        InOpacity: f32,
+    }
+
+    let mut parameters = Parameters { InOpacity: opacity };
+
+    call(
+        widget,
+        &mut SET_RENDER_OPACITY,
+        "Function /Script/UMG.Widget.SetRenderOpacity",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+}
+
+// Sets what a TextBlock shows.
+//
+// We can't run FText's destructor, so each call leaks the small text it
+// makes. Only call it when the text changes, not every frame.
+pub unsafe fn set_text(text_block: *mut UObject, text: &str) {
+    #[repr(C)]
+    struct Parameters {
+        InText: FText,
+    }
+
+    let Some(text) = kismet::string_to_text(text) else {
+        return;
+    };
+
+    let mut parameters = Parameters { InText: text };
+
+    call(
+        text_block,
+        &mut SET_TEXT,
+        "Function /Script/UMG.TextBlock.SetText",
+        (&mut parameters as *mut Parameters).cast(),
+    );
+}
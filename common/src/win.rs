@@ -2,10 +2,16 @@ use windows::Win32::Foundation::HMODULE;
 use windows::Win32::System::LibraryLoader::DisableThreadLibraryCalls;
 
 pub mod module;
-pub use module::Module;
+pub use module::{LoadedModule, Module};
+
+// Writes that undo themselves, for hooks and other patches to the game's code.
+mod patch;
+pub use patch::{BytePatch, Patch};
 
 pub mod random;
 
+pub mod threads;
+
 pub const DLL_PROCESS_DETACH: u32 = 0;
 pub const DLL_PROCESS_ATTACH: u32 = 1;
 pub const STD_OUTPUT_HANDLE: u32 = 0xFFFF_FFF5;
@@ -21,10 +27,7 @@ pub unsafe fn dll_main(
 ) -> i32 {
     if reason == DLL_PROCESS_ATTACH {
         DisableThreadLibraryCalls(dll);
-        std::thread::spawn(move || unsafe {
-            std::thread::sleep(std::time::Duration::from_secs(10));
-            on_attach(dll)
-        });
+        spawn_attach(dll, on_attach);
     } else if reason == DLL_PROCESS_DETACH {
         on_detach();
     }
@@ -32,4 +35,32 @@ pub unsafe fn dll_main(
     1
 }
 
-pub unsafe fn idle() {}
+// `on_attach` runs right away, so it waits for the game itself. See ready.
+#[cfg(feature = "std")]
+unsafe fn spawn_attach(dll: HMODULE, on_attach: ThreadProc) {
+    std::thread::spawn(move || unsafe { on_attach(dll) });
+}
+
+// Without std, the same through CreateThread. There's only ever one attach,
+// so its arguments wait in a static.
+#[cfg(not(feature = "std"))]
+static mut ATTACH: Option<(HMODULE, ThreadProc)> = None;
+
+#[cfg(not(feature = "std"))]
+unsafe fn spawn_attach(dll: HMODULE, on_attach: ThreadProc) {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{CreateThread, THREAD_CREATION_FLAGS};
+
+    unsafe extern "system" fn attach(_: *mut core::ffi::c_void) -> u32 {
+        match ATTACH {
+            Some((dll, on_attach)) => on_attach(dll),
+            None => 0,
+        }
+    }
+
+    ATTACH = Some((dll, on_attach));
+
+    if let Ok(thread) = CreateThread(None, 0, Some(attach), None, THREAD_CREATION_FLAGS(0), None) {
+        CloseHandle(thread);
+    }
+}
@@ -1,16 +1,131 @@
+use windows::core::PCSTR;
+use windows::Win32::Foundation::HMODULE;
 use windows::Win32::System::LibraryLoader::GetModuleHandleA;
+use windows::Win32::System::ProcessStatus::{
+    EnumProcessModules, GetModuleBaseNameA, GetModuleInformation, MODULEINFO,
+};
+use windows::Win32::System::Threading::GetCurrentProcess;
 
+use crate::list::{self, List};
 use crate::util;
 
+use core::ffi::c_void;
+use core::mem;
 use core::slice;
+use core::str;
+
+pub mod pe;
+mod rtti;
+mod xref;
+pub use xref::Xrefs;
 
 #[derive(macros::NoPanicErrorDebug)]
 pub enum Error {
     GetModuleHandle,
     FindTextSection,
+    FindVTable,
+    FindString,
+    EnumProcessModules,
+    GetModuleInformation,
+    List(#[from] list::Error),
+}
+
+// More than enough for the few hundred modules the game loads.
+const MAX_MODULES: usize = 1024;
+
+const MAX_PATH: usize = 260;
+
+// A module loaded into the game's process.
+pub struct LoadedModule {
+    name: List<u8, MAX_PATH>,
+    base: usize,
+    size: usize,
+}
+
+impl LoadedModule {
+    pub fn name(&self) -> &str {
+        // SAFETY: We only store names that we checked are UTF-8.
+        unsafe { str::from_utf8_unchecked(self.name.as_slice()) }
+    }
+
+    // Finds the primary vtable of a C++ class by its RTTI name, e.g. "UWorld".
+    pub unsafe fn find_vtable(&self, class: &str) -> Result<*const *const c_void, Error> {
+        rtti::find_vtable(self.base, class)
+    }
+
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // The scannable code of this module.
+    pub unsafe fn module(&self) -> Result<Module, Error> {
+        Module::from_base(self.base)
+    }
+}
+
+// Calls `f` with every module loaded into the game's process, starting with
+// the game's executable.
+pub unsafe fn for_each_loaded(mut f: impl FnMut(&LoadedModule)) -> Result<(), Error> {
+    let process = GetCurrentProcess();
+    let mut modules = [HMODULE::default(); MAX_MODULES];
+    let mut bytes_needed = 0;
+
+    #[allow(clippy::cast_possible_truncation)]
+    let ok = EnumProcessModules(
+        process,
+        modules.as_mut_ptr(),
+        mem::size_of_val(&modules) as u32,
+        &mut bytes_needed,
+    );
+
+    if !ok.as_bool() {
+        return Err(Error::EnumProcessModules);
+    }
+
+    let count = (bytes_needed as usize / mem::size_of::<HMODULE>()).min(MAX_MODULES);
+
+    for &module in &modules[..count] {
+        let mut information = MODULEINFO::default();
+
+        #[allow(clippy::cast_possible_truncation)]
+        let ok = GetModuleInformation(
+            process,
+            module,
+            &mut information,
+            mem::size_of::<MODULEINFO>() as u32,
+        );
+
+        if !ok.as_bool() {
+            return Err(Error::GetModuleInformation);
+        }
+
+        let mut buffer = [0; MAX_PATH];
+        let len = GetModuleBaseNameA(process, module, &mut buffer) as usize;
+        let name = buffer.get(..len).unwrap_or_default();
+
+        let mut loaded = LoadedModule {
+            name: List::new(),
+            base: information.lpBaseOfDll as usize,
+            size: information.SizeOfImage as usize,
+        };
+
+        // Module names are ANSI. Skip the name if it isn't also UTF-8.
+        if str::from_utf8(name).is_ok() {
+            loaded.name.write_bytes(name)?;
+        }
+
+        f(&loaded);
+    }
+
+    Ok(())
 }
 
 pub struct Module {
+    base: usize,
     start: usize,
     size: usize,
 }
@@ -18,36 +133,76 @@ pub struct Module {
 impl Module {
     const CAVE_BYTES: [u8; 3] = [0x00, 0x90, 0xCC];
 
+    // The game's executable.
     pub unsafe fn current() -> Result<Self, Error> {
-        const SECTION: [u8; 5] = *b".text";
-        const PAGE: usize = 0x1000;
-        const PE_HEADER_SIZE: usize = PAGE; // overkill for our search.
-
         let base = GetModuleHandleA(None).map_err(|_| Error::GetModuleHandle)?;
+        Self::from_base(base.0 as usize)
+    }
 
-        let pe_header: &[u8] = slice::from_raw_parts(base.0 as *const u8, PE_HEADER_SIZE);
+    // A module that's already loaded into the game, e.g. "steam_api64.dll".
+    pub unsafe fn named(name: &str) -> Result<Self, Error> {
+        // Null-terminate the name for Windows.
+        let mut c_name = List::<u8, MAX_PATH>::new();
+        c_name.write_bytes(name.as_bytes())?;
+        c_name.push(0)?;
 
-        let section_header: *const SectionHeader = pe_header
-            .windows(SECTION.len())
-            .find(|&w| w == SECTION)
-            .map(|w| w.as_ptr().cast())
-            .ok_or(Error::FindTextSection)?;
+        let base = GetModuleHandleA(PCSTR(c_name.as_slice().as_ptr()))
+            .map_err(|_| Error::GetModuleHandle)?;
 
-        Ok(Self {
-            start: base.0 as usize + (*section_header).virtual_address as usize,
-            size: util::align((*section_header).size_of_raw_data as usize, PAGE),
-        })
+        Self::from_base(base.0 as usize)
     }
 
+    pub unsafe fn from_base(base: usize) -> Result<Self, Error> {
+        let (start, size) = section_range(base, ".text").ok_or(Error::FindTextSection)?;
+        Ok(Self { base, start, size })
+    }
+
+    // Scans the module's code.
     pub unsafe fn find<T>(&self, pattern: &[Option<u8>]) -> Option<*const T> {
+        scan(self.start, self.size, pattern)
+    }
+
+    // Scans only the named section, e.g. ".rdata". Returns None if the module
+    // has no such section.
+    pub unsafe fn find_in_section<T>(
+        &self,
+        section: &str,
+        pattern: &[Option<u8>],
+    ) -> Option<*const T> {
+        let (start, size) = section_range(self.base, section)?;
+        scan(start, size, pattern)
+    }
+
+    // The start and length of the named section, e.g. ".data".
+    pub unsafe fn section(&self, name: &str) -> Option<(usize, usize)> {
+        section_range(self.base, name)
+    }
+
+    // Where the module's code comes closest to `pattern`, and how many of the
+    // pattern's bytes match there. Slow, so it's only for explaining why
+    // find() failed.
+    pub unsafe fn best_match(&self, pattern: &[Option<u8>]) -> Option<(*const u8, usize)> {
         slice::from_raw_parts(self.start as *const u8, self.size)
             .windows(pattern.len())
-            .find(|w| {
-                w.iter()
+            .map(|w| {
+                let matches = w
+                    .iter()
                     .zip(pattern)
-                    .all(|(&w, p)| p.map_or(true, |p| w == p))
+                    .filter(|&(&w, &p)| p == Some(w))
+                    .count();
+
+                (w.as_ptr(), matches)
             })
-            .map(|w| w.as_ptr().cast())
+            .max_by_key(|&(_, matches)| matches)
+    }
+
+    pub unsafe fn find_code<T>(&self, pattern: &[Option<u8>]) -> Option<*const T> {
+        self.find(pattern)
+    }
+
+    // Scans read-only data, where string literals and vtables live.
+    pub unsafe fn find_data<T>(&self, pattern: &[Option<u8>]) -> Option<*const T> {
+        self.find_in_section(".rdata", pattern)
     }
 
     pub unsafe fn find_mut<T>(&self, pattern: &[Option<u8>]) -> Option<*mut T> {
@@ -70,6 +225,73 @@ impl Module {
         None
     }
 
+    // Like find_mut(), but only if the pattern matches exactly once. For
+    // patterns too generic to trust their first match.
+    pub unsafe fn find_unique_mut<T>(&self, pattern: &[Option<u8>]) -> Option<*mut T> {
+        let mut matches = slice::from_raw_parts(self.start as *const u8, self.size)
+            .windows(pattern.len())
+            .filter(|w| is_match(w, pattern));
+
+        let first = matches.next()?;
+
+        if matches.next().is_some() {
+            return None;
+        }
+
+        Some(first.as_ptr() as *mut T)
+    }
+
+    // Whether `address` looks like the start of a function in this module's
+    // code. MSVC pads between functions with int3, so the byte before one is
+    // padding or the previous function's ret.
+    pub unsafe fn is_function_start(&self, address: usize) -> bool {
+        if address <= self.start || address >= self.start + self.size {
+            return false;
+        }
+
+        let code = address as *const u8;
+        matches!(*code.sub(1), 0xCC | 0xC3 | 0x90) && !matches!(*code, 0xCC | 0xC3)
+    }
+
+    // Resolve a function that this module exports, e.g. "CreateDXGIFactory"
+    // from dxgi.dll, without linking against the module.
+    pub unsafe fn export<T>(&self, name: &str) -> Option<*const T> {
+        let nt_headers = pe::nt_headers(self.base)?;
+        pe::find_export(self.base, &nt_headers, name).map(|address| address as *const T)
+    }
+
+    // Finds the primary vtable of a C++ class by its RTTI name, e.g. "UWorld".
+    pub unsafe fn find_vtable(&self, class: &str) -> Result<*const *const c_void, Error> {
+        rtti::find_vtable(self.base, class)
+    }
+
+    // Every RIP-relative reference to `target` from this module's code.
+    pub fn xrefs(&self, target: usize) -> Xrefs {
+        Xrefs::new(self.start, self.size, target)
+    }
+
+    // Every RIP-relative reference to a string literal, e.g. an error message
+    // printed next to the global we're after.
+    pub unsafe fn string_xrefs(&self, text: &str) -> Result<Xrefs, Error> {
+        Ok(self.xrefs(xref::find_string(self.base, text)?))
+    }
+
+    pub unsafe fn wide_string_xrefs(&self, text: &str) -> Result<Xrefs, Error> {
+        Ok(self.xrefs(xref::find_wide_string(self.base, text)?))
+    }
+
+    // The import address table slot for a function this module imports, e.g.
+    // "SetCursorPos" from "user32.dll". Swap the slot to hook every call this
+    // module makes to that function.
+    pub unsafe fn import_slot(&self, dll: &str, function: &str) -> Option<*mut *const c_void> {
+        let nt_headers = pe::nt_headers(self.base)?;
+        pe::find_import(self.base, &nt_headers, dll, function).map(|slot| slot.cast())
+    }
+
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
     pub fn start(&self) -> usize {
         self.start
     }
@@ -168,16 +390,28 @@ impl Module {
     }
 }
 
-#[repr(C)]
-struct SectionHeader {
-    name: [u8; 8],
-    virtual_size: u32,
-    virtual_address: u32,
-    size_of_raw_data: u32,
-    pointer_to_raw_data: u32,
-    pointer_to_relocations: u32,
-    pointer_to_line_numbers: u32,
-    number_of_relocations: u16,
-    number_of_line_numbers: u16,
-    characteristics: u32,
+// Where a section is mapped in memory, as a start address and a size.
+unsafe fn section_range(base: usize, name: &str) -> Option<(usize, usize)> {
+    const PAGE: usize = 0x1000;
+
+    let header = pe::find_section(base, name)?;
+
+    Some((
+        base + header.virtual_address as usize,
+        util::align(header.virtual_size as usize, PAGE),
+    ))
+}
+
+unsafe fn scan<T>(start: usize, size: usize, pattern: &[Option<u8>]) -> Option<*const T> {
+    slice::from_raw_parts(start as *const u8, size)
+        .windows(pattern.len())
+        .find(|w| is_match(w, pattern))
+        .map(|w| w.as_ptr().cast())
+}
+
+fn is_match(bytes: &[u8], pattern: &[Option<u8>]) -> bool {
+    bytes
+        .iter()
+        .zip(pattern)
+        .all(|(&b, p)| p.is_none_or(|p| b == p))
 }
@@ -0,0 +1,300 @@
+// Just enough of the PE format to find a loaded module's sections, exports,
+// and imports.
+// https://docs.microsoft.com/en-us/windows/win32/debug/pe-format
+
+use core::cmp::Ordering;
+use core::ffi::CStr;
+use core::mem;
+
+const DOS_SIGNATURE: u16 = 0x5A4D; // MZ
+const NT_SIGNATURE: u32 = 0x0000_4550; // PE\0\0
+const OPTIONAL_HEADER_64_MAGIC: u16 = 0x20B;
+
+const E_LFANEW_OFFSET: usize = 0x3C;
+
+const DIRECTORY_ENTRY_EXPORT: usize = 0;
+const DIRECTORY_ENTRY_IMPORT: usize = 1;
+
+// Set in an import lookup entry that imports by ordinal instead of by name.
+const IMPORT_BY_ORDINAL: u64 = 1 << 63;
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct FileHeader {
+    pub machine: u16,
+    pub number_of_sections: u16,
+    pub time_date_stamp: u32,
+    pub pointer_to_symbol_table: u32,
+    pub number_of_symbols: u32,
+    pub size_of_optional_header: u16,
+    pub characteristics: u16,
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct DataDirectory {
+    pub virtual_address: u32,
+    pub size: u32,
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct OptionalHeader64 {
+    pub magic: u16,
+    pub major_linker_version: u8,
+    pub minor_linker_version: u8,
+    pub size_of_code: u32,
+    pub size_of_initialized_data: u32,
+    pub size_of_uninitialized_data: u32,
+    pub address_of_entry_point: u32,
+    pub base_of_code: u32,
+    pub image_base: u64,
+    pub section_alignment: u32,
+    pub file_alignment: u32,
+    pub major_operating_system_version: u16,
+    pub minor_operating_system_version: u16,
+    pub major_image_version: u16,
+    pub minor_image_version: u16,
+    pub major_subsystem_version: u16,
+    pub minor_subsystem_version: u16,
+    pub win32_version_value: u32,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    pub check_sum: u32,
+    pub subsystem: u16,
+    pub dll_characteristics: u16,
+    pub size_of_stack_reserve: u64,
+    pub size_of_stack_commit: u64,
+    pub size_of_heap_reserve: u64,
+    pub size_of_heap_commit: u64,
+    pub loader_flags: u32,
+    pub number_of_rva_and_sizes: u32,
+    pub data_directory: [DataDirectory; 16],
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NtHeaders64 {
+    pub signature: u32,
+    pub file_header: FileHeader,
+    pub optional_header: OptionalHeader64,
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct ExportDirectory {
+    pub characteristics: u32,
+    pub time_date_stamp: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub name: u32,
+    pub base: u32,
+    pub number_of_functions: u32,
+    pub number_of_names: u32,
+    pub address_of_functions: u32,
+    pub address_of_names: u32,
+    pub address_of_name_ordinals: u32,
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct ImportDescriptor {
+    pub original_first_thunk: u32,
+    pub time_date_stamp: u32,
+    pub forwarder_chain: u32,
+    pub name: u32,
+    pub first_thunk: u32,
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct SectionHeader {
+    pub name: [u8; 8],
+    pub virtual_size: u32,
+    pub virtual_address: u32,
+    pub size_of_raw_data: u32,
+    pub pointer_to_raw_data: u32,
+    pub pointer_to_relocations: u32,
+    pub pointer_to_line_numbers: u32,
+    pub number_of_relocations: u16,
+    pub number_of_line_numbers: u16,
+    pub characteristics: u32,
+}
+
+impl SectionHeader {
+    // Section names are padded with nulls up to eight bytes.
+    pub fn name(&self) -> &[u8] {
+        let len = self
+            .name
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(self.name.len());
+
+        &self.name[..len]
+    }
+}
+
+// Where the NT headers start, relative to the module base.
+unsafe fn nt_headers_offset(base: usize) -> Option<usize> {
+    let dos_signature = (base as *const u16).read_unaligned();
+
+    if dos_signature != DOS_SIGNATURE {
+        return None;
+    }
+
+    Some(((base + E_LFANEW_OFFSET) as *const u32).read_unaligned() as usize)
+}
+
+pub unsafe fn nt_headers(base: usize) -> Option<NtHeaders64> {
+    let offset = nt_headers_offset(base)?;
+    let nt_headers = ((base + offset) as *const NtHeaders64).read_unaligned();
+
+    if nt_headers.signature == NT_SIGNATURE
+        && nt_headers.optional_header.magic == OPTIONAL_HEADER_64_MAGIC
+    {
+        Some(nt_headers)
+    } else {
+        None
+    }
+}
+
+pub unsafe fn find_section(base: usize, name: &str) -> Option<SectionHeader> {
+    let nt_headers = nt_headers(base)?;
+
+    // The section table follows the optional header, whatever its size.
+    let first_section = base
+        + nt_headers_offset(base)?
+        + mem::size_of::<u32>()
+        + mem::size_of::<FileHeader>()
+        + nt_headers.file_header.size_of_optional_header as usize;
+
+    (0..nt_headers.file_header.number_of_sections as usize)
+        .map(|i| {
+            (first_section as *const SectionHeader)
+                .add(i)
+                .read_unaligned()
+        })
+        .find(|section| section.name() == name.as_bytes())
+}
+
+// The address of the function that the module exports as `name`. Exports that
+// forward to another module aren't followed.
+pub unsafe fn find_export(base: usize, nt_headers: &NtHeaders64, name: &str) -> Option<usize> {
+    let directory = nt_headers
+        .optional_header
+        .data_directory
+        .get(DIRECTORY_ENTRY_EXPORT)?;
+
+    if directory.virtual_address == 0 || directory.size == 0 {
+        return None;
+    }
+
+    let directory_start = directory.virtual_address as usize;
+    let directory_end = directory_start + directory.size as usize;
+    let exports = ((base + directory_start) as *const ExportDirectory).read_unaligned();
+
+    let rva = |rva: u32| base + rva as usize;
+    let names = rva(exports.address_of_names) as *const u32;
+    let ordinals = rva(exports.address_of_name_ordinals) as *const u16;
+    let functions = rva(exports.address_of_functions) as *const u32;
+
+    // The names are sorted, so binary search them.
+    let mut low = 0;
+    let mut high = exports.number_of_names as usize;
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+        let export_name = CStr::from_ptr(rva(names.add(middle).read_unaligned()) as *const _);
+
+        match export_name.to_bytes().cmp(name.as_bytes()) {
+            Ordering::Less => low = middle + 1,
+            Ordering::Greater => high = middle,
+
+            Ordering::Equal => {
+                let ordinal = ordinals.add(middle).read_unaligned() as usize;
+
+                if ordinal >= exports.number_of_functions as usize {
+                    return None;
+                }
+
+                let function = functions.add(ordinal).read_unaligned() as usize;
+
+                // An address inside the export directory is a forwarder
+                // string like "NTDLL.RtlAllocateHeap", not code.
+                return if (directory_start..directory_end).contains(&function) {
+                    None
+                } else {
+                    Some(base + function)
+                };
+            }
+        }
+    }
+
+    None
+}
+
+// The import address table entry that the loader filled in with the address of
+// `function` from `dll`, e.g. "user32.dll" and "SetCursorPos". Ordinal imports
+// aren't supported.
+pub unsafe fn find_import(
+    base: usize,
+    nt_headers: &NtHeaders64,
+    dll: &str,
+    function: &str,
+) -> Option<*mut usize> {
+    let directory = nt_headers
+        .optional_header
+        .data_directory
+        .get(DIRECTORY_ENTRY_IMPORT)?;
+
+    if directory.virtual_address == 0 || directory.size == 0 {
+        return None;
+    }
+
+    let rva = |rva: u32| base + rva as usize;
+    let mut descriptors = rva(directory.virtual_address) as *const ImportDescriptor;
+
+    loop {
+        let descriptor = descriptors.read_unaligned();
+
+        // The table ends with a zeroed descriptor.
+        if descriptor.name == 0 {
+            return None;
+        }
+
+        let dll_name = CStr::from_ptr(rva(descriptor.name) as *const _);
+
+        if dll_name.to_bytes().eq_ignore_ascii_case(dll.as_bytes()) {
+            // Names come from the lookup table. The loader overwrites the
+            // address table with addresses, so it only has names on disk.
+            let lookup = rva(if descriptor.original_first_thunk == 0 {
+                descriptor.first_thunk
+            } else {
+                descriptor.original_first_thunk
+            }) as *const u64;
+
+            let addresses = rva(descriptor.first_thunk) as *mut usize;
+
+            for i in 0.. {
+                let entry = lookup.add(i).read_unaligned();
+
+                if entry == 0 {
+                    break;
+                }
+
+                if entry & IMPORT_BY_ORDINAL != 0 {
+                    continue;
+                }
+
+                // Skip the two-byte hint that precedes the name.
+                let name = CStr::from_ptr((rva(entry as u32) + mem::size_of::<u16>()) as *const _);
+
+                if name.to_bytes() == function.as_bytes() {
+                    return Some(addresses.add(i));
+                }
+            }
+        }
+
+        descriptors = descriptors.add(1);
+    }
+}
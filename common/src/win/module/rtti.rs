@@ -0,0 +1,87 @@
+// Finds vtables through MSVC's run-time type information, which survives game
+// updates better than instruction patterns.
+// http://www.openrce.org/articles/full_view/23
+
+use super::{section_range, Error};
+use crate::list::List;
+
+use core::ffi::c_void;
+use core::mem;
+use core::slice;
+
+// Type descriptor names are mangled: "class UWorld" is ".?AVUWorld@@".
+const MAX_MANGLED_LEN: usize = 256;
+
+// The vtable's complete object locator. x64 locators store image-relative
+// addresses instead of pointers.
+#[allow(dead_code)]
+#[repr(C)]
+struct CompleteObjectLocator {
+    signature: u32,
+    offset: u32,
+    constructor_displacement_offset: u32,
+    type_descriptor: u32,
+    class_descriptor: u32,
+    this: u32,
+}
+
+const COL_SIGNATURE_64: u32 = 1;
+
+// The name follows the type descriptor's vtable pointer and a spare pointer.
+const TYPE_DESCRIPTOR_NAME_OFFSET: usize = 2 * mem::size_of::<usize>();
+
+// Finds the primary vtable of the C++ class or struct named `name`, e.g.
+// "UWorld".
+pub unsafe fn find_vtable(base: usize, name: &str) -> Result<*const *const c_void, Error> {
+    for prefix in [b".?AV", b".?AU"] {
+        let mut mangled = List::<u8, MAX_MANGLED_LEN>::new();
+        mangled.write_bytes(prefix)?;
+        mangled.write_bytes(name.as_bytes())?;
+        mangled.write_bytes(b"@@\0")?;
+
+        if let Some(vtable) = find_vtable_by_mangled_name(base, mangled.as_slice()) {
+            return Ok(vtable);
+        }
+    }
+
+    Err(Error::FindVTable)
+}
+
+unsafe fn find_vtable_by_mangled_name(base: usize, mangled: &[u8]) -> Option<*const *const c_void> {
+    let name = find_bytes(section_bytes(base, ".data")?, mangled)?;
+    let type_descriptor = name - TYPE_DESCRIPTOR_NAME_OFFSET;
+    let type_descriptor_rva = u32::try_from(type_descriptor - base).ok()?;
+
+    let rdata = section_bytes(base, ".rdata")?;
+
+    // Find the locator that describes the primary vtable of this type.
+    let locator = rdata
+        .windows(mem::size_of::<CompleteObjectLocator>())
+        .step_by(mem::align_of::<CompleteObjectLocator>())
+        .map(|w| w.as_ptr() as usize)
+        .find(|&address| {
+            let locator = (address as *const CompleteObjectLocator).read_unaligned();
+
+            locator.signature == COL_SIGNATURE_64
+                && locator.offset == 0
+                && locator.type_descriptor == type_descriptor_rva
+                && locator.this as usize == address - base
+        })?;
+
+    // The vtable is preceded by a pointer to its locator.
+    let locator_pointer = find_bytes(rdata, &locator.to_le_bytes())?;
+    Some((locator_pointer + mem::size_of::<usize>()) as *const *const c_void)
+}
+
+unsafe fn section_bytes(base: usize, section: &str) -> Option<&'static [u8]> {
+    let (start, size) = section_range(base, section)?;
+    Some(slice::from_raw_parts(start as *const u8, size))
+}
+
+// The address of the first occurrence of `needle` in `haystack`.
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .find(|w| *w == needle)
+        .map(|w| w.as_ptr() as usize)
+}
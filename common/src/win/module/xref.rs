@@ -0,0 +1,83 @@
+// Finds code that refers to an address through a RIP-relative operand, like
+// `lea rcx, [rip + disp32]` or `mov rax, [rip + disp32]`. We don't decode
+// instructions, so we assume the displacement is the last thing in the
+// instruction. That holds for the loads and stores this is meant for, but not
+// for instructions that also carry an immediate.
+
+use super::{section_range, Error};
+use crate::list::List;
+
+use core::mem;
+use core::slice;
+
+const DISPLACEMENT_SIZE: usize = mem::size_of::<i32>();
+
+pub struct Xrefs {
+    target: usize,
+    cursor: usize,
+    end: usize,
+}
+
+impl Xrefs {
+    pub(super) fn new(start: usize, size: usize, target: usize) -> Self {
+        Self {
+            target,
+            cursor: start,
+            end: (start + size).saturating_sub(DISPLACEMENT_SIZE),
+        }
+    }
+}
+
+impl Iterator for Xrefs {
+    // The address of a displacement that resolves to the target.
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.cursor < self.end {
+            let displacement = self.cursor;
+            self.cursor += 1;
+
+            let offset = unsafe { (displacement as *const i32).read_unaligned() };
+            let next_instruction = displacement + DISPLACEMENT_SIZE;
+
+            if next_instruction.wrapping_add(offset as isize as usize) == self.target {
+                return Some(displacement);
+            }
+        }
+
+        None
+    }
+}
+
+// The longest string we can search for, in bytes, including the terminator.
+const MAX_STRING_LEN: usize = 512;
+
+// The address of a null-terminated string literal in read-only data.
+pub unsafe fn find_string(base: usize, text: &str) -> Result<usize, Error> {
+    let mut bytes = List::<u8, MAX_STRING_LEN>::new();
+    bytes.write_bytes(text.as_bytes())?;
+    bytes.push(0)?;
+    find_literal(base, bytes.as_slice())
+}
+
+// The address of a null-terminated UTF-16 string literal in read-only data.
+// Unreal's TEXT("...") literals are wide.
+pub unsafe fn find_wide_string(base: usize, text: &str) -> Result<usize, Error> {
+    let mut bytes = List::<u8, MAX_STRING_LEN>::new();
+
+    for unit in text.encode_utf16().chain([0]) {
+        bytes.write_bytes(&unit.to_le_bytes())?;
+    }
+
+    find_literal(base, bytes.as_slice())
+}
+
+unsafe fn find_literal(base: usize, literal: &[u8]) -> Result<usize, Error> {
+    let (start, size) = section_range(base, ".rdata").ok_or(Error::FindString)?;
+
+    slice::from_raw_parts(start as *const u8, size)
+        .windows(literal.len())
+        .find(|w| *w == literal)
+        .map(|w| w.as_ptr() as usize)
+        .ok_or(Error::FindString)
+}
@@ -0,0 +1,116 @@
+use crate::win::threads::{self, SuspendedThreads};
+use core::mem;
+use core::ptr;
+use core::slice;
+use windows::Win32::System::Memory::{
+    VirtualProtect, PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS,
+};
+
+pub struct Patch<T: Copy> {
+    address: *mut T,
+    original: T,
+}
+
+impl<T: Copy> Patch<T> {
+    pub unsafe fn new(address: *mut T, new_value: T) -> Patch<T> {
+        let original = *address;
+
+        Self::write(address, new_value);
+
+        Patch { address, original }
+    }
+
+    // Like new(), but suspends every other thread while writing, in case one
+    // of them is running the code we're patching.
+    #[allow(dead_code)]
+    pub unsafe fn new_suspended(address: *mut T, new_value: T) -> Result<Patch<T>, threads::Error> {
+        let start = address as usize;
+        let _suspended = SuspendedThreads::new(start..start + mem::size_of::<T>())?;
+        Ok(Self::new(address, new_value))
+    }
+
+    unsafe fn write(address: *mut T, new_value: T) {
+        let mut old_protection: PAGE_PROTECTION_FLAGS = Default::default();
+        VirtualProtect(
+            address.cast(),
+            mem::size_of::<T>(),
+            PAGE_EXECUTE_READWRITE,
+            &mut old_protection,
+        );
+        *address = new_value;
+        VirtualProtect(
+            address.cast(),
+            mem::size_of::<T>(),
+            old_protection,
+            &mut old_protection,
+        );
+    }
+}
+
+impl<T: Copy> Drop for Patch<T> {
+    fn drop(&mut self) {
+        unsafe {
+            Self::write(self.address, self.original);
+        }
+    }
+}
+
+// Like Patch, but for byte strings whose length is only known at runtime. Up
+// to N bytes.
+pub struct BytePatch<const N: usize> {
+    address: *mut u8,
+    original: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> BytePatch<N> {
+    pub unsafe fn new(address: *mut u8, new_bytes: &[u8]) -> BytePatch<N> {
+        let len = new_bytes.len().min(N);
+        let mut original = [0; N];
+        original[..len].copy_from_slice(slice::from_raw_parts(address, len));
+
+        Self::write(address, &new_bytes[..len]);
+
+        BytePatch {
+            address,
+            original,
+            len,
+        }
+    }
+
+    // Like new(), but suspends every other thread while writing, in case one
+    // of them is running the code we're patching.
+    pub unsafe fn new_suspended(
+        address: *mut u8,
+        new_bytes: &[u8],
+    ) -> Result<BytePatch<N>, threads::Error> {
+        let start = address as usize;
+        let _suspended = SuspendedThreads::new(start..start + new_bytes.len())?;
+        Ok(Self::new(address, new_bytes))
+    }
+
+    unsafe fn write(address: *mut u8, new_bytes: &[u8]) {
+        let mut old_protection: PAGE_PROTECTION_FLAGS = Default::default();
+        VirtualProtect(
+            address.cast(),
+            new_bytes.len(),
+            PAGE_EXECUTE_READWRITE,
+            &mut old_protection,
+        );
+        ptr::copy_nonoverlapping(new_bytes.as_ptr(), address, new_bytes.len());
+        VirtualProtect(
+            address.cast(),
+            new_bytes.len(),
+            old_protection,
+            &mut old_protection,
+        );
+    }
+}
+
+impl<const N: usize> Drop for BytePatch<N> {
+    fn drop(&mut self) {
+        unsafe {
+            Self::write(self.address, &self.original[..self.len]);
+        }
+    }
+}
@@ -1,3 +1,30 @@
+#[cfg(feature = "std")]
 pub fn u32() -> u32 {
     rand::random::<u32>()
 }
+
+// Without rand, mixes the performance counter with splitmix64. Not for
+// secrets, just for values that differ from run to run.
+#[cfg(not(feature = "std"))]
+pub fn u32() -> u32 {
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use windows::Win32::System::Performance::QueryPerformanceCounter;
+
+    static CALLS: AtomicU64 = AtomicU64::new(0);
+
+    let mut counter = 0;
+
+    unsafe {
+        QueryPerformanceCounter(&mut counter);
+    }
+
+    let calls = CALLS.fetch_add(1, Ordering::Relaxed);
+
+    let mut z = (counter as u64)
+        .wrapping_add(calls.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) as u32
+}
@@ -0,0 +1,140 @@
+// Suspends every other thread in the game while we patch code, so that no
+// thread runs a half-written instruction.
+
+use crate::list::{self, List};
+use core::mem;
+use core::ops::Range;
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use windows::Win32::System::Diagnostics::Debug::{GetThreadContext, SetThreadContext, CONTEXT};
+use windows::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+};
+use windows::Win32::System::Threading::{
+    GetCurrentProcessId, GetCurrentThreadId, OpenThread, ResumeThread, Sleep, SuspendThread,
+    THREAD_GET_CONTEXT, THREAD_SET_CONTEXT, THREAD_SUSPEND_RESUME,
+};
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Snapshot,
+    ThreadInPatch,
+    List(#[from] list::Error),
+}
+
+// The game runs well under a hundred threads.
+const MAX_THREADS: usize = 512;
+
+// How many times to let a thread run out of the range we're patching before
+// giving up.
+const MAX_ATTEMPTS: usize = 100;
+
+// CONTEXT_CONTROL and CONTEXT_DEBUG_REGISTERS for x64, which windows doesn't
+// export.
+const CONTEXT_CONTROL: u32 = 0x0010_0001;
+const CONTEXT_DEBUG_REGISTERS: u32 = 0x0010_0010;
+
+pub struct SuspendedThreads {
+    threads: List<HANDLE, MAX_THREADS>,
+}
+
+impl SuspendedThreads {
+    // Suspends every other thread, making sure none of them stopped inside
+    // `patch`. Threads resume when this is dropped.
+    pub unsafe fn new(patch: Range<usize>) -> Result<Self, Error> {
+        for _ in 0..MAX_ATTEMPTS {
+            let suspended = Self::suspend_all()?;
+
+            if !suspended.any_inside(&patch) {
+                return Ok(suspended);
+            }
+
+            // Resume everyone and give the thread a moment to move on.
+            drop(suspended);
+            Sleep(1);
+        }
+
+        Err(Error::ThreadInPatch)
+    }
+
+    // Lets `f` edit the debug registers of every suspended thread.
+    #[allow(dead_code)]
+    pub unsafe fn update_debug_registers(&self, mut f: impl FnMut(&mut CONTEXT)) {
+        for &thread in self.threads.iter() {
+            let mut context = CONTEXT {
+                ContextFlags: CONTEXT_DEBUG_REGISTERS,
+                ..Default::default()
+            };
+
+            if GetThreadContext(thread, &mut context).as_bool() {
+                f(&mut context);
+                SetThreadContext(thread, &context);
+            }
+        }
+    }
+
+    pub unsafe fn suspend_all() -> Result<Self, Error> {
+        let snapshot =
+            CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0).map_err(|_| Error::Snapshot)?;
+
+        let mut suspended = Self {
+            threads: List::new(),
+        };
+
+        let mut entry = THREADENTRY32 {
+            dwSize: mem::size_of::<THREADENTRY32>() as u32,
+            ..Default::default()
+        };
+
+        let process = GetCurrentProcessId();
+        let this_thread = GetCurrentThreadId();
+        let mut more = Thread32First(snapshot, &mut entry).as_bool();
+
+        while more {
+            if entry.th32OwnerProcessID == process && entry.th32ThreadID != this_thread {
+                if let Ok(thread) = OpenThread(
+                    THREAD_SUSPEND_RESUME | THREAD_GET_CONTEXT | THREAD_SET_CONTEXT,
+                    false,
+                    entry.th32ThreadID,
+                ) {
+                    SuspendThread(thread);
+
+                    if let Err(e) = suspended.threads.push(thread) {
+                        ResumeThread(thread);
+                        CloseHandle(thread);
+                        CloseHandle(snapshot);
+                        return Err(e.into());
+                    }
+                }
+            }
+
+            more = Thread32Next(snapshot, &mut entry).as_bool();
+        }
+
+        CloseHandle(snapshot);
+        Ok(suspended)
+    }
+
+    unsafe fn any_inside(&self, range: &Range<usize>) -> bool {
+        self.threads.iter().any(|&thread| {
+            let mut context = CONTEXT {
+                ContextFlags: CONTEXT_CONTROL,
+                ..Default::default()
+            };
+
+            // If we can't tell where the thread is, assume the worst.
+            !GetThreadContext(thread, &mut context).as_bool()
+                || range.contains(&(context.Rip as usize))
+        })
+    }
+}
+
+impl Drop for SuspendedThreads {
+    fn drop(&mut self) {
+        unsafe {
+            for &thread in self.threads.iter() {
+                ResumeThread(thread);
+                CloseHandle(thread);
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// Times object lookups with and without common::live's name index, which is
+// the measurement behind FUObjectArray::find using it.
+//
+// drg.bench [n]   look each of NAMES up n times both ways, and log the
+//                 average time of one lookup
+
+use common::{live, GUObjectArray};
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "usage: drg.bench [n]";
+
+const DEFAULT_COUNT: u32 = 10;
+
+// Things every level has, from near the start of GUObjectArray to the end.
+const NAMES: [&str; 4] = [
+    "Object /Script/CoreUObject.Default__Object",
+    "Class /Script/Engine.PlayerController",
+    "Function /Script/Engine.PlayerController.ClientRestart",
+    "Class /Script/FSD.FSDPlayerController",
+];
+
+pub unsafe fn run(args: &str) {
+    let count = match args {
+        "" => DEFAULT_COUNT,
+        count => match count.parse() {
+            Ok(count) if count > 0 => count,
+            _ => {
+                common::log!("{}", USAGE);
+                return;
+            }
+        },
+    };
+
+    if !live::is_listening() {
+        common::log!("drg.bench: the name index isn't running, so both ways walk GUObjectArray");
+    }
+
+    for name in NAMES {
+        let indexed = time(count, || (*GUObjectArray).find(name).is_ok());
+        let walked = time(count, || (*GUObjectArray).find_unindexed(name).is_ok());
+
+        match (indexed, walked) {
+            (Some(indexed), Some(walked)) => common::log!(
+                "drg.bench: {}: {:.1} us indexed, {:.1} us walking",
+                name,
+                indexed.as_secs_f64() * 1e6,
+                walked.as_secs_f64() * 1e6
+            ),
+            _ => common::log!("drg.bench: {}: not found", name),
+        }
+    }
+}
+
+// The average time `find` took, or None if it didn't find anything.
+fn time(count: u32, mut find: impl FnMut() -> bool) -> Option<Duration> {
+    let start = Instant::now();
+
+    for _ in 0..count {
+        if !find() {
+            return None;
+        }
+    }
+
+    Some(start.elapsed() / count)
+}
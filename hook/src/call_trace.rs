@@ -0,0 +1,65 @@
+// Logs each call ProcessEvent makes to one function, with the chain of
+// blueprint functions that made it, the innermost first. Which blueprint
+// called something is often more telling than the call itself.
+//
+// drg.trace /Script/FSD.FSDPlayerController.ClientShowMessage
+// drg.trace off
+
+use crate::invoke;
+use crate::process_event::{self, Filter};
+use common::{GUObjectArray, UFunction, UObject};
+use core::ffi::c_void;
+use core::ptr;
+
+const USAGE: &str = "usage: drg.trace <function path>|off";
+
+static mut FUNCTION: *mut UFunction = ptr::null_mut();
+
+pub unsafe fn run(args: &str) {
+    match args {
+        "" => common::log!("{}", USAGE),
+        "off" => stop(),
+        path => start(path),
+    }
+}
+
+unsafe fn start(path: &str) {
+    let function = (*GUObjectArray).find_by_path(path).cast::<UFunction>();
+
+    if function.is_null() {
+        common::log!("drg.trace: no function called {}", path);
+        return;
+    }
+
+    stop();
+
+    match process_event::register_for(Filter::Function(function), on_process_event) {
+        Ok(()) => {
+            FUNCTION = function;
+            common::log!("drg.trace: tracing {}", *function);
+        }
+        Err(e) => common::log!("drg.trace: failed to start: {:?}", e),
+    }
+}
+
+unsafe fn stop() {
+    if !FUNCTION.is_null() {
+        process_event::unregister(on_process_event);
+        FUNCTION = ptr::null_mut();
+    }
+}
+
+unsafe fn on_process_event(object: *mut UObject, function: *mut UFunction, _: *mut c_void) {
+    let stack = invoke::script_stack(function, invoke::current_frame());
+
+    if stack.is_empty() {
+        common::log!("drg.trace: {} on {}, from native code", *function, *object);
+    } else {
+        common::log!(
+            "drg.trace: {} on {}, from {}",
+            *function,
+            *object,
+            stack.join(" <- ")
+        );
+    }
+}
@@ -0,0 +1,160 @@
+// Named pointer chains from the "chains" section of the config. See
+// common::chain for the syntax. A chain is resolved the first time it's used,
+// then again whenever the level changes, since a new level usually means new
+// objects at new addresses.
+//
+// drg.chain                  every chain and its value
+// drg.chain health           just that one
+// drg.chain health 100       write 100 to it
+// drg.chain reload           read the config again
+
+use crate::policy::{Feature, Scope};
+use common::chain::Chain;
+use common::json;
+use common::scan::{self, Value};
+use common::{ObjectHandle, UObject};
+
+const USAGE: &str = "usage: drg.chain [<name> [value] | reload]";
+
+static WRITE: Feature = Feature {
+    name: "drg.chain writes",
+    scope: Scope::HostOnly,
+    disable: None,
+};
+
+struct Named {
+    name: String,
+    chain: Chain,
+    // The world it was resolved in, and where it led.
+    resolved: Option<(ObjectHandle<UObject>, usize)>,
+}
+
+static mut CHAINS: Option<Vec<Named>> = None;
+
+// Where the named chain leads in the current level.
+#[allow(dead_code)]
+pub unsafe fn address(name: &str) -> Option<usize> {
+    let named = chains().iter_mut().find(|n| n.name == name)?;
+    resolve(named)
+}
+
+#[allow(dead_code)]
+pub unsafe fn read(name: &str) -> Option<Value> {
+    let named = chains().iter_mut().find(|n| n.name == name)?;
+    let address = resolve(named)?;
+    scan::read(address, named.chain.value_type())
+}
+
+pub unsafe fn run(args: &str) {
+    let mut words = args.split_whitespace();
+
+    match (words.next(), words.next(), words.next()) {
+        (None, _, _) => {
+            if chains().is_empty() {
+                common::log!("drg.chain: no chains. add some to the config's \"chains\"");
+            }
+
+            for named in chains().iter_mut() {
+                log_value(named);
+            }
+        }
+        (Some("reload"), None, None) => {
+            CHAINS = None;
+            common::log!("drg.chain: {} chains", chains().len());
+        }
+        (Some(name), value, None) => {
+            let Some(named) = chains().iter_mut().find(|n| n.name == name) else {
+                common::log!("drg.chain: no chain called {}", name);
+                return;
+            };
+
+            match value {
+                Some(value) => write(named, value),
+                None => log_value(named),
+            }
+        }
+        _ => common::log!("{}", USAGE),
+    }
+}
+
+unsafe fn chains() -> &'static mut Vec<Named> {
+    CHAINS.get_or_insert_with(load)
+}
+
+unsafe fn load() -> Vec<Named> {
+    let config = match crate::config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            common::log!("drg.chain: failed to read the config: {:?}", e);
+            return Vec::new();
+        }
+    };
+
+    let Some(json::Value::Object(members)) = config.get("chains") else {
+        return Vec::new();
+    };
+
+    let mut chains = Vec::new();
+
+    for (name, text) in members {
+        let Some(text) = text.as_str() else {
+            common::log!("drg.chain: {} isn't a string", name);
+            continue;
+        };
+
+        match Chain::parse(text) {
+            Ok(chain) => chains.push(Named {
+                name: name.clone(),
+                chain,
+                resolved: None,
+            }),
+            Err(e) => common::log!("drg.chain: {}: {:?}", name, e),
+        }
+    }
+
+    chains
+}
+
+unsafe fn resolve(named: &mut Named) -> Option<usize> {
+    let world = common::player::world();
+
+    match named.resolved {
+        Some((resolved_in, address)) if resolved_in.resolve() == Some(world) => Some(address),
+        _ => {
+            named.resolved = named
+                .chain
+                .resolve()
+                .map(|address| (ObjectHandle::new(world), address));
+            named.resolved.map(|(_, address)| address)
+        }
+    }
+}
+
+unsafe fn log_value(named: &mut Named) {
+    let value = resolve(named)
+        .and_then(|address| Some((address, scan::read(address, named.chain.value_type())?)));
+
+    match value {
+        Some((address, value)) => {
+            common::log!("drg.chain: {} ({:#x}) = {}", named.name, address, value);
+        }
+        None => common::log!("drg.chain: {} doesn't lead anywhere right now", named.name),
+    }
+}
+
+unsafe fn write(named: &mut Named, text: &str) {
+    let Some(value) = Value::parse(named.chain.value_type(), text) else {
+        common::log!("drg.chain: {} isn't a valid value", text);
+        return;
+    };
+
+    if !crate::policy::enable(&WRITE) {
+        return;
+    }
+
+    match resolve(named) {
+        Some(address) if scan::write(address, value) => log_value(named),
+        Some(address) => common::log!("drg.chain: can't write to {:#x}", address),
+        None => common::log!("drg.chain: {} doesn't lead anywhere right now", named.name),
+    }
+}
@@ -0,0 +1,33 @@
+// Settings from "drg-native.json" next to the game's executable. Everything in
+// it is optional, and so is the file. For example:
+//
+// {
+//     "chains": {
+//         "health": "FSD-Win64-Shipping.exe+0x1234 -> +0x18 -> +0x40 as f32"
+//     }
+// }
+
+use common::json::{self, Value};
+use std::fs;
+use std::io;
+
+const CONFIG_FILE: &str = "drg-native.json";
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Io(#[from] io::Error),
+    Json(#[from] json::Error),
+    NoFolder,
+}
+
+// Reads the file fresh, so edits are picked up without restarting the game.
+// An empty object if there's no file.
+pub fn load() -> Result<Value, Error> {
+    let path = crate::data_dir(CONFIG_FILE).ok_or(Error::NoFolder)?;
+
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Value::parse(&text)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Value::Object(Vec::new())),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,56 @@
+// Console commands handled in Rust. Typing "drg.outline on" into the game's
+// console runs the handler for "drg.outline" with "on" as its arguments.
+// Anything we don't recognize goes to the engine as usual.
+
+use common::list::List;
+use common::FString;
+use core::fmt::Write;
+
+// Longer lines aren't ours.
+const MAX_LINE_LEN: usize = 256;
+
+pub struct Command {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub run: unsafe fn(args: &str),
+}
+
+// Returns whether `line` was one of `commands`.
+pub unsafe fn dispatch(commands: &[Command], line: &FString) -> bool {
+    let mut text = List::<u8, MAX_LINE_LEN>::new();
+
+    if write!(text, "{}", line).is_err() {
+        return false;
+    }
+
+    // SAFETY: FString's Display only writes whole UTF-8 characters.
+    dispatch_line(commands, core::str::from_utf8_unchecked(text.as_slice()))
+}
+
+// Like `dispatch`, for lines that don't come from the game's console.
+pub unsafe fn dispatch_line(commands: &[Command], line: &str) -> bool {
+    let text = line.trim();
+    let (name, args) = text.split_once(' ').unwrap_or((text, ""));
+
+    if name.eq_ignore_ascii_case("drg.help") {
+        help(commands);
+        return true;
+    }
+
+    match commands.iter().find(|c| c.name.eq_ignore_ascii_case(name)) {
+        Some(command) => {
+            (command.run)(args.trim());
+            true
+        }
+
+        None => false,
+    }
+}
+
+fn help(commands: &[Command]) {
+    common::log!("drg.help: list these commands");
+
+    for command in commands {
+        common::log!("{}: {}", command.name, command.help);
+    }
+}
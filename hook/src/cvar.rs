@@ -0,0 +1,175 @@
+// Console variables, like r.ScreenPercentage, fov or r.Fog, read and set the
+// way typing them into the console would, through the local player
+// controller's ConsoleCommand. That also gives us what the command printed.
+// Names are looked up in the console manager first, since the console would
+// otherwise pass an unknown one on to the game as a command.
+//
+// drg.cvar <name>           its value
+// drg.cvar <name> <value>   set it
+// drg.cvar dump             write every console variable and command to
+//                           "cvars.txt" next to the game's executable
+//
+// From Rust:
+//
+// let percentage = cvar::get("r.ScreenPercentage")?;
+// cvar::set("r.ScreenPercentage", "50")?;
+
+use common::scan::{self, ValueType};
+use common::{win, FString, TMap};
+use core::ffi::c_void;
+use core::{mem, ptr};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: drg.cvar <name> [value] | dump";
+
+const CVARS_FILE: &str = "cvars.txt";
+
+// What IConsoleManager::Singleton points to. Its map of every registered
+// variable and command comes right after its vtable.
+#[repr(C)]
+struct FConsoleManager {
+    vtable: *const c_void,
+    ConsoleObjects: TMap<FString, *mut c_void>,
+}
+
+static mut MANAGER: *mut FConsoleManager = ptr::null_mut();
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    FindConsoleManager,
+    Io(#[from] io::Error),
+    NoFolder,
+    // There's no local player to run commands as, like in the main menu's
+    // loading screens.
+    NoPlayerController,
+    // The console manager has nothing by this name.
+    UnknownName(String),
+    // What the console printed instead of a value.
+    UnknownVariable(String),
+}
+
+pub unsafe fn run(args: &str) {
+    let mut words = args.split_whitespace();
+
+    let result = match (words.next(), words.next(), words.next()) {
+        (Some("dump"), None, None) => {
+            dump().map(|path| common::log!("drg.cvar: wrote {}", path.display()))
+        }
+        (Some(name), None, None) => get(name).map(|value| common::log!("{} = {}", name, value)),
+        (Some(name), Some(value), None) => {
+            set(name, value).map(|value| common::log!("{} = {}", name, value))
+        }
+        _ => {
+            common::log!("{}", USAGE);
+            Ok(())
+        }
+    };
+
+    if let Err(e) = result {
+        common::log!("drg.cvar: {:?}", e);
+    }
+}
+
+// The variable's value, as the console shows it.
+pub unsafe fn get(name: &str) -> Result<String, Error> {
+    find(name)?;
+    quoted_value(exec(name)?)
+}
+
+// Returns the new value, as the console shows it. Fails with what the console
+// printed if it didn't take the value, e.g. because something with a higher
+// priority than the console set the variable.
+pub unsafe fn set(name: &str, value: &str) -> Result<String, Error> {
+    find(name)?;
+    quoted_value(exec(&format!("{} {}", name, value))?)
+}
+
+// The console answers both with `name = "value"`, followed by who last set it.
+fn quoted_value(output: String) -> Result<String, Error> {
+    match output.split('"').nth(1) {
+        Some(value) => Ok(value.to_owned()),
+        None => Err(Error::UnknownVariable(output)),
+    }
+}
+
+// The variable or command called `name`, which the console matches without
+// regard to case.
+pub unsafe fn find(name: &str) -> Result<*mut c_void, Error> {
+    let manager = manager()?;
+
+    (*manager)
+        .ConsoleObjects
+        .iter()
+        .find(|(key, _)| key.to_string().eq_ignore_ascii_case(name))
+        .map(|(_, &object)| object)
+        .ok_or_else(|| Error::UnknownName(name.to_owned()))
+}
+
+unsafe fn manager() -> Result<*mut FConsoleManager, Error> {
+    if MANAGER.is_null() {
+        MANAGER = match common::offsets::get("ConsoleManager") {
+            Some(address) => *(address as *const *mut FConsoleManager),
+            None => find_manager().unwrap_or(ptr::null_mut()),
+        };
+    }
+
+    if MANAGER.is_null() {
+        Err(Error::FindConsoleManager)
+    } else {
+        Ok(MANAGER)
+    }
+}
+
+// The console manager is created at startup and never freed, so the global
+// that points to it is the only pointer in .data to an object with
+// FConsoleManager's vtable. Most of .data isn't pointers, so what it points
+// to is read through scan::read, which survives bad addresses.
+unsafe fn find_manager() -> Option<*mut FConsoleManager> {
+    let module = win::Module::current().ok()?;
+    let vtable = module.find_vtable("FConsoleManager").ok()? as i64;
+    let (start, size) = module.section(".data")?;
+
+    (start..start + size)
+        .step_by(mem::size_of::<usize>())
+        .map(|global| *(global as *const usize))
+        .filter(|&object| object != 0 && object % mem::size_of::<usize>() == 0)
+        .find(|&object| {
+            matches!(scan::read(object, ValueType::I64), Some(scan::Value::I64(v)) if v == vtable)
+        })
+        .map(|object| object as *mut FConsoleManager)
+}
+
+// Runs `command` as if it were typed into the console, and returns what it
+// printed. Goes straight to the engine, so it can't run our own commands.
+pub unsafe fn exec(command: &str) -> Result<String, Error> {
+    type ConsoleCommand =
+        unsafe extern "C" fn(*mut c_void, *mut FString, *const FString, bool) -> *mut FString;
+
+    let controller = common::player::local_controller();
+
+    if controller.is_null() {
+        return Err(Error::NoPlayerController);
+    }
+
+    let command: Vec<u16> = command.encode_utf16().chain([0]).collect();
+    let command = FString::from(command.as_slice());
+    let mut output = FString::new();
+
+    let original = mem::transmute::<*const c_void, ConsoleCommand>(crate::CONSOLE_COMMAND);
+    original(controller.cast(), &mut output, &command, false);
+
+    let text = output.to_string();
+    output.free();
+    Ok(text)
+}
+
+// Writes what DumpConsoleCommands prints, which is every registered console
+// variable and command. Returns the file's path.
+pub unsafe fn dump() -> Result<PathBuf, Error> {
+    let path = crate::data_dir(CVARS_FILE).ok_or(Error::NoFolder)?;
+
+    fs::write(&path, exec("DumpConsoleCommands")?)?;
+    Ok(path)
+}
@@ -0,0 +1,152 @@
+// The console side of common::data_table:
+//
+// drg.datatable list          every loaded data table and its row count
+// drg.datatable dump [name]   write every table, or those whose name has
+//                             `name` in it, to the "datatables" folder next
+//                             to the game's executable, as CSV and JSON
+//
+// A row's values are flattened into columns like snapshots are, e.g.
+// "Damage", "Resistances.Fire" and "Drops[0].Chance".
+
+use common::data_table;
+use common::snapshot::Snapshot;
+use common::{JsonString, UObject};
+use core::fmt::Write as _;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: drg.datatable list | dump [name]";
+
+const DATA_TABLES_FOLDER: &str = "datatables";
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Io(#[from] io::Error),
+    NoFolder,
+}
+
+pub unsafe fn run(args: &str) {
+    let mut words = args.split_whitespace();
+
+    match (words.next(), words.next(), words.next()) {
+        (Some("list"), None, None) => list(),
+        (Some("dump"), filter, None) => dump(filter.unwrap_or("")),
+        _ => common::log!("{}", USAGE),
+    }
+}
+
+unsafe fn list() {
+    for table in data_table::tables() {
+        common::log!(
+            "drg.datatable: {} ({} rows)",
+            (*table).name(),
+            data_table::row_count(table)
+        );
+    }
+}
+
+unsafe fn dump(filter: &str) {
+    let filter = filter.to_lowercase();
+    let mut written = 0;
+
+    for table in data_table::tables() {
+        if !(*table).name().to_lowercase().contains(&filter) {
+            continue;
+        }
+
+        match save(table) {
+            Ok(_) => written += 1,
+            Err(e) => common::log!(
+                "drg.datatable: failed to write {}: {:?}",
+                (*table).name(),
+                e
+            ),
+        }
+    }
+
+    common::log!(
+        "drg.datatable: wrote {} tables to {}",
+        written,
+        DATA_TABLES_FOLDER
+    );
+}
+
+// Writes `table` as CSV, and as JSON next to it. Returns the CSV's path.
+unsafe fn save(table: *mut UObject) -> Result<PathBuf, Error> {
+    let folder = crate::data_dir(DATA_TABLES_FOLDER).ok_or(Error::NoFolder)?;
+
+    fs::create_dir_all(&folder)?;
+
+    let rows = data_table::rows(table);
+    let columns = columns(&rows);
+
+    let mut csv = String::from("Name");
+
+    for column in &columns {
+        let _ = write!(csv, ",{}", csv_field(column));
+    }
+
+    csv.push('\n');
+
+    let mut json = format!("{{\"table\":\"{}\",\"rows\":[", JsonString(&*table));
+
+    for (i, row) in rows.iter().enumerate() {
+        csv.push_str(&csv_field(&row.object));
+
+        for column in &columns {
+            let _ = write!(csv, ",{}", csv_field(row.get(column).unwrap_or("")));
+        }
+
+        csv.push('\n');
+
+        if i > 0 {
+            json.push(',');
+        }
+
+        let _ = write!(
+            json,
+            "\n{{\"name\":\"{}\",\"values\":{{",
+            JsonString(&row.object)
+        );
+
+        for (j, (path, value)) in row.values.iter().enumerate() {
+            if j > 0 {
+                json.push(',');
+            }
+
+            let _ = write!(json, "\"{}\":\"{}\"", JsonString(path), JsonString(value));
+        }
+
+        json.push_str("}}");
+    }
+
+    json.push_str("\n]}");
+
+    let path = folder.join(format!("{}.csv", (*table).name()));
+    fs::write(&path, csv)?;
+    fs::write(path.with_extension("json"), json)?;
+    Ok(path)
+}
+
+// Every path any row has, in the order they're first seen. Rows of one table
+// share a struct, but arrays make some rows longer than others.
+fn columns(rows: &[Snapshot]) -> Vec<&str> {
+    let mut columns: Vec<&str> = Vec::new();
+    let mut seen = BTreeSet::new();
+
+    for row in rows {
+        for (path, _) in &row.values {
+            if seen.insert(path.as_str()) {
+                columns.push(path);
+            }
+        }
+    }
+
+    columns
+}
+
+fn csv_field(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
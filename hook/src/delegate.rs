@@ -0,0 +1,191 @@
+// Binds Rust callbacks to dynamic multicast delegates, so that we receive
+// engine events the same way blueprints do.
+//
+// A delegate broadcast calls a UFunction, looked up by name, on each bound
+// object. For each binding we make a native copy of the delegate's signature
+// function that calls `trampoline`, give it a unique name, and add it to the
+// function map of the carrier object's class. Then we bind the carrier and
+// that name to the delegate.
+//
+// Sparse delegates, like AActor::OnDestroyed, keep their invocation lists
+// elsewhere, so binding one fails with Error::Sparse.
+
+use common::list::{self, List};
+use common::{
+    EClassCastFlags, FFrame, FMulticastDelegateProperty, FMulticastScriptDelegate, FProperty,
+    GUObjectArray, ObjectHandle, PropertyValue, UFunction, UObject,
+};
+use core::ffi::c_void;
+use core::ptr;
+
+const USAGE: &str = "usage: drg.delegate watch <object path>.<delegate> | unwatch <n>|all";
+
+// Called with our copy of the signature function and the broadcast's
+// parameters, which are laid out like that function's parameter struct.
+pub type Callback = unsafe fn(function: *mut UFunction, parameters: *mut u8);
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    FindCarrier,
+    FindProperty,
+    NotMulticast,
+    Sparse,
+    List(#[from] list::Error),
+}
+
+struct Binding {
+    id: u32,
+    function: *mut UFunction,
+    owner: ObjectHandle<UObject>,
+    delegate: *mut FMulticastScriptDelegate,
+    callback: Callback,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Handle(u32);
+
+static mut BINDINGS: List<Binding, 64> = List::new();
+static mut NEXT_ID: u32 = 0;
+static mut CARRIER: *mut UObject = ptr::null_mut();
+
+// Delegates can only be bound to real objects. Object's default object lives
+// as long as the game does, and its class has almost no functions of its own.
+unsafe fn carrier() -> Result<*mut UObject, Error> {
+    if CARRIER.is_null() {
+        CARRIER = (*GUObjectArray)
+            .find("Object /Script/CoreUObject.Default__Object")
+            .map_err(|_| Error::FindCarrier)?;
+    }
+
+    Ok(CARRIER)
+}
+
+// Calls `callback` whenever `owner`'s multicast delegate called `name`, e.g.
+// HealthComponent's OnDeath, is broadcast.
+pub unsafe fn bind(owner: *mut UObject, name: &str, callback: Callback) -> Result<Handle, Error> {
+    let property = (*(*owner).class()).find_property(name);
+
+    if property.is_null() {
+        return Err(Error::FindProperty);
+    }
+
+    if (*property).is(EClassCastFlags::CASTCLASS_FMulticastSparseDelegateProperty) {
+        return Err(Error::Sparse);
+    }
+
+    if !(*property).is(EClassCastFlags::CASTCLASS_FMulticastInlineDelegateProperty) {
+        return Err(Error::NotMulticast);
+    }
+
+    let signature = (*property.cast::<FMulticastDelegateProperty>()).SignatureFunction;
+    let delegate = (*property).value_ptr_mut::<FMulticastScriptDelegate>(owner.cast(), 0);
+    let carrier = carrier()?;
+    let class = (*carrier).class().cast_mut();
+    let id = NEXT_ID;
+
+    // Numbered names don't need a new entry in the name pool. Numbers start
+    // at 1, since 0 means the name has none.
+    let name = (*signature).NamePrivate.with_number(id + 1);
+    let function = (*signature).clone_native(name, class.cast(), trampoline);
+
+    BINDINGS.push(Binding {
+        id,
+        function,
+        owner: ObjectHandle::new(owner),
+        delegate,
+        callback,
+    })?;
+
+    (*class).FuncMap.insert(name, function);
+    (*delegate).add(carrier, name);
+
+    NEXT_ID = NEXT_ID.wrapping_add(1);
+    Ok(Handle(id))
+}
+
+pub unsafe fn unbind(Handle(id): Handle) -> bool {
+    if let Some(index) = BINDINGS.iter().position(|b| b.id == id) {
+        if let Ok(binding) = BINDINGS.swap_remove(index) {
+            remove(&binding);
+            return true;
+        }
+    }
+
+    false
+}
+
+pub unsafe fn unbind_all() {
+    for binding in BINDINGS.iter() {
+        remove(binding);
+    }
+
+    BINDINGS.clear();
+}
+
+// The function stays in the carrier's function map, since a broadcast may be
+// in progress. Its binding is gone, so `trampoline` ignores it.
+unsafe fn remove(binding: &Binding) {
+    // The delegate went away with its owner.
+    if binding.owner.resolve().is_none() {
+        return;
+    }
+
+    (*binding.delegate).remove(CARRIER, (*binding.function).NamePrivate);
+}
+
+unsafe extern "C" fn trampoline(_: *mut UObject, stack: *mut FFrame, _: *mut c_void) {
+    let function = (*stack).Node;
+
+    if let Some(binding) = BINDINGS.iter().find(|b| b.function == function) {
+        (binding.callback)(function, (*stack).Locals);
+    }
+}
+
+pub unsafe fn run(args: &str) {
+    let mut words = args.split_whitespace();
+
+    match (words.next(), words.next(), words.next()) {
+        (Some("watch"), Some(path), None) => watch(path),
+        (Some("unwatch"), Some("all"), None) => unbind_all(),
+        (Some("unwatch"), Some(id), None) => match id.parse() {
+            Ok(id) if unbind(Handle(id)) => {}
+            _ => common::log!("drg.delegate: nothing is watched as {}", id),
+        },
+        _ => common::log!("{}", USAGE),
+    }
+}
+
+unsafe fn watch(path: &str) {
+    let Some((object_path, name)) = path.rsplit_once('.') else {
+        common::log!("{}", USAGE);
+        return;
+    };
+
+    let object = (*GUObjectArray).find_by_path(object_path);
+
+    if object.is_null() {
+        common::log!("drg.delegate: no object called {}", object_path);
+        return;
+    }
+
+    match bind(object, name, log_broadcast) {
+        Ok(Handle(id)) => common::log!("drg.delegate: watching {} as {}", path, id),
+        Err(e) => common::log!("drg.delegate: can't watch {}: {:?}", path, e),
+    }
+}
+
+unsafe fn log_broadcast(function: *mut UFunction, parameters: *mut u8) {
+    common::log!("drg.delegate: {}", *function);
+
+    let mut property = (*function).ChildProperties.cast::<FProperty>();
+
+    while !property.is_null() {
+        let value = PropertyValue {
+            property,
+            container: parameters.cast(),
+        };
+
+        common::log!("drg.delegate:   {} = {}", (*property).name(), value);
+        property = (*property).base.Next.cast();
+    }
+}
@@ -0,0 +1,367 @@
+// Draws lines, boxes, circles and text over the game. The canvas is only
+// valid while the HUD draws, so calls made at any other time during a frame,
+// like from a tick callback, are queued and drawn together when the HUD next
+// draws. Positions and sizes are in screen pixels. "drg.draw on" draws a test
+// pattern, to check that drawing works after a game update.
+
+use crate::{process_event, tick};
+use common::asset::find_cached;
+use common::list::{self, List};
+use common::{FLinearColor, FString, FVector2D, ObjectHandle, UFunction, UObject};
+use core::f32::consts::TAU;
+use core::ffi::c_void;
+use core::ptr;
+use sdk::Engine::HUD;
+
+const MAX_SHAPES: usize = 512;
+const MAX_TEXT_LEN: usize = 64;
+
+// Enough that circles look round at the sizes we draw them.
+const CIRCLE_SEGMENTS: usize = 24;
+
+static mut SHAPES: List<Shape, MAX_SHAPES> = List::new();
+
+static mut FONT: *mut UObject = ptr::null_mut();
+static mut DRAW_LINE: *mut UFunction = ptr::null_mut();
+static mut DRAW_BOX: *mut UFunction = ptr::null_mut();
+static mut DRAW_TEXT: *mut UFunction = ptr::null_mut();
+
+// The local player's HUD, and the ReceiveDrawHUD it runs, which is its
+// blueprint's override if it has one.
+static mut LOCAL_HUD: ObjectHandle<UObject> = ObjectHandle::NULL;
+static mut RECEIVE_DRAW_HUD: *mut UFunction = ptr::null_mut();
+
+static mut TEST_PATTERN: bool = false;
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    List(#[from] list::Error),
+}
+
+enum Kind {
+    Line {
+        a: FVector2D,
+        b: FVector2D,
+    },
+    Rect {
+        position: FVector2D,
+        size: FVector2D,
+    },
+    Circle {
+        center: FVector2D,
+        radius: f32,
+    },
+    Text {
+        position: FVector2D,
+        centered: bool,
+
+        // Null-terminated UTF-16, for an FString to borrow.
+        text: List<u16, MAX_TEXT_LEN>,
+    },
+}
+
+struct Shape {
+    kind: Kind,
+    thickness: f32,
+    color: FLinearColor,
+}
+
+pub unsafe fn line(
+    a: FVector2D,
+    b: FVector2D,
+    thickness: f32,
+    color: FLinearColor,
+) -> Result<(), Error> {
+    push(Kind::Line { a, b }, thickness, color)
+}
+
+// An unfilled box with its top left corner at `position`.
+pub unsafe fn rect(
+    position: FVector2D,
+    size: FVector2D,
+    thickness: f32,
+    color: FLinearColor,
+) -> Result<(), Error> {
+    push(Kind::Rect { position, size }, thickness, color)
+}
+
+pub unsafe fn circle(
+    center: FVector2D,
+    radius: f32,
+    thickness: f32,
+    color: FLinearColor,
+) -> Result<(), Error> {
+    push(Kind::Circle { center, radius }, thickness, color)
+}
+
+// Text with its top left corner at `position`, or centered on it.
+pub unsafe fn text(
+    position: FVector2D,
+    text: &str,
+    centered: bool,
+    color: FLinearColor,
+) -> Result<(), Error> {
+    let mut units = List::new();
+
+    for unit in text.encode_utf16().chain([0]) {
+        units.push(unit)?;
+    }
+
+    push(
+        Kind::Text {
+            position,
+            centered,
+            text: units,
+        },
+        0.0,
+        color,
+    )
+}
+
+unsafe fn push(kind: Kind, thickness: f32, color: FLinearColor) -> Result<(), Error> {
+    SHAPES.push(Shape {
+        kind,
+        thickness,
+        color,
+    })?;
+
+    Ok(())
+}
+
+pub fn set_test_pattern(enabled: bool) {
+    unsafe { TEST_PATTERN = enabled };
+}
+
+// Draws the queue while alive. Only drop it once the ProcessEvent hook is gone.
+pub struct Draw;
+
+impl Draw {
+    pub unsafe fn new() -> Result<Self, list::Error> {
+        process_event::register(on_process_event)?;
+
+        if let Err(e) = tick::register(find_local_hud) {
+            process_event::unregister(on_process_event);
+            return Err(e);
+        }
+
+        Ok(Self)
+    }
+}
+
+impl Drop for Draw {
+    fn drop(&mut self) {
+        unsafe {
+            tick::unregister(find_local_hud);
+            process_event::unregister(on_process_event);
+        }
+    }
+}
+
+// The HUD changes with the level, and its class with the game mode.
+unsafe fn find_local_hud(_: f32) {
+    let controller = common::player::local_controller();
+
+    let hud = if controller.is_null() {
+        ptr::null_mut()
+    } else {
+        (*controller).object_property("MyHUD")
+    };
+
+    if hud.is_null() {
+        LOCAL_HUD = ObjectHandle::NULL;
+        RECEIVE_DRAW_HUD = ptr::null_mut();
+    } else if LOCAL_HUD.resolve() != Some(hud) {
+        LOCAL_HUD = ObjectHandle::new(hud);
+        RECEIVE_DRAW_HUD = (*(*hud).class()).find_function("ReceiveDrawHUD");
+    }
+}
+
+// AHUD::DrawHUD() calls ReceiveDrawHUD through ProcessEvent every frame, with
+// the canvas set up, whether or not a blueprint implements it. Hooking the
+// event's UFunction instead would never fire: ProcessEvent runs the
+// blueprint's override, or returns early when there's no script to run.
+unsafe fn on_process_event(object: *mut UObject, function: *mut UFunction, _: *mut c_void) {
+    if function.is_null() || function != RECEIVE_DRAW_HUD || LOCAL_HUD.resolve() != Some(object) {
+        return;
+    }
+
+    if TEST_PATTERN {
+        queue_test_pattern();
+    }
+
+    let canvas = (*object.cast::<HUD>()).Canvas.cast::<UObject>();
+
+    if !canvas.is_null() {
+        for shape in SHAPES.iter() {
+            draw(canvas, shape);
+        }
+    }
+
+    SHAPES.clear();
+}
+
+// One of each shape, in the top left corner.
+unsafe fn queue_test_pattern() {
+    let color = FLinearColor::GREEN;
+    let at = |x, y| FVector2D { X: x, Y: y };
+
+    let result = line(at(100.0, 100.0), at(300.0, 100.0), 2.0, color)
+        .and_then(|_| rect(at(100.0, 120.0), at(200.0, 100.0), 2.0, color))
+        .and_then(|_| circle(at(200.0, 320.0), 80.0, 2.0, color))
+        .and_then(|_| text(at(100.0, 420.0), "drg-native draw test", false, color));
+
+    if let Err(e) = result {
+        common::log!("draw: failed to queue the test pattern: {:?}", e);
+    }
+}
+
+unsafe fn draw(canvas: *mut UObject, shape: &Shape) {
+    match shape.kind {
+        Kind::Line { a, b } => draw_line(canvas, a, b, shape.thickness, shape.color),
+
+        Kind::Rect { position, size } => {
+            draw_box(canvas, position, size, shape.thickness, shape.color)
+        }
+
+        Kind::Circle { center, radius } => {
+            let point = |i: usize| {
+                let angle = TAU * i as f32 / CIRCLE_SEGMENTS as f32;
+
+                FVector2D {
+                    X: center.X + radius * angle.cos(),
+                    Y: center.Y + radius * angle.sin(),
+                }
+            };
+
+            for i in 0..CIRCLE_SEGMENTS {
+                draw_line(canvas, point(i), point(i + 1), shape.thickness, shape.color);
+            }
+        }
+
+        Kind::Text {
+            position,
+            centered,
+            ref text,
+        } => draw_text(canvas, position, text.as_slice(), centered, shape.color),
+    }
+}
+
+unsafe fn draw_line(
+    canvas: *mut UObject,
+    a: FVector2D,
+    b: FVector2D,
+    thickness: f32,
+    color: FLinearColor,
+) {
+    #[repr(C)]
+    struct Parameters {
+        ScreenPositionA: FVector2D,
+        ScreenPositionB: FVector2D,
+        Thickness: f32,
+        RenderColor: FLinearColor,
+    }
+
+    let function = find_cached(&mut DRAW_LINE, "Function /Script/Engine.Canvas.K2_DrawLine");
+
+    if !function.is_null() {
+        let mut parameters = Parameters {
+            ScreenPositionA: a,
+            ScreenPositionB: b,
+            Thickness: thickness,
+            RenderColor: color,
+        };
+
+        UObject::process_event(
+            canvas,
+            function,
+            (&mut parameters as *mut Parameters).cast(),
+        );
+    }
+}
+
+unsafe fn draw_box(
+    canvas: *mut UObject,
+    position: FVector2D,
+    size: FVector2D,
+    thickness: f32,
+    color: FLinearColor,
+) {
+    #[repr(C)]
+    struct Parameters {
+        ScreenPosition: FVector2D,
+        ScreenSize: FVector2D,
+        Thickness: f32,
+        RenderColor: FLinearColor,
+    }
+
+    let function = find_cached(&mut DRAW_BOX, "Function /Script/Engine.Canvas.K2_DrawBox");
+
+    if !function.is_null() {
+        let mut parameters = Parameters {
+            ScreenPosition: position,
+            ScreenSize: size,
+            Thickness: thickness,
+            RenderColor: color,
+        };
+
+        UObject::process_event(
+            canvas,
+            function,
+            (&mut parameters as *mut Parameters).cast(),
+        );
+    }
+}
+
+unsafe fn draw_text(
+    canvas: *mut UObject,
+    position: FVector2D,
+    text: &[u16],
+    centered: bool,
+    color: FLinearColor,
+) {
+    #[repr(C)]
+    struct Parameters {
+        RenderFont: *mut UObject,
+        RenderText: FString,
+        ScreenPosition: FVector2D,
+        Scale: FVector2D,
+        RenderColor: FLinearColor,
+        Kerning: f32,
+        ShadowColor: FLinearColor,
+        ShadowOffset: FVector2D,
+        bCentreX: bool,
+        bCentreY: bool,
+        bOutlined: bool,
+        OutlineColor: FLinearColor,
+    }
+
+    let function = find_cached(&mut DRAW_TEXT, "Function /Script/Engine.Canvas.K2_DrawText");
+
+    // K2_DrawText() draws nothing without a font.
+    let font = find_cached(&mut FONT, "Font /Engine/EngineFonts/Roboto.Roboto");
+
+    if function.is_null() || font.is_null() {
+        return;
+    }
+
+    let mut parameters = Parameters {
+        RenderFont: font,
+        RenderText: FString::from(text),
+        ScreenPosition: position,
+        Scale: FVector2D { X: 1.0, Y: 1.0 },
+        RenderColor: color,
+        Kerning: 0.0,
+        ShadowColor: FLinearColor::BLACK,
+        ShadowOffset: FVector2D { X: 1.0, Y: 1.0 },
+        bCentreX: centered,
+        bCentreY: centered,
+        bOutlined: false,
+        OutlineColor: FLinearColor::BLACK,
+    };
+
+    UObject::process_event(
+        canvas,
+        function,
+        (&mut parameters as *mut Parameters).cast(),
+    );
+}
@@ -0,0 +1,251 @@
+// Game events, translated from the UFunctions that signal them so that
+// features don't each have to recognize those functions themselves. World
+// changes are noticed by checking the viewport's world every frame instead,
+// since every way of loading a level ends up replacing it.
+//
+// unsafe fn on_event(event: &Event) {
+//     if let Event::PlayerDowned { character } = *event {
+//         common::log!("{} went down", *character);
+//     }
+// }
+//
+// event::subscribe(on_event)?;
+
+use crate::process_event::{self, Filter};
+use common::inspect::{self, Primitive};
+use common::list::{self, Listeners};
+use common::{
+    EClassCastFlags, FByteProperty, FEnumProperty, FProperty, GUObjectArray, ObjectHandle, UEnum,
+    UFunction, UObject,
+};
+use core::ffi::c_void;
+use core::ptr;
+
+#[derive(Copy, Clone)]
+pub enum Event {
+    // A mission's level started. `hazard` is its difficulty setting, like
+    // DFC_Haz_02_Challenging, or null if the game state doesn't have one.
+    MissionStart { seed: u32, hazard: *mut UObject },
+
+    // Any player's character went down, not just ours.
+    PlayerDowned { character: *mut UObject },
+
+    // The mission is over and the level is about to end, either because the
+    // team extracted or because everyone went down. `controller` is ours.
+    MissionEnd { controller: *mut UObject },
+
+    // A different level was loaded, like a mission after the space rig or the
+    // space rig after a mission. Objects from the last world are gone, or will
+    // be after the next garbage collection, so drop any pointers to them.
+    // Classes and other objects from /Script packages stay.
+    WorldChanged { world: *mut UObject },
+}
+
+pub type Subscriber = unsafe fn(event: &Event);
+
+type Translate = unsafe fn(
+    object: *mut UObject,
+    function: *mut UFunction,
+    parameters: *mut c_void,
+) -> Option<Event>;
+
+struct Watch {
+    path: &'static str,
+    translate: Translate,
+}
+
+// The analytics wrapper's HandleMatchStarted looks out of place, but it's the
+// one function seen going through ProcessEvent once as each level's match
+// starts (see hook/some_native_functions.txt), and it lives on the game
+// instance, so it's there for host and client alike. The game state's own
+// signals don't do as well: OnRep_GeneratedMissionSeed only runs on clients,
+// and HasMatchStarted is a query that's called every frame.
+const WATCHES: [Watch; 3] = [
+    Watch {
+        path: "/Script/FSD.GoogleAnalyticsWrapper.HandleMatchStarted",
+        translate: mission_start,
+    },
+    Watch {
+        path: "/Script/FSD.InventoryComponent.OnCharacterStateChanged",
+        translate: player_downed,
+    },
+    Watch {
+        path: "/Script/FSD.FSDPlayerController.Client_EndLevel_WaitForData",
+        translate: mission_end,
+    },
+];
+
+// The functions WATCHES names, in the same order. Null for any that this
+// version of the game doesn't have.
+static mut FUNCTIONS: [*mut UFunction; WATCHES.len()] = [ptr::null_mut(); WATCHES.len()];
+
+static mut SUBSCRIBERS: Listeners<Subscriber, 32> = Listeners::new();
+
+// A handle, so that a new world at the old one's address is still a change.
+static mut WORLD: ObjectHandle<UObject> = ObjectHandle::NULL;
+
+#[allow(dead_code)]
+pub unsafe fn subscribe(subscriber: Subscriber) -> Result<(), list::Error> {
+    SUBSCRIBERS.add(subscriber)
+}
+
+#[allow(dead_code)]
+pub unsafe fn unsubscribe(subscriber: Subscriber) {
+    SUBSCRIBERS.remove(|&s| s as usize == subscriber as usize);
+}
+
+// Watches for events while alive. Only drop it once the ProcessEvent hook is
+// gone.
+pub struct Events;
+
+impl Events {
+    pub unsafe fn new() -> Result<Self, list::Error> {
+        for (watch, function) in WATCHES.iter().zip(FUNCTIONS.iter_mut()) {
+            *function = (*GUObjectArray).find_by_path(watch.path).cast();
+
+            if function.is_null() {
+                common::log!("event: no function called {}", watch.path);
+            }
+        }
+
+        WORLD = ObjectHandle::new(common::player::world());
+
+        if let Err(e) = register() {
+            process_event::unregister(on_process_event);
+            return Err(e);
+        }
+
+        Ok(Self)
+    }
+}
+
+// Only the watched functions reach on_process_event.
+unsafe fn register() -> Result<(), list::Error> {
+    for &function in FUNCTIONS.iter().filter(|f| !f.is_null()) {
+        process_event::register_for(Filter::Function(function), on_process_event)?;
+    }
+
+    crate::tick::register(watch_world)
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        unsafe {
+            crate::tick::unregister(watch_world);
+            process_event::unregister(on_process_event);
+        }
+    }
+}
+
+unsafe fn watch_world(_: f32) {
+    let world = common::player::world();
+
+    if world.is_null() || WORLD.resolve() == Some(world) {
+        return;
+    }
+
+    WORLD = ObjectHandle::new(world);
+    common::log!("event: now in {}", *world);
+    publish(&Event::WorldChanged { world });
+}
+
+unsafe fn on_process_event(
+    object: *mut UObject,
+    function: *mut UFunction,
+    parameters: *mut c_void,
+) {
+    if SUBSCRIBERS.is_empty() {
+        return;
+    }
+
+    let Some(index) = FUNCTIONS.iter().position(|&f| f == function) else {
+        return;
+    };
+
+    if let Some(event) = (WATCHES[index].translate)(object, function, parameters) {
+        publish(&event);
+    }
+}
+
+unsafe fn publish(event: &Event) {
+    Listeners::for_each(ptr::addr_of_mut!(SUBSCRIBERS), |subscriber| {
+        subscriber(event)
+    });
+}
+
+// Every level starts a match, including the space rig. Only a mission's game
+// state has a seed.
+unsafe fn mission_start(_: *mut UObject, _: *mut UFunction, _: *mut c_void) -> Option<Event> {
+    let game_state = common::player::game_state();
+
+    if game_state.is_null() {
+        return None;
+    }
+
+    let seed = (*(*game_state).class()).find_property("GeneratedMissionSeed");
+
+    if seed.is_null() {
+        return None;
+    }
+
+    let Some(Primitive::Integer(seed)) = inspect::read_primitive(seed, game_state.cast()) else {
+        return None;
+    };
+
+    // The difficulty is the game state's only DifficultySetting.
+    let hazard = inspect::find_object_of(game_state, "DifficultySetting");
+
+    Some(Event::MissionStart {
+        seed: seed as u32,
+        hazard,
+    })
+}
+
+// The inventory component hears about every state change of the character
+// that owns it.
+unsafe fn player_downed(
+    object: *mut UObject,
+    function: *mut UFunction,
+    parameters: *mut c_void,
+) -> Option<Event> {
+    let mut is_down = false;
+
+    inspect::for_each_property(function.cast(), |property| {
+        if let Some(name) = enum_name(property, parameters) {
+            is_down |= name.rsplit("::").next() == Some("Down");
+        }
+    });
+
+    let character = (*object).outer();
+
+    if is_down && !character.is_null() && inspect::is_a(character, "PlayerCharacter") {
+        Some(Event::PlayerDowned { character })
+    } else {
+        None
+    }
+}
+
+unsafe fn mission_end(object: *mut UObject, _: *mut UFunction, _: *mut c_void) -> Option<Event> {
+    Some(Event::MissionEnd { controller: object })
+}
+
+// The name of the enum value in an enum or byte property, like
+// "ECharacterState::Down".
+unsafe fn enum_name<'a>(property: *const FProperty, container: *const c_void) -> Option<&'a str> {
+    let enumeration: *const UEnum = if (*property).is(EClassCastFlags::CASTCLASS_FEnumProperty) {
+        (*property.cast::<FEnumProperty>()).Enumeration
+    } else if (*property).is(EClassCastFlags::CASTCLASS_FByteProperty) {
+        (*property.cast::<FByteProperty>()).Enumeration
+    } else {
+        ptr::null()
+    };
+
+    if enumeration.is_null() {
+        return None;
+    }
+
+    match inspect::read_primitive(property, container)? {
+        Primitive::Integer(value) => (*enumeration).name_for_value(value),
+        _ => None,
+    }
+}
@@ -0,0 +1,64 @@
+// Says whether a hotkey turned a feature on or off, since it's hard to tell
+// in fullscreen. The toggle is logged, and plays a sound if the "sounds"
+// section of the config has one for the feature:
+//
+// "sounds": {
+//     "god_mode": "/Game/Audio/UI/Cue_Click.Cue_Click",
+//     "lighting": { "on": "/Game/...Cue_On", "off": "/Game/...Cue_Off" }
+// }
+//
+// A single sound is pitched up for on and down for off.
+
+use common::json::Value;
+use common::{asset, kismet};
+
+const VOLUME: f32 = 1.0;
+const ON_PITCH: f32 = 1.25;
+const OFF_PITCH: f32 = 0.8;
+
+pub unsafe fn toggled(feature: &str, enabled: bool) {
+    common::log!("{}: {}", feature, if enabled { "on" } else { "off" });
+
+    let config = match crate::config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            common::log!("feedback: failed to read the config: {:?}", e);
+            return;
+        }
+    };
+
+    let Some(sound) = config.get("sounds").and_then(|sounds| sounds.get(feature)) else {
+        return;
+    };
+
+    let (path, pitch) = match sound {
+        Value::String(path) => (path.as_str(), if enabled { ON_PITCH } else { OFF_PITCH }),
+        sound => {
+            let path = sound.get(if enabled { "on" } else { "off" });
+
+            match path.and_then(Value::as_str) {
+                Some(path) => (path, 1.0),
+                None => return,
+            }
+        }
+    };
+
+    let sound = asset::load_by_path(path);
+
+    if sound.is_null() {
+        common::log!("feedback: no sound called {}", path);
+        return;
+    }
+
+    // An asset loaded by path has no other referencer, so it would be
+    // collected and loaded from disk again on every toggle.
+    if let Err(e) = crate::gc::keep(sound) {
+        common::log!("feedback: failed to keep {}: {:?}", path, e);
+    }
+
+    let world = common::player::world();
+
+    if !world.is_null() {
+        kismet::play_sound_2d(world, sound, VOLUME, pitch);
+    }
+}
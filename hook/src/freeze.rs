@@ -0,0 +1,407 @@
+// Values written back every frame, so that the game can't change them.
+//
+// drg.freeze                                  list what's frozen
+// drg.freeze /Game/...BP_PlayerCharacter_C_0.bCanSprint true
+// drg.freeze 0x1f2e3d4c5b60 f32 100
+// drg.unfreeze 0|all
+//
+// Without a value, the current one is frozen. A property whose object is
+// destroyed is unfrozen, and so is an address when the level changes, since
+// it was most likely in one of the last level's objects.
+//
+// Properties in the config's "freezes" section are frozen in every level, as
+// soon as their objects exist:
+//
+// "freezes": {
+//     "/Game/...BP_PlayerCharacter_C_0.bCanSprint": true
+// }
+
+use crate::event::{self, Event};
+use crate::policy::{Feature, Scope};
+use crate::scheduler::{self, Handle};
+use common::inspect::{self, Primitive};
+use common::json;
+use common::list::{self, List};
+use common::scan::{self, ValueType};
+use common::{FProperty, GUObjectArray, ObjectHandle, UObject};
+use core::fmt::{self, Display, Formatter};
+use core::ptr;
+
+const USAGE: &str = "usage: drg.freeze [<object path>.<property> [value] | \
+                     <address> <u8|i16|i32|i64|f32|f64> [value]]";
+
+#[derive(Copy, Clone)]
+pub enum Freeze {
+    Property {
+        object: ObjectHandle<UObject>,
+        property: *const FProperty,
+        value: Primitive,
+    },
+    Address {
+        address: usize,
+        value: scan::Value,
+    },
+}
+
+static mut FROZEN: List<Freeze, 64> = List::new();
+
+// How often to look for the objects of the config's freezes.
+const RETRY_INTERVAL: f32 = 1.0;
+
+// The config's freezes, as paths and values, whose objects this level doesn't
+// have yet. Most are spawned after the level loads.
+static mut PENDING: Vec<(String, String)> = Vec::new();
+static mut RETRY: Option<Handle> = None;
+
+// Frozen values can be anything, including what the host replicates.
+pub static FEATURE: Feature = Feature {
+    name: "drg.freeze",
+    scope: Scope::HostOnly,
+    disable: Some(disable),
+};
+
+// Replaces any freeze of the same property or address.
+pub unsafe fn add(freeze: Freeze) -> Result<(), list::Error> {
+    if let Some(index) = FROZEN.iter().position(|f| same_target(f, &freeze)) {
+        *FROZEN.get_mut(index)? = freeze;
+        return Ok(());
+    }
+
+    if FROZEN.is_empty() {
+        crate::tick::register(apply)?;
+    }
+
+    let result = FROZEN.push(freeze);
+
+    if FROZEN.is_empty() {
+        crate::tick::unregister(apply);
+    }
+
+    result
+}
+
+pub unsafe fn subscribe() -> Result<(), list::Error> {
+    event::subscribe(on_event)?;
+    load_config();
+    Ok(())
+}
+
+pub unsafe fn unsubscribe() {
+    event::unsubscribe(on_event);
+    stop_retrying();
+}
+
+pub unsafe fn remove(index: usize) -> Option<Freeze> {
+    let freeze = FROZEN.swap_remove(index).ok();
+
+    if FROZEN.is_empty() {
+        crate::tick::unregister(apply);
+    }
+
+    freeze
+}
+
+pub unsafe fn clear() {
+    FROZEN.clear();
+    crate::tick::unregister(apply);
+}
+
+unsafe fn on_event(event: &Event) {
+    let Event::WorldChanged { .. } = *event else {
+        return;
+    };
+
+    let before = FROZEN.len();
+    FROZEN.retain(|freeze| matches!(freeze, Freeze::Property { .. }));
+    let unfrozen = before - FROZEN.len();
+
+    if FROZEN.is_empty() {
+        crate::tick::unregister(apply);
+    }
+
+    if unfrozen > 0 {
+        common::log!(
+            "drg.freeze: unfroze {} addresses from the last level",
+            unfrozen
+        );
+    }
+
+    load_config();
+}
+
+// Reads the config's freezes, for this level to find their objects.
+unsafe fn load_config() {
+    PENDING.clear();
+
+    let config = match crate::config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            common::log!("drg.freeze: failed to read the config: {:?}", e);
+            return;
+        }
+    };
+
+    if let Some(json::Value::Object(members)) = config.get("freezes") {
+        for (path, value) in members {
+            let text = value
+                .as_str()
+                .map_or_else(|| value.to_string(), str::to_owned);
+            PENDING.push((path.clone(), text));
+        }
+    }
+
+    if PENDING.is_empty() {
+        stop_retrying();
+    } else if RETRY.is_none() {
+        match scheduler::every(RETRY_INTERVAL, freeze_pending) {
+            Ok(handle) => RETRY = Some(handle),
+            Err(e) => common::log!("drg.freeze: can't load the config's freezes: {:?}", e),
+        }
+    }
+}
+
+unsafe fn freeze_pending() {
+    let mut found = Vec::new();
+
+    // Until the level changes, a path that doesn't resolve is assumed to be
+    // an object that hasn't spawned yet.
+    PENDING.retain(|(path, value)| match freeze_property(path, Some(value)) {
+        Ok(freeze) => {
+            found.push(freeze);
+            false
+        }
+        Err(_) => true,
+    });
+
+    if PENDING.is_empty() {
+        stop_retrying();
+    }
+
+    if found.is_empty() || !crate::policy::enable(&FEATURE) {
+        return;
+    }
+
+    for freeze in found {
+        match add(freeze) {
+            Ok(()) => common::log!("drg.freeze: froze {} from the config", Describe(&freeze)),
+            Err(e) => common::log!("drg.freeze: failed to freeze: {:?}", e),
+        }
+    }
+}
+
+unsafe fn stop_retrying() {
+    if let Some(handle) = RETRY.take() {
+        scheduler::cancel(handle);
+    }
+}
+
+unsafe fn disable() {
+    if !FROZEN.is_empty() {
+        common::log!("drg.freeze: unfroze {} values", FROZEN.len());
+    }
+
+    clear();
+}
+
+pub unsafe fn freeze_command(args: &str) {
+    let mut words = args.split_whitespace();
+
+    let result = match (words.next(), words.next(), words.next()) {
+        (None, _, _) => {
+            list();
+            return;
+        }
+        (Some(address), Some(value_type), value) if address.starts_with("0x") => {
+            freeze_address(address, value_type, value)
+        }
+        (Some(path), value, None) => freeze_property(path, value),
+        _ => Err(USAGE.to_owned()),
+    };
+
+    if result.is_ok() && !crate::policy::enable(&FEATURE) {
+        return;
+    }
+
+    match result {
+        Ok(freeze) => match add(freeze) {
+            Ok(()) => common::log!("drg.freeze: froze {}", Describe(&freeze)),
+            Err(e) => common::log!("drg.freeze: failed to freeze: {:?}", e),
+        },
+        Err(message) => common::log!("drg.freeze: {}", message),
+    }
+}
+
+pub unsafe fn unfreeze_command(args: &str) {
+    if args == "all" {
+        clear();
+        return;
+    }
+
+    let freeze = args.parse().ok().and_then(|index| remove(index));
+
+    match freeze {
+        Some(freeze) => common::log!("drg.unfreeze: unfroze {}", Describe(&freeze)),
+        None => common::log!("usage: drg.unfreeze <n>|all. see drg.freeze"),
+    }
+}
+
+unsafe fn list() {
+    if FROZEN.is_empty() {
+        common::log!("drg.freeze: nothing is frozen");
+    }
+
+    for (i, freeze) in FROZEN.iter().enumerate() {
+        common::log!("drg.freeze: {}: {}", i, Describe(freeze));
+    }
+}
+
+unsafe fn freeze_address(
+    address: &str,
+    value_type: &str,
+    value: Option<&str>,
+) -> Result<Freeze, String> {
+    let address = usize::from_str_radix(&address[2..], 16)
+        .map_err(|_| format!("{} isn't an address", address))?;
+
+    let value_type = ValueType::parse(value_type).ok_or_else(|| USAGE.to_owned())?;
+
+    let value = match value {
+        Some(value) => scan::Value::parse(value_type, value)
+            .ok_or_else(|| format!("{} isn't a valid value", value))?,
+        None => {
+            scan::read(address, value_type).ok_or_else(|| format!("can't read {:#x}", address))?
+        }
+    };
+
+    Ok(Freeze::Address { address, value })
+}
+
+unsafe fn freeze_property(path: &str, value: Option<&str>) -> Result<Freeze, String> {
+    let (object_path, name) = path.rsplit_once('.').ok_or_else(|| USAGE.to_owned())?;
+
+    let object = (*GUObjectArray).find_by_path(object_path);
+
+    if object.is_null() {
+        return Err(format!("no object called {}", object_path));
+    }
+
+    let property = (*(*object).class()).find_property(name);
+
+    if property.is_null() {
+        return Err(format!("{} has no property called {}", object_path, name));
+    }
+
+    // The current value tells us what type of value to parse.
+    let current = inspect::read_primitive(property, object.cast())
+        .ok_or_else(|| format!("{} isn't a bool, number or object", name))?;
+
+    let value = match value {
+        Some(text) => parse_primitive(current, text)
+            .ok_or_else(|| format!("{} isn't a valid value for {}", text, name))?,
+        None => current,
+    };
+
+    Ok(Freeze::Property {
+        object: ObjectHandle::new(object),
+        property,
+        value,
+    })
+}
+
+unsafe fn parse_primitive(like: Primitive, text: &str) -> Option<Primitive> {
+    Some(match like {
+        Primitive::Bool(_) => Primitive::Bool(text.parse().ok()?),
+        Primitive::Integer(_) => Primitive::Integer(text.parse().ok()?),
+        Primitive::Float(_) => Primitive::Float(text.parse().ok()?),
+        Primitive::Object(_) if text == "null" => Primitive::Object(ptr::null_mut()),
+        Primitive::Object(_) => {
+            let object = (*GUObjectArray).find_by_path(text);
+            (!object.is_null()).then_some(Primitive::Object(object))?
+        }
+    })
+}
+
+fn same_target(a: &Freeze, b: &Freeze) -> bool {
+    match (a, b) {
+        (
+            Freeze::Property {
+                object: a_object,
+                property: a_property,
+                ..
+            },
+            Freeze::Property {
+                object: b_object,
+                property: b_property,
+                ..
+            },
+        ) => a_object == b_object && a_property == b_property,
+        (Freeze::Address { address: a, .. }, Freeze::Address { address: b, .. }) => a == b,
+        _ => false,
+    }
+}
+
+unsafe fn apply(_: f32) {
+    let mut i = 0;
+
+    FROZEN.retain(|&mut freeze| {
+        let keep = match freeze {
+            Freeze::Property {
+                object,
+                property,
+                value,
+            } => object.resolve().is_some_and(|object| {
+                inspect::write_primitive(property, object.cast(), value).is_ok()
+            }),
+
+            // The address may be freed and reused, so failing to write isn't
+            // reason enough to stop.
+            Freeze::Address { address, value } => {
+                scan::write(address, value);
+                true
+            }
+        };
+
+        if !keep {
+            common::log!("drg.freeze: unfroze {}, its object is gone", i);
+        }
+
+        i += 1;
+        keep
+    });
+
+    if FROZEN.is_empty() {
+        crate::tick::unregister(apply);
+    }
+}
+
+struct Describe<'a>(&'a Freeze);
+
+impl Display for Describe<'_> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        unsafe {
+            match *self.0 {
+                Freeze::Property {
+                    object,
+                    property,
+                    value,
+                } => {
+                    match object.resolve() {
+                        Some(object) => write!(f, "{}", *object)?,
+                        None => write!(f, "(destroyed)")?,
+                    }
+
+                    write!(f, ".{} = ", (*property).name())?;
+
+                    match value {
+                        Primitive::Bool(b) => write!(f, "{}", b),
+                        Primitive::Integer(n) => write!(f, "{}", n),
+                        Primitive::Float(n) => write!(f, "{}", n),
+                        Primitive::Object(o) if o.is_null() => write!(f, "null"),
+                        Primitive::Object(o) => write!(f, "{}", *o),
+                    }
+                }
+                Freeze::Address { address, value } => write!(f, "{:#x} = {}", address, value),
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+// Keeps objects we create from being garbage collected, and lets them go when
+// we unload. Use `keep` rather than UObject::add_to_root directly, so that
+// nothing we made outlives us in the root set.
+
+use common::list::{self, List};
+use common::{ObjectHandle, UObject};
+
+static mut KEPT: List<ObjectHandle<UObject>, 256> = List::new();
+
+pub unsafe fn keep(object: *mut UObject) -> Result<(), list::Error> {
+    if object.is_null() || (*object).is_rooted() {
+        return Ok(());
+    }
+
+    KEPT.push(ObjectHandle::new(object))?;
+    (*object).add_to_root();
+    Ok(())
+}
+
+pub unsafe fn release_all() {
+    for kept in KEPT.iter() {
+        if let Some(object) = kept.resolve() {
+            (*object).remove_from_root();
+        }
+    }
+
+    KEPT.clear();
+}
@@ -0,0 +1,158 @@
+// Counts how often each UFunction is called through ProcessEvent over a
+// mission. Rarely called functions make good hooks, and hot ones are where not
+// to do heavy work. "drg.heatmap on" starts counting and writes a report at
+// the end of each mission to the "heatmaps" folder next to the game's
+// executable, as both CSV and JSON. "drg.heatmap save" writes one right away.
+
+use crate::event::{self, Event};
+use crate::process_event;
+use common::list;
+use common::{JsonString, UFunction, UObject};
+use core::ffi::c_void;
+use core::fmt::Write as _;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use windows::Win32::System::Threading::GetCurrentThreadId;
+
+const HEATMAPS_FOLDER: &str = "heatmaps";
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    List(#[from] list::Error),
+    Io(#[from] io::Error),
+    NoFolder,
+}
+
+struct Count {
+    // The mission's levels, and their functions, may be unloaded before the
+    // report is written.
+    name: String,
+    calls: u64,
+}
+
+static mut ENABLED: bool = false;
+
+// Only calls on the game thread are counted, so that COUNTS isn't shared.
+static mut GAME_THREAD: u32 = 0;
+
+static mut COUNTS: BTreeMap<*mut UFunction, Count> = BTreeMap::new();
+
+// When the current counts started.
+static mut STARTED: Option<Instant> = None;
+
+pub unsafe fn start() -> Result<(), Error> {
+    if ENABLED {
+        return Ok(());
+    }
+
+    process_event::register(on_process_event)?;
+
+    if let Err(e) = event::subscribe(on_event) {
+        process_event::unregister(on_process_event);
+        return Err(e.into());
+    }
+
+    GAME_THREAD = GetCurrentThreadId();
+    reset();
+    ENABLED = true;
+    Ok(())
+}
+
+pub unsafe fn stop() {
+    if !ENABLED {
+        return;
+    }
+
+    ENABLED = false;
+    process_event::unregister(on_process_event);
+    event::unsubscribe(on_event);
+}
+
+// Writes the counts so far. Returns the CSV report's path. The JSON report is
+// next to it.
+pub unsafe fn save() -> Result<PathBuf, Error> {
+    let folder = crate::data_dir(HEATMAPS_FOLDER).ok_or(Error::NoFolder)?;
+
+    fs::create_dir_all(&folder)?;
+
+    let seconds = STARTED.map_or(0.0, |started| started.elapsed().as_secs_f64());
+
+    // Most called first.
+    let mut counts: Vec<&Count> = COUNTS.values().collect();
+    counts.sort_by(|a, b| b.calls.cmp(&a.calls));
+
+    let mut csv = String::from("function,calls,calls_per_second\n");
+    let mut json = format!("{{\"seconds\":{},\"functions\":[", seconds);
+
+    for (i, count) in counts.iter().enumerate() {
+        let per_second = if seconds > 0.0 {
+            count.calls as f64 / seconds
+        } else {
+            0.0
+        };
+
+        let _ = writeln!(
+            csv,
+            "\"{}\",{},{:.3}",
+            count.name.replace('"', "\"\""),
+            count.calls,
+            per_second
+        );
+
+        if i > 0 {
+            json.push(',');
+        }
+
+        let _ = write!(
+            json,
+            "{{\"name\":\"{}\",\"calls\":{},\"calls_per_second\":{}}}",
+            JsonString(&count.name),
+            count.calls,
+            per_second
+        );
+    }
+
+    json.push_str("]}");
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+
+    let path = folder.join(format!("heatmap-{}.csv", stamp));
+    fs::write(&path, csv)?;
+    fs::write(path.with_extension("json"), json)?;
+    Ok(path)
+}
+
+unsafe fn reset() {
+    COUNTS.clear();
+    STARTED = Some(Instant::now());
+}
+
+unsafe fn on_process_event(_: *mut UObject, function: *mut UFunction, _: *mut c_void) {
+    if !ENABLED || GetCurrentThreadId() != GAME_THREAD {
+        return;
+    }
+
+    COUNTS
+        .entry(function)
+        .or_insert_with(|| Count {
+            name: format!("{}", *function),
+            calls: 0,
+        })
+        .calls += 1;
+}
+
+unsafe fn on_event(event: &Event) {
+    match *event {
+        Event::MissionStart { .. } => reset(),
+        Event::MissionEnd { .. } => match save() {
+            Ok(path) => common::log!("heatmap: wrote {}", path.display()),
+            Err(e) => common::log!("heatmap: failed to write a report: {:?}", e),
+        },
+        Event::PlayerDowned { .. } | Event::WorldChanged { .. } => {}
+    }
+}
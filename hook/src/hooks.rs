@@ -1,4 +1,5 @@
-use common::{win, FNativeFuncPtr, UClass, UFunction, UObject};
+use common::list::{self, List};
+use common::{win, FNativeFuncPtr, UFunction, UObject};
 use core::ffi::c_void;
 use core::mem::MaybeUninit;
 use core::ptr;
@@ -6,25 +7,39 @@ use core::ptr;
 mod detour;
 use detour::Detour;
 
-mod patch;
-use patch::Patch;
+mod disasm;
+
+mod hardware;
+use hardware::HardwareBreakpointHook;
+
+// Not used by any hook yet.
+#[allow(dead_code)]
+mod iat;
+
+use win::{BytePatch, Patch};
 
 mod user;
+pub use user::run_command;
 use user::OneTimeModifications;
 
+mod vtable;
+use vtable::VTableHook;
+
+const MAX_UE_HOOKS: usize = 64;
+
+// UGameEngine::Tick(float DeltaSeconds, bool bIdleMode). The offset table's
+// "EngineTickVTableIndex" overrides it.
+const ENGINE_TICK_VTABLE_INDEX: usize = 78;
+
+static mut ENGINE_TICK: *const c_void = ptr::null();
+
+// Whether ReplicateActor is hooked with a breakpoint, so my_replicate_actor
+// has to call the original through hardware::call_original.
+static mut REPLICATE_ACTOR_BREAKPOINT: bool = false;
+
 static mut ON_ITEM_AMOUNT_CHANGED: MaybeUninit<FNativeFuncPtr> = MaybeUninit::uninit();
 static mut GET_ITEM_NAME: MaybeUninit<FNativeFuncPtr> = MaybeUninit::uninit();
 // static mut ON_FLARE: MaybeUninit<FNativeFuncPtr> = MaybeUninit::uninit();
-static mut ON_KEYPRESS_INSERT: MaybeUninit<FNativeFuncPtr> = MaybeUninit::uninit();
-static mut ON_KEYPRESS_DELETE: MaybeUninit<FNativeFuncPtr> = MaybeUninit::uninit();
-
-static mut AMMO_DRIVEN_WEAPON: *const UClass = ptr::null();
-static mut THROWN_GRENADE_ITEM: *const UClass = ptr::null();
-static mut DOUBLE_DRILL_ITEM: *const UClass = ptr::null();
-static mut HITSCAN_BASE_COMPONENT: *const UClass = ptr::null();
-static mut ZIP_LINE_ITEM: *const UClass = ptr::null();
-static mut GRAPPLING_HOOK_GUN: *const UClass = ptr::null();
-static mut OUTLINE_COMPONENT: *const UClass = ptr::null();
 
 static mut SERVER_REGISTER_HIT: *mut UFunction = ptr::null_mut();
 static mut SERVER_REGISTER_HIT_MULTI: *mut UFunction = ptr::null_mut();
@@ -40,54 +55,177 @@ static mut SERVER_SET_CONTROLLER_READY: *mut UFunction = ptr::null_mut();
 #[derive(macros::NoPanicErrorDebug)]
 pub enum Error {
     Detour(#[from] detour::Error),
+    Hardware(#[from] hardware::Error),
     FindStatic(&'static str),
+    List(#[from] list::Error),
+    Live(#[from] common::live::Error),
+    // The slot doesn't hold UGameEngine::Tick.
+    EngineTickVTableIndex(usize),
+}
+
+// UEngine::Tick is pure virtual, so UGameEngine's slot for it holds its own
+// code, where UEngine's holds _purecall.
+unsafe fn check_engine_tick_vtable_index(module: &win::Module, index: usize) -> Result<(), Error> {
+    let slot = *(*crate::GEngine.cast::<UObject>()).vtable.add(index) as usize;
+    let text = module.start()..module.start() + module.size();
+
+    let base_slot = module
+        .find_vtable("UEngine")
+        .ok()
+        .map(|vtable| *vtable.add(index) as usize);
+
+    if !text.contains(&slot) || base_slot == Some(slot) {
+        return Err(Error::EngineTickVTableIndex(index));
+    }
+
+    Ok(())
 }
 
 pub struct Hooks {
     _one_time_modifications: OneTimeModifications,
 
-    _process_remote_function_for_channel: Detour<7>,
-    // _function_invoke: Detour<5>,
-    _add_cheats: Detour<5>,
-    // _post_actor_construction: Detour<6>,
-    // _get_preferred_unique_net_id: Detour<5>,
+    _engine_tick: VTableHook,
+    _process_remote_function_for_channel: Detour,
+    _function_invoke: Detour,
+    _add_cheats: Detour,
+    _console_command: Detour,
+    _process_event: Detour,
+    _replicate_actor: Option<Detour>,
+    _replicate_actor_breakpoint: Option<HardwareBreakpointHook>,
+    // _post_actor_construction: Detour,
+    // _get_preferred_unique_net_id: Detour,
     _on_item_amount_changed: UFunctionHook,
     _get_item_name: UFunctionHook,
     // _on_flare: UFunctionHook,
-    _on_keypress_insert: UFunctionHook,
-    _on_keypress_delete: UFunctionHook,
+    _ue_hooks: List<UFunctionHook, MAX_UE_HOOKS>,
+
+    // After the hooks that call into events, scripts and plugins, so that
+    // they're dropped once those hooks are gone.
+    _live: common::live::Listeners,
+    _events: crate::event::Events,
+    _draw: crate::draw::Draw,
+    _scripts: crate::script::Scripts,
+    _plugins: crate::plugin::Plugins,
+
+    // None if the port is taken, like by a second copy of the game.
+    _server: Option<crate::server::Server>,
 }
 
 impl Hooks {
     pub unsafe fn new(module: &win::Module) -> Result<Self, Error> {
         Self::find_statics()?;
 
+        // Before the hooks that call into plugins, so that every plugin has
+        // initialized by its first callback.
+        let engine_tick_vtable_index =
+            common::offsets::index("EngineTickVTableIndex").unwrap_or(ENGINE_TICK_VTABLE_INDEX);
+        check_engine_tick_vtable_index(module, engine_tick_vtable_index)?;
+
+        let plugins = crate::plugin::Plugins::load();
+        let (replicate_actor, replicate_actor_breakpoint) = Self::hook_replicate_actor(module)?;
+
         Ok(Self {
             _one_time_modifications: OneTimeModifications::new(),
 
-            _process_remote_function_for_channel: Detour::new(module, &mut crate::PROCESS_REMOTE_FUNCTION_FOR_CHANNEL, user::my_process_remote_function_for_channel as *const c_void)?,
-            // _function_invoke: Detour::new(module, &mut crate::FUNCTION_INVOKE, user::my_function_invoke as *const c_void)?,
-            _add_cheats: Detour::new(module, &mut crate::ADD_CHEATS, user::my_add_cheats as *const c_void)?,
+            _engine_tick: VTableHook::new(
+                crate::GEngine.cast(),
+                engine_tick_vtable_index,
+                &mut ENGINE_TICK,
+                user::my_engine_tick as *const c_void,
+            ),
+            _process_remote_function_for_channel: Detour::new(
+                module,
+                &mut crate::PROCESS_REMOTE_FUNCTION_FOR_CHANNEL,
+                user::my_process_remote_function_for_channel as *const c_void,
+            )?,
+            _function_invoke: Detour::new(
+                module,
+                &mut crate::FUNCTION_INVOKE,
+                user::my_function_invoke as *const c_void,
+            )?,
+            _add_cheats: Detour::new(
+                module,
+                &mut crate::ADD_CHEATS,
+                user::my_add_cheats as *const c_void,
+            )?,
+            _console_command: Detour::new(
+                module,
+                &mut crate::CONSOLE_COMMAND,
+                user::my_console_command as *const c_void,
+            )?,
+            _process_event: Detour::new(
+                module,
+                &mut crate::PROCESS_EVENT,
+                user::my_process_event as *const c_void,
+            )?,
+            _replicate_actor: replicate_actor,
+            _replicate_actor_breakpoint: replicate_actor_breakpoint,
             // _post_actor_construction: Detour::new(module, &mut crate::POST_ACTOR_CONSTRUCTION, user::my_post_actor_construction as *const c_void)?,
             // _get_preferred_unique_net_id: Detour::new(module, &mut crate::GET_PREFERRED_UNIQUE_NET_ID, user::my_get_preferred_unique_net_id as *const c_void)?,
-
-            _on_item_amount_changed: UFunctionHook::new("Function /Script/FSD.AmmoCountWidget.OnItemAmountChanged", ON_ITEM_AMOUNT_CHANGED.as_mut_ptr(), user::my_on_item_amount_changed)?,
-            _get_item_name: UFunctionHook::new("Function /Script/FSD.Item.GetItemName", GET_ITEM_NAME.as_mut_ptr(), user::my_get_item_name)?,
+            _on_item_amount_changed: UFunctionHook::new(
+                "Function /Script/FSD.AmmoCountWidget.OnItemAmountChanged",
+                ON_ITEM_AMOUNT_CHANGED.as_mut_ptr(),
+                user::my_on_item_amount_changed,
+            )?,
+            _get_item_name: UFunctionHook::new(
+                "Function /Script/FSD.Item.GetItemName",
+                GET_ITEM_NAME.as_mut_ptr(),
+                user::my_get_item_name,
+            )?,
             // _on_flare: UFunctionHook::new("Function /Game/UI/MainOnscreenHUD/HUD_Flares.HUD_Flares_C.OnFlareCountChanged", ON_FLARE.as_mut_ptr(), user::my_on_flare)?,
-            _on_keypress_insert: UFunctionHook::new("Function /Game/Character/BP_PlayerCharacter.BP_PlayerCharacter_C.InpActEvt_Insert_K2Node_InputKeyEvent", ON_KEYPRESS_INSERT.as_mut_ptr(), user::my_on_keypress_insert)?,
-            _on_keypress_delete: UFunctionHook::new("Function /Game/Character/BP_PlayerCharacter.BP_PlayerCharacter_C.InpActEvt_Delete_K2Node_InputKeyEvent", ON_KEYPRESS_DELETE.as_mut_ptr(), user::my_on_keypress_delete)?,
+            _ue_hooks: Self::install_ue_hooks()?,
+            _live: common::live::Listeners::new()?,
+            _events: crate::event::Events::new()?,
+            _draw: crate::draw::Draw::new()?,
+            _scripts: crate::script::Scripts,
+            _plugins: plugins,
+            _server: crate::server::Server::start()
+                .map_err(|e| common::log!("failed to start server: {:?}", e))
+                .ok(),
         })
     }
 
-    unsafe fn find_statics() -> Result<(), Error> {
-        AMMO_DRIVEN_WEAPON = find("Class /Script/FSD.AmmoDrivenWeapon")?.cast();
-        THROWN_GRENADE_ITEM = find("Class /Script/FSD.ThrownGrenadeItem")?.cast();
-        DOUBLE_DRILL_ITEM = find("Class /Script/FSD.DoubleDrillItem")?.cast();
-        HITSCAN_BASE_COMPONENT = find("Class /Script/FSD.HitscanBaseComponent")?.cast();
-        ZIP_LINE_ITEM = find("Class /Script/FSD.ZipLineItem")?.cast();
-        GRAPPLING_HOOK_GUN = find("Class /Script/FSD.GrapplingHookGun")?.cast();
-        OUTLINE_COMPONENT = find("Class /Script/FSD.OutlineComponent")?.cast();
+    // Falls back to a breakpoint if ReplicateActor's prologue can't be
+    // relocated for a detour, since a breakpoint doesn't move any code.
+    unsafe fn hook_replicate_actor(
+        module: &win::Module,
+    ) -> Result<(Option<Detour>, Option<HardwareBreakpointHook>), Error> {
+        let target = crate::REPLICATE_ACTOR;
+
+        if target.is_null() {
+            return Ok((None, None));
+        }
+
+        let hook = user::my_replicate_actor as *const c_void;
+
+        match Detour::new(module, &mut crate::REPLICATE_ACTOR, hook) {
+            Ok(detour) => Ok((Some(detour), None)),
+            Err(e) => {
+                common::log!("unable to detour ReplicateActor: {:?}", e);
+                crate::REPLICATE_ACTOR = target;
+
+                let breakpoint = HardwareBreakpointHook::new(target, hook)?;
+                REPLICATE_ACTOR_BREAKPOINT = true;
+                Ok((None, Some(breakpoint)))
+            }
+        }
+    }
+
+    unsafe fn install_ue_hooks() -> Result<List<UFunctionHook, MAX_UE_HOOKS>, Error> {
+        let mut hooks = List::new();
+
+        for hook in crate::ue_hook::iter() {
+            hooks.push(UFunctionHook::new(
+                hook.path,
+                hook.original.as_mut_ptr(),
+                hook.hook,
+            )?)?;
+        }
 
+        Ok(hooks)
+    }
+
+    unsafe fn find_statics() -> Result<(), Error> {
         SERVER_REGISTER_HIT =
             find("Function /Script/FSD.HitscanComponent.Server_RegisterHit")?.cast();
         SERVER_REGISTER_HIT_MULTI =
@@ -116,6 +254,9 @@ impl Hooks {
 impl Drop for Hooks {
     fn drop(&mut self) {
         unsafe {
+            crate::delegate::unbind_all();
+            crate::gc::release_all();
+
             for &function in user::SEEN_FUNCTIONS.iter() {
                 (*function).seen_count = 0;
             }
@@ -1,5 +1,7 @@
-use crate::hooks::Patch;
+use crate::hooks::disasm::{self, MAX_INSTRUCTION_LEN};
+use crate::hooks::{BytePatch, Patch};
 use common::win;
+use common::win::threads;
 use core::ffi::c_void;
 use core::mem::ManuallyDrop;
 use core::slice;
@@ -7,47 +9,62 @@ use core::slice;
 #[derive(macros::NoPanicErrorDebug)]
 pub enum Error {
     NoCodeCave,
-    JmpLenIsSmallerThanFiveBytes,
     CaveIsTooSmall(usize, usize),
+    UnknownInstruction(usize),
+    ShortBranch(usize),
+    RelativeOperandOutOfRange(usize),
+    Threads(#[from] threads::Error),
 }
 
 pub const JMP_TO_HOOK_LEN: usize = 12;
 pub const JMP_TO_ORIG_LEN: usize = 5;
 
-pub struct Detour<const JMP_LEN: usize> {
-    jmp: ManuallyDrop<Patch<[u8; JMP_LEN]>>,
-    code_cave: ManuallyDrop<CodeCave<JMP_LEN>>,
+// We overwrite the start of the original function with a 5-byte jmp. The last
+// instruction we steal may start on the jmp's last byte, so we can steal up to
+// four bytes plus one whole instruction.
+const MAX_STOLEN_LEN: usize = JMP_TO_ORIG_LEN - 1 + MAX_INSTRUCTION_LEN;
+
+pub struct Detour {
+    jmp: ManuallyDrop<BytePatch<MAX_STOLEN_LEN>>,
+    code_cave: ManuallyDrop<CodeCave>,
 }
 
-impl<const JMP_LEN: usize> Detour<JMP_LEN> {
+impl Detour {
     pub unsafe fn new(
         module: &win::Module,
         original: *mut *mut c_void,
         hook: *const c_void,
-    ) -> Result<Detour<JMP_LEN>, Error> {
-        if JMP_LEN < 5 {
-            return Err(Error::JmpLenIsSmallerThanFiveBytes);
-        }
+    ) -> Result<Detour, Error> {
+        let stolen_len = Self::stolen_len((*original).cast())?;
 
         let code_cave = module
             .find_code_cave(
                 *original.cast(),
-                JMP_LEN + JMP_TO_HOOK_LEN + JMP_TO_ORIG_LEN,
+                JMP_TO_HOOK_LEN + stolen_len + JMP_TO_ORIG_LEN,
             )
             .ok_or(Error::NoCodeCave)?;
 
-        let code_cave_patch = ManuallyDrop::new(CodeCave::new(code_cave, *original.cast(), hook)?);
+        let code_cave_patch = ManuallyDrop::new(CodeCave::new(
+            code_cave,
+            *original.cast(),
+            stolen_len,
+            hook,
+        )?);
 
         // There's something to be desired about this variable name...
         let original_original = *original;
 
         // TODO(unhook): Restore to original address.
-        *original = code_cave.as_mut_ptr().add(12).cast();
+        *original = code_cave.as_mut_ptr().add(JMP_TO_HOOK_LEN).cast();
 
-        let jmp = ManuallyDrop::new(Patch::new(
+        let jmp_patch = Self::create_jmp_patch(code_cave, original_original);
+
+        // Other threads may be running the original function right now. The
+        // cave isn't reachable yet, so only this patch needs them suspended.
+        let jmp = ManuallyDrop::new(BytePatch::new_suspended(
             original_original.cast(),
-            Self::create_jmp_patch(code_cave, original_original),
-        ));
+            &jmp_patch[..stolen_len],
+        )?);
 
         Ok(Detour {
             jmp,
@@ -55,8 +72,24 @@ impl<const JMP_LEN: usize> Detour<JMP_LEN> {
         })
     }
 
-    unsafe fn create_jmp_patch(code_cave: &[u8], original: *const c_void) -> [u8; JMP_LEN] {
-        let mut patch = [0x90; JMP_LEN];
+    // The number of bytes covered by the whole instructions that the jmp
+    // overwrites.
+    unsafe fn stolen_len(original: *const u8) -> Result<usize, Error> {
+        let mut len = 0;
+
+        while len < JMP_TO_ORIG_LEN {
+            let instruction =
+                disasm::decode(original.add(len)).ok_or(Error::UnknownInstruction(len))?;
+            len += instruction.len;
+        }
+
+        Ok(len)
+    }
+
+    unsafe fn create_jmp_patch(code_cave: &[u8], original: *const c_void) -> [u8; MAX_STOLEN_LEN] {
+        // nop the rest of the stolen bytes. Otherwise we would leave half an
+        // instruction behind the jmp.
+        let mut patch = [0x90; MAX_STOLEN_LEN];
 
         // jmp code_cave
         patch[0] = 0xE9;
@@ -72,7 +105,7 @@ impl<const JMP_LEN: usize> Detour<JMP_LEN> {
     }
 }
 
-impl<const JMP_LEN: usize> Drop for Detour<JMP_LEN> {
+impl Drop for Detour {
     fn drop(&mut self) {
         unsafe {
             ManuallyDrop::drop(&mut self.jmp);
@@ -83,52 +116,90 @@ impl<const JMP_LEN: usize> Drop for Detour<JMP_LEN> {
     }
 }
 
-pub struct CodeCave<const JMP_LEN: usize> {
+pub struct CodeCave {
     _jmp_to_hook: Patch<[u8; JMP_TO_HOOK_LEN]>,
-    _original_bytes: Patch<[u8; JMP_LEN]>,
+    _original_bytes: BytePatch<MAX_STOLEN_LEN>,
     _jmp_to_original: Patch<[u8; JMP_TO_ORIG_LEN]>,
 }
 
-impl<const JMP_LEN: usize> CodeCave<JMP_LEN> {
+impl CodeCave {
     pub unsafe fn new(
         code_cave: &mut [u8],
         original: *const u8,
+        stolen_len: usize,
         hook: *const c_void,
-    ) -> Result<CodeCave<JMP_LEN>, Error> {
+    ) -> Result<CodeCave, Error> {
         let mut jmp_to_hook = [
             0x48, 0xB8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xE0,
         ];
         jmp_to_hook[2..10].copy_from_slice(&(hook as usize).to_le_bytes());
 
-        let mut original_bytes = [0; JMP_LEN];
-        original_bytes.copy_from_slice(slice::from_raw_parts(original, JMP_LEN));
-
         let mut jmp_to_original = [0xE9, 0x00, 0x00, 0x00, 0x00];
 
-        let total_patch_len = jmp_to_hook.len() + original_bytes.len() + jmp_to_original.len();
+        let total_patch_len = jmp_to_hook.len() + stolen_len + jmp_to_original.len();
 
         if code_cave.len() < total_patch_len {
             return Err(Error::CaveIsTooSmall(code_cave.len(), total_patch_len));
         }
 
+        let code_cave = code_cave.as_mut_ptr();
+        let stolen_destination = code_cave.add(jmp_to_hook.len());
+
+        let original_bytes = Self::relocate(original, stolen_destination, stolen_len)?;
+
         jmp_to_original[1..].copy_from_slice({
-            let destination = original as usize + JMP_LEN;
-            let source = code_cave.as_ptr() as usize + total_patch_len;
+            let destination = original as usize + stolen_len;
+            let source = code_cave as usize + total_patch_len;
             let relative_distance = destination.wrapping_sub(source) as u32;
             &relative_distance.to_le_bytes()
         });
 
-        let code_cave = code_cave.as_mut_ptr();
-
         Ok(CodeCave {
             _jmp_to_hook: Patch::new(code_cave.cast(), jmp_to_hook),
-            _original_bytes: Patch::new(code_cave.add(jmp_to_hook.len()).cast(), original_bytes),
+            _original_bytes: BytePatch::new(stolen_destination, &original_bytes[..stolen_len]),
             _jmp_to_original: Patch::new(
-                code_cave
-                    .add(jmp_to_hook.len() + original_bytes.len())
-                    .cast(),
+                code_cave.add(jmp_to_hook.len() + stolen_len).cast(),
                 jmp_to_original,
             ),
         })
     }
+
+    // Copies the stolen instructions, fixing up operands that are relative to
+    // the instruction pointer so that they still point to the same place when
+    // run from `destination`.
+    unsafe fn relocate(
+        source: *const u8,
+        destination: *const u8,
+        len: usize,
+    ) -> Result<[u8; MAX_STOLEN_LEN], Error> {
+        let mut bytes = [0; MAX_STOLEN_LEN];
+        bytes[..len].copy_from_slice(slice::from_raw_parts(source, len));
+
+        let delta = source as isize - destination as isize;
+        let mut offset = 0;
+
+        while offset < len {
+            let instruction =
+                disasm::decode(source.add(offset)).ok_or(Error::UnknownInstruction(offset))?;
+
+            if let Some(relative) = instruction.relative {
+                if relative.size != 4 {
+                    // A rel8 branch can't reach its target from the cave.
+                    return Err(Error::ShortBranch(offset));
+                }
+
+                let operand = &mut bytes[offset + relative.offset..][..4];
+                let old = i32::from_le_bytes([operand[0], operand[1], operand[2], operand[3]]);
+
+                let new = i32::try_from(old as isize + delta)
+                    .map_err(|_| Error::RelativeOperandOutOfRange(offset))?;
+
+                operand.copy_from_slice(&new.to_le_bytes());
+            }
+
+            offset += instruction.len;
+        }
+
+        Ok(bytes)
+    }
 }
@@ -0,0 +1,237 @@
+// A minimal x86-64 length disassembler. It knows just enough about the
+// instruction encoding to find where an instruction ends and whether it has an
+// operand relative to the instruction pointer. That's all we need to move a
+// function's first few instructions into a code cave.
+// https://wiki.osdev.org/X86-64_Instruction_Encoding
+
+pub const MAX_INSTRUCTION_LEN: usize = 15;
+
+#[derive(Copy, Clone)]
+pub struct Instruction {
+    pub len: usize,
+
+    // An operand that's relative to the end of this instruction, such as a
+    // [rip + disp32] memory operand or a branch target.
+    pub relative: Option<Relative>,
+}
+
+#[derive(Copy, Clone)]
+pub struct Relative {
+    // Where the operand starts within the instruction.
+    pub offset: usize,
+
+    // 1 for rel8 branches, 4 for everything else.
+    pub size: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Immediate {
+    None,
+    Byte,
+    Word,
+
+    // Four bytes, or two with an operand-size prefix.
+    Dword,
+
+    // Eight bytes with REX.W, otherwise the same as Dword.
+    DwordOrQword,
+}
+
+// Returns None for instructions we don't know how to decode.
+pub unsafe fn decode(code: *const u8) -> Option<Instruction> {
+    let mut cursor = 0;
+    let mut operand_size_prefix = false;
+    let mut rex_w = false;
+
+    // Legacy prefixes.
+    loop {
+        match *code.add(cursor) {
+            0x66 => operand_size_prefix = true,
+            0x67 | 0xF0 | 0xF2 | 0xF3 | 0x2E | 0x36 | 0x3E | 0x26 | 0x64 | 0x65 => {}
+            _ => break,
+        }
+
+        cursor += 1;
+    }
+
+    // REX prefix.
+    if let 0x40..=0x4F = *code.add(cursor) {
+        rex_w = *code.add(cursor) & 0x8 != 0;
+        cursor += 1;
+    }
+
+    let opcode = *code.add(cursor);
+    cursor += 1;
+
+    let mut branch = None;
+
+    let (has_modrm, immediate) = if opcode == 0x0F {
+        let opcode = *code.add(cursor);
+        cursor += 1;
+
+        match opcode {
+            // Jcc rel32
+            0x80..=0x8F => {
+                branch = Some(4);
+                (false, Immediate::None)
+            }
+
+            // syscall, ud2, cpuid
+            0x05 | 0x0B | 0xA2 => (false, Immediate::None),
+
+            // Three-byte opcodes.
+            0x38 => {
+                cursor += 1;
+                (true, Immediate::None)
+            }
+
+            0x3A => {
+                cursor += 1;
+                (true, Immediate::Byte)
+            }
+
+            0x70..=0x73 | 0xA4 | 0xAC | 0xBA | 0xC2 | 0xC4..=0xC6 => (true, Immediate::Byte),
+
+            _ => (true, Immediate::None),
+        }
+    } else {
+        match opcode {
+            // ALU ops on r/m.
+            0x00..=0x03 | 0x08..=0x0B | 0x10..=0x13 | 0x18..=0x1B => (true, Immediate::None),
+            0x20..=0x23 | 0x28..=0x2B | 0x30..=0x33 | 0x38..=0x3B => (true, Immediate::None),
+
+            // ALU ops on al/eax with an immediate.
+            0x04 | 0x0C | 0x14 | 0x1C | 0x24 | 0x2C | 0x34 | 0x3C => (false, Immediate::Byte),
+            0x05 | 0x0D | 0x15 | 0x1D | 0x25 | 0x2D | 0x35 | 0x3D => (false, Immediate::Dword),
+
+            // push/pop r64
+            0x50..=0x5F => (false, Immediate::None),
+
+            0x63 => (true, Immediate::None),
+            0x68 => (false, Immediate::Dword),
+            0x69 => (true, Immediate::Dword),
+            0x6A => (false, Immediate::Byte),
+            0x6B => (true, Immediate::Byte),
+
+            // Jcc rel8
+            0x70..=0x7F => {
+                branch = Some(1);
+                (false, Immediate::None)
+            }
+
+            0x80 | 0x82 | 0x83 => (true, Immediate::Byte),
+            0x81 => (true, Immediate::Dword),
+
+            // test, xchg, mov, lea, pop r/m
+            0x84..=0x8F => (true, Immediate::None),
+
+            // nop, xchg, cbw, cwd
+            0x90..=0x99 => (false, Immediate::None),
+
+            0xA8 => (false, Immediate::Byte),
+            0xA9 => (false, Immediate::Dword),
+            0xB0..=0xB7 => (false, Immediate::Byte),
+            0xB8..=0xBF => (false, Immediate::DwordOrQword),
+            0xC0 | 0xC1 | 0xC6 => (true, Immediate::Byte),
+            0xC2 => (false, Immediate::Word),
+            0xC3 | 0xC9 | 0xCC => (false, Immediate::None),
+            0xC7 => (true, Immediate::Dword),
+            0xCD => (false, Immediate::Byte),
+            0xD0..=0xD3 => (true, Immediate::None),
+
+            // call/jmp rel32
+            0xE8 | 0xE9 => {
+                branch = Some(4);
+                (false, Immediate::None)
+            }
+
+            // jmp rel8
+            0xEB => {
+                branch = Some(1);
+                (false, Immediate::None)
+            }
+
+            // test r/m, imm has an immediate. The rest of the group doesn't.
+            0xF6 | 0xF7 => {
+                let reg = (*code.add(cursor) >> 3) & 0b111;
+
+                let immediate = match (reg, opcode) {
+                    (0 | 1, 0xF6) => Immediate::Byte,
+                    (0 | 1, _) => Immediate::Dword,
+                    _ => Immediate::None,
+                };
+
+                (true, immediate)
+            }
+
+            0xFE | 0xFF => (true, Immediate::None),
+
+            _ => return None,
+        }
+    };
+
+    let mut relative = None;
+
+    if has_modrm {
+        let modrm = *code.add(cursor);
+        cursor += 1;
+
+        let mode = modrm >> 6;
+        let rm = modrm & 0b111;
+
+        if mode != 0b11 && rm == 0b100 {
+            let sib = *code.add(cursor);
+            cursor += 1;
+
+            if mode == 0b00 && sib & 0b111 == 0b101 {
+                // [index * scale + disp32]
+                cursor += 4;
+            }
+        }
+
+        match mode {
+            0b00 if rm == 0b101 => {
+                // [rip + disp32]
+                relative = Some(Relative {
+                    offset: cursor,
+                    size: 4,
+                });
+
+                cursor += 4;
+            }
+
+            0b01 => cursor += 1,
+            0b10 => cursor += 4,
+            _ => {}
+        }
+    }
+
+    if let Some(size) = branch {
+        relative = Some(Relative {
+            offset: cursor,
+            size,
+        });
+
+        cursor += size;
+    }
+
+    cursor += match immediate {
+        Immediate::None => 0,
+        Immediate::Byte => 1,
+        Immediate::Word => 2,
+        Immediate::Dword if operand_size_prefix => 2,
+        Immediate::Dword => 4,
+        Immediate::DwordOrQword if rex_w => 8,
+        Immediate::DwordOrQword if operand_size_prefix => 2,
+        Immediate::DwordOrQword => 4,
+    };
+
+    if cursor > MAX_INSTRUCTION_LEN {
+        return None;
+    }
+
+    Some(Instruction {
+        len: cursor,
+        relative,
+    })
+}
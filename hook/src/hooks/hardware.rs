@@ -0,0 +1,154 @@
+// Hooks functions with the CPU's debug registers instead of patching code. An
+// execute breakpoint on the function's first instruction raises a single-step
+// exception, and our vectored exception handler sends the thread to the hook.
+//
+// Limitations: there are only four debug registers, and threads created after
+// the hook is installed don't get the breakpoint.
+
+use common::win::threads::{self, SuspendedThreads};
+use core::cell::Cell;
+use core::ffi::c_void;
+use core::ptr;
+use windows::Win32::Foundation::EXCEPTION_SINGLE_STEP;
+use windows::Win32::System::Diagnostics::Debug::{
+    AddVectoredExceptionHandler, RemoveVectoredExceptionHandler, CONTEXT, EXCEPTION_POINTERS,
+};
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    NoFreeDebugRegister,
+    AddExceptionHandler,
+    Threads(#[from] threads::Error),
+}
+
+const NUM_DEBUG_REGISTERS: usize = 4;
+
+const EXCEPTION_CONTINUE_EXECUTION: i32 = -1;
+const EXCEPTION_CONTINUE_SEARCH: i32 = 0;
+
+// Set in EFLAGS to run one instruction without hitting its breakpoint.
+const RESUME_FLAG: u32 = 1 << 16;
+
+static mut TARGETS: [usize; NUM_DEBUG_REGISTERS] = [0; NUM_DEBUG_REGISTERS];
+static mut HOOKS: [usize; NUM_DEBUG_REGISTERS] = [0; NUM_DEBUG_REGISTERS];
+static mut EXCEPTION_HANDLER: *mut c_void = ptr::null_mut();
+
+thread_local! {
+    // Whether this thread's next breakpoint hit should run the original.
+    static BYPASS: Cell<bool> = Cell::new(false);
+}
+
+pub struct HardwareBreakpointHook {
+    slot: usize,
+}
+
+impl HardwareBreakpointHook {
+    pub unsafe fn new(target: *const c_void, hook: *const c_void) -> Result<Self, Error> {
+        let slot = TARGETS
+            .iter()
+            .position(|&target| target == 0)
+            .ok_or(Error::NoFreeDebugRegister)?;
+
+        if EXCEPTION_HANDLER.is_null() {
+            EXCEPTION_HANDLER = AddVectoredExceptionHandler(1, Some(on_exception));
+
+            if EXCEPTION_HANDLER.is_null() {
+                return Err(Error::AddExceptionHandler);
+            }
+        }
+
+        HOOKS[slot] = hook as usize;
+        TARGETS[slot] = target as usize;
+
+        let suspended = match SuspendedThreads::suspend_all() {
+            Ok(suspended) => suspended,
+            Err(e) => {
+                release(slot);
+                return Err(e.into());
+            }
+        };
+
+        suspended.update_debug_registers(|context| {
+            set_debug_register(context, slot, target as u64);
+
+            // Enable the breakpoint locally: break on execution of one byte.
+            context.Dr7 &= !(0b1111 << (16 + 4 * slot));
+            context.Dr7 |= 1 << (2 * slot);
+        });
+
+        Ok(Self { slot })
+    }
+}
+
+impl Drop for HardwareBreakpointHook {
+    fn drop(&mut self) {
+        unsafe {
+            // If the threads still have the breakpoint, its slot and the
+            // handler have to stay, or the next hit would crash the game.
+            let Ok(suspended) = SuspendedThreads::suspend_all() else {
+                return;
+            };
+
+            suspended.update_debug_registers(|context| {
+                set_debug_register(context, self.slot, 0);
+                context.Dr7 &= !(1 << (2 * self.slot));
+            });
+
+            release(self.slot);
+        }
+    }
+}
+
+// Frees the slot, and removes the exception handler along with the last one.
+unsafe fn release(slot: usize) {
+    TARGETS[slot] = 0;
+    HOOKS[slot] = 0;
+
+    if TARGETS.iter().all(|&target| target == 0) {
+        RemoveVectoredExceptionHandler(EXCEPTION_HANDLER);
+        EXCEPTION_HANDLER = ptr::null_mut();
+    }
+}
+
+// The hook must call the original through this. Otherwise the original would
+// hit the breakpoint and land back in the hook.
+pub unsafe fn call_original<R>(original: impl FnOnce() -> R) -> R {
+    BYPASS.with(|bypass| bypass.set(true));
+    original()
+}
+
+fn set_debug_register(context: &mut CONTEXT, slot: usize, value: u64) {
+    match slot {
+        0 => context.Dr0 = value,
+        1 => context.Dr1 = value,
+        2 => context.Dr2 = value,
+        _ => context.Dr3 = value,
+    }
+}
+
+unsafe extern "system" fn on_exception(info: *mut EXCEPTION_POINTERS) -> i32 {
+    let record = &*(*info).ExceptionRecord;
+    let context = &mut *(*info).ContextRecord;
+
+    if record.ExceptionCode != EXCEPTION_SINGLE_STEP {
+        return EXCEPTION_CONTINUE_SEARCH;
+    }
+
+    let address = record.ExceptionAddress as usize;
+
+    let slot = match TARGETS.iter().position(|&target| target == address) {
+        Some(slot) => slot,
+
+        // Someone else's breakpoint.
+        None => return EXCEPTION_CONTINUE_SEARCH,
+    };
+
+    if BYPASS.with(|bypass| bypass.replace(false)) {
+        // Step over the breakpoint into the original.
+        context.EFlags |= RESUME_FLAG;
+    } else {
+        context.Rip = HOOKS[slot] as u64;
+    }
+
+    EXCEPTION_CONTINUE_EXECUTION
+}
@@ -0,0 +1,34 @@
+use crate::hooks::Patch;
+use common::win;
+use core::ffi::c_void;
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    FindImport(&'static str, &'static str),
+}
+
+pub struct IatHook {
+    _slot: Patch<*const c_void>,
+}
+
+impl IatHook {
+    pub unsafe fn new(
+        module: &win::Module,
+        dll: &'static str,
+        function: &'static str,
+        where_to_place_original: *mut *const c_void,
+        hook: *const c_void,
+    ) -> Result<IatHook, Error> {
+        // Only calls made by `module` go through its import table. Other
+        // modules, and code that looked the function up itself, are unaffected.
+        let slot = module
+            .import_slot(dll, function)
+            .ok_or(Error::FindImport(dll, function))?;
+
+        *where_to_place_original = *slot;
+
+        Ok(IatHook {
+            _slot: Patch::new(slot, hook),
+        })
+    }
+}
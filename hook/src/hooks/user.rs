@@ -1,21 +1,64 @@
+use crate::console::Command;
+use crate::policy::{Feature, Scope};
+use crate::ue_hook::Call;
 use common::win::random;
-use common::{self, EClassCastFlags, FFrame, List, UFunction, UObject};
+use common::{self, ChunkedList, EClassCastFlags, FFrame, FString, JsonString, UFunction, UObject};
 use core::ffi::c_void;
 use core::mem;
 use sdk::Engine::{Actor, LocalPlayer};
 use sdk::FSD::{FSDCheatManager, FSDPlayerController, PlayerCharacter};
+use windows::Win32::UI::Input::KeyboardAndMouse::VK_F7;
 
+mod inspect;
 mod pawn;
 mod weapon;
 
 mod render;
 
-pub static mut SEEN_FUNCTIONS: List<*mut UFunction, 4096> = List::new();
+pub static mut SEEN_FUNCTIONS: ChunkedList<*mut UFunction, 4096> = ChunkedList::new();
+
+static GOD_MODE: Feature = Feature {
+    name: "god mode",
+    scope: Scope::SoloOnly,
+    disable: Some(disable_god_mode),
+};
 
 pub struct OneTimeModifications;
 
 impl OneTimeModifications {
     pub unsafe fn new() -> Self {
+        if let Err(e) = crate::hotkey::register(VK_F7, inspect::inspect_under_crosshair) {
+            common::log!("Unable to register the inspect hotkey: {:?}", e);
+        }
+
+        if let Err(e) = crate::mission::subscribe() {
+            common::log!("Unable to log missions: {:?}", e);
+        }
+
+        if let Err(e) = crate::policy::start() {
+            common::log!("Unable to watch for other players: {:?}", e);
+        }
+
+        if let Err(e) = crate::freeze::subscribe() {
+            common::log!("Unable to unfreeze addresses on level changes: {:?}", e);
+        }
+
+        if let Err(e) = crate::scanner::subscribe() {
+            common::log!("Unable to forget scans on level changes: {:?}", e);
+        }
+
+        common::functions::build();
+
+        if let Err(e) = crate::event::subscribe(rebuild_functions) {
+            common::log!("Unable to refresh functions on level changes: {:?}", e);
+        }
+
+        common::player::init(crate::GEngine.cast_mut().cast());
+
+        if let Err(e) = crate::event::subscribe(refresh_player) {
+            common::log!("Unable to refresh the player on level changes: {:?}", e);
+        }
+
         Self
     }
 }
@@ -23,11 +66,34 @@ impl OneTimeModifications {
 impl Drop for OneTimeModifications {
     fn drop(&mut self) {
         unsafe {
+            crate::layouts::unsubscribe();
+            crate::event::unsubscribe(refresh_player);
+            common::player::clear();
+            crate::event::unsubscribe(rebuild_functions);
+            common::functions::clear();
+            crate::scanner::unsubscribe();
+            crate::freeze::unsubscribe();
+            crate::policy::stop();
+            crate::mission::unsubscribe();
             render::restore_lighting();
         }
     }
 }
 
+// Blueprint functions are loaded and unloaded with their levels.
+unsafe fn rebuild_functions(event: &crate::event::Event) {
+    if let crate::event::Event::WorldChanged { .. } = *event {
+        common::functions::build();
+    }
+}
+
+// Each level has its own player controller.
+unsafe fn refresh_player(event: &crate::event::Event) {
+    if let crate::event::Event::WorldChanged { .. } = *event {
+        common::player::refresh();
+    }
+}
+
 #[allow(dead_code)]
 unsafe fn set_custom_name(controller: *mut FSDPlayerController) {
     const NAME: [u16; 5] = [0x6e, 0x6f, 0x6f, 0x62, 0];
@@ -66,7 +132,20 @@ pub unsafe extern "C" fn my_process_remote_function_for_channel(
         crate::PROCESS_REMOTE_FUNCTION_FOR_CHANNEL,
     );
 
-    if weapon::is_server_register_hit(function) {
+    crate::replication::rpc_sent(function);
+    crate::rpc_trace::sent(object, function, stack);
+
+    crate::server::publish(
+        "rpc",
+        format_args!(
+            "{{\"object\":\"{}\",\"function\":\"{}\"}}",
+            JsonString(&*object),
+            JsonString((*function).name())
+        ),
+    );
+
+    // Extra hits land in the host's game, so only with nobody else to see them.
+    if weapon::is_server_register_hit(function) && crate::policy::permits(Scope::SoloOnly) {
         for _ in 0..2 {
             original(
                 net_driver,
@@ -101,18 +180,41 @@ pub unsafe extern "C" fn my_process_remote_function_for_channel(
     );
 }
 
-// pub unsafe extern "C" fn my_function_invoke(
-//     function: *mut UFunction,
-//     object: *mut UObject,
-//     stack: *mut FFrame,
-//     result: *mut c_void,
-// ) {
-//     type FunctionInvoke =
-//         unsafe extern "C" fn(*mut UFunction, *mut UObject, *mut FFrame, *mut c_void);
-//     print_if_unseen(object, function);
-//     let original = mem::transmute::<*const c_void, FunctionInvoke>(crate::FUNCTION_INVOKE);
-//     original(function, object, stack, result);
-// }
+// int64 UActorChannel::ReplicateActor(), which returns the bits it sent.
+pub unsafe extern "C" fn my_replicate_actor(actor_channel: *mut UObject) -> i64 {
+    type ReplicateActor = unsafe extern "C" fn(*mut UObject) -> i64;
+    let original = mem::transmute::<*const c_void, ReplicateActor>(crate::REPLICATE_ACTOR);
+    let bits = if super::REPLICATE_ACTOR_BREAKPOINT {
+        super::hardware::call_original(|| original(actor_channel))
+    } else {
+        original(actor_channel)
+    };
+
+    crate::replication::actor_replicated((*actor_channel).object_property("Actor"), bits);
+    bits
+}
+
+pub unsafe extern "C" fn my_function_invoke(
+    function: *mut UFunction,
+    object: *mut UObject,
+    stack: *mut FFrame,
+    result: *mut c_void,
+) {
+    type FunctionInvoke =
+        unsafe extern "C" fn(*mut UFunction, *mut UObject, *mut FFrame, *mut c_void);
+    let original = mem::transmute::<*const c_void, FunctionInvoke>(crate::FUNCTION_INVOKE);
+    crate::invoke::dispatch(object, function, stack);
+    let outer = crate::invoke::enter(stack);
+    original(function, object, stack, result);
+    crate::invoke::exit(outer);
+}
+
+pub unsafe extern "C" fn my_engine_tick(engine: *mut c_void, delta_seconds: f32, idle_mode: bool) {
+    type EngineTick = unsafe extern "C" fn(*mut c_void, f32, bool);
+    let original = mem::transmute::<*const c_void, EngineTick>(super::ENGINE_TICK);
+    original(engine, delta_seconds, idle_mode);
+    crate::tick::dispatch(delta_seconds);
+}
 
 pub unsafe extern "C" fn my_add_cheats(controller: *mut FSDPlayerController, _: bool) {
     type AddCheats = unsafe extern "C" fn(*mut FSDPlayerController, bool);
@@ -120,6 +222,334 @@ pub unsafe extern "C" fn my_add_cheats(controller: *mut FSDPlayerController, _:
     original(controller, true);
 }
 
+pub unsafe extern "C" fn my_process_event(
+    object: *mut UObject,
+    function: *mut UFunction,
+    parameters: *mut c_void,
+) {
+    type ProcessEvent = unsafe extern "C" fn(*mut UObject, *mut UFunction, *mut c_void);
+    let original = mem::transmute::<*const c_void, ProcessEvent>(crate::PROCESS_EVENT);
+    let start = crate::profiler::enter();
+    crate::process_event::dispatch(object, function, parameters);
+    original(object, function, parameters);
+    crate::profiler::exit(function, start);
+}
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "drg.outline",
+        help: "on|off, outline every pawn",
+        run: outline_command,
+    },
+    Command {
+        name: "drg.lighting",
+        help: "toggle the level's lighting",
+        run: lighting_command,
+    },
+    Command {
+        name: "drg.inspect",
+        help: "log every property of the actor under the crosshair (also F7)",
+        run: inspect_command,
+    },
+    Command {
+        name: "drg.profile",
+        help: "on|off|reset, time every UFunction called through ProcessEvent",
+        run: profile_command,
+    },
+    Command {
+        name: "drg.replication",
+        help: "on|off|reset, count the actors and RPCs sent over the network",
+        run: replication_command,
+    },
+    Command {
+        name: "drg.rpctrace",
+        help: "on|off, record every RPC and write them out as a timeline",
+        run: rpc_trace_command,
+    },
+    Command {
+        name: "drg.trace",
+        help: "<function path>|off, log each ProcessEvent call to a function and the blueprints that made it",
+        run: trace_command,
+    },
+    Command {
+        name: "drg.heatmap",
+        help: "on|off|save, count UFunction calls and write a report at mission end",
+        run: heatmap_command,
+    },
+    Command {
+        name: "drg.scan",
+        help: "new|exact|changed|unchanged|list|set|pin|unpin|clear, find values in memory",
+        run: scan_command,
+    },
+    Command {
+        name: "drg.freeze",
+        help: "[<object path>.<property> [value] | <address> <type> [value]], write a value every frame",
+        run: freeze_command,
+    },
+    Command {
+        name: "drg.unfreeze",
+        help: "<n>|all, stop writing a value from drg.freeze",
+        run: unfreeze_command,
+    },
+    Command {
+        name: "drg.chain",
+        help: "[<name> [value] | reload], read or write a pointer chain from the config",
+        run: chain_command,
+    },
+    Command {
+        name: "drg.snapshot",
+        help: "take <name> <object path> | diff <name> [name] | list | clear, see what changed on an object",
+        run: snapshot_command,
+    },
+    Command {
+        name: "drg.text",
+        help: "tables | keys <table> | get <table> <key> | set <table> <key> <text> | mark <table>, read or override string table entries",
+        run: text_command,
+    },
+    Command {
+        name: "drg.cvar",
+        help: "<name> [value] | dump, read or set a console variable",
+        run: cvar_command,
+    },
+    Command {
+        name: "drg.ini",
+        help: "<file> <section> <key> [value], read or set one of the game's config files",
+        run: ini_command,
+    },
+    Command {
+        name: "drg.bench",
+        help: "[n], time object lookups with and without the name index",
+        run: bench_command,
+    },
+    Command {
+        name: "drg.layouts",
+        help: "[soak on|off], check the SDK's struct layouts against the game",
+        run: layouts_command,
+    },
+    Command {
+        name: "drg.datatable",
+        help: "list | dump [name], write data tables out as CSV and JSON",
+        run: data_table_command,
+    },
+    Command {
+        name: "drg.mission",
+        help: "[json on|off], log what was generated for the current mission",
+        run: mission_command,
+    },
+    Command {
+        name: "drg.loadout",
+        help: "[<n> [<stat> <value> | reset [stat]]], edit the stats of your items",
+        run: loadout_command,
+    },
+    Command {
+        name: "drg.policy",
+        help: "log what kind of game we're in and which features it allows",
+        run: policy_command,
+    },
+    Command {
+        name: "drg.delegate",
+        help: "watch <object path>.<delegate> | unwatch <n>|all, log each broadcast of a delegate",
+        run: delegate_command,
+    },
+    Command {
+        name: "drg.draw",
+        help: "on|off, draw a test pattern over the game",
+        run: draw_command,
+    },
+    Command {
+        name: "drg.eject",
+        help: "take the hooks out and unload",
+        run: eject_command,
+    },
+];
+
+// Runs one of our console commands from somewhere other than the console.
+// Returns false if `line` isn't one of ours.
+pub unsafe fn run_command(line: &str) -> bool {
+    crate::console::dispatch_line(COMMANDS, line)
+}
+
+// The hidden return value comes before the arguments.
+pub unsafe extern "C" fn my_console_command(
+    controller: *mut FSDPlayerController,
+    result: *mut FString,
+    command: *const FString,
+    write_to_log: bool,
+) -> *mut FString {
+    if crate::console::dispatch(COMMANDS, &*command) {
+        result.write(FString::new());
+        return result;
+    }
+
+    type ConsoleCommand = unsafe extern "C" fn(
+        *mut FSDPlayerController,
+        *mut FString,
+        *const FString,
+        bool,
+    ) -> *mut FString;
+    let original = mem::transmute::<*const c_void, ConsoleCommand>(crate::CONSOLE_COMMAND);
+    original(controller, result, command, write_to_log)
+}
+
+// The prompt's thread does the unloading, once this returns and the game
+// thread is out of our code.
+unsafe fn eject_command(_: &str) {
+    common::prompt::request_eject();
+}
+
+unsafe fn delegate_command(args: &str) {
+    crate::delegate::run(args);
+}
+
+unsafe fn draw_command(args: &str) {
+    match args {
+        "on" => crate::draw::set_test_pattern(true),
+        "off" => crate::draw::set_test_pattern(false),
+        _ => common::log!("usage: drg.draw on|off"),
+    }
+}
+
+unsafe fn outline_command(args: &str) {
+    let enabled = match args {
+        "on" => true,
+        "off" => false,
+        _ => {
+            common::log!("usage: drg.outline on|off");
+            return;
+        }
+    };
+
+    for object in pawn::pawns() {
+        pawn::set_outline(object.cast(), enabled);
+    }
+}
+
+unsafe fn lighting_command(_: &str) {
+    render::toggle_lighting();
+}
+
+unsafe fn inspect_command(_: &str) {
+    inspect::inspect_under_crosshair();
+}
+
+unsafe fn profile_command(args: &str) {
+    match args {
+        "on" => {
+            if let Err(e) = crate::profiler::start() {
+                common::log!("drg.profile: failed to start: {:?}", e);
+            }
+        }
+        "off" => crate::profiler::stop(),
+        "reset" => crate::profiler::reset(),
+        _ => common::log!("usage: drg.profile on|off|reset"),
+    }
+}
+
+unsafe fn replication_command(args: &str) {
+    match args {
+        "on" => {
+            if let Err(e) = crate::replication::start() {
+                common::log!("drg.replication: failed to start: {:?}", e);
+            }
+        }
+        "off" => crate::replication::stop(),
+        "reset" => crate::replication::reset(),
+        _ => common::log!("usage: drg.replication on|off|reset"),
+    }
+}
+
+unsafe fn trace_command(args: &str) {
+    crate::call_trace::run(args);
+}
+
+unsafe fn rpc_trace_command(args: &str) {
+    match args {
+        "on" => {
+            if let Err(e) = crate::rpc_trace::start() {
+                common::log!("drg.rpctrace: failed to start: {:?}", e);
+            }
+        }
+        "off" if !crate::rpc_trace::is_enabled() => common::log!("drg.rpctrace: not tracing"),
+        "off" => match crate::rpc_trace::stop() {
+            Ok(path) => common::log!("drg.rpctrace: wrote {}", path.display()),
+            Err(e) => common::log!("drg.rpctrace: failed to write the trace: {:?}", e),
+        },
+        _ => common::log!("usage: drg.rpctrace on|off"),
+    }
+}
+
+unsafe fn heatmap_command(args: &str) {
+    match args {
+        "on" => {
+            if let Err(e) = crate::heatmap::start() {
+                common::log!("drg.heatmap: failed to start: {:?}", e);
+            }
+        }
+        "off" => crate::heatmap::stop(),
+        "save" => match crate::heatmap::save() {
+            Ok(path) => common::log!("drg.heatmap: wrote {}", path.display()),
+            Err(e) => common::log!("drg.heatmap: failed to write a report: {:?}", e),
+        },
+        _ => common::log!("usage: drg.heatmap on|off|save"),
+    }
+}
+
+unsafe fn scan_command(args: &str) {
+    crate::scanner::run(args);
+}
+
+unsafe fn freeze_command(args: &str) {
+    crate::freeze::freeze_command(args);
+}
+
+unsafe fn unfreeze_command(args: &str) {
+    crate::freeze::unfreeze_command(args);
+}
+
+unsafe fn chain_command(args: &str) {
+    crate::chain::run(args);
+}
+
+unsafe fn snapshot_command(args: &str) {
+    crate::snapshot::run(args);
+}
+
+unsafe fn text_command(args: &str) {
+    crate::string_table::run(args);
+}
+
+unsafe fn cvar_command(args: &str) {
+    crate::cvar::run(args);
+}
+
+unsafe fn ini_command(args: &str) {
+    crate::ini::run(args);
+}
+
+unsafe fn bench_command(args: &str) {
+    crate::bench::run(args);
+}
+
+unsafe fn layouts_command(args: &str) {
+    crate::layouts::run(args);
+}
+
+unsafe fn data_table_command(args: &str) {
+    crate::data_table::run(args);
+}
+
+unsafe fn mission_command(args: &str) {
+    crate::mission::run(args);
+}
+
+unsafe fn loadout_command(args: &str) {
+    crate::loadout::run(args);
+}
+
+unsafe fn policy_command(args: &str) {
+    crate::policy::run(args);
+}
+
 pub unsafe extern "C" fn my_on_item_amount_changed(
     context: *mut UObject,
     stack: *mut FFrame,
@@ -150,15 +580,32 @@ pub unsafe extern "C" fn my_get_item_name(
 //     (*super::ON_FLARE.as_ptr())(context, stack, result);
 // }
 
-pub unsafe extern "C" fn my_on_keypress_insert(
-    context: *mut UObject,
-    stack: *mut FFrame,
-    result: *mut c_void,
-) {
-    let character = context.cast::<PlayerCharacter>();
-    let health = (*character).HealthComponent;
-    (*health).ToggleCanTakeDamage();
-    (*super::ON_KEYPRESS_INSERT.as_ptr())(context, stack, result);
+#[macros::ue_hook(
+    "/Game/Character/BP_PlayerCharacter.BP_PlayerCharacter_C:InpActEvt_Insert_K2Node_InputKeyEvent"
+)]
+unsafe fn on_keypress_insert(call: &mut Call<()>) {
+    if let Some(character) = PlayerCharacter::from_object(call.object()) {
+        let health = (*character).HealthComponent;
+
+        // Only turning it on needs permission.
+        if !(*health).GetCanTakeDamage() || crate::policy::enable(&GOD_MODE) {
+            (*health).ToggleCanTakeDamage();
+            crate::feedback::toggled("god_mode", !(*health).GetCanTakeDamage());
+        }
+    }
+
+    call.call_original();
+}
+
+unsafe fn disable_god_mode() {
+    if let Some(character) = PlayerCharacter::from_object(common::player::local_pawn()) {
+        let health = (*character).HealthComponent;
+
+        if !(*health).GetCanTakeDamage() {
+            (*health).ToggleCanTakeDamage();
+            crate::feedback::toggled("god_mode", false);
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -174,13 +621,15 @@ unsafe fn get_game_data() -> *mut sdk::FSD::GameData {
     }
 }
 
-pub unsafe extern "C" fn my_on_keypress_delete(
-    context: *mut UObject,
-    stack: *mut FFrame,
-    result: *mut c_void,
-) {
-    render::toggle_lighting();
-    (*super::ON_KEYPRESS_DELETE.as_ptr())(context, stack, result);
+#[macros::ue_hook(
+    "/Game/Character/BP_PlayerCharacter.BP_PlayerCharacter_C:InpActEvt_Delete_K2Node_InputKeyEvent"
+)]
+unsafe fn on_keypress_delete(call: &mut Call<()>) {
+    if let Some(lit) = render::toggle_lighting() {
+        crate::feedback::toggled("lighting", lit);
+    }
+
+    call.call_original();
 }
 
 #[allow(dead_code)]
@@ -192,7 +641,7 @@ pub unsafe extern "C" fn my_post_actor_construction(actor: *mut Actor) {
     let obj = actor.cast::<UObject>();
 
     if (*obj).fast_is(EClassCastFlags::CASTCLASS_APawn) {
-        pawn::set_outline(obj.cast())
+        pawn::set_outline(obj.cast(), true)
     }
 }
 
@@ -203,7 +652,7 @@ unsafe fn print_if_unseen(object: *mut UObject, function: *mut UFunction) {
             (*function).seen_count = 1;
             common::log!("{} {}", *object, *function);
         } else {
-            common::log!("SEEN_FUNCTIONS is full. Increase its capacity.");
+            common::log!("Unable to track any more seen functions.");
         }
     }
 }
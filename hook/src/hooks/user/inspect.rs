@@ -0,0 +1,28 @@
+use common::{inspect, trace};
+
+// Logs every property of the actor under the crosshair.
+pub unsafe fn inspect_under_crosshair() {
+    let controller = common::player::local_controller();
+
+    if controller.is_null() {
+        common::log!("inspect: no player controller");
+        return;
+    }
+
+    let actor = match trace::under_crosshair(controller, trace::DEFAULT_DISTANCE) {
+        Some(hit) => hit.Actor.get(),
+        None => {
+            common::log!("inspect: nothing under the crosshair");
+            return;
+        }
+    };
+
+    // Terrain and other world geometry has no actor.
+    if actor.is_null() {
+        common::log!("inspect: hit something that isn't an actor");
+        return;
+    }
+
+    common::log!("inspect: {}", *actor);
+    inspect::dump(actor);
+}
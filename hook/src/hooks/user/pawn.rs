@@ -1,14 +1,19 @@
-use crate::hooks::OUTLINE_COMPONENT;
 use common::UObject;
 use sdk::Engine::Pawn;
 use sdk::FSD::OutlineComponent;
 
-pub unsafe fn set_outline(pawn: *mut Pawn) {
+// Every pawn in the world, without walking all of GUObjectArray.
+pub unsafe fn pawns() -> impl Iterator<Item = *mut UObject> {
+    common::live::objects_of(Pawn::static_class())
+        .into_iter()
+        .filter(|&object| !(*object).is_default_object())
+}
+
+pub unsafe fn set_outline(pawn: *mut Pawn, enabled: bool) {
     for &component in (*pawn).BlueprintCreatedComponents.iter() {
-        if (*component.cast::<UObject>()).is(OUTLINE_COMPONENT) {
-            let component = component.cast::<OutlineComponent>();
+        if let Some(component) = OutlineComponent::from_object(component.cast()) {
             (*component).UnlockOutline();
-            (*component).ToggleDefaultOutline(true);
+            (*component).ToggleDefaultOutline(enabled);
             (*component).LockOutline();
         }
     }
@@ -46,10 +46,20 @@ pub unsafe fn restore_lighting() {
     set_view_mode_index(ViewModeIndex::Lit);
 }
 
-pub unsafe fn toggle_lighting() {
+// Returns whether the lighting is now on.
+pub unsafe fn toggle_lighting() -> Option<bool> {
     match get_view_mode_index() {
-        Ok(ViewModeIndex::Unlit) => restore_lighting(),
-        Ok(ViewModeIndex::Lit) => remove_lighting(),
-        Err(e) => common::log!("toggle_lighting() error: {:?}", e),
+        Ok(ViewModeIndex::Unlit) => {
+            restore_lighting();
+            Some(true)
+        }
+        Ok(ViewModeIndex::Lit) => {
+            remove_lighting();
+            Some(false)
+        }
+        Err(e) => {
+            common::log!("toggle_lighting() error: {:?}", e);
+            None
+        }
     }
 }
@@ -9,17 +9,13 @@ pub unsafe fn on_item_amount_changed(widget: *mut AmmoCountWidget) {
 
     let item = (*widget).Item.cast::<UObject>();
 
-    if (*item).is(AMMO_DRIVEN_WEAPON) {
-        let weapon = item.cast::<AmmoDrivenWeapon>();
-
-        if (*weapon).AmmoCount < (*weapon).ClipSize {
-            (*weapon).AmmoCount = (*weapon).ClipSize;
+    if let Some(weapon) = (*item).try_downcast_mut::<AmmoDrivenWeapon>() {
+        if weapon.AmmoCount < weapon.ClipSize {
+            weapon.AmmoCount = weapon.ClipSize;
         }
-    } else if (*item).is(DOUBLE_DRILL_ITEM) {
-        let drill = item.cast::<DoubleDrillItem>();
-
-        if (*drill).Fuel < 2.0 {
-            (*drill).Fuel = 2.0;
+    } else if let Some(drill) = (*item).try_downcast_mut::<DoubleDrillItem>() {
+        if drill.Fuel < 2.0 {
+            drill.Fuel = 2.0;
         }
     }
 }
@@ -33,19 +29,14 @@ pub unsafe fn on_item_equipped(item: *mut Item) {
 
     let item = item.cast::<UObject>();
 
-    if (*item).is(AMMO_DRIVEN_WEAPON) {
-        let weapon = item.cast();
-
+    if let Some(weapon) = (*item).try_downcast_mut::<AmmoDrivenWeapon>() {
         no_recoil(weapon);
 
-        let fire = (*weapon).WeaponFire.cast::<UObject>();
-
-        if !fire.is_null() && (*fire).is(HITSCAN_BASE_COMPONENT) {
-            no_spread(fire.cast());
+        if let Some(hitscan) = HitscanBaseComponent::from_object(weapon.WeaponFire.cast()) {
+            no_spread(hitscan);
         }
-    } else if (*item).is(THROWN_GRENADE_ITEM) {
-        let item = item.cast::<ThrownGrenadeItem>();
-        (*item).Server_Resupply(1.0);
+    } else if let Some(grenade) = (*item).try_downcast_mut::<ThrownGrenadeItem>() {
+        grenade.Server_Resupply(1.0);
     }
 }
 
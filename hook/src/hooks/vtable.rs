@@ -0,0 +1,25 @@
+use crate::hooks::Patch;
+use common::UObject;
+use core::ffi::c_void;
+
+pub struct VTableHook {
+    _entry: Patch<*const c_void>,
+}
+
+impl VTableHook {
+    pub unsafe fn new(
+        object: *const UObject,
+        index: usize,
+        where_to_place_original: *mut *const c_void,
+        hook: *const c_void,
+    ) -> VTableHook {
+        // The vtable is shared by every instance of the object's class, so this
+        // hooks the function for all of them.
+        let entry = (*object).vtable.add(index);
+        *where_to_place_original = *entry;
+
+        VTableHook {
+            _entry: Patch::new(entry, hook),
+        }
+    }
+}
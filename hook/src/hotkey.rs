@@ -0,0 +1,56 @@
+// Runs tasks when keys are pressed. Polls the keyboard once per frame, so it
+// works for keys that the game doesn't bind.
+
+use crate::tick::Task;
+use common::list::{self, List, Listeners};
+use core::ptr;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, VIRTUAL_KEY};
+
+const MAX_HOTKEYS: usize = 32;
+
+#[derive(Copy, Clone)]
+struct Hotkey {
+    key: VIRTUAL_KEY,
+    task: Task,
+}
+
+static mut HOTKEYS: Listeners<Hotkey, MAX_HOTKEYS> = Listeners::new();
+
+// Whether each key was down at the last poll, by virtual key code, so that a
+// key bound twice is only pressed once per poll.
+static mut IS_DOWN: [bool; 256] = [false; 256];
+
+// Run `task` on the game thread whenever `key` goes down.
+pub unsafe fn register(key: VIRTUAL_KEY, task: Task) -> Result<(), list::Error> {
+    HOTKEYS.add(Hotkey { key, task })
+}
+
+#[allow(dead_code)]
+pub unsafe fn unregister(key: VIRTUAL_KEY) {
+    HOTKEYS.remove(|h| h.key == key);
+}
+
+pub unsafe fn poll() {
+    let mut pressed = List::<VIRTUAL_KEY, MAX_HOTKEYS>::new();
+
+    for hotkey in HOTKEYS.iter() {
+        let Some(was_down) = IS_DOWN.get_mut(usize::from(hotkey.key.0)) else {
+            continue;
+        };
+
+        // The high bit is set while the key is down.
+        let is_down = GetAsyncKeyState(i32::from(hotkey.key.0)) < 0;
+
+        if is_down && !*was_down {
+            let _ = pressed.push(hotkey.key);
+        }
+
+        *was_down = is_down;
+    }
+
+    Listeners::for_each(ptr::addr_of_mut!(HOTKEYS), |hotkey| {
+        if pressed.iter().any(|&key| key == hotkey.key) {
+            (hotkey.task)();
+        }
+    });
+}
@@ -0,0 +1,111 @@
+// The game's own config files, the ones in FSD/Saved/Config/WindowsNoEditor,
+// for reading engine settings and keeping ours where the game keeps its own.
+// See common::ini for the format.
+//
+// drg.ini <file> <section> <key>           a value
+// drg.ini <file> <section> <key> <value>   set it
+//
+// `file` is one of game, engine, input, scalability or user, for
+// GameUserSettings.ini. Sections with spaces can't be given on the console.
+//
+// These are only the saved settings. The defaults packed with the game aren't
+// here. The game reads most of them at startup, so a change usually takes
+// effect on the next launch, and one the game also makes while running may be
+// overwritten when it saves that file.
+
+use common::ini::Ini;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: drg.ini game|engine|input|scalability|user <section> <key> [value]";
+
+// From the executable, in FSD/Binaries/Win64.
+const CONFIG_FOLDER: &str = "../../Saved/Config/WindowsNoEditor";
+
+#[derive(Copy, Clone)]
+pub enum File {
+    Game,
+    Engine,
+    Input,
+    Scalability,
+    GameUserSettings,
+}
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Io(#[from] io::Error),
+    NoFolder,
+}
+
+impl File {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "game" => Some(Self::Game),
+            "engine" => Some(Self::Engine),
+            "input" => Some(Self::Input),
+            "scalability" => Some(Self::Scalability),
+            "user" => Some(Self::GameUserSettings),
+            _ => None,
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Game => "Game.ini",
+            Self::Engine => "Engine.ini",
+            Self::Input => "Input.ini",
+            Self::Scalability => "Scalability.ini",
+            Self::GameUserSettings => "GameUserSettings.ini",
+        }
+    }
+
+    fn path(self) -> Result<PathBuf, Error> {
+        crate::data_dir(CONFIG_FOLDER)
+            .map(|folder| folder.join(self.file_name()))
+            .ok_or(Error::NoFolder)
+    }
+}
+
+pub unsafe fn run(args: &str) {
+    let mut words = args.split_whitespace();
+
+    let file = words.next().and_then(File::from_name);
+
+    let result = match (file, words.next(), words.next(), words.next()) {
+        (Some(file), Some(section), Some(key), None) => get(file, section, key).map(|value| {
+            common::log!("{} = {}", key, value.as_deref().unwrap_or("<unset>"));
+        }),
+        (Some(file), Some(section), Some(key), Some(value)) => set(file, section, key, value),
+        _ => {
+            common::log!("{}", USAGE);
+            Ok(())
+        }
+    };
+
+    if let Err(e) = result {
+        common::log!("drg.ini: {:?}", e);
+    }
+}
+
+// The key's value, or None if the file, section or key isn't there.
+pub fn get(file: File, section: &str, key: &str) -> Result<Option<String>, Error> {
+    Ok(load(file)?.get(section, key).map(String::from))
+}
+
+// Sets the key, creating the section, and the file, if needed.
+pub fn set(file: File, section: &str, key: &str, value: &str) -> Result<(), Error> {
+    let mut ini = load(file)?;
+    ini.set(section, key, value);
+    fs::write(file.path()?, ini.to_string())?;
+    Ok(())
+}
+
+// Empty if there's no file yet.
+fn load(file: File) -> Result<Ini, Error> {
+    match fs::read_to_string(file.path()?) {
+        Ok(text) => Ok(Ini::parse(&text)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Ini::parse("")),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,72 @@
+// Listeners for every UFunction called through UFunction::Invoke, which
+// ProcessEvent goes through too, along with calls from one blueprint function
+// to another and blueprint calls to native functions, which ProcessEvent never
+// sees. Like process_event.rs, whose Registry and Filter this uses, listeners
+// run before the function does.
+//
+// For a blueprint function, `stack` is its own frame, and its Locals start
+// with the parameters. For a native function, it's the caller's frame, whose
+// Code is about to be read for the parameters, so they aren't known yet.
+//
+// The hook also keeps track of the innermost frame, so that calls that don't
+// come with one, like those through ProcessEvent, can still be traced back to
+// the blueprint that made them.
+
+use crate::process_event::{Filter, Registry};
+use common::list;
+use common::{FFrame, UFunction, UObject};
+use core::cell::Cell;
+use core::ptr;
+
+pub type Listener = unsafe fn(object: *mut UObject, function: *mut UFunction, stack: *mut FFrame);
+
+static mut LISTENERS: Registry<Listener> = Registry::new();
+
+thread_local! {
+    static FRAME: Cell<*mut FFrame> = Cell::new(ptr::null_mut());
+}
+
+pub unsafe fn register_for(filter: Filter, listener: Listener) -> Result<(), list::Error> {
+    LISTENERS.register(filter, listener)
+}
+
+pub unsafe fn unregister(listener: Listener) {
+    LISTENERS.unregister(|l| l as usize == listener as usize);
+}
+
+pub unsafe fn dispatch(object: *mut UObject, function: *mut UFunction, stack: *mut FFrame) {
+    Registry::dispatch(ptr::addr_of_mut!(LISTENERS), function, |listener| {
+        listener(object, function, stack)
+    });
+}
+
+// Makes `stack` the innermost frame until exit() is given what this returns.
+pub fn enter(stack: *mut FFrame) -> *mut FFrame {
+    FRAME.with(|frame| frame.replace(stack))
+}
+
+pub fn exit(outer: *mut FFrame) {
+    FRAME.with(|frame| frame.set(outer));
+}
+
+// The frame of the innermost Invoke on this thread, or null if there's none.
+pub fn current_frame() -> *mut FFrame {
+    FRAME.with(Cell::get)
+}
+
+// The blueprint functions that led to `function` being called with `stack`,
+// the innermost first. A blueprint function is invoked with its own frame,
+// and a native one with its caller's.
+pub unsafe fn script_stack(function: *mut UFunction, stack: *mut FFrame) -> Vec<String> {
+    if stack.is_null() {
+        return Vec::new();
+    }
+
+    let mut frames = (*stack).frames().peekable();
+    frames.next_if(|&frame| (*frame).Node == function);
+
+    frames
+        .filter(|&frame| !(*frame).Node.is_null())
+        .map(|frame| format!("{}", *(*frame).Node))
+        .collect()
+}
@@ -0,0 +1,131 @@
+// Checks the SDK we were built with against the game's reflection data, so
+// that a stale SDK shows up in the log instead of as corrupted memory. Each
+// struct's size has to be its PropertiesSize rounded up to its alignment, and
+// each field has to be where its property says it is.
+//
+// drg.layouts            check every loaded struct
+// drg.layouts soak on    also check the structs each new level loads, like
+//                        blueprint classes, as the world changes
+// drg.layouts soak off
+//
+// Structs that aren't loaded are skipped. The layouts come from the SDK's
+// layouts.rs, which sdk_gen writes along with the rest of it.
+
+use crate::event::{self, Event};
+use common::{GUObjectArray, UStruct};
+use sdk::layouts::{Layout, LAYOUTS};
+use std::collections::BTreeSet;
+
+const USAGE: &str = "usage: drg.layouts [soak on|off]";
+
+// Which of LAYOUTS we've checked, so that soaking only checks what's new.
+static mut CHECKED: BTreeSet<usize> = BTreeSet::new();
+
+static mut SOAKING: bool = false;
+
+pub unsafe fn run(args: &str) {
+    let mut words = args.split_whitespace();
+
+    match (words.next(), words.next()) {
+        (None, _) => {
+            CHECKED.clear();
+            check();
+        }
+        (Some("soak"), Some("on")) => {
+            if !SOAKING {
+                if let Err(e) = event::subscribe(on_event) {
+                    common::log!("drg.layouts: {:?}", e);
+                    return;
+                }
+
+                SOAKING = true;
+            }
+
+            check();
+        }
+        (Some("soak"), Some("off")) => unsubscribe(),
+        _ => common::log!("{}", USAGE),
+    }
+}
+
+pub unsafe fn unsubscribe() {
+    if SOAKING {
+        event::unsubscribe(on_event);
+        SOAKING = false;
+    }
+}
+
+unsafe fn on_event(event: &Event) {
+    if let Event::WorldChanged { .. } = *event {
+        check();
+    }
+}
+
+// Checks each loaded struct that hasn't been checked yet.
+unsafe fn check() {
+    let mut checked = 0;
+    let mut mismatches = 0;
+
+    for (i, layout) in LAYOUTS.iter().enumerate() {
+        if CHECKED.contains(&i) {
+            continue;
+        }
+
+        let Ok(structure) = (*GUObjectArray).find(layout.name) else {
+            continue;
+        };
+
+        CHECKED.insert(i);
+        checked += 1;
+        mismatches += check_layout(layout, structure.cast());
+    }
+
+    common::log!(
+        "drg.layouts: checked {} structs, {} mismatches, {} of {} checked so far",
+        checked,
+        mismatches,
+        CHECKED.len(),
+        LAYOUTS.len()
+    );
+}
+
+// Logs each way the layout differs from the struct. Returns how many there
+// were.
+unsafe fn check_layout(layout: &Layout, structure: *const UStruct) -> usize {
+    let mut mismatches = 0;
+
+    let alignment = (*structure).MinAlignment.max(1) as usize;
+    let size = ((*structure).PropertiesSize as usize).div_ceil(alignment) * alignment;
+
+    if layout.size != size {
+        common::log!(
+            "drg.layouts: {} is {:#x} bytes in the SDK but {:#x} in the game",
+            layout.name,
+            layout.size,
+            size
+        );
+
+        mismatches += 1;
+    }
+
+    for &(name, offset) in layout.fields {
+        let property = (*structure).find_property(name);
+
+        if property.is_null() {
+            common::log!("drg.layouts: {} has no {} in the game", layout.name, name);
+            mismatches += 1;
+        } else if (*property).Offset as usize != offset {
+            common::log!(
+                "drg.layouts: {}.{} is at {:#x} in the SDK but {:#x} in the game",
+                layout.name,
+                name,
+                offset,
+                (*property).Offset
+            );
+
+            mismatches += 1;
+        }
+    }
+
+    mismatches
+}
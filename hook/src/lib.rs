@@ -1,13 +1,54 @@
-use common::{self, win};
+use common::{self, diagnose, win, UObject};
 use core::ffi::c_void;
+use core::mem;
 use core::ptr;
 use sdk::Engine::Engine;
+use std::path::PathBuf;
 use windows::Win32::Foundation::HMODULE;
 use windows::Win32::System::LibraryLoader::FreeLibraryAndExitThread;
 
+mod bench;
+mod call_trace;
+mod chain;
+mod config;
+mod console;
+mod cvar;
+mod data_table;
+mod delegate;
+mod draw;
+mod event;
+mod feedback;
+mod freeze;
+mod gc;
+
 mod hooks;
 use hooks::Hooks;
 
+mod heatmap;
+mod hotkey;
+mod ini;
+mod invoke;
+mod layouts;
+mod loadout;
+mod mission;
+mod offsets;
+mod plugin;
+mod policy;
+mod process_event;
+mod profiler;
+mod prompt;
+mod replication;
+mod rpc_trace;
+
+mod scanner;
+mod scheduler;
+mod script;
+mod server;
+mod snapshot;
+mod string_table;
+mod tick;
+mod ue_hook;
+
 #[derive(macros::NoPanicErrorDebug)]
 enum Error {
     Common(#[from] common::Error),
@@ -17,18 +58,29 @@ enum Error {
     FindFunctionInvoke,
     FindProcessRemoteFunctionForChannel,
     FindAddCheats,
+    FindConsoleCommand,
+    FindProcessEvent,
     FindPostActorConstruction,
     FindGetPreferredUniqueNetId,
+    // GEngine, its viewport or a world wasn't there before the timeout.
+    EngineNotReady,
 }
 
 #[allow(non_upper_case_globals)]
 static mut GEngine: *const Engine = ptr::null();
 
+// Where the game keeps GEngine, which is null until the engine's been created.
+static mut GENGINE_ADDRESS: *const *const Engine = ptr::null();
+
 static mut FUNCTION_INVOKE: *mut c_void = ptr::null_mut();
 static mut PROCESS_REMOTE_FUNCTION_FOR_CHANNEL: *mut c_void = ptr::null_mut();
 static mut ADD_CHEATS: *mut c_void = ptr::null_mut();
+static mut CONSOLE_COMMAND: *mut c_void = ptr::null_mut();
+static mut PROCESS_EVENT: *mut c_void = ptr::null_mut();
 static mut POST_ACTOR_CONSTRUCTION: *mut c_void = ptr::null_mut();
 static mut GET_PREFERRED_UNIQUE_NET_ID: *mut c_void = ptr::null_mut();
+// Null unless the offsets table has it.
+static mut REPLICATE_ACTOR: *mut c_void = ptr::null_mut();
 
 #[no_mangle]
 unsafe extern "system" fn DllMain(dll: HMODULE, reason: u32, _: *mut ()) -> i32 {
@@ -46,31 +98,125 @@ unsafe extern "system" fn on_attach(dll: HMODULE) -> u32 {
 
 unsafe fn on_detach() {}
 
+// `name` in the folder with the game's executable, FSD/Binaries/Win64, which
+// is where our files go. None if the executable's path can't be had.
+fn data_dir(name: &str) -> Option<PathBuf> {
+    Some(std::env::current_exe().ok()?.parent()?.join(name))
+}
+
+// Every file with the given extension in one of data_dir's folders, sorted so
+// that they load in a predictable order. Empty if the folder doesn't exist.
+fn data_files(folder: &str, extension: &str) -> Vec<PathBuf> {
+    let Some(Ok(entries)) = data_dir(folder).map(std::fs::read_dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+        })
+        .collect();
+
+    files.sort();
+    files
+}
+
 unsafe fn run() -> Result<(), Error> {
     let module = win::Module::current()?;
 
-    init_globals(&module)?;
+    offsets::select(&module);
+
+    // Signatures can fail to match before the executable is unpacked, and
+    // GEngine is null until the engine's been created. Once the signatures
+    // match, only the pointers are polled.
+    let mut found_globals = false;
+    let is_ready = common::ready::wait(|| waiting_for(&module, &mut found_globals));
+
+    // Waiting out DRG_NATIVE_ATTACH_DELAY doesn't poll at all. Otherwise this
+    // is once more out of the loop, for the reason the signatures never
+    // matched.
+    if !found_globals {
+        init_globals(&module)?;
+        GEngine = *GENGINE_ADDRESS;
+    }
+
+    // The hooks are called as soon as they're in, and crash if what they use
+    // isn't there yet.
+    if !is_ready {
+        return Err(Error::EngineNotReady);
+    }
+
+    // Read out of GEngine's vtable, so it has to wait for the engine.
+    find_process_event(&module)?;
 
     {
         let _hooks = Hooks::new(&module)?;
-        common::idle();
+
+        // Only eject takes the hooks out, whether it's typed at the prompt
+        // or comes from drg.eject in the game.
+        prompt::run();
     }
 
     Ok(())
 }
 
+// What the game still needs before the hooks can go in, or None once it's
+// ready.
+unsafe fn waiting_for(module: &win::Module, found_globals: &mut bool) -> Option<&'static str> {
+    if !*found_globals {
+        if init_globals(module).is_err() {
+            return Some("the game's globals");
+        }
+
+        *found_globals = true;
+    }
+
+    GEngine = *GENGINE_ADDRESS;
+
+    if GEngine.is_null() {
+        Some("GEngine")
+    } else if !is_game_viewport_valid() {
+        Some("the game viewport")
+    } else if !common::ready::world_exists() {
+        Some("a world")
+    } else {
+        None
+    }
+}
+
+unsafe fn is_game_viewport_valid() -> bool {
+    let viewport = (*GEngine).GameViewport.cast::<UObject>();
+
+    if viewport.is_null() {
+        return false;
+    }
+
+    let item = (*common::GUObjectArray).index_to_object((*viewport).InternalIndex);
+    !item.is_null() && (*item).Object == viewport && (*item).is_valid()
+}
+
 unsafe fn init_globals(module: &win::Module) -> Result<(), Error> {
     common::init_globals(module)?;
     find_global_engine(module)?;
     find_function_invoke(module)?;
     find_process_remote_function_for_channel(module)?;
     find_add_cheats(module)?;
+    find_console_command(module)?;
+    find_replicate_actor();
     // find_post_actor_construction(module)?;
     // find_get_preferred_unique_net_id(module)?;
     Ok(())
 }
 
 unsafe fn find_global_engine(module: &win::Module) -> Result<(), Error> {
+    if let Some(address) = common::offsets::get("GEngine") {
+        GENGINE_ADDRESS = address as *const *const Engine;
+        return Ok(());
+    }
+
     // 00007FF72626A8F5 | 48:8B0D 64353105         | mov rcx,qword ptr ds:[7FF72B57DE60]     |
     // 00007FF72626A8FC | 49:8BD6                  | mov rdx,r14                             |
     // 00007FF72626A8FF | 48:8B01                  | mov rax,qword ptr ds:[rcx]              |
@@ -96,15 +242,23 @@ unsafe fn find_global_engine(module: &win::Module) -> Result<(), Error> {
         Some(0x00),
         Some(0x00),
     ];
-    let mov_rcx_global_engine: *const u8 = module.find(&PATTERN).ok_or(Error::FindGlobalEngine)?;
+    let mov_rcx_global_engine: *const u8 = module.find(&PATTERN).ok_or_else(|| {
+        diagnose::pattern_not_found(module, "GEngine", &PATTERN);
+        Error::FindGlobalEngine
+    })?;
     let relative_offset = mov_rcx_global_engine.add(3).cast::<i32>().read_unaligned();
-    GEngine = *mov_rcx_global_engine
+    GENGINE_ADDRESS = mov_rcx_global_engine
         .offset(7 + relative_offset as isize)
         .cast::<*const Engine>();
     Ok(())
 }
 
 unsafe fn find_function_invoke(module: &win::Module) -> Result<(), Error> {
+    if let Some(address) = common::offsets::get("FunctionInvoke") {
+        FUNCTION_INVOKE = address as *mut c_void;
+        return Ok(());
+    }
+
     const PATTERN: [Option<u8>; 14] = [
         Some(0x4D),
         Some(0x8B),
@@ -121,7 +275,10 @@ unsafe fn find_function_invoke(module: &win::Module) -> Result<(), Error> {
         Some(0xCE),
         Some(0xE8),
     ];
-    let mov_r9_r14: *mut u8 = module.find_mut(&PATTERN).ok_or(Error::FindFunctionInvoke)?;
+    let mov_r9_r14: *mut u8 = module.find_mut(&PATTERN).ok_or_else(|| {
+        diagnose::pattern_not_found(module, "FunctionInvoke", &PATTERN);
+        Error::FindFunctionInvoke
+    })?;
     let base = mov_r9_r14.add(PATTERN.len() + 4);
     let relative_offset = base.sub(4).cast::<i32>().read_unaligned();
     FUNCTION_INVOKE = base.offset(relative_offset as isize).cast();
@@ -129,6 +286,11 @@ unsafe fn find_function_invoke(module: &win::Module) -> Result<(), Error> {
 }
 
 unsafe fn find_process_remote_function_for_channel(module: &win::Module) -> Result<(), Error> {
+    if let Some(address) = common::offsets::get("ProcessRemoteFunctionForChannel") {
+        PROCESS_REMOTE_FUNCTION_FOR_CHANNEL = address as *mut c_void;
+        return Ok(());
+    }
+
     const PATTERN: [Option<u8>; 19] = [
         Some(0x48),
         Some(0x8B),
@@ -150,13 +312,19 @@ unsafe fn find_process_remote_function_for_channel(module: &win::Module) -> Resu
         Some(0x41),
         Some(0x56),
     ];
-    PROCESS_REMOTE_FUNCTION_FOR_CHANNEL = module
-        .find_mut(&PATTERN)
-        .ok_or(Error::FindProcessRemoteFunctionForChannel)?;
+    PROCESS_REMOTE_FUNCTION_FOR_CHANNEL = module.find_mut(&PATTERN).ok_or_else(|| {
+        diagnose::pattern_not_found(module, "ProcessRemoteFunctionForChannel", &PATTERN);
+        Error::FindProcessRemoteFunctionForChannel
+    })?;
     Ok(())
 }
 
 unsafe fn find_add_cheats(module: &win::Module) -> Result<(), Error> {
+    if let Some(address) = common::offsets::get("AddCheats") {
+        ADD_CHEATS = address as *mut c_void;
+        return Ok(());
+    }
+
     const PATTERN: [Option<u8>; 21] = [
         Some(0x48),
         Some(0x89),
@@ -180,10 +348,128 @@ unsafe fn find_add_cheats(module: &win::Module) -> Result<(), Error> {
         Some(0xB6),
         Some(0xDA),
     ];
-    ADD_CHEATS = module.find_mut(&PATTERN).ok_or(Error::FindAddCheats)?;
+    ADD_CHEATS = module.find_mut(&PATTERN).ok_or_else(|| {
+        diagnose::pattern_not_found(module, "AddCheats", &PATTERN);
+        Error::FindAddCheats
+    })?;
     Ok(())
 }
 
+// APlayerController::ConsoleCommand(const FString& Command, bool bWriteToLog)
+unsafe fn find_console_command(module: &win::Module) -> Result<(), Error> {
+    if let Some(address) = common::offsets::get("ConsoleCommand") {
+        CONSOLE_COMMAND = address as *mut c_void;
+        return Ok(());
+    }
+
+    const PATTERN: [Option<u8>; 27] = [
+        Some(0x48),
+        Some(0x89),
+        Some(0x5C),
+        Some(0x24),
+        None,
+        Some(0x48),
+        Some(0x89),
+        Some(0x74),
+        Some(0x24),
+        None,
+        Some(0x55),
+        Some(0x57),
+        Some(0x41),
+        Some(0x54),
+        Some(0x41),
+        Some(0x56),
+        Some(0x41),
+        Some(0x57),
+        Some(0x48),
+        Some(0x8D),
+        Some(0x6C),
+        Some(0x24),
+        None,
+        Some(0x48),
+        Some(0x81),
+        Some(0xEC),
+        None,
+    ];
+    // The prologue is common, so it has to match once, and the match has to
+    // be one of APlayerController's virtual functions like ConsoleCommand is.
+    let Some(console_command) = module.find_unique_mut::<c_void>(&PATTERN) else {
+        diagnose::pattern_not_found(module, "ConsoleCommand", &PATTERN);
+        return Err(Error::FindConsoleCommand);
+    };
+
+    if !is_player_controller_virtual(module, console_command) {
+        common::log!(
+            "diagnose: ConsoleCommand candidate {:?} isn't in APlayerController's vtable",
+            console_command
+        );
+        return Err(Error::FindConsoleCommand);
+    }
+
+    CONSOLE_COMMAND = console_command;
+    Ok(())
+}
+
+unsafe fn is_player_controller_virtual(module: &win::Module, function: *mut c_void) -> bool {
+    // APlayerController has a few hundred virtual functions.
+    const MAX_SLOTS: usize = 1024;
+
+    let Ok(vtable) = module.find_vtable("APlayerController") else {
+        return false;
+    };
+
+    let text = module.start()..module.start() + module.size();
+
+    (0..MAX_SLOTS)
+        .map(|i| *vtable.add(i) as usize)
+        .take_while(|slot| text.contains(slot))
+        .any(|slot| slot == function as usize)
+}
+
+// UObject::ProcessEvent(UFunction* Function, void* Parms). The engine
+// doesn't override it, so its vtable has the one every UObject ends up in.
+unsafe fn find_process_event(module: &win::Module) -> Result<(), Error> {
+    if GEngine.is_null() {
+        diagnose::build(module);
+        common::log!("diagnose: GEngine is null, so there's no vtable to find ProcessEvent in");
+        return Err(Error::FindProcessEvent);
+    }
+
+    let engine = GEngine.cast::<UObject>();
+    PROCESS_EVENT = (*engine).process_event_address();
+
+    if PROCESS_EVENT.is_null() {
+        // The slots around the one we read, to see if the index moved.
+        const SLOTS_BEFORE: usize = 4;
+        const SLOTS: usize = 8;
+
+        let index = UObject::process_event_vtable_index();
+        let first = index.saturating_sub(SLOTS_BEFORE);
+        let vtable = (*engine).vtable;
+
+        diagnose::build(module);
+
+        common::log!(
+            "diagnose: GEngine's vtable at exe+{} has no ProcessEvent at index {}. from index {}:\n{}",
+            common::Hex((vtable as usize).wrapping_sub(module.base())),
+            index,
+            first,
+            common::hexdump(vtable.add(first), SLOTS * mem::size_of::<usize>())
+        );
+
+        Err(Error::FindProcessEvent)
+    } else {
+        Ok(())
+    }
+}
+
+// There's no signature for UActorChannel::ReplicateActor yet.
+unsafe fn find_replicate_actor() {
+    if let Some(address) = common::offsets::get("ReplicateActor") {
+        REPLICATE_ACTOR = address as *mut c_void;
+    }
+}
+
 #[allow(dead_code)]
 unsafe fn find_post_actor_construction(module: &win::Module) -> Result<(), Error> {
     // 00007FF63827FECD | 48:8BCF                  | mov rcx,rdi                             |
@@ -0,0 +1,215 @@
+// Live edits to the stats of the local player's items, like damage, magazine
+// size and cooldowns:
+//
+// drg.loadout                       the items, numbered
+// drg.loadout 0                     the first item's numeric properties
+// drg.loadout 0 ClipSize 100        set one
+// drg.loadout 0 reset [ClipSize]    put one, or all, back to the default
+//
+// Stats are an item's own numeric properties and those of the components it
+// owns, named like "DamageComponent.Damage". Defaults come from the class's
+// default object, so a component's default is its class's, not what the
+// item's blueprint set it to.
+
+use crate::policy::{Feature, Scope};
+use common::inspect::{self, Primitive};
+use common::{EClassCastFlags, FProperty, GUObjectArray, UObject};
+use core::fmt::{self, Display, Formatter};
+use core::ptr;
+
+const USAGE: &str = "usage: drg.loadout [<n> [<stat> <value> | reset [stat]]]";
+
+// Resetting is always allowed, to undo edits made before someone joined.
+static EDIT: Feature = Feature {
+    name: "drg.loadout edits",
+    scope: Scope::HostOnly,
+    disable: None,
+};
+
+struct Stat {
+    object: *mut UObject,
+    // Of the object's class. Null if it wasn't found.
+    default_object: *mut UObject,
+    property: *const FProperty,
+    // With the component's property name in front for a component's stat.
+    name: String,
+}
+
+pub unsafe fn run(args: &str) {
+    let mut words = args.split_whitespace();
+
+    let pawn = common::player::local_pawn();
+
+    if pawn.is_null() {
+        common::log!("drg.loadout: no local player");
+        return;
+    }
+
+    let items = items(pawn);
+
+    let Some(index) = words.next() else {
+        for (i, &item) in items.iter().enumerate() {
+            common::log!("drg.loadout: {}: {}", i, (*item).name());
+        }
+
+        return;
+    };
+
+    let Some(&item) = index.parse::<usize>().ok().and_then(|i| items.get(i)) else {
+        common::log!("drg.loadout: no item {}. see drg.loadout", index);
+        return;
+    };
+
+    let stats = stats(item);
+
+    match (words.next(), words.next(), words.next()) {
+        (None, _, _) => {
+            for stat in &stats {
+                log_stat(stat);
+            }
+        }
+        (Some("reset"), name, None) => {
+            let mut reset = 0;
+
+            for stat in stats
+                .iter()
+                .filter(|s| name.is_none() || name == Some(s.name.as_str()))
+            {
+                if let Some(default) = default(stat) {
+                    if inspect::write_primitive(stat.property, stat.object.cast(), default).is_ok()
+                    {
+                        reset += 1;
+                    }
+                }
+            }
+
+            common::log!("drg.loadout: reset {} stats", reset);
+        }
+        (Some(name), Some(value), None) => {
+            let Some(stat) = stats.iter().find(|s| s.name == name) else {
+                common::log!("drg.loadout: no stat called {}", name);
+                return;
+            };
+
+            let value = match value.parse::<i64>() {
+                Ok(n) => Primitive::Integer(n),
+                Err(_) => match value.parse::<f64>() {
+                    Ok(n) => Primitive::Float(n),
+                    Err(_) => {
+                        common::log!("drg.loadout: {} isn't a number", value);
+                        return;
+                    }
+                },
+            };
+
+            if !crate::policy::enable(&EDIT) {
+                return;
+            }
+
+            match inspect::write_primitive(stat.property, stat.object.cast(), value) {
+                Ok(()) => log_stat(stat),
+                Err(e) => common::log!("drg.loadout: failed to set {}: {:?}", name, e),
+            }
+        }
+        _ => common::log!("{}", USAGE),
+    }
+}
+
+// The items that `pawn` owns, like its weapons and tools.
+unsafe fn items(pawn: *mut UObject) -> Vec<*mut UObject> {
+    (*GUObjectArray)
+        .iter()
+        .filter(|&object| {
+            !object.is_null()
+                && !(*object).is_default_object()
+                && inspect::is_a(object, "Item")
+                && (*object).object_property("Owner") == pawn
+        })
+        .collect()
+}
+
+unsafe fn stats(item: *mut UObject) -> Vec<Stat> {
+    let mut stats = Vec::new();
+    add_stats(&mut stats, item, "");
+
+    inspect::for_each_property((*item).class().cast(), |property| {
+        if let Some(Primitive::Object(component)) = inspect::read_primitive(property, item.cast()) {
+            if !component.is_null() && (*component).outer() == item {
+                add_stats(&mut stats, component, (*property).name());
+            }
+        }
+    });
+
+    stats
+}
+
+unsafe fn add_stats(stats: &mut Vec<Stat>, object: *mut UObject, prefix: &str) {
+    let default_object = default_object(object);
+
+    inspect::for_each_property((*object).class().cast(), |property| {
+        // Enums read as integers too, but aren't stats.
+        let is_number = (*property).is(EClassCastFlags::CASTCLASS_FNumericProperty)
+            && inspect::read_primitive(property, object.cast()).is_some();
+
+        if is_number {
+            stats.push(Stat {
+                object,
+                default_object,
+                property,
+                name: if prefix.is_empty() {
+                    (*property).name().to_owned()
+                } else {
+                    format!("{}.{}", prefix, (*property).name())
+                },
+            });
+        }
+    });
+}
+
+unsafe fn default(stat: &Stat) -> Option<Primitive> {
+    if stat.default_object.is_null() {
+        None
+    } else {
+        inspect::read_primitive(stat.property, stat.default_object.cast())
+    }
+}
+
+// A class's default object is next to it, named "Default__" and the class's
+// name.
+unsafe fn default_object(object: *mut UObject) -> *mut UObject {
+    let class = (*object).class().cast::<UObject>();
+    let mut path = String::new();
+
+    if (*class).write_path(&mut path).is_err() {
+        return ptr::null_mut();
+    }
+
+    let Some((outer, name)) = path.rsplit_once('.') else {
+        return ptr::null_mut();
+    };
+
+    (*GUObjectArray).find_by_path(&format!("{}.Default__{}", outer, name))
+}
+
+unsafe fn log_stat(stat: &Stat) {
+    let now = inspect::read_primitive(stat.property, stat.object.cast());
+
+    common::log!(
+        "drg.loadout: {} = {} (default {})",
+        stat.name,
+        Number(now),
+        Number(default(stat))
+    );
+}
+
+struct Number(Option<Primitive>);
+
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match self.0 {
+            Some(Primitive::Integer(n)) => write!(f, "{}", n),
+            Some(Primitive::Float(n)) => write!(f, "{}", n),
+            _ => write!(f, "?"),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+// Logs what the game generated for each mission when it starts: its seed,
+// hazard, and the generated mission, which holds the biome, objectives and
+// the DNA the cave was built from. "drg.mission" logs the current one, and
+// "drg.mission json on" also writes everything to the "missions" folder next
+// to the game's executable, one file per mission.
+
+use crate::event::{self, Event};
+use common::inspect;
+use common::list;
+use common::snapshot::Snapshot;
+use common::{GUObjectArray, JsonString, UObject};
+use core::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MISSIONS_FOLDER: &str = "missions";
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Io(#[from] io::Error),
+    NoFolder,
+}
+
+static mut WRITE_JSON: bool = false;
+
+pub unsafe fn subscribe() -> Result<(), list::Error> {
+    event::subscribe(on_event)
+}
+
+pub unsafe fn unsubscribe() {
+    event::unsubscribe(on_event);
+}
+
+pub unsafe fn run(args: &str) {
+    match args {
+        "" => dump(None),
+        "json on" => WRITE_JSON = true,
+        "json off" => WRITE_JSON = false,
+        _ => common::log!("usage: drg.mission [json on|off]"),
+    }
+}
+
+unsafe fn on_event(event: &Event) {
+    if let Event::MissionStart { seed, .. } = *event {
+        dump(Some(seed));
+    }
+}
+
+unsafe fn dump(seed: Option<u32>) {
+    let game_state = common::player::game_state();
+
+    if game_state.is_null() {
+        common::log!("mission: no game state");
+        return;
+    }
+
+    let hazard = inspect::find_object_of(game_state, "DifficultySetting");
+    let mission = inspect::find_object_of(game_state, "GeneratedMission");
+
+    // The procedural setup is the actor that carves the cave.
+    let setup = (*GUObjectArray).iter().find(|&object| {
+        !object.is_null()
+            && !(*object).is_default_object()
+            && inspect::is_a(object, "ProceduralSetup")
+    });
+
+    if let Some(seed) = seed {
+        common::log!("mission: seed {}", seed);
+    }
+
+    if !hazard.is_null() {
+        common::log!("mission: hazard {}", (*hazard).name());
+    }
+
+    if mission.is_null() {
+        common::log!("mission: no generated mission on the game state");
+    }
+
+    let mission = (!mission.is_null()).then(|| Snapshot::take(mission));
+    let setup = setup.map(|setup| Snapshot::take(setup));
+
+    // The top-level values are the summary. The rest is for the JSON file.
+    if let Some(mission) = &mission {
+        for (path, value) in &mission.values {
+            if !path.contains(['.', '[']) {
+                common::log!("mission: {} = {}", path, value);
+            }
+        }
+    }
+
+    if WRITE_JSON {
+        match write_json(seed, hazard, mission.as_ref(), setup.as_ref()) {
+            Ok(path) => common::log!("mission: wrote {}", path.display()),
+            Err(e) => common::log!("mission: failed to write a report: {:?}", e),
+        }
+    }
+}
+
+// {"seed": 123, "hazard": "...", "mission": {"object": "...", "values": {...}},
+// "procedural_setup": {...}}
+unsafe fn write_json(
+    seed: Option<u32>,
+    hazard: *mut UObject,
+    mission: Option<&Snapshot>,
+    setup: Option<&Snapshot>,
+) -> Result<PathBuf, Error> {
+    let folder = crate::data_dir(MISSIONS_FOLDER).ok_or(Error::NoFolder)?;
+
+    fs::create_dir_all(&folder)?;
+
+    let mut json = String::from("{\"seed\":");
+
+    match seed {
+        Some(seed) => {
+            let _ = write!(json, "{}", seed);
+        }
+        None => json.push_str("null"),
+    }
+
+    json.push_str(",\"hazard\":");
+
+    if hazard.is_null() {
+        json.push_str("null");
+    } else {
+        let _ = write!(json, "\"{}\"", JsonString((*hazard).name()));
+    }
+
+    json.push_str(",\"mission\":");
+    write_snapshot(&mut json, mission);
+    json.push_str(",\"procedural_setup\":");
+    write_snapshot(&mut json, setup);
+    json.push('}');
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+
+    let path = folder.join(format!("mission-{}-{}.json", seed.unwrap_or(0), stamp));
+    fs::write(&path, json)?;
+    Ok(path)
+}
+
+fn write_snapshot(json: &mut String, snapshot: Option<&Snapshot>) {
+    let Some(snapshot) = snapshot else {
+        json.push_str("null");
+        return;
+    };
+
+    let _ = write!(
+        json,
+        "{{\"object\":\"{}\",\"values\":{{",
+        JsonString(&snapshot.object)
+    );
+
+    for (i, (path, value)) in snapshot.values.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+
+        let _ = write!(json, "\"{}\":\"{}\"", JsonString(path), JsonString(value));
+    }
+
+    json.push_str("}}");
+}
@@ -0,0 +1,31 @@
+// Picks the offset table for this build of the game, before anything is
+// looked up. A "drg-native-offsets.json" next to the game's executable comes
+// first, so that an update can be handled by dropping in a new file. Then the
+// tables built into the DLL. See common::offsets for the format.
+
+use common::{offsets, win};
+use std::fs;
+use std::io;
+
+const OFFSETS_FILE: &str = "drg-native-offsets.json";
+
+const BUNDLED: &str = include_str!("../offsets.json");
+
+pub unsafe fn select(module: &win::Module) {
+    let file = crate::data_dir(OFFSETS_FILE).map(fs::read_to_string);
+
+    let file = match file {
+        Some(Ok(text)) => Some(text),
+        Some(Err(e)) if e.kind() == io::ErrorKind::NotFound => None,
+        Some(Err(e)) => {
+            common::log!("offsets: failed to read {}: {:?}", OFFSETS_FILE, e);
+            None
+        }
+        None => None,
+    };
+
+    match &file {
+        Some(file) => offsets::select(module, &[(OFFSETS_FILE, file), ("the DLL", BUNDLED)]),
+        None => offsets::select(module, &[("the DLL", BUNDLED)]),
+    };
+}
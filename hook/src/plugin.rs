@@ -0,0 +1,323 @@
+// Loads every DLL in the "plugins" folder next to the game's executable. See
+// common::plugin for what a plugin exports and what it's given.
+
+use crate::event::{self, Event};
+use crate::policy::{self, Change};
+use crate::process_event::{self, Filter};
+use crate::{invoke, tick};
+use common::list::Listeners;
+use common::plugin::{
+    Context, EventKind, EventListener, Init, InvokeListener, Plugin, ProcessEventListener,
+    Shutdown, TickCallback, ABI_VERSION, INIT_SYMBOL, SHUTDOWN_SYMBOL,
+};
+use common::{inspect, FFrame, FProperty, GUObjectArray, UFunction, UObject};
+use core::ffi::c_void;
+use core::mem;
+use core::ptr;
+use core::slice;
+use std::borrow::Cow;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use windows::core::{PCSTR, PCWSTR};
+use windows::Win32::Foundation::HMODULE;
+use windows::Win32::System::LibraryLoader::{FreeLibrary, GetProcAddress, LoadLibraryW};
+
+const PLUGINS_FOLDER: &str = "plugins";
+
+// Shared by every plugin.
+const MAX_CALLBACKS: usize = 64;
+
+#[derive(macros::NoPanicErrorDebug)]
+enum Error {
+    LoadLibrary,
+    MissingInit,
+    InitFailed,
+}
+
+// Indexed by Plugin, to label log lines.
+static mut NAMES: Vec<String> = Vec::new();
+
+static mut TICK_CALLBACKS: Listeners<(Plugin, TickCallback), MAX_CALLBACKS> = Listeners::new();
+static mut LISTENERS: Listeners<(Plugin, ProcessEventListener), MAX_CALLBACKS> = Listeners::new();
+static mut INVOKE_LISTENERS: Listeners<(Plugin, InvokeListener), MAX_CALLBACKS> = Listeners::new();
+static mut EVENT_LISTENERS: Listeners<(Plugin, EventListener), MAX_CALLBACKS> = Listeners::new();
+
+struct Loaded {
+    plugin: Plugin,
+    module: HMODULE,
+    shutdown: Option<Shutdown>,
+
+    // Handed to the plugin, so it must stay put until the plugin is gone.
+    _context: Box<Context>,
+}
+
+// Shuts down and unloads every plugin when dropped. Only drop it once the tick
+// and ProcessEvent hooks are gone.
+pub struct Plugins {
+    loaded: Vec<Loaded>,
+}
+
+impl Plugins {
+    pub unsafe fn load() -> Self {
+        let mut loaded = Vec::new();
+
+        for path in crate::data_files(PLUGINS_FOLDER, "dll") {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let plugin = NAMES.len();
+            NAMES.push(name.to_string());
+
+            match load(plugin, &path) {
+                Ok(l) => {
+                    common::log!("plugin: loaded {}", name);
+                    loaded.push(l);
+                }
+                Err(e) => {
+                    forget(plugin);
+                    common::log!("plugin: {}: {:?}", name, e);
+                }
+            }
+        }
+
+        if !loaded.is_empty() {
+            if let Err(e) = tick::register(on_tick) {
+                common::log!("plugin: failed to listen for ticks: {:?}", e);
+            }
+
+            if let Err(e) = process_event::register(on_process_event) {
+                common::log!("plugin: failed to listen for ProcessEvent: {:?}", e);
+            }
+
+            if let Err(e) = invoke::register_for(Filter::All, on_invoke) {
+                common::log!("plugin: failed to listen for invokes: {:?}", e);
+            }
+
+            if let Err(e) = event::subscribe(on_event) {
+                common::log!("plugin: failed to listen for game events: {:?}", e);
+            }
+        }
+
+        Self { loaded }
+    }
+}
+
+impl Drop for Plugins {
+    fn drop(&mut self) {
+        unsafe {
+            tick::unregister(on_tick);
+            process_event::unregister(on_process_event);
+            invoke::unregister(on_invoke);
+            event::unsubscribe(on_event);
+
+            // Newest first, in case a plugin uses one that loaded before it.
+            while let Some(loaded) = self.loaded.pop() {
+                if let Some(shutdown) = loaded.shutdown {
+                    shutdown();
+                }
+
+                forget(loaded.plugin);
+                let _ = FreeLibrary(loaded.module);
+            }
+        }
+    }
+}
+
+unsafe fn load(plugin: Plugin, path: &Path) -> Result<Loaded, Error> {
+    let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+
+    let module = LoadLibraryW(PCWSTR(wide_path.as_ptr())).map_err(|_| Error::LoadLibrary)?;
+
+    let Some(init) = GetProcAddress(module, PCSTR(INIT_SYMBOL.as_ptr())) else {
+        let _ = FreeLibrary(module);
+        return Err(Error::MissingInit);
+    };
+
+    let init = mem::transmute::<_, Init>(init);
+
+    let shutdown = GetProcAddress(module, PCSTR(SHUTDOWN_SYMBOL.as_ptr()))
+        .map(|shutdown| mem::transmute::<_, Shutdown>(shutdown));
+
+    let context = Box::new(Context {
+        abi_version: ABI_VERSION,
+        plugin,
+        log,
+        register_tick,
+        unregister_tick,
+        register_process_event,
+        unregister_process_event,
+        run_command,
+        find_object,
+        is_a,
+        find_property,
+        property_address,
+        object_name,
+        register_invoke,
+        unregister_invoke,
+        register_event,
+        unregister_event,
+    });
+
+    if !init(&*context) {
+        let _ = FreeLibrary(module);
+        return Err(Error::InitFailed);
+    }
+
+    Ok(Loaded {
+        plugin,
+        module,
+        shutdown,
+        _context: context,
+    })
+}
+
+// Removes any callbacks the plugin left registered.
+unsafe fn forget(plugin: Plugin) {
+    TICK_CALLBACKS.remove_all(|&(p, _)| p == plugin);
+    LISTENERS.remove_all(|&(p, _)| p == plugin);
+    INVOKE_LISTENERS.remove_all(|&(p, _)| p == plugin);
+    EVENT_LISTENERS.remove_all(|&(p, _)| p == plugin);
+}
+
+unsafe fn on_tick(delta_seconds: f32) {
+    Listeners::for_each(ptr::addr_of_mut!(TICK_CALLBACKS), |(_, callback)| {
+        callback(delta_seconds)
+    });
+}
+
+unsafe fn on_process_event(
+    object: *mut UObject,
+    function: *mut UFunction,
+    parameters: *mut c_void,
+) {
+    Listeners::for_each(ptr::addr_of_mut!(LISTENERS), |(_, listener)| {
+        listener(object, function, parameters)
+    });
+}
+
+unsafe fn on_invoke(object: *mut UObject, function: *mut UFunction, stack: *mut FFrame) {
+    Listeners::for_each(ptr::addr_of_mut!(INVOKE_LISTENERS), |(_, listener)| {
+        listener(object, function, stack)
+    });
+}
+
+unsafe fn on_event(event: &Event) {
+    let (kind, object, seed) = match *event {
+        Event::MissionStart { seed, hazard } => (EventKind::MissionStart, hazard, seed),
+        Event::PlayerDowned { character } => (EventKind::PlayerDowned, character, 0),
+        Event::MissionEnd { controller } => (EventKind::MissionEnd, controller, 0),
+        Event::WorldChanged { world } => (EventKind::WorldChanged, world, 0),
+    };
+
+    Listeners::for_each(ptr::addr_of_mut!(EVENT_LISTENERS), |(_, listener)| {
+        listener(kind, object, seed)
+    });
+}
+
+unsafe fn string<'a>(text: *const u8, len: usize) -> Cow<'a, str> {
+    if text.is_null() {
+        Cow::Borrowed("")
+    } else {
+        String::from_utf8_lossy(slice::from_raw_parts(text, len))
+    }
+}
+
+unsafe extern "C" fn log(plugin: Plugin, text: *const u8, len: usize) {
+    let name = NAMES.get(plugin).map_or("?", String::as_str);
+    common::log!("{}: {}", name, string(text, len));
+}
+
+unsafe extern "C" fn register_tick(plugin: Plugin, callback: TickCallback) -> bool {
+    TICK_CALLBACKS.add((plugin, callback)).is_ok()
+}
+
+unsafe extern "C" fn unregister_tick(plugin: Plugin, callback: TickCallback) {
+    TICK_CALLBACKS.remove(|&(p, c)| p == plugin && c as usize == callback as usize);
+}
+
+unsafe extern "C" fn register_process_event(
+    plugin: Plugin,
+    listener: ProcessEventListener,
+) -> bool {
+    LISTENERS.add((plugin, listener)).is_ok()
+}
+
+unsafe extern "C" fn unregister_process_event(plugin: Plugin, listener: ProcessEventListener) {
+    LISTENERS.remove(|&(p, l)| p == plugin && l as usize == listener as usize);
+}
+
+unsafe extern "C" fn register_invoke(plugin: Plugin, listener: InvokeListener) -> bool {
+    INVOKE_LISTENERS.add((plugin, listener)).is_ok()
+}
+
+unsafe extern "C" fn unregister_invoke(plugin: Plugin, listener: InvokeListener) {
+    INVOKE_LISTENERS.remove(|&(p, l)| p == plugin && l as usize == listener as usize);
+}
+
+unsafe extern "C" fn register_event(plugin: Plugin, listener: EventListener) -> bool {
+    EVENT_LISTENERS.add((plugin, listener)).is_ok()
+}
+
+unsafe extern "C" fn unregister_event(plugin: Plugin, listener: EventListener) {
+    EVENT_LISTENERS.remove(|&(p, l)| p == plugin && l as usize == listener as usize);
+}
+
+unsafe extern "C" fn run_command(line: *const u8, len: usize) -> bool {
+    policy::apply(Change::Command(&string(line, len))).unwrap_or(false)
+}
+
+unsafe extern "C" fn find_object(path: *const u8, len: usize) -> *mut UObject {
+    (*GUObjectArray).find_by_path(&string(path, len))
+}
+
+unsafe extern "C" fn is_a(object: *const UObject, class: *const u8, len: usize) -> bool {
+    !object.is_null() && inspect::is_a(object, &string(class, len))
+}
+
+unsafe extern "C" fn find_property(
+    object: *const UObject,
+    name: *const u8,
+    len: usize,
+) -> *const FProperty {
+    if object.is_null() {
+        return ptr::null();
+    }
+
+    (*(*object).class()).find_property(&string(name, len))
+}
+
+unsafe extern "C" fn property_address(
+    object: *mut UObject,
+    name: *const u8,
+    len: usize,
+) -> *mut c_void {
+    // A plugin can write anything through the address, so it's a change.
+    if !policy::allows_changes() {
+        return ptr::null_mut();
+    }
+
+    let property = find_property(object, name, len);
+
+    if property.is_null() {
+        ptr::null_mut()
+    } else {
+        object.cast::<u8>().add((*property).offset()).cast()
+    }
+}
+
+unsafe extern "C" fn object_name(
+    object: *const UObject,
+    buffer: *mut u8,
+    capacity: usize,
+) -> usize {
+    if object.is_null() {
+        return 0;
+    }
+
+    let name = format!("{}", *object);
+
+    if !buffer.is_null() {
+        let len = name.len().min(capacity);
+        ptr::copy_nonoverlapping(name.as_ptr(), buffer, len);
+    }
+
+    name.len()
+}
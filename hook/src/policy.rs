@@ -0,0 +1,278 @@
+// Keeps features that change the game away from other players' games. Each
+// feature has a scope:
+//
+// SafeAnywhere  only changes what we see, like outlines
+// HostOnly      changes the game, so only in a game we host
+// SoloOnly      only with nobody else in the game
+//
+// Commands and hotkeys ask `enable` before turning a feature on, which refuses
+// in the wrong kind of game. The game is checked every second, and when it
+// changes, e.g. someone joins or we join someone, features that it no longer
+// allows are turned off.
+//
+// With "sandbox_only": true in the config, host-only features are solo-only
+// too, for playing in public lobbies without having to think about it.
+//
+// Scripts, RPC clients and plugins change the game by name, so what they
+// change is up to them. Every property write, function call and command they
+// make goes through `apply`, which treats them all as one host-only feature.
+
+use common::inspect::{self, Primitive, WriteError};
+use common::json::Value;
+use common::list::{self, List};
+use common::{kismet, FProperty, TArray, UFunction, UObject};
+use core::ffi::c_void;
+use core::fmt::{self, Display, Formatter};
+use core::ptr;
+
+// Seconds between checks of what kind of game we're in.
+const CHECK_INTERVAL: f32 = 1.0;
+
+const MAX_ENABLED: usize = 32;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Scope {
+    SafeAnywhere,
+    HostOnly,
+    SoloOnly,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Session {
+    // Nobody else is in our game, or we aren't in one.
+    Solo,
+    // Other players are in our game.
+    Host,
+    // We're in someone else's game.
+    Client,
+}
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    // This game doesn't allow changes by name.
+    Refused,
+    Write(#[from] WriteError),
+}
+
+// A change asked for by name, by a script, an RPC client or a plugin.
+pub enum Change<'a> {
+    Set {
+        object: *mut UObject,
+        property: *const FProperty,
+        value: Primitive,
+    },
+    Call {
+        object: *mut UObject,
+        function: *mut UFunction,
+        parameters: *mut c_void,
+    },
+    // One of our console commands, which may also check its own feature.
+    Command(&'a str),
+}
+
+static BY_NAME: Feature = Feature {
+    name: "changes by scripts, RPC clients and plugins",
+    scope: Scope::HostOnly,
+    disable: None,
+};
+
+pub struct Feature {
+    pub name: &'static str,
+    pub scope: Scope,
+    // Turns off a feature that stays on, like a toggle. It may already be off.
+    // None for one-off actions, which have nothing to turn off.
+    pub disable: Option<unsafe fn()>,
+}
+
+static mut SESSION: Session = Session::Solo;
+static mut SANDBOX_ONLY: bool = false;
+static mut SINCE_CHECK: f32 = 0.0;
+
+// The features `enable` let on that can be turned off.
+static mut ENABLED: List<&'static Feature, MAX_ENABLED> = List::new();
+
+pub unsafe fn start() -> Result<(), list::Error> {
+    load_config();
+    SESSION = detect();
+    crate::tick::register(check)
+}
+
+pub unsafe fn stop() {
+    crate::tick::unregister(check);
+    ENABLED.clear();
+}
+
+// Whether `feature` may be turned on in this game. Logs why not.
+pub unsafe fn enable(feature: &'static Feature) -> bool {
+    SESSION = detect();
+
+    if !permits(feature.scope) {
+        common::log!(
+            "policy: {} is {}, and {}",
+            feature.name,
+            feature.scope,
+            SESSION
+        );
+
+        return false;
+    }
+
+    if feature.disable.is_some() && !ENABLED.iter().any(|&f| ptr::eq(f, feature)) {
+        // We couldn't turn it off when the game changes.
+        if let Err(e) = ENABLED.push(feature) {
+            common::log!("policy: can't track {}: {:?}", feature.name, e);
+            return false;
+        }
+    }
+
+    true
+}
+
+// Whether this game allows `scope`, as of the last check. Doesn't log, for
+// code that checks often.
+pub unsafe fn permits(scope: Scope) -> bool {
+    match scope {
+        Scope::SafeAnywhere => true,
+        Scope::HostOnly if !SANDBOX_ONLY => SESSION != Session::Client,
+        Scope::HostOnly | Scope::SoloOnly => SESSION == Session::Solo,
+    }
+}
+
+pub unsafe fn run(_: &str) {
+    common::log!(
+        "drg.policy: {}{}",
+        SESSION,
+        if SANDBOX_ONLY { ", sandbox only" } else { "" }
+    );
+
+    for feature in ENABLED.iter() {
+        common::log!("drg.policy: {} ({}) may be on", feature.name, feature.scope);
+    }
+}
+
+// Makes `change` if this game allows it. Returns whether a command was one of
+// ours, and true for anything else. Doesn't log, since callers report errors
+// their own way and some make changes every frame.
+pub unsafe fn apply(change: Change) -> Result<bool, Error> {
+    if !allows_changes() {
+        return Err(Error::Refused);
+    }
+
+    match change {
+        Change::Set {
+            object,
+            property,
+            value,
+        } => inspect::write_primitive(property, object.cast(), value)?,
+        Change::Call {
+            object,
+            function,
+            parameters,
+        } => UObject::process_event(object, function, parameters),
+        Change::Command(line) => return Ok(crate::hooks::run_command(line)),
+    }
+
+    Ok(true)
+}
+
+// Whether `apply` would make changes in this game, for what can't go through
+// it, like an address handed to a plugin.
+pub unsafe fn allows_changes() -> bool {
+    permits(BY_NAME.scope)
+}
+
+unsafe fn check(delta_seconds: f32) {
+    SINCE_CHECK += delta_seconds;
+
+    if SINCE_CHECK < CHECK_INTERVAL {
+        return;
+    }
+
+    SINCE_CHECK = 0.0;
+    let session = detect();
+
+    if session == SESSION {
+        return;
+    }
+
+    SESSION = session;
+    load_config();
+    common::log!("policy: {}", SESSION);
+
+    let mut disallowed = List::<&'static Feature, MAX_ENABLED>::new();
+
+    ENABLED.retain(|&mut feature| {
+        // Both lists hold the same number, so this can't fail.
+        permits(feature.scope) || disallowed.push(feature).is_err()
+    });
+
+    // After retain, since turning a feature off may go through policy again.
+    for feature in disallowed.iter() {
+        common::log!("policy: turning off {}", feature.name);
+
+        if let Some(disable) = feature.disable {
+            disable();
+        }
+    }
+}
+
+unsafe fn detect() -> Session {
+    let world = common::player::world();
+
+    if world.is_null() {
+        return Session::Solo;
+    }
+
+    // Also when IsServer couldn't be called, to be safe.
+    if !kismet::is_server(world) {
+        return Session::Client;
+    }
+
+    let game_state = common::player::game_state();
+
+    let players = if game_state.is_null() {
+        0
+    } else {
+        inspect::property_mut::<TArray<*mut UObject>>(game_state, "PlayerArray")
+            .map_or(0, |players| (*players).len())
+    };
+
+    if players > 1 {
+        Session::Host
+    } else {
+        Session::Solo
+    }
+}
+
+unsafe fn load_config() {
+    SANDBOX_ONLY = match crate::config::load() {
+        Ok(config) => config
+            .get("sandbox_only")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        Err(e) => {
+            common::log!("policy: failed to read the config: {:?}", e);
+            false
+        }
+    };
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.write_str(match self {
+            Self::SafeAnywhere => "safe anywhere",
+            Self::HostOnly => "host only",
+            Self::SoloOnly => "solo only",
+        })
+    }
+}
+
+impl Display for Session {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.write_str(match self {
+            Self::Solo => "we're playing alone",
+            Self::Host => "we're hosting other players",
+            Self::Client => "we're in someone else's game",
+        })
+    }
+}
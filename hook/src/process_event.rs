@@ -0,0 +1,86 @@
+// Listeners for every UFunction called through ProcessEvent: blueprint events,
+// RPCs, and anything called by name, like our own kismet calls. Listeners run
+// before the function does, so they see its parameters but not its outputs.
+//
+// A listener can ask for only some calls with a Filter, which is checked
+// before it's called, so listening for one function costs every other call a
+// comparison instead of a call. invoke.rs keeps its listeners the same way.
+
+use common::list::{self, Listeners};
+use common::{EFunctionFlags, UFunction, UObject};
+use core::ffi::c_void;
+use core::ptr;
+
+pub type Listener =
+    unsafe fn(object: *mut UObject, function: *mut UFunction, parameters: *mut c_void);
+
+#[derive(Copy, Clone)]
+pub enum Filter {
+    All,
+    Function(*mut UFunction),
+    // Functions with all of these flags, like FUNC_Net for RPCs.
+    Flags(EFunctionFlags),
+}
+
+impl Filter {
+    unsafe fn matches(self, function: *mut UFunction) -> bool {
+        match self {
+            Self::All => true,
+            Self::Function(f) => f == function,
+            Self::Flags(flags) => (*function).FunctionFlags.contains(flags),
+        }
+    }
+}
+
+pub struct Registry<L: Copy> {
+    listeners: Listeners<(Filter, L), 32>,
+}
+
+impl<L: Copy> Registry<L> {
+    pub const fn new() -> Self {
+        Self {
+            listeners: Listeners::new(),
+        }
+    }
+
+    pub fn register(&mut self, filter: Filter, listener: L) -> Result<(), list::Error> {
+        self.listeners.add((filter, listener))
+    }
+
+    // Removes every registration of the listener that `matches`, whatever
+    // its filter.
+    pub fn unregister(&mut self, mut matches: impl FnMut(L) -> bool) {
+        self.listeners.remove_all(|&(_, l)| matches(l));
+    }
+
+    // Calls `f` with each listener whose filter takes `function`.
+    pub unsafe fn dispatch(this: *mut Self, function: *mut UFunction, mut f: impl FnMut(L)) {
+        let listeners = ptr::addr_of_mut!((*this).listeners);
+
+        Listeners::for_each(listeners, |(filter, listener)| {
+            if filter.matches(function) {
+                f(listener);
+            }
+        });
+    }
+}
+
+static mut LISTENERS: Registry<Listener> = Registry::new();
+
+pub unsafe fn register(listener: Listener) -> Result<(), list::Error> {
+    register_for(Filter::All, listener)
+}
+
+pub unsafe fn register_for(filter: Filter, listener: Listener) -> Result<(), list::Error> {
+    LISTENERS.register(filter, listener)
+}
+
+pub unsafe fn unregister(listener: Listener) {
+    LISTENERS.unregister(|l| l as usize == listener as usize);
+}
+
+pub unsafe fn dispatch(object: *mut UObject, function: *mut UFunction, parameters: *mut c_void) {
+    Registry::dispatch(ptr::addr_of_mut!(LISTENERS), function, |listener| {
+        listener(object, function, parameters)
+    });
+}
@@ -0,0 +1,151 @@
+// Times every UFunction called through ProcessEvent, to find the blueprints
+// behind a stutter. "drg.profile on" starts profiling and logs the functions
+// that took the longest every few seconds. The server's /profile gives the
+// same as JSON.
+//
+// A function's total time includes the functions it calls through
+// ProcessEvent. Its exclusive time doesn't.
+
+use common::list;
+use common::UFunction;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use windows::Win32::System::Threading::GetCurrentThreadId;
+
+// How often to log the top functions while profiling, in seconds.
+const REPORT_INTERVAL: f32 = 10.0;
+const REPORT_LEN: usize = 10;
+
+pub struct Stats {
+    // Copied on the first call. A blueprint function can be gone by the time
+    // the stats are read.
+    pub name: String,
+    pub calls: u64,
+    pub total: Duration,
+    pub exclusive: Duration,
+}
+
+static mut ENABLED: bool = false;
+
+// Only calls on the game thread are timed. Calls on other threads would
+// interleave with its calls on CHILD_TIME.
+static mut GAME_THREAD: u32 = 0;
+
+static mut STATS: BTreeMap<*mut UFunction, Stats> = BTreeMap::new();
+
+// For each timed call in progress, the time spent in the calls it made.
+static mut CHILD_TIME: Vec<Duration> = Vec::new();
+
+// When the current stats started being collected.
+static mut STARTED: Option<Instant> = None;
+
+static mut SINCE_REPORT: f32 = 0.0;
+
+pub unsafe fn start() -> Result<(), list::Error> {
+    if ENABLED {
+        return Ok(());
+    }
+
+    crate::tick::register(on_tick)?;
+
+    GAME_THREAD = GetCurrentThreadId();
+    SINCE_REPORT = 0.0;
+    STARTED.get_or_insert_with(Instant::now);
+    ENABLED = true;
+    Ok(())
+}
+
+pub unsafe fn stop() {
+    if !ENABLED {
+        return;
+    }
+
+    ENABLED = false;
+    crate::tick::unregister(on_tick);
+    report();
+}
+
+pub unsafe fn reset() {
+    STATS.clear();
+    STARTED = ENABLED.then(Instant::now);
+}
+
+pub unsafe fn is_enabled() -> bool {
+    ENABLED
+}
+
+// How long the current stats have been collected for.
+pub unsafe fn elapsed() -> Duration {
+    STARTED.map_or(Duration::ZERO, |started| started.elapsed())
+}
+
+// The functions with the most exclusive time, most first.
+pub unsafe fn top(limit: usize) -> Vec<&'static Stats> {
+    let mut stats: Vec<&Stats> = STATS.values().collect();
+    stats.sort_by(|a, b| b.exclusive.cmp(&a.exclusive));
+    stats.truncate(limit);
+    stats
+}
+
+// Called before a function runs. Returns when it started, or None if it isn't
+// being timed.
+pub unsafe fn enter() -> Option<Instant> {
+    if !ENABLED || GetCurrentThreadId() != GAME_THREAD {
+        return None;
+    }
+
+    CHILD_TIME.push(Duration::ZERO);
+    Some(Instant::now())
+}
+
+// Called after the function returns, with what `enter` returned. Still counts
+// the call if profiling stopped while it ran.
+pub unsafe fn exit(function: *mut UFunction, start: Option<Instant>) {
+    let Some(start) = start else {
+        return;
+    };
+
+    let total = start.elapsed();
+    let children = CHILD_TIME.pop().unwrap_or_default();
+
+    if let Some(parent) = CHILD_TIME.last_mut() {
+        *parent += total;
+    }
+
+    let stats = STATS.entry(function).or_insert_with(|| Stats {
+        name: format!("{}", *function),
+        calls: 0,
+        total: Duration::ZERO,
+        exclusive: Duration::ZERO,
+    });
+
+    stats.calls += 1;
+    stats.total += total;
+    stats.exclusive += total.saturating_sub(children);
+}
+
+unsafe fn on_tick(delta_seconds: f32) {
+    SINCE_REPORT += delta_seconds;
+
+    if SINCE_REPORT >= REPORT_INTERVAL {
+        SINCE_REPORT = 0.0;
+        report();
+    }
+}
+
+unsafe fn report() {
+    common::log!(
+        "profile: top functions by exclusive time over {:.1}s",
+        elapsed().as_secs_f32()
+    );
+
+    for stats in top(REPORT_LEN) {
+        common::log!(
+            "profile: {:>8} calls {:>10.2} ms total {:>10.2} ms exclusive  {}",
+            stats.calls,
+            stats.total.as_secs_f64() * 1000.0,
+            stats.exclusive.as_secs_f64() * 1000.0,
+            stats.name
+        );
+    }
+}
@@ -0,0 +1,184 @@
+// What the hook adds to common::prompt, the prompt it waits at on the game's
+// console window:
+//
+// run <line>         one of our commands, or the engine's, logging what it
+//                    printed
+// objects [text]     write every object whose full name contains the text,
+//                    ignoring case, to "objects.txt" next to the game's
+//                    executable
+// toggle <feature>   turn outline, profile, replication, rpctrace or heatmap
+//                    on, or off if it was turned on here
+//
+// continue stops reading the console but leaves the hooks in until eject
+// takes them out and unloads us, typed here or as drg.eject in the game. The console is read on our own thread, so commands wait for
+// the game thread and run between frames.
+
+use crate::tick;
+use common::prompt::{self, Command};
+use common::GUObjectArray;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+const OBJECTS_FILE: &str = "objects.txt";
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "run",
+        help: "<line>, run one of our commands or an engine console command",
+        run: run_command,
+    },
+    Command {
+        name: "objects",
+        help: "[text], write the objects whose names contain the text to objects.txt",
+        run: objects_command,
+    },
+    Command {
+        name: "toggle",
+        help: "outline|profile|replication|rpctrace|heatmap, turn a feature on or off",
+        run: toggle_command,
+    },
+];
+
+#[derive(macros::NoPanicErrorDebug)]
+enum Error {
+    Io(#[from] io::Error),
+    NoFolder,
+}
+
+// A feature whose command takes on|off.
+struct Toggle {
+    name: &'static str,
+    command: &'static str,
+    // Only as far as toggle knows. Turning it on or off from the game's
+    // console doesn't change it.
+    on: AtomicBool,
+}
+
+static TOGGLES: [Toggle; 5] = [
+    Toggle::new("outline", "drg.outline"),
+    Toggle::new("profile", "drg.profile"),
+    Toggle::new("replication", "drg.replication"),
+    Toggle::new("rpctrace", "drg.rpctrace"),
+    Toggle::new("heatmap", "drg.heatmap"),
+];
+
+enum Job {
+    Line(String),
+    Objects(String),
+}
+
+// Filled by the prompt, drained by the game thread.
+static JOBS: Mutex<VecDeque<Job>> = Mutex::new(VecDeque::new());
+
+impl Toggle {
+    const fn new(name: &'static str, command: &'static str) -> Self {
+        Self {
+            name,
+            command,
+            on: AtomicBool::new(false),
+        }
+    }
+}
+
+// Returns once eject is typed or requested.
+pub unsafe fn run() {
+    prompt::run(COMMANDS);
+}
+
+unsafe fn run_command(args: &str) {
+    if args.is_empty() {
+        common::log!("usage: run <line>");
+    } else {
+        queue(Job::Line(args.to_owned()));
+    }
+}
+
+unsafe fn objects_command(args: &str) {
+    queue(Job::Objects(args.to_owned()));
+}
+
+unsafe fn toggle_command(args: &str) {
+    let Some(toggle) = TOGGLES.iter().find(|t| t.name.eq_ignore_ascii_case(args)) else {
+        common::log!("usage: toggle outline|profile|replication|rpctrace|heatmap");
+        return;
+    };
+
+    let on = !toggle.on.fetch_xor(true, Ordering::Relaxed);
+    let state = if on { "on" } else { "off" };
+    queue(Job::Line(format!("{} {}", toggle.command, state)));
+}
+
+fn queue(job: Job) {
+    match JOBS.lock() {
+        Ok(mut jobs) => jobs.push_back(job),
+        Err(_) => return,
+    }
+
+    // If the queue is full, the job waits for the next one that isn't.
+    if tick::run_on_game_thread(run_jobs).is_err() {
+        common::log!("The game thread is busy. The command will run with the next one.");
+    }
+}
+
+unsafe fn run_jobs() {
+    let jobs: Vec<Job> = match JOBS.lock() {
+        Ok(mut jobs) => jobs.drain(..).collect(),
+        Err(_) => return,
+    };
+
+    for job in jobs {
+        match job {
+            Job::Line(line) => run_line(&line),
+            Job::Objects(text) => match dump_objects(&text) {
+                Ok((count, path)) => common::log!("Wrote {} objects to {}", count, path.display()),
+                Err(e) => common::log!("objects: {:?}", e),
+            },
+        }
+    }
+}
+
+unsafe fn run_line(line: &str) {
+    if crate::hooks::run_command(line) {
+        return;
+    }
+
+    match crate::cvar::exec(line) {
+        Ok(output) => {
+            let output = output.trim_end();
+
+            if !output.is_empty() {
+                common::log!("{}", output);
+            }
+        }
+        Err(e) => common::log!("run: {:?}", e),
+    }
+}
+
+// Returns how many objects were written, and where.
+unsafe fn dump_objects(text: &str) -> Result<(usize, PathBuf), Error> {
+    let path = crate::data_dir(OBJECTS_FILE).ok_or(Error::NoFolder)?;
+
+    let text = text.to_ascii_lowercase();
+    let mut out = String::new();
+    let mut name = String::new();
+    let mut count = 0;
+
+    for object in (*GUObjectArray).iter().filter(|o| !o.is_null()) {
+        name.clear();
+        let _ = write!(name, "{}", *object);
+
+        if name.to_ascii_lowercase().contains(&text) {
+            out.push_str(&name);
+            out.push('\n');
+            count += 1;
+        }
+    }
+
+    fs::write(&path, out)?;
+    Ok((count, path))
+}
@@ -0,0 +1,131 @@
+// Logs what the game sends over the network: which classes of actor get
+// replicated and how often, and which RPCs are called. "drg.replication on"
+// starts counting and logs the busiest every few seconds. Useful for working
+// out DRG's netcode, and for checking that something we only do as host
+// doesn't show up in what clients are sent.
+//
+// Actors are counted as UActorChannel::ReplicateActor sends them, which only
+// the host does. It has no signature yet, so it's only hooked when the
+// offsets table has "ReplicateActor". RPCs are counted on both sides.
+
+use common::list;
+use common::{UClass, UFunction, UObject};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+// How often to log the busiest while counting, in seconds.
+const REPORT_INTERVAL: f32 = 10.0;
+const REPORT_LEN: usize = 10;
+
+struct Count {
+    // Kept as text. The keys may be dangling by the time we log.
+    name: String,
+    times: u64,
+    bits: i64,
+}
+
+static mut ENABLED: bool = false;
+
+static mut ACTORS: BTreeMap<*const UClass, Count> = BTreeMap::new();
+static mut RPCS: BTreeMap<*mut UFunction, Count> = BTreeMap::new();
+
+// When the current counts started.
+static mut STARTED: Option<Instant> = None;
+
+static mut SINCE_REPORT: f32 = 0.0;
+
+pub unsafe fn start() -> Result<(), list::Error> {
+    if ENABLED {
+        return Ok(());
+    }
+
+    crate::tick::register(on_tick)?;
+
+    SINCE_REPORT = 0.0;
+    STARTED.get_or_insert_with(Instant::now);
+    ENABLED = true;
+    Ok(())
+}
+
+pub unsafe fn stop() {
+    if !ENABLED {
+        return;
+    }
+
+    ENABLED = false;
+    crate::tick::unregister(on_tick);
+    report();
+}
+
+pub unsafe fn reset() {
+    ACTORS.clear();
+    RPCS.clear();
+    STARTED = ENABLED.then(Instant::now);
+}
+
+// Called after `actor` is replicated, with the number of bits that were sent
+// for it, which is 0 when none of its properties changed.
+pub unsafe fn actor_replicated(actor: *mut UObject, bits: i64) {
+    if !ENABLED || actor.is_null() {
+        return;
+    }
+
+    let class = (*actor).class();
+    count(&mut ACTORS, class, || (*class).name().to_string(), bits);
+}
+
+// Called as `function` is sent to the other side.
+pub unsafe fn rpc_sent(function: *mut UFunction) {
+    if ENABLED {
+        count(&mut RPCS, function, || format!("{}", *function), 0);
+    }
+}
+
+fn count<K: Ord>(
+    counts: &mut BTreeMap<K, Count>,
+    key: K,
+    name: impl FnOnce() -> String,
+    bits: i64,
+) {
+    let count = counts.entry(key).or_insert_with(|| Count {
+        name: name(),
+        times: 0,
+        bits: 0,
+    });
+
+    count.times += 1;
+    count.bits += bits;
+}
+
+unsafe fn on_tick(delta_seconds: f32) {
+    SINCE_REPORT += delta_seconds;
+
+    if SINCE_REPORT >= REPORT_INTERVAL {
+        SINCE_REPORT = 0.0;
+        report();
+    }
+}
+
+unsafe fn report() {
+    let seconds = STARTED
+        .map_or(0.0, |started| started.elapsed().as_secs_f64())
+        .max(f64::EPSILON);
+
+    common::log!("replication: busiest over {:.1}s", seconds);
+
+    for (kind, counts) in [("actor", &ACTORS), ("rpc", &RPCS)] {
+        let mut counts: Vec<&Count> = counts.values().collect();
+        counts.sort_by(|a, b| b.times.cmp(&a.times));
+
+        for count in counts.into_iter().take(REPORT_LEN) {
+            common::log!(
+                "replication: {:<5} {:>8} times {:>8.1}/s {:>10} bytes  {}",
+                kind,
+                count.times,
+                count.times as f64 / seconds,
+                count.bits / 8,
+                count.name
+            );
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// Records every RPC, in order, for reading the game's netcode as a timeline.
+// "drg.rpctrace on" starts recording and "drg.rpctrace off" writes what was
+// recorded to the "traces" folder next to the game's executable as JSON:
+//
+// {"seconds":12.5,"dropped":0,"calls":[
+//     {"at":0.016,"side":"sent","kind":"server","function":"Function /Script/FSD.X.ServerY",
+//      "object":"...","owners":["...","..."],"stack":["...","..."]},
+//     ...
+// ]}
+//
+// A call is "sent" when it goes over the network, and "run" when it runs
+// here, which is how the side it was sent to sees it. Calls made on the side
+// that runs them, like a server RPC called by the host, are only "run".
+// `owners` is the object's chain of Owners, the nearest first, which ends at
+// the player controller for a player's RPCs. `stack` is the chain of
+// blueprint functions that made the call, the innermost first. It's empty when
+// native code made the call, which is also where a blueprint event called by
+// the engine starts.
+
+use crate::invoke;
+use crate::process_event::Filter;
+use common::list;
+use common::{EFunctionFlags, FFrame, JsonString, UFunction, UObject};
+use core::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use windows::Win32::System::Threading::GetCurrentThreadId;
+
+const TRACES_FOLDER: &str = "traces";
+
+// Further calls are counted but not recorded, so that a forgotten trace
+// doesn't eat all the memory.
+const MAX_CALLS: usize = 100_000;
+
+// Owner chains are short. This only guards against a cycle.
+const MAX_OWNERS: usize = 8;
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    List(#[from] list::Error),
+    Io(#[from] io::Error),
+    NoFolder,
+}
+
+struct Call {
+    // Since the trace started.
+    at: f64,
+    sent: bool,
+    kind: &'static str,
+    // Taken at the call, since the objects may be gone by the time it's saved.
+    function: String,
+    object: String,
+    owners: Vec<String>,
+    stack: Vec<String>,
+}
+
+static mut ENABLED: bool = false;
+
+// Only calls on the game thread are recorded, so that CALLS isn't shared.
+static mut GAME_THREAD: u32 = 0;
+
+static mut CALLS: Vec<Call> = Vec::new();
+static mut DROPPED: u64 = 0;
+static mut STARTED: Option<Instant> = None;
+
+pub unsafe fn start() -> Result<(), list::Error> {
+    if ENABLED {
+        return Ok(());
+    }
+
+    invoke::register_for(Filter::Flags(EFunctionFlags::FUNC_Net), on_invoke)?;
+
+    GAME_THREAD = GetCurrentThreadId();
+    CALLS.clear();
+    DROPPED = 0;
+    STARTED = Some(Instant::now());
+    ENABLED = true;
+    Ok(())
+}
+
+// Stops recording and writes the trace. Returns its path.
+pub unsafe fn stop() -> Result<PathBuf, Error> {
+    if ENABLED {
+        ENABLED = false;
+        invoke::unregister(on_invoke);
+    }
+
+    save()
+}
+
+pub unsafe fn is_enabled() -> bool {
+    ENABLED
+}
+
+// Called as `function` is sent over the network, with the frame of the
+// blueprint that called it, if one did.
+pub unsafe fn sent(object: *mut UObject, function: *mut UFunction, stack: *mut FFrame) {
+    record(object, function, stack, true);
+}
+
+unsafe fn on_invoke(object: *mut UObject, function: *mut UFunction, stack: *mut FFrame) {
+    record(object, function, stack, false);
+}
+
+unsafe fn record(object: *mut UObject, function: *mut UFunction, stack: *mut FFrame, sent: bool) {
+    if !ENABLED
+        || GetCurrentThreadId() != GAME_THREAD
+        || !(*function).FunctionFlags.contains(EFunctionFlags::FUNC_Net)
+    {
+        return;
+    }
+
+    if CALLS.len() >= MAX_CALLS {
+        DROPPED += 1;
+        return;
+    }
+
+    CALLS.push(Call {
+        at: STARTED.map_or(0.0, |started| started.elapsed().as_secs_f64()),
+        sent,
+        kind: kind(&(*function).FunctionFlags),
+        function: format!("{}", *function),
+        object: format!("{}", *object),
+        owners: owners(object),
+        stack: invoke::script_stack(function, stack),
+    });
+}
+
+fn kind(flags: &EFunctionFlags) -> &'static str {
+    if flags.contains(EFunctionFlags::FUNC_NetServer) {
+        "server"
+    } else if flags.contains(EFunctionFlags::FUNC_NetClient) {
+        "client"
+    } else if flags.contains(EFunctionFlags::FUNC_NetMulticast) {
+        "multicast"
+    } else {
+        "net"
+    }
+}
+
+unsafe fn owners(object: *mut UObject) -> Vec<String> {
+    let mut owners = Vec::new();
+    let mut owner = (*object).object_property("Owner");
+
+    while !owner.is_null() && owner != object && owners.len() < MAX_OWNERS {
+        owners.push(format!("{}", *owner));
+        owner = (*owner).object_property("Owner");
+    }
+
+    owners
+}
+
+unsafe fn save() -> Result<PathBuf, Error> {
+    let folder = crate::data_dir(TRACES_FOLDER).ok_or(Error::NoFolder)?;
+
+    fs::create_dir_all(&folder)?;
+
+    let seconds = STARTED.map_or(0.0, |started| started.elapsed().as_secs_f64());
+    let mut json = format!(
+        "{{\"seconds\":{},\"dropped\":{},\"calls\":[",
+        seconds, DROPPED
+    );
+
+    for (i, call) in CALLS.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+
+        let _ = write!(
+            json,
+            "\n{{\"at\":{:.6},\"side\":\"{}\",\"kind\":\"{}\",\"function\":\"{}\",\"object\":\"{}\",\"owners\":[",
+            call.at,
+            if call.sent { "sent" } else { "run" },
+            call.kind,
+            JsonString(&call.function),
+            JsonString(&call.object)
+        );
+
+        write_strings(&mut json, &call.owners);
+        json.push_str("],\"stack\":[");
+        write_strings(&mut json, &call.stack);
+        json.push_str("]}");
+    }
+
+    json.push_str("\n]}");
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+
+    let path = folder.join(format!("rpc-{}.json", stamp));
+    fs::write(&path, json)?;
+    Ok(path)
+}
+
+fn write_strings(json: &mut String, strings: &[String]) {
+    for (i, string) in strings.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+
+        let _ = write!(json, "\"{}\"", JsonString(string));
+    }
+}
@@ -0,0 +1,264 @@
+// The console side of common::scan. A session of drg.scan commands narrows
+// down where a value lives:
+//
+// drg.scan new i32 100    every i32 that's 100
+// drg.scan exact 95       those that are now 95
+// drg.scan changed        those that changed since the last scan
+// drg.scan unchanged      those that didn't
+// drg.scan list           the first few, with their current values
+// drg.scan set 3 999      write 999 to the fourth one
+// drg.scan pin 3 [999]    freeze it at its current value, or 999. see freeze.rs
+// drg.scan clear          forget the candidates
+//
+// Candidates are forgotten when the level changes too, since they were most
+// likely in the last level's objects.
+//
+// Scanning reads all of the game's memory, which takes seconds, so new, exact,
+// changed and unchanged run on their own thread and log when they're done.
+
+use crate::event::{self, Event};
+use crate::freeze::{self, Freeze};
+use crate::policy::{Feature, Scope};
+use crate::tick;
+use common::list;
+use common::scan::{self, Candidate, Filter, Value, ValueType};
+use std::mem;
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+// More than this and the first scan was too vague to be worth narrowing.
+const MAX_CANDIDATES: usize = 1_000_000;
+
+const LIST_LEN: usize = 20;
+
+const USAGE: &str = "usage: drg.scan new <u8|i16|i32|i64|f32|f64> <value> | exact <value> | \
+                     changed | unchanged | list | set <n> <value> | pin <n> [value] | clear";
+
+static mut CANDIDATES: Vec<Candidate> = Vec::new();
+
+// The scan that's running, if any.
+static mut WORKER: Option<JoinHandle<()>> = None;
+
+// Bumped when the level changes, so that a scan started in the last level is
+// thrown away.
+static mut GENERATION: u32 = 0;
+
+// A finished scan's candidates, and the generation it started in, for the
+// game thread to pick up.
+static RESULT: Mutex<Option<(u32, Vec<Candidate>)>> = Mutex::new(None);
+
+static SET: Feature = Feature {
+    name: "drg.scan set",
+    scope: Scope::HostOnly,
+    disable: None,
+};
+
+pub unsafe fn subscribe() -> Result<(), list::Error> {
+    event::subscribe(on_event)
+}
+
+pub unsafe fn unsubscribe() {
+    event::unsubscribe(on_event);
+
+    // It's running our code, which is about to be unloaded.
+    if let Some(worker) = WORKER.take() {
+        let _ = worker.join();
+    }
+}
+
+pub unsafe fn run(args: &str) {
+    finish();
+
+    if WORKER.is_some() {
+        common::log!("drg.scan: still scanning");
+        return;
+    }
+
+    let mut words = args.split_whitespace();
+
+    match (words.next(), words.next(), words.next()) {
+        (Some("new"), Some(value_type), Some(value)) => {
+            let Some(value_type) = ValueType::parse(value_type) else {
+                common::log!("{}", USAGE);
+                return;
+            };
+
+            let Some(value) = parse(value_type, value) else {
+                return;
+            };
+
+            start(move || scan::first_scan(value, MAX_CANDIDATES));
+        }
+        (Some("exact"), Some(value), None) => {
+            let Some(value_type) = CANDIDATES.first().map(|c| c.value.value_type()) else {
+                common::log!("drg.scan: no candidates. start with drg.scan new");
+                return;
+            };
+
+            if let Some(value) = parse(value_type, value) {
+                refine(Filter::Exact(value));
+            }
+        }
+        (Some("changed"), None, None) => refine(Filter::Changed),
+        (Some("unchanged"), None, None) => refine(Filter::Unchanged),
+        (Some("list"), None, None) => list(),
+        (Some("set"), Some(index), Some(value)) => {
+            let Some(candidate) = candidate(index) else {
+                return;
+            };
+
+            if !crate::policy::enable(&SET) {
+                return;
+            }
+
+            if let Some(value) = parse(candidate.value.value_type(), value) {
+                if !scan::write(candidate.address, value) {
+                    common::log!("drg.scan: can't write to {:#x}", candidate.address);
+                }
+            }
+        }
+        (Some("pin"), Some(index), value) => {
+            let Some(mut candidate) = candidate(index) else {
+                return;
+            };
+
+            if !crate::policy::enable(&freeze::FEATURE) {
+                return;
+            }
+
+            candidate.value = match value {
+                Some(value) => match parse(candidate.value.value_type(), value) {
+                    Some(value) => value,
+                    None => return,
+                },
+                None => match scan::read(candidate.address, candidate.value.value_type()) {
+                    Some(value) => value,
+                    None => {
+                        common::log!("drg.scan: can't read {:#x}", candidate.address);
+                        return;
+                    }
+                },
+            };
+
+            let freeze = Freeze::Address {
+                address: candidate.address,
+                value: candidate.value,
+            };
+
+            if let Err(e) = freeze::add(freeze) {
+                common::log!("drg.scan: failed to pin: {:?}", e);
+            }
+        }
+        (Some("clear"), None, None) => CANDIDATES = Vec::new(),
+        _ => common::log!("{}", USAGE),
+    }
+}
+
+unsafe fn on_event(event: &Event) {
+    if let Event::WorldChanged { .. } = *event {
+        GENERATION = GENERATION.wrapping_add(1);
+
+        if !CANDIDATES.is_empty() {
+            common::log!(
+                "drg.scan: forgot {} candidates from the last level",
+                CANDIDATES.len()
+            );
+
+            CANDIDATES = Vec::new();
+        }
+    }
+}
+
+unsafe fn refine(filter: Filter) {
+    let mut candidates = mem::take(&mut CANDIDATES);
+
+    start(move || {
+        scan::refine(&mut candidates, filter);
+        candidates
+    });
+}
+
+unsafe fn start(scan: impl FnOnce() -> Vec<Candidate> + Send + 'static) {
+    let generation = GENERATION;
+
+    let worker = thread::spawn(move || {
+        let candidates = scan();
+
+        if let Ok(mut result) = RESULT.lock() {
+            *result = Some((generation, candidates));
+        }
+
+        // If the queue is full, the next drg.scan picks the result up.
+        let _ = tick::run_on_game_thread(finish);
+    });
+
+    WORKER = Some(worker);
+    common::log!("drg.scan: scanning");
+}
+
+// Takes the finished scan's candidates, if there are any.
+unsafe fn finish() {
+    let Some((generation, candidates)) = RESULT.lock().ok().and_then(|mut result| result.take())
+    else {
+        return;
+    };
+
+    if let Some(worker) = WORKER.take() {
+        let _ = worker.join();
+    }
+
+    if generation == GENERATION {
+        CANDIDATES = candidates;
+        log_count();
+    } else {
+        common::log!("drg.scan: threw away a scan from the last level");
+    }
+}
+
+unsafe fn log_count() {
+    common::log!("drg.scan: {} candidates", CANDIDATES.len());
+
+    if CANDIDATES.len() == MAX_CANDIDATES {
+        common::log!("drg.scan: stopped at the limit. try a rarer value");
+    }
+
+    if !CANDIDATES.is_empty() && CANDIDATES.len() <= LIST_LEN {
+        list();
+    }
+}
+
+unsafe fn list() {
+    for (i, candidate) in CANDIDATES.iter().take(LIST_LEN).enumerate() {
+        match scan::read(candidate.address, candidate.value.value_type()) {
+            Some(value) => common::log!("drg.scan: {}: {:#x} = {}", i, candidate.address, value),
+            None => common::log!("drg.scan: {}: {:#x} is gone", i, candidate.address),
+        }
+    }
+
+    if CANDIDATES.len() > LIST_LEN {
+        common::log!("drg.scan: and {} more", CANDIDATES.len() - LIST_LEN);
+    }
+}
+
+unsafe fn candidate(index: &str) -> Option<Candidate> {
+    let candidate = index
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| CANDIDATES.get(index).copied());
+
+    if candidate.is_none() {
+        common::log!("drg.scan: no candidate {}. see drg.scan list", index);
+    }
+
+    candidate
+}
+
+fn parse(value_type: ValueType, text: &str) -> Option<Value> {
+    let value = Value::parse(value_type, text);
+
+    if value.is_none() {
+        common::log!("drg.scan: {} isn't a valid value", text);
+    }
+
+    value
+}
@@ -0,0 +1,82 @@
+use crate::tick::Task;
+use common::list::{self, List};
+
+struct Scheduled {
+    id: u32,
+    remaining: f32,
+    period: Option<f32>,
+    task: Task,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Handle(u32);
+
+const MAX_SCHEDULED: usize = 64;
+
+static mut SCHEDULED: List<Scheduled, MAX_SCHEDULED> = List::new();
+static mut NEXT_ID: u32 = 0;
+
+unsafe fn schedule(seconds: f32, period: Option<f32>, task: Task) -> Result<Handle, list::Error> {
+    let id = NEXT_ID;
+
+    SCHEDULED.push(Scheduled {
+        id,
+        remaining: seconds,
+        period,
+        task,
+    })?;
+
+    NEXT_ID = NEXT_ID.wrapping_add(1);
+    Ok(Handle(id))
+}
+
+// Run `task` once, `seconds` from now.
+#[allow(dead_code)]
+pub unsafe fn after(seconds: f32, task: Task) -> Result<Handle, list::Error> {
+    schedule(seconds, None, task)
+}
+
+// Run `task` every `seconds`, starting `seconds` from now.
+pub unsafe fn every(seconds: f32, task: Task) -> Result<Handle, list::Error> {
+    schedule(seconds, Some(seconds), task)
+}
+
+pub unsafe fn cancel(Handle(id): Handle) -> bool {
+    if let Some(index) = SCHEDULED.iter().position(|s| s.id == id) {
+        SCHEDULED.swap_remove(index).is_ok()
+    } else {
+        false
+    }
+}
+
+// Due tasks run after the bookkeeping, so that they can schedule and cancel
+// tasks, themselves included, without any being skipped.
+pub unsafe fn tick(delta_seconds: f32) {
+    let mut due = List::<Task, MAX_SCHEDULED>::new();
+
+    SCHEDULED.retain(|scheduled| {
+        scheduled.remaining -= delta_seconds;
+
+        if scheduled.remaining > 0.0 {
+            return true;
+        }
+
+        // Can't fail, since both lists hold the same number.
+        let _ = due.push(scheduled.task);
+
+        match scheduled.period {
+            Some(period) => {
+                // Carry over the overshoot so that repeating tasks don't
+                // drift, but don't try to catch up on frames longer than the
+                // period.
+                scheduled.remaining = (scheduled.remaining + period).max(0.0);
+                true
+            }
+            None => false,
+        }
+    });
+
+    for &task in due.iter() {
+        task();
+    }
+}
@@ -0,0 +1,533 @@
+// Lua scripts, for tweaks that don't need a rebuilt DLL. Every *.lua file in
+// the "scripts" folder next to the game's executable is run at startup and
+// again whenever any of them changes. Scripts run on the game thread.
+//
+// local player = ue.find_all("PlayerCharacter")[1] -- never a class default
+// print(player:get("Health"))
+// player:set("bCanSprint", true)
+// player:call("Jump")
+//
+// ue.on("/Script/Engine.Actor.ReceiveDestroyed", function(object, params)
+//     print(tostring(object) .. " was destroyed")
+// end)
+//
+// ue.on_tick(function(delta_seconds) end)
+// ue.command("drg.outline on")
+//
+// set, call and command are host-only. See policy.
+
+use crate::policy::{self, Change};
+use crate::process_event;
+use common::inspect::{self, Primitive};
+use common::list::{self, Listeners};
+use common::{
+    EClassCastFlags, EPropertyFlags, FProperty, GUObjectArray, ObjectHandle, PropertyValue,
+    UFunction, UObject,
+};
+use core::ffi::c_void;
+use core::mem;
+use core::ptr;
+use mlua::{
+    AnyUserData, Function, Lua, MetaMethod, RegistryKey, Table, UserData, UserDataMethods, Value,
+    Variadic,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+const SCRIPTS_FOLDER: &str = "scripts";
+
+// How often to look for changed scripts, in seconds.
+const RELOAD_CHECK_INTERVAL: f32 = 1.0;
+
+// Each of ue.on() and ue.on_tick() can hold this many.
+const MAX_CALLBACKS: usize = 256;
+
+static mut LUA: Option<Lua> = None;
+
+// Each script file and when it was last modified, as of the last load.
+static mut LOADED: Vec<(PathBuf, SystemTime)> = Vec::new();
+
+static mut SINCE_RELOAD_CHECK: f32 = RELOAD_CHECK_INTERVAL;
+
+// Callbacks from ue.on() and ue.on_tick(). Shared, so that a dispatch can hold
+// on to one while it runs.
+static mut LISTENERS: Listeners<(*mut UFunction, Rc<RegistryKey>), MAX_CALLBACKS> =
+    Listeners::new();
+static mut TICK_CALLBACKS: Listeners<Rc<RegistryKey>, MAX_CALLBACKS> = Listeners::new();
+
+// Set while a script is running, so that the functions a script calls don't
+// raise events in scripts. Otherwise a listener could recurse into itself.
+static mut IN_SCRIPT: bool = false;
+
+// Unloads every script when dropped. Only drop it once nothing can call into
+// scripts, after the tick and ProcessEvent hooks are gone.
+pub struct Scripts;
+
+impl Drop for Scripts {
+    fn drop(&mut self) {
+        unsafe {
+            unload();
+        }
+    }
+}
+
+pub unsafe fn tick(delta_seconds: f32) {
+    SINCE_RELOAD_CHECK += delta_seconds;
+
+    if SINCE_RELOAD_CHECK >= RELOAD_CHECK_INTERVAL {
+        SINCE_RELOAD_CHECK = 0.0;
+        reload_if_changed();
+    }
+
+    let Some(lua) = LUA.as_ref() else {
+        return;
+    };
+
+    Listeners::for_each(ptr::addr_of_mut!(TICK_CALLBACKS), |key| {
+        if let Ok(callback) = lua.registry_value::<Function>(&key) {
+            run(callback, delta_seconds);
+        }
+    });
+}
+
+unsafe fn reload_if_changed() {
+    let scripts = list_scripts();
+
+    if scripts == LOADED {
+        return;
+    }
+
+    unload();
+    LOADED = scripts.clone();
+
+    if scripts.is_empty() {
+        return;
+    }
+
+    let lua = Lua::new();
+
+    if let Err(e) = install_api(&lua) {
+        common::log!("script: failed to set up the ue table: {}", e);
+        return;
+    }
+
+    if let Err(e) = process_event::register(on_process_event) {
+        common::log!("script: failed to listen for events: {:?}", e);
+    }
+
+    for (path, _) in &scripts {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+        let result = match fs::read_to_string(path) {
+            Ok(source) => {
+                IN_SCRIPT = true;
+                let result = lua.load(&source).set_name(name.as_ref()).exec();
+                IN_SCRIPT = false;
+                result.map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        match result {
+            Ok(()) => common::log!("script: loaded {}", name),
+            Err(e) => common::log!("script: {}: {}", name, e),
+        }
+    }
+
+    LUA = Some(lua);
+}
+
+unsafe fn unload() {
+    // The keys refer to the old state, so they go with it.
+    LISTENERS.clear();
+    TICK_CALLBACKS.clear();
+    LUA = None;
+    process_event::unregister(on_process_event);
+}
+
+// Every *.lua file in the scripts folder, with when it was last modified.
+fn list_scripts() -> Vec<(PathBuf, SystemTime)> {
+    crate::data_files(SCRIPTS_FOLDER, "lua")
+        .into_iter()
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).ok()?.modified().ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+unsafe fn on_process_event(
+    object: *mut UObject,
+    function: *mut UFunction,
+    parameters: *mut c_void,
+) {
+    if IN_SCRIPT || LISTENERS.is_empty() {
+        return;
+    }
+
+    let Some(lua) = LUA.as_ref() else {
+        return;
+    };
+
+    Listeners::for_each(ptr::addr_of_mut!(LISTENERS), |(listened, key)| {
+        if listened != function {
+            return;
+        }
+
+        let Ok(callback) = lua.registry_value::<Function>(&key) else {
+            return;
+        };
+
+        match parameters_table(lua, function, parameters) {
+            Ok(params) => run(callback, (Object::new(object), params)),
+            Err(e) => common::log!("script: {}", e),
+        }
+    });
+}
+
+// Calls a script's callback, logging any error it raises.
+unsafe fn run<'lua>(callback: Function<'lua>, args: impl mlua::IntoLuaMulti<'lua>) {
+    IN_SCRIPT = true;
+    let result = callback.call::<_, ()>(args);
+    IN_SCRIPT = false;
+
+    if let Err(e) = result {
+        common::log!("script: {}", e);
+    }
+}
+
+unsafe fn install_api(lua: &Lua) -> mlua::Result<()> {
+    let ue = lua.create_table()?;
+
+    ue.set(
+        "find",
+        lua.create_function(|_, path: String| {
+            let object = (*GUObjectArray).find_by_path(&path);
+            Ok((!object.is_null()).then(|| Object::new(object)))
+        })?,
+    )?;
+
+    ue.set(
+        "find_all",
+        lua.create_function(|_, class: String| {
+            let objects: Vec<Object> = (*GUObjectArray)
+                .iter()
+                .filter(|&object| {
+                    !object.is_null()
+                        && !(*object).is_default_object()
+                        && inspect::is_a(object, &class)
+                })
+                .map(|object| Object::new(object))
+                .collect();
+
+            Ok(objects)
+        })?,
+    )?;
+
+    ue.set(
+        "on",
+        lua.create_function(|lua, (path, callback): (String, Function)| {
+            let function = (*GUObjectArray).find_by_path(&path);
+
+            if function.is_null() || !(*function).fast_is(EClassCastFlags::CASTCLASS_UFunction) {
+                return Err(error(format!("no function called {}", path)));
+            }
+
+            let key = Rc::new(lua.create_registry_value(callback)?);
+            LISTENERS.add((function.cast(), key)).map_err(full)
+        })?,
+    )?;
+
+    ue.set(
+        "on_tick",
+        lua.create_function(|lua, callback: Function| {
+            let key = Rc::new(lua.create_registry_value(callback)?);
+            TICK_CALLBACKS.add(key).map_err(full)
+        })?,
+    )?;
+
+    ue.set(
+        "command",
+        lua.create_function(|_, line: String| {
+            policy::apply(Change::Command(&line)).map_err(refused)
+        })?,
+    )?;
+
+    let log = lua.create_function(|_, values: Variadic<Value>| {
+        let mut line = String::new();
+
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                line.push(' ');
+            }
+
+            line.push_str(&value.to_string()?);
+        }
+
+        common::log!("{}", line);
+        Ok(())
+    })?;
+
+    ue.set("log", log.clone())?;
+
+    let globals = lua.globals();
+    globals.set("print", log)?;
+    globals.set("ue", ue)
+}
+
+// A weak reference, so that a script holding on to an object doesn't crash
+// the game once it's destroyed.
+#[derive(Copy, Clone)]
+struct Object(ObjectHandle<UObject>);
+
+impl Object {
+    unsafe fn new(object: *mut UObject) -> Self {
+        Self(ObjectHandle::new(object))
+    }
+
+    unsafe fn get(&self) -> mlua::Result<*mut UObject> {
+        self.0
+            .resolve()
+            .ok_or_else(|| error("the object no longer exists"))
+    }
+}
+
+impl UserData for Object {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method("get", |lua, this, name: String| unsafe {
+            let object = this.get()?;
+            let property = find_property(object, &name)?;
+            to_lua(lua, property, object.cast())
+        });
+
+        methods.add_method("set", |_, this, (name, value): (String, Value)| unsafe {
+            let object = this.get()?;
+            let property = find_property(object, &name)?;
+
+            let change = Change::Set {
+                object,
+                property,
+                value: primitive(property, value)?,
+            };
+
+            policy::apply(change).map(|_| ()).map_err(refused)
+        });
+
+        methods.add_method(
+            "call",
+            |lua, this, (name, args): (String, Option<Table>)| unsafe {
+                call(lua, this.get()?, &name, args)
+            },
+        );
+
+        methods.add_method("name", |_, this, ()| unsafe {
+            Ok(String::from((*this.get()?).name()))
+        });
+
+        methods.add_method("class", |_, this, ()| unsafe {
+            Ok(String::from((*(*this.get()?).class()).name()))
+        });
+
+        methods.add_method("is_valid", |_, this, ()| unsafe {
+            Ok(this.0.resolve().is_some())
+        });
+
+        methods.add_meta_method(MetaMethod::ToString, |_, this, ()| unsafe {
+            Ok(match this.0.resolve() {
+                Some(object) => format!("{}", *object),
+                None => String::from("(destroyed object)"),
+            })
+        });
+
+        methods.add_meta_method(MetaMethod::Eq, |_, this, other: AnyUserData| unsafe {
+            let other = other.borrow::<Object>()?;
+            Ok(this.0.resolve() == other.0.resolve())
+        });
+    }
+}
+
+// Calls `object`'s function `name` with `args`, a table of parameters by
+// name. Returns the return value and out parameters in a table, by name.
+unsafe fn call<'lua>(
+    lua: &'lua Lua,
+    object: *mut UObject,
+    name: &str,
+    args: Option<Table<'lua>>,
+) -> mlua::Result<Table<'lua>> {
+    let function = (*(*object).class()).find_function(name);
+
+    if function.is_null() {
+        return Err(error(format!("no function called {}", name)));
+    }
+
+    // u128s so that parameters like FTransform are aligned. Zeroed is a valid
+    // empty value for every parameter type, including strings and arrays.
+    let mut parms = vec![0u128; (*function).parms_size().div_ceil(mem::size_of::<u128>())];
+    let container = parms.as_mut_ptr().cast::<c_void>();
+
+    if let Some(args) = args {
+        for (key, value) in args.pairs::<String, Value>().flatten() {
+            let property = (*function).find_property(&key);
+
+            if property.is_null() || !(*property).flags().contains(EPropertyFlags::CPF_Parm) {
+                return Err(error(format!("{} has no parameter called {}", name, key)));
+            }
+
+            write(property, container, value)?;
+        }
+    }
+
+    let change = Change::Call {
+        object,
+        function,
+        parameters: container,
+    };
+
+    policy::apply(change).map_err(refused)?;
+
+    let outputs = lua.create_table()?;
+
+    for_each_parameter(function, |property| {
+        let flags = (*property).flags();
+
+        if flags.contains(EPropertyFlags::CPF_OutParm)
+            || flags.contains(EPropertyFlags::CPF_ReturnParm)
+        {
+            outputs.set((*property).name(), to_lua(lua, property, container)?)?;
+        }
+
+        Ok(())
+    })?;
+
+    // Strings and arrays the function returned are leaked. The engine's
+    // allocator would have to free them.
+    Ok(outputs)
+}
+
+// The parameters of a call to `function`, by name.
+unsafe fn parameters_table<'lua>(
+    lua: &'lua Lua,
+    function: *mut UFunction,
+    parameters: *mut c_void,
+) -> mlua::Result<Table<'lua>> {
+    let table = lua.create_table()?;
+
+    if !parameters.is_null() {
+        for_each_parameter(function, |property| {
+            table.set((*property).name(), to_lua(lua, property, parameters)?)
+        })?;
+    }
+
+    Ok(table)
+}
+
+unsafe fn for_each_parameter(
+    function: *mut UFunction,
+    mut f: impl FnMut(*const FProperty) -> mlua::Result<()>,
+) -> mlua::Result<()> {
+    let mut result = Ok(());
+
+    inspect::for_each_property(function.cast(), |property| {
+        if result.is_ok() && (*property).flags().contains(EPropertyFlags::CPF_Parm) {
+            result = f(property);
+        }
+    });
+
+    result
+}
+
+unsafe fn find_property(object: *mut UObject, name: &str) -> mlua::Result<*const FProperty> {
+    let property = (*(*object).class()).find_property(name);
+
+    if property.is_null() {
+        Err(error(format!("no property called {}", name)))
+    } else {
+        Ok(property)
+    }
+}
+
+// Booleans, numbers and objects as themselves. Anything else as the text the
+// object browser shows, like "{Vector}".
+unsafe fn to_lua<'lua>(
+    lua: &'lua Lua,
+    property: *const FProperty,
+    container: *const c_void,
+) -> mlua::Result<Value<'lua>> {
+    Ok(match inspect::read_primitive(property, container) {
+        Some(Primitive::Bool(b)) => Value::Boolean(b),
+        Some(Primitive::Integer(n)) => Value::Integer(n),
+        Some(Primitive::Float(n)) => Value::Number(n),
+        Some(Primitive::Object(object)) if object.is_null() => Value::Nil,
+        Some(Primitive::Object(object)) => {
+            Value::UserData(lua.create_userdata(Object::new(object))?)
+        }
+        None => {
+            let text = format!(
+                "{}",
+                PropertyValue {
+                    property,
+                    container,
+                }
+            );
+
+            Value::String(lua.create_string(&text)?)
+        }
+    })
+}
+
+unsafe fn write(
+    property: *const FProperty,
+    container: *mut c_void,
+    value: Value,
+) -> mlua::Result<()> {
+    let primitive = primitive(property, value)?;
+
+    inspect::write_primitive(property, container, primitive)
+        .map_err(|e| error(format!("can't set {}: {:?}", (*property).name(), e)))
+}
+
+// Objects can be given as objects, paths, or nil.
+unsafe fn primitive(property: *const FProperty, value: Value) -> mlua::Result<Primitive> {
+    let name = (*property).name();
+
+    Ok(match value {
+        Value::Boolean(b) => Primitive::Bool(b),
+        Value::Integer(n) => Primitive::Integer(n),
+        Value::Number(n) => Primitive::Float(n),
+        Value::Nil => Primitive::Object(ptr::null_mut()),
+        Value::UserData(ref object) => Primitive::Object(object.borrow::<Object>()?.get()?),
+        Value::String(ref path) => {
+            let object = (*GUObjectArray).find_by_path(path.to_str()?);
+
+            if object.is_null() {
+                return Err(error(format!("no object called {}", path.to_str()?)));
+            }
+
+            Primitive::Object(object)
+        }
+        _ => {
+            return Err(error(format!(
+                "{} can't be set to a {}",
+                name,
+                value.type_name()
+            )))
+        }
+    })
+}
+
+fn full(_: list::Error) -> mlua::Error {
+    error(format!("more than {} callbacks", MAX_CALLBACKS))
+}
+
+fn refused(e: policy::Error) -> mlua::Error {
+    match e {
+        policy::Error::Refused => error("not allowed in this game, see drg.policy"),
+        policy::Error::Write(e) => error(format!("can't set: {:?}", e)),
+    }
+}
+
+fn error(message: impl Into<String>) -> mlua::Error {
+    mlua::Error::RuntimeError(message.into())
+}
@@ -0,0 +1,654 @@
+// A small HTTP server on localhost for browsing live objects from a web page
+// or a script. Every endpoint answers with JSON:
+//
+// GET /objects?class=PlayerCharacter&limit=100
+//     Objects of a class or its subclasses.
+// GET /search?name=health&limit=100
+//     Objects whose name contains the text, ignoring case.
+// GET /object?index=123456
+// GET /object?path=/Game/GameData.GameData
+//     An object's reflected properties.
+// GET /profile?limit=20
+//     The functions that took the longest while profiling. See profiler.rs.
+// GET /rpc
+//     A WebSocket for remote control. See rpc.rs.
+//
+// Requests are read on the server's own threads but answered on the game
+// thread between frames, so that objects can't change or be collected while
+// we read them.
+//
+// Browsers may only read the answers from pages in ALLOWED_ORIGINS, so that
+// a web page we happen to have open can't see into the game. The WebSocket can
+// change the game, so it also refuses other origins outright, and wants the
+// token the server makes each time it starts, as /rpc?token=... It's logged
+// and written to "rpc-token.txt" next to the game's executable.
+
+use crate::{profiler, tick};
+use common::win::random;
+use common::{inspect, GUObjectArray, JsonString, PropertyValue, UObject};
+use core::fmt::{self, Write as _};
+use core::ptr;
+use core::str;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+mod rpc;
+pub use rpc::publish;
+
+mod websocket;
+use websocket::Frame;
+
+const ADDRESS: &str = "127.0.0.1:8377";
+
+// Pages served from here. Tools that aren't browsers send no Origin.
+const ALLOWED_ORIGINS: &[&str] = &["http://127.0.0.1:8377", "http://localhost:8377"];
+
+const TOKEN_FILE: &str = "rpc-token.txt";
+
+// For listings that don't give a limit. Keeps a bare /search?name=a from
+// building a response with every object in the game.
+const DEFAULT_LIMIT: usize = 1000;
+
+// Anything longer isn't a GET from a browser.
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+
+// How often the server checks whether it should stop, and how often
+// WebSockets are sent their notifications.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// The game thread doesn't tick during long hitches, like loading a mission.
+const GAME_THREAD_TIMEOUT: Duration = Duration::from_secs(5);
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(5);
+
+static STOP: AtomicBool = AtomicBool::new(false);
+
+// What /rpc wants. New each time the server starts.
+static TOKEN: Mutex<String> = Mutex::new(String::new());
+
+// Gives each WebSocket an id for its subscriptions.
+static NEXT_CONNECTION: AtomicU64 = AtomicU64::new(0);
+
+// Work waiting for the game thread, and its answers.
+static JOBS: Mutex<Jobs> = Mutex::new(Jobs {
+    next_id: 0,
+    pending: VecDeque::new(),
+    done: Vec::new(),
+});
+
+static ANSWERED: Condvar = Condvar::new();
+
+type Job = Box<dyn FnOnce() -> Response + Send>;
+
+struct Jobs {
+    next_id: u64,
+    pending: VecDeque<(u64, Job)>,
+    done: Vec<(u64, Response)>,
+}
+
+struct Response {
+    status: &'static str,
+    body: String,
+}
+
+struct Request {
+    target: String,
+    origin: Option<String>,
+
+    // Only for WebSocket upgrades.
+    websocket_key: Option<String>,
+}
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Io(#[from] io::Error),
+}
+
+// Serves until dropped.
+pub struct Server {
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    pub fn start() -> Result<Self, Error> {
+        let listener = TcpListener::bind(ADDRESS)?;
+
+        // Nonblocking so that the thread notices when it should stop.
+        listener.set_nonblocking(true)?;
+        STOP.store(false, Ordering::SeqCst);
+
+        let token = new_token();
+
+        if let Some(path) = crate::data_dir(TOKEN_FILE) {
+            fs::write(path, &token)?;
+        }
+
+        common::log!("serving objects at http://{}", ADDRESS);
+        common::log!("rpc at ws://{}/rpc?token={}", ADDRESS, token);
+
+        if let Ok(mut current) = TOKEN.lock() {
+            *current = token;
+        }
+
+        Ok(Self {
+            thread: Some(thread::spawn(move || serve(listener))),
+        })
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        // The threads run our code, so they have to be gone before the DLL is
+        // unloaded.
+        STOP.store(true, Ordering::SeqCst);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(listener: TcpListener) {
+    let mut websockets: Vec<JoinHandle<()>> = Vec::new();
+
+    while !STOP.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => match handle_connection(stream) {
+                Ok(Some(websocket)) => websockets.push(websocket),
+                Ok(None) => {}
+                Err(e) => common::log!("server: {:?}", e),
+            },
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => common::log!("server: {:?}", e),
+        }
+
+        websockets.retain(|websocket| !websocket.is_finished());
+    }
+
+    for websocket in websockets {
+        let _ = websocket.join();
+    }
+}
+
+// Returns the thread serving the connection if it became a WebSocket.
+fn handle_connection(mut stream: TcpStream) -> Result<Option<JoinHandle<()>>, Error> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+    stream.set_write_timeout(Some(SOCKET_TIMEOUT))?;
+
+    let request = read_request(&mut stream)?;
+
+    let response = match &request {
+        Some(Request {
+            target,
+            origin,
+            websocket_key: Some(key),
+        }) if path(target) == "/rpc" => {
+            if !origin.as_deref().is_none_or(is_allowed) {
+                error("403 Forbidden", "pages from other origins can't use /rpc")
+            } else if !has_token(target) {
+                error("401 Unauthorized", "/rpc needs ?token= from rpc-token.txt")
+            } else {
+                websocket::accept(&mut stream, key)?;
+                return Ok(Some(thread::spawn(move || serve_websocket(stream))));
+            }
+        }
+        Some(Request { target, .. }) => {
+            let target = target.clone();
+
+            on_game_thread(move || unsafe { route(&target) })
+                .unwrap_or_else(|| error("503 Service Unavailable", "game thread didn't answer"))
+        }
+        None => error("400 Bad Request", "expected a GET request"),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+         Content-Type: application/json; charset=utf-8\r\n",
+        response.status,
+    )?;
+
+    // Only pages we allow get to read the answer. Others are refused by the
+    // browser, not by us.
+    if let Some(origin) = request
+        .and_then(|request| request.origin)
+        .filter(|origin| is_allowed(origin))
+    {
+        write!(
+            stream,
+            "Access-Control-Allow-Origin: {}\r\nVary: Origin\r\n",
+            origin
+        )?;
+    }
+
+    write!(
+        stream,
+        "Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        response.body.len()
+    )?;
+
+    stream.write_all(response.body.as_bytes())?;
+    Ok(None)
+}
+
+// None if the request isn't a GET.
+fn read_request(stream: &mut TcpStream) -> Result<Option<Request>, Error> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+
+    // A GET has no body, so the headers are everything.
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_LEN {
+        let len = stream.read(&mut buffer)?;
+
+        if len == 0 {
+            break;
+        }
+
+        request.extend_from_slice(&buffer[..len]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut lines = request.lines();
+    let mut words = lines.next().unwrap_or("").split(' ');
+
+    let target = match (words.next(), words.next()) {
+        (Some("GET"), Some(target)) => target.to_owned(),
+        _ => return Ok(None),
+    };
+
+    let headers: Vec<(&str, &str)> = lines.filter_map(|line| line.split_once(':')).collect();
+
+    let header = |wanted: &str| {
+        headers
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(wanted))
+            .map(|(_, value)| value.trim().to_owned())
+    };
+
+    Ok(Some(Request {
+        target,
+        origin: header("Origin"),
+        websocket_key: header("Sec-WebSocket-Key"),
+    }))
+}
+
+// The target without its query.
+fn path(target: &str) -> &str {
+    target.split_once('?').map_or(target, |(path, _)| path)
+}
+
+fn has_token(target: &str) -> bool {
+    let (_, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let (Some(given), Ok(token)) = (Query(query).get("token"), TOKEN.lock()) else {
+        return false;
+    };
+
+    // Compares every byte, so that how long it takes says nothing about how
+    // much of the token was right.
+    !token.is_empty()
+        && given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn new_token() -> String {
+    (0..4).map(|_| format!("{:08x}", random::u32())).collect()
+}
+
+fn is_allowed(origin: &str) -> bool {
+    ALLOWED_ORIGINS
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+}
+
+fn serve_websocket(stream: TcpStream) {
+    let connection = NEXT_CONNECTION.fetch_add(1, Ordering::SeqCst);
+
+    if let Err(e) = serve_rpc(stream, connection) {
+        common::log!("rpc: {:?}", e);
+    }
+
+    rpc::disconnect(connection);
+}
+
+fn serve_rpc(mut stream: TcpStream, connection: u64) -> Result<(), Error> {
+    // Short reads so that notifications go out promptly.
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+
+    let mut received = Vec::new();
+    let mut buffer = [0; 4096];
+
+    while !STOP.load(Ordering::SeqCst) {
+        match stream.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(len) => received.extend_from_slice(&buffer[..len]),
+            // Nothing arrived in time. Which error that is depends on the OS.
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        while let Some(frame) = websocket::read_frame(&mut received)? {
+            match frame {
+                Frame::Text(message) => {
+                    let unanswered = message.clone();
+                    let response =
+                        on_game_thread(move || ok(unsafe { rpc::handle(connection, &message) }))
+                            .map(|response| response.body)
+                            .unwrap_or_else(|| rpc::unanswered(&unanswered));
+
+                    websocket::write_text(&mut stream, &response)?;
+                }
+                Frame::Ping(payload) => websocket::write_pong(&mut stream, &payload)?,
+                Frame::Close => {
+                    websocket::write_close(&mut stream)?;
+                    return Ok(());
+                }
+                Frame::Other => {}
+            }
+        }
+
+        for notification in rpc::take_notifications(connection) {
+            websocket::write_text(&mut stream, &notification)?;
+        }
+    }
+
+    websocket::write_close(&mut stream)?;
+    Ok(())
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+// Runs `job` on the game thread and waits for its response. None if the game
+// thread doesn't get to it in time.
+fn on_game_thread(job: impl FnOnce() -> Response + Send + 'static) -> Option<Response> {
+    let mut jobs = JOBS.lock().ok()?;
+
+    jobs.next_id += 1;
+    let id = jobs.next_id;
+    jobs.pending.push_back((id, Box::new(job)));
+
+    if tick::run_on_game_thread(run_jobs).is_err() {
+        jobs.pending.retain(|&(pending, _)| pending != id);
+        return None;
+    }
+
+    let (mut jobs, _) = ANSWERED
+        .wait_timeout_while(jobs, GAME_THREAD_TIMEOUT, |jobs| {
+            !jobs.done.iter().any(|&(done, _)| done == id)
+        })
+        .ok()?;
+
+    // Jobs run with the lock held, so a job that timed out is either still
+    // pending or already done, never running.
+    jobs.pending.retain(|&(pending, _)| pending != id);
+
+    let index = jobs.done.iter().position(|&(done, _)| done == id)?;
+    Some(jobs.done.swap_remove(index).1)
+}
+
+unsafe fn run_jobs() {
+    let Ok(mut jobs) = JOBS.lock() else {
+        return;
+    };
+
+    while let Some((id, job)) = jobs.pending.pop_front() {
+        let response = job();
+        jobs.done.push((id, response));
+    }
+
+    ANSWERED.notify_all();
+}
+
+unsafe fn route(target: &str) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = Query(query);
+
+    let limit = match query.get("limit") {
+        Some(limit) => match limit.parse() {
+            Ok(limit) => limit,
+            Err(_) => return error("400 Bad Request", "limit must be a number"),
+        },
+        None => DEFAULT_LIMIT,
+    };
+
+    match path {
+        "/objects" => match query.get("class") {
+            Some(class) => list(limit, |object| inspect::is_a(object, &class)),
+            None => error("400 Bad Request", "missing class"),
+        },
+        "/search" => match query.get("name") {
+            Some(name) => {
+                let name = name.to_lowercase();
+                list(limit, |object| {
+                    (*object).name().to_lowercase().contains(&name)
+                })
+            }
+            None => error("400 Bad Request", "missing name"),
+        },
+        "/object" => {
+            let object = if let Some(index) = query.get("index") {
+                match index.parse() {
+                    Ok(index) => object_at(index),
+                    Err(_) => return error("400 Bad Request", "index must be a number"),
+                }
+            } else if let Some(path) = query.get("path") {
+                (*GUObjectArray).find_by_path(&path)
+            } else {
+                return error("400 Bad Request", "missing index or path");
+            };
+
+            if object.is_null() {
+                error("404 Not Found", "no such object")
+            } else {
+                describe(object)
+            }
+        }
+        "/profile" => profile(limit),
+        _ => error(
+            "404 Not Found",
+            "try /objects?class=, /search?name=, /object?index= or /profile",
+        ),
+    }
+}
+
+// [{"index": 123456, "name": "Class /Script/FSD.PlayerCharacter"}, ...]
+unsafe fn list(limit: usize, mut predicate: impl FnMut(*mut UObject) -> bool) -> Response {
+    let mut body = String::from("[");
+    let mut count = 0;
+
+    for object in (*GUObjectArray).iter() {
+        if count == limit {
+            break;
+        }
+
+        if object.is_null() || !predicate(object) {
+            continue;
+        }
+
+        if count > 0 {
+            body.push(',');
+        }
+
+        let _ = write_reference(&mut body, object);
+        count += 1;
+    }
+
+    body.push(']');
+    ok(body)
+}
+
+// {"index": ..., "name": ..., "outer": {...}, "properties": [{"name": ..., "value": ...}, ...]}
+unsafe fn describe(object: *mut UObject) -> Response {
+    let mut body = String::new();
+
+    let result = (|| -> Result<(), fmt::Error> {
+        write!(
+            body,
+            "{{\"index\":{},\"name\":\"{}\",\"outer\":",
+            (*object).InternalIndex,
+            JsonString(&*object)
+        )?;
+
+        let outer = (*object).outer();
+
+        if outer.is_null() {
+            body.push_str("null");
+        } else {
+            write_reference(&mut body, outer)?;
+        }
+
+        body.push_str(",\"properties\":[");
+        let mut first = true;
+        let mut result = Ok(());
+
+        inspect::for_each_property((*object).class().cast(), |property| {
+            if !first {
+                body.push(',');
+            }
+
+            first = false;
+
+            result = result.and_then(|_| {
+                write!(
+                    body,
+                    "{{\"name\":\"{}\",\"value\":\"{}\"}}",
+                    JsonString((*property).name()),
+                    JsonString(PropertyValue {
+                        property,
+                        container: object.cast(),
+                    })
+                )
+            });
+        });
+
+        result?;
+        body.push_str("]}");
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => ok(body),
+        Err(_) => error("500 Internal Server Error", "failed to format object"),
+    }
+}
+
+// {"enabled": true, "seconds": 12.5, "functions": [{"name": ..., "calls": ...,
+// "total_ms": ..., "exclusive_ms": ...}, ...]}, by exclusive time.
+unsafe fn profile(limit: usize) -> Response {
+    let mut body = format!(
+        "{{\"enabled\":{},\"seconds\":{},\"functions\":[",
+        profiler::is_enabled(),
+        profiler::elapsed().as_secs_f64()
+    );
+
+    for (i, stats) in profiler::top(limit).into_iter().enumerate() {
+        if i > 0 {
+            body.push(',');
+        }
+
+        let _ = write!(
+            body,
+            "{{\"name\":\"{}\",\"calls\":{},\"total_ms\":{},\"exclusive_ms\":{}}}",
+            JsonString(&stats.name),
+            stats.calls,
+            stats.total.as_secs_f64() * 1000.0,
+            stats.exclusive.as_secs_f64() * 1000.0
+        );
+    }
+
+    body.push_str("]}");
+    ok(body)
+}
+
+unsafe fn write_reference(out: &mut String, object: *mut UObject) -> Result<(), fmt::Error> {
+    write!(
+        out,
+        "{{\"index\":{},\"name\":\"{}\"}}",
+        (*object).InternalIndex,
+        JsonString(&*object)
+    )
+}
+
+unsafe fn object_at(index: i32) -> *mut UObject {
+    let item = (*GUObjectArray).index_to_object(index);
+
+    if index < 0 || item.is_null() {
+        ptr::null_mut()
+    } else {
+        (*item).Object
+    }
+}
+
+fn ok(body: String) -> Response {
+    Response {
+        status: "200 OK",
+        body,
+    }
+}
+
+fn error(status: &'static str, message: &str) -> Response {
+    Response {
+        status,
+        body: format!("{{\"error\":\"{}\"}}", JsonString(message)),
+    }
+}
+
+// The "a=1&b=2" after the '?'.
+struct Query<'a>(&'a str);
+
+impl Query<'_> {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|&(k, _)| k == key)
+            .map(|(_, value)| percent_decode(value))
+    }
+}
+
+// Undoes the %XX and '+' escapes that browsers put in query strings.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = || {
+            let digits = bytes.get(i + 1..i + 3)?;
+            u8::from_str_radix(str::from_utf8(digits).ok()?, 16).ok()
+        };
+
+        match (bytes[i], hex()) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
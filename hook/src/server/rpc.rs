@@ -0,0 +1,449 @@
+// JSON-RPC 2.0 over the WebSocket at ws://127.0.0.1:8377/rpc?token=..., for
+// tools that drive the game instead of just looking at it. See server.rs for
+// the token. Objects are given by path, "/Game/GameData.GameData", or by
+// index, 123456. Values come back formatted like the object browser's.
+//
+// {"id": 1, "method": "get", "params": {"object": ..., "property": "Health"}}
+// {"id": 2, "method": "set", "params": {"object": ..., "property": "Health", "value": 100}}
+// {"id": 3, "method": "call", "params": {"object": ..., "function": "Jump", "args": {}}}
+// {"id": 4, "method": "command", "params": {"line": "drg.outline on"}}
+// {"id": 5, "method": "subscribe", "params": {"event": "rpc"}}
+// {"id": 6, "method": "unsubscribe", "params": {"event": "rpc"}}
+//
+// Subscribers are sent notifications like
+// {"jsonrpc": "2.0", "method": "rpc", "params": {"object": ..., "function": ...}}
+//
+// set, call and command are host-only. See policy.
+
+use crate::policy::{self, Change};
+use common::inspect::{self, Primitive};
+use common::json::Value;
+use common::{
+    EClassCastFlags, EPropertyFlags, FProperty, GUObjectArray, JsonString, PropertyValue, UObject,
+};
+use core::ffi::c_void;
+use core::fmt::{self, Write as _};
+use core::mem;
+use core::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// Events that can be subscribed to.
+const EVENTS: &[(&str, &str)] = &[(
+    "rpc",
+    "a function we call on the server, like Server_RegisterHit",
+)];
+
+// JSON-RPC's error codes.
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const INTERNAL_ERROR: i32 = -32603;
+
+static SUBSCRIBERS: Mutex<Vec<Subscriber>> = Mutex::new(Vec::new());
+
+// Lets `publish` skip formatting, and the lock, when nobody is listening.
+static SUBSCRIPTION_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+struct Subscriber {
+    connection: u64,
+    event: &'static str,
+    outbox: Vec<String>,
+}
+
+struct Error {
+    code: i32,
+    message: String,
+}
+
+impl Error {
+    fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+// Answers one message from `connection`. Runs on the game thread.
+pub unsafe fn handle(connection: u64, message: &str) -> String {
+    let request = match Value::parse(message) {
+        Ok(request) => request,
+        Err(e) => {
+            return response(
+                &Value::Null,
+                Err(Error::new(PARSE_ERROR, format!("{:?}", e))),
+            )
+        }
+    };
+
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let empty = Value::Object(Vec::new());
+    let params = request.get("params").unwrap_or(&empty);
+
+    let result = match request.get("method").and_then(Value::as_str) {
+        Some("get") => get(params),
+        Some("set") => set(params),
+        Some("call") => call(params),
+        Some("command") => command(params),
+        Some("subscribe") => subscribe(connection, params),
+        Some("unsubscribe") => unsubscribe(connection, params),
+        Some(method) => Err(Error::new(
+            METHOD_NOT_FOUND,
+            format!("no method called {}", method),
+        )),
+        None => Err(Error::new(INVALID_REQUEST, "missing method")),
+    };
+
+    response(&id, result)
+}
+
+// The answer to a message that the game thread didn't get to in time.
+pub fn unanswered(message: &str) -> String {
+    let id = Value::parse(message)
+        .ok()
+        .and_then(|request| request.get("id").cloned())
+        .unwrap_or(Value::Null);
+
+    response(
+        &id,
+        Err(Error::new(
+            INTERNAL_ERROR,
+            "the game thread didn't answer in time",
+        )),
+    )
+}
+
+fn response(id: &Value, result: Result<String, Error>) -> String {
+    match result {
+        Ok(result) => format!(
+            "{{\"jsonrpc\":\"2.0\",\"id\":{},\"result\":{}}}",
+            id, result
+        ),
+        Err(e) => format!(
+            "{{\"jsonrpc\":\"2.0\",\"id\":{},\"error\":{{\"code\":{},\"message\":\"{}\"}}}}",
+            id,
+            e.code,
+            JsonString(e.message)
+        ),
+    }
+}
+
+// {"value": "100"}
+unsafe fn get(params: &Value) -> Result<String, Error> {
+    let object = object(params)?;
+    let property = property(object, params)?;
+
+    Ok(format!(
+        "{{\"value\":\"{}\"}}",
+        JsonString(PropertyValue {
+            property,
+            container: object.cast(),
+        })
+    ))
+}
+
+// true
+unsafe fn set(params: &Value) -> Result<String, Error> {
+    let object = object(params)?;
+    let property = property(object, params)?;
+    let value = params
+        .get("value")
+        .ok_or_else(|| Error::params("missing value"))?;
+
+    let change = Change::Set {
+        object,
+        property,
+        value: primitive(property, value)?,
+    };
+
+    policy::apply(change).map_err(refused)?;
+    Ok(String::from("true"))
+}
+
+// The return value and any out parameters, by name:
+// {"ReturnValue": "true", "OutHit": "{HitResult}"}
+unsafe fn call(params: &Value) -> Result<String, Error> {
+    let object = object(params)?;
+    let name = string(params, "function")?;
+    let function = (*(*object).class()).find_function(name);
+
+    if function.is_null() {
+        return Err(Error::params(format!("no function called {}", name)));
+    }
+
+    let empty = Value::Object(Vec::new());
+    let args = params.get("args").unwrap_or(&empty);
+
+    // u128s so that parameters like FTransform are aligned. Zeroed is a valid
+    // empty value for every parameter type, including strings and arrays.
+    let mut parms = vec![0u128; (*function).parms_size().div_ceil(mem::size_of::<u128>())];
+    let container = parms.as_mut_ptr().cast::<c_void>();
+    let mut result = Ok(());
+
+    inspect::for_each_property(function.cast(), |property| {
+        let flags = (*property).flags();
+
+        if result.is_ok()
+            && flags.contains(EPropertyFlags::CPF_Parm)
+            && !flags.contains(EPropertyFlags::CPF_ReturnParm)
+        {
+            if let Some(value) = args.get((*property).name()) {
+                result = write_value(property, container, value);
+            }
+        }
+    });
+
+    result?;
+
+    let change = Change::Call {
+        object,
+        function,
+        parameters: container,
+    };
+
+    policy::apply(change).map_err(refused)?;
+
+    let mut outputs = String::from("{");
+
+    inspect::for_each_property(function.cast(), |property| {
+        let flags = (*property).flags();
+
+        if flags.contains(EPropertyFlags::CPF_OutParm)
+            || flags.contains(EPropertyFlags::CPF_ReturnParm)
+        {
+            if outputs.len() > 1 {
+                outputs.push(',');
+            }
+
+            let _ = write!(
+                outputs,
+                "\"{}\":\"{}\"",
+                JsonString((*property).name()),
+                JsonString(PropertyValue {
+                    property,
+                    container,
+                })
+            );
+        }
+    });
+
+    outputs.push('}');
+
+    // Strings and arrays the function returned are leaked. The engine's
+    // allocator would have to free them.
+    Ok(outputs)
+}
+
+// {"handled": true}, or false if it isn't one of our console commands.
+unsafe fn command(params: &Value) -> Result<String, Error> {
+    let handled = policy::apply(Change::Command(string(params, "line")?)).map_err(refused)?;
+    Ok(format!("{{\"handled\":{}}}", handled))
+}
+
+fn subscribe(connection: u64, params: &Value) -> Result<String, Error> {
+    let event = event(params)?;
+    let mut subscribers = lock_subscribers()?;
+
+    if !subscribers
+        .iter()
+        .any(|s| s.connection == connection && s.event == event)
+    {
+        subscribers.push(Subscriber {
+            connection,
+            event,
+            outbox: Vec::new(),
+        });
+
+        SUBSCRIPTION_COUNT.fetch_add(1, Ordering::SeqCst);
+    }
+
+    Ok(String::from("true"))
+}
+
+fn unsubscribe(connection: u64, params: &Value) -> Result<String, Error> {
+    let event = event(params)?;
+    let mut subscribers = lock_subscribers()?;
+
+    if let Some(index) = subscribers
+        .iter()
+        .position(|s| s.connection == connection && s.event == event)
+    {
+        subscribers.swap_remove(index);
+        SUBSCRIPTION_COUNT.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    Ok(String::from("true"))
+}
+
+fn lock_subscribers() -> Result<std::sync::MutexGuard<'static, Vec<Subscriber>>, Error> {
+    SUBSCRIBERS
+        .lock()
+        .map_err(|_| Error::new(INVALID_REQUEST, "poisoned lock"))
+}
+
+// Sends `event` to its subscribers, with `params` as the notification's
+// params. `params` is only formatted if someone is subscribed.
+pub fn publish(event: &str, params: fmt::Arguments) {
+    if SUBSCRIPTION_COUNT.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+
+    let Ok(mut subscribers) = SUBSCRIBERS.lock() else {
+        return;
+    };
+
+    let mut notification = None;
+
+    for subscriber in subscribers.iter_mut().filter(|s| s.event == event) {
+        let notification = notification.get_or_insert_with(|| {
+            format!(
+                "{{\"jsonrpc\":\"2.0\",\"method\":\"{}\",\"params\":{}}}",
+                event, params
+            )
+        });
+
+        subscriber.outbox.push(notification.clone());
+    }
+}
+
+// Takes the notifications waiting for `connection`.
+pub fn take_notifications(connection: u64) -> Vec<String> {
+    let Ok(mut subscribers) = SUBSCRIBERS.lock() else {
+        return Vec::new();
+    };
+
+    subscribers
+        .iter_mut()
+        .filter(|s| s.connection == connection)
+        .flat_map(|s| s.outbox.drain(..))
+        .collect()
+}
+
+// Drops `connection`'s subscriptions once it's closed.
+pub fn disconnect(connection: u64) {
+    let Ok(mut subscribers) = SUBSCRIBERS.lock() else {
+        return;
+    };
+
+    let before = subscribers.len();
+    subscribers.retain(|s| s.connection != connection);
+    SUBSCRIPTION_COUNT.fetch_sub(before - subscribers.len(), Ordering::SeqCst);
+}
+
+fn event(params: &Value) -> Result<&'static str, Error> {
+    let name = string(params, "event")?;
+
+    EVENTS
+        .iter()
+        .find(|&&(event, _)| event == name)
+        .map(|&(event, _)| event)
+        .ok_or_else(|| {
+            let mut message = format!("no event called {}. try", name);
+
+            for (event, description) in EVENTS {
+                let _ = write!(message, " {} ({})", event, description);
+            }
+
+            Error::params(message)
+        })
+}
+
+fn string<'a>(params: &'a Value, key: &str) -> Result<&'a str, Error> {
+    params
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::params(format!("missing {}", key)))
+}
+
+// params.object, as a path or an index.
+unsafe fn object(params: &Value) -> Result<*mut UObject, Error> {
+    let object = resolve_object(
+        params
+            .get("object")
+            .ok_or_else(|| Error::params("missing object"))?,
+    );
+
+    if object.is_null() {
+        Err(Error::params("no such object"))
+    } else {
+        Ok(object)
+    }
+}
+
+unsafe fn resolve_object(value: &Value) -> *mut UObject {
+    match *value {
+        Value::String(ref path) => (*GUObjectArray).find_by_path(path),
+        Value::Number(index) if (0.0..=f64::from(i32::MAX)).contains(&index) => {
+            let item = (*GUObjectArray).index_to_object(index as i32);
+
+            if item.is_null() {
+                ptr::null_mut()
+            } else {
+                (*item).Object
+            }
+        }
+        _ => ptr::null_mut(),
+    }
+}
+
+unsafe fn property(object: *mut UObject, params: &Value) -> Result<*const FProperty, Error> {
+    let name = string(params, "property")?;
+    let property = (*(*object).class()).find_property(name);
+
+    if property.is_null() {
+        Err(Error::params(format!("no property called {}", name)))
+    } else {
+        Ok(property)
+    }
+}
+
+unsafe fn write_value(
+    property: *const FProperty,
+    container: *mut c_void,
+    value: &Value,
+) -> Result<(), Error> {
+    let primitive = primitive(property, value)?;
+
+    inspect::write_primitive(property, container, primitive).map_err(|e| {
+        let name = (*property).name();
+        Error::params(format!("can't set {} to {}: {:?}", name, value, e))
+    })
+}
+
+// Booleans, numbers, enums by value, and objects by path, index or null.
+unsafe fn primitive(property: *const FProperty, value: &Value) -> Result<Primitive, Error> {
+    let name = (*property).name();
+    let error =
+        |reason: &str| Error::params(format!("can't set {} to {}: {}", name, value, reason));
+
+    let primitive = if (*property).is(EClassCastFlags::CASTCLASS_FObjectProperty) {
+        match resolve_object(value) {
+            object if object.is_null() && *value != Value::Null => {
+                return Err(error("no such object"))
+            }
+            object => Primitive::Object(object),
+        }
+    } else {
+        match *value {
+            Value::Bool(b) => Primitive::Bool(b),
+            Value::Number(n) if n.fract() == 0.0 => Primitive::Integer(n as i64),
+            Value::Number(n) => Primitive::Float(n),
+            _ => return Err(error("not a boolean or number")),
+        }
+    };
+
+    Ok(primitive)
+}
+
+fn refused(e: policy::Error) -> Error {
+    match e {
+        policy::Error::Refused => Error::new(INTERNAL_ERROR, "not allowed in this game"),
+        policy::Error::Write(e) => Error::params(format!("{:?}", e)),
+    }
+}
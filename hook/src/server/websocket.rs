@@ -0,0 +1,235 @@
+// Just enough of RFC 6455 for a local tool to talk to us: the opening
+// handshake and unfragmented text frames.
+
+use std::io::{self, Write};
+
+// Appended to the client's key before hashing, per the RFC.
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// No message we expect comes close.
+const MAX_PAYLOAD_LEN: u64 = 1024 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+pub enum Frame {
+    Text(String),
+    Ping(Vec<u8>),
+    Close,
+    // Binary frames, pongs, and anything else we ignore.
+    Other,
+}
+
+// Answers a request carrying `Sec-WebSocket-Key: key`.
+pub fn accept(out: &mut impl Write, key: &str) -> io::Result<()> {
+    let mut input = Vec::from(key.trim().as_bytes());
+    input.extend_from_slice(HANDSHAKE_GUID.as_bytes());
+
+    write!(
+        out,
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        base64(&sha1(&input))
+    )
+}
+
+// Takes the first complete frame off the front of `buffer`. None if more
+// bytes are needed. Clients must mask their frames.
+pub fn read_frame(buffer: &mut Vec<u8>) -> io::Result<Option<Frame>> {
+    let (Some(&first), Some(&second)) = (buffer.first(), buffer.get(1)) else {
+        return Ok(None);
+    };
+
+    let is_final = first & 0x80 != 0;
+    let opcode = first & 0x0F;
+
+    if !is_final || opcode == OPCODE_CONTINUATION {
+        return Err(invalid("fragmented messages aren't supported"));
+    }
+
+    if second & 0x80 == 0 {
+        return Err(invalid("client frames must be masked"));
+    }
+
+    let (len, mut cursor) = match second & 0x7F {
+        126 => match buffer.get(2..4) {
+            Some(bytes) => (u64::from(u16::from_be_bytes([bytes[0], bytes[1]])), 4),
+            None => return Ok(None),
+        },
+        127 => match buffer.get(2..10) {
+            Some(bytes) => {
+                let mut len = [0; 8];
+                len.copy_from_slice(bytes);
+                (u64::from_be_bytes(len), 10)
+            }
+            None => return Ok(None),
+        },
+        len => (u64::from(len), 2),
+    };
+
+    if len > MAX_PAYLOAD_LEN {
+        return Err(invalid("frame is too large"));
+    }
+
+    let len = len as usize;
+
+    let Some(mask) = buffer.get(cursor..cursor + 4) else {
+        return Ok(None);
+    };
+
+    let mask = [mask[0], mask[1], mask[2], mask[3]];
+    cursor += 4;
+
+    let Some(payload) = buffer.get(cursor..cursor + len) else {
+        return Ok(None);
+    };
+
+    let payload: Vec<u8> = payload
+        .iter()
+        .zip(mask.iter().cycle())
+        .map(|(byte, mask)| byte ^ mask)
+        .collect();
+
+    buffer.drain(..cursor + len);
+
+    Ok(Some(match opcode {
+        OPCODE_TEXT => match String::from_utf8(payload) {
+            Ok(text) => Frame::Text(text),
+            Err(_) => return Err(invalid("text frame isn't UTF-8")),
+        },
+        OPCODE_PING => Frame::Ping(payload),
+        OPCODE_CLOSE => Frame::Close,
+        _ => Frame::Other,
+    }))
+}
+
+pub fn write_text(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write_frame(out, OPCODE_TEXT, text.as_bytes())
+}
+
+pub fn write_pong(out: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    write_frame(out, OPCODE_PONG, payload)
+}
+
+pub fn write_close(out: &mut impl Write) -> io::Result<()> {
+    write_frame(out, OPCODE_CLOSE, &[])
+}
+
+// Server frames are never masked.
+fn write_frame(out: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut header = Vec::with_capacity(10);
+    header.push(0x80 | opcode);
+
+    match payload.len() {
+        len @ 0..=125 => header.push(len as u8),
+        len @ 126..=0xFFFF => {
+            header.push(126);
+            header.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            header.push(127);
+            header.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+
+    out.write_all(&header)?;
+    out.write_all(payload)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// The handshake is the only thing we hash, so SHA-1 is fine here.
+fn sha1(input: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    // Pad to a whole number of 64 byte blocks, ending with the length in
+    // bits.
+    let mut message = Vec::from(input);
+    message.push(0x80);
+
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+
+    message.extend_from_slice(&(input.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, x) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(x);
+        }
+    }
+
+    let mut digest = [0; 20];
+
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+
+    digest
+}
+
+fn base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(input.len().div_ceil(3) * 4);
+
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(char::from(ALPHABET[(n >> (18 - 6 * i)) as usize & 63]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
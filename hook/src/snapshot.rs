@@ -0,0 +1,120 @@
+// The console side of common::snapshot:
+//
+// drg.snapshot take before /Game/...BP_PlayerCharacter_C_0
+// drg.snapshot diff before          what's changed on it since
+// drg.snapshot take after /Game/...BP_PlayerCharacter_C_1
+// drg.snapshot diff before after    what's different between the two
+// drg.snapshot list
+// drg.snapshot clear
+
+use common::snapshot::Snapshot;
+use common::{GUObjectArray, ObjectHandle, UObject};
+
+const USAGE: &str = "usage: drg.snapshot take <name> <object path> | diff <name> [name] | \
+                     list | clear";
+
+struct Saved {
+    name: String,
+    // To diff against how it is now.
+    object: ObjectHandle<UObject>,
+    snapshot: Snapshot,
+}
+
+static mut SAVED: Vec<Saved> = Vec::new();
+
+pub unsafe fn run(args: &str) {
+    let mut words = args.split_whitespace();
+
+    match (words.next(), words.next(), words.next(), words.next()) {
+        (Some("take"), Some(name), Some(path), None) => take(name, path),
+        (Some("diff"), Some(before), after, None) => diff(before, after),
+        (Some("list"), None, None, None) => {
+            for saved in SAVED.iter() {
+                common::log!(
+                    "drg.snapshot: {}: {} values of {}",
+                    saved.name,
+                    saved.snapshot.values.len(),
+                    saved.snapshot.object
+                );
+            }
+        }
+        (Some("clear"), None, None, None) => SAVED = Vec::new(),
+        _ => common::log!("{}", USAGE),
+    }
+}
+
+unsafe fn take(name: &str, path: &str) {
+    let object = (*GUObjectArray).find_by_path(path);
+
+    if object.is_null() {
+        common::log!("drg.snapshot: no object called {}", path);
+        return;
+    }
+
+    let saved = Saved {
+        name: name.to_owned(),
+        object: ObjectHandle::new(object),
+        snapshot: Snapshot::take(object),
+    };
+
+    common::log!(
+        "drg.snapshot: {}: {} values",
+        name,
+        saved.snapshot.values.len()
+    );
+
+    match SAVED.iter_mut().find(|s| s.name == name) {
+        Some(existing) => *existing = saved,
+        None => SAVED.push(saved),
+    }
+}
+
+unsafe fn diff(before: &str, after: Option<&str>) {
+    let Some(before) = find(before) else {
+        return;
+    };
+
+    let now;
+
+    let after = match after {
+        Some(after) => match find(after) {
+            Some(after) => &after.snapshot,
+            None => return,
+        },
+        None => {
+            let Some(object) = before.object.resolve() else {
+                common::log!("drg.snapshot: {}'s object is gone", before.name);
+                return;
+            };
+
+            now = Snapshot::take(object);
+            &now
+        }
+    };
+
+    let changes = before.snapshot.diff(after);
+
+    for change in &changes {
+        common::log!(
+            "drg.snapshot: {}: {} -> {}",
+            change.path,
+            change.before.unwrap_or("(none)"),
+            change.after.unwrap_or("(none)")
+        );
+    }
+
+    common::log!("drg.snapshot: {} changes", changes.len());
+}
+
+unsafe fn find(name: &str) -> Option<&'static Saved> {
+    let saved = SAVED.iter().find(|s| s.name == name);
+
+    if saved.is_none() {
+        common::log!(
+            "drg.snapshot: no snapshot called {}. see drg.snapshot list",
+            name
+        );
+    }
+
+    saved
+}
@@ -0,0 +1,86 @@
+// The console side of common::string_table:
+//
+// drg.text tables                   every string table
+// drg.text keys <table>             every key in one
+// drg.text get <table> <key>        an entry, as written and as shown
+// drg.text set <table> <key> <text> show something else for it
+// drg.text mark <table>             prefix every entry with its key, to find
+//                                   which one a piece of UI shows
+
+use crate::policy::{Feature, Scope};
+use common::string_table;
+
+const USAGE: &str = "usage: drg.text tables | keys <table> | get <table> <key> | \
+                     set <table> <key> <text> | mark <table>";
+
+// Overrides only show on our screen.
+static OVERRIDE: Feature = Feature {
+    name: "drg.text overrides",
+    scope: Scope::SafeAnywhere,
+    disable: None,
+};
+
+pub unsafe fn run(args: &str) {
+    let mut words = args.split_whitespace();
+    let (command, table, key) = (words.next(), words.next(), words.next());
+
+    // The text to set is the rest of the line, with its words put back
+    // together by single spaces.
+    let text = words.collect::<Vec<_>>().join(" ");
+    let text = (!text.is_empty()).then_some(text.as_str());
+
+    if matches!(command, Some("set" | "mark")) && !crate::policy::enable(&OVERRIDE) {
+        return;
+    }
+
+    let result = match (command, table, key, text) {
+        (Some("tables"), None, None, None) => string_table::tables().map(|tables| {
+            for table in tables {
+                common::log!("drg.text: {}", table);
+            }
+        }),
+        (Some("keys"), Some(table), None, None) => string_table::keys(table).map(|keys| {
+            for key in keys {
+                common::log!("drg.text: {}", key);
+            }
+        }),
+        (Some("get"), Some(table), Some(key), None) => get(table, key),
+        (Some("set"), Some(table), Some(key), Some(text)) => string_table::set(table, key, text)
+            .map(|previous| {
+                common::log!("drg.text: {} was \"{}\"", key, previous);
+            }),
+        (Some("mark"), Some(table), None, None) => mark(table),
+        _ => {
+            common::log!("{}", USAGE);
+            Ok(())
+        }
+    };
+
+    if let Err(e) = result {
+        common::log!("drg.text: {:?}", e);
+    }
+}
+
+unsafe fn get(table: &str, key: &str) -> Result<(), string_table::Error> {
+    let source = string_table::source_string(table, key)?;
+    let display = string_table::display_string(table, key)?;
+
+    common::log!("drg.text: source: \"{}\"", source);
+    common::log!("drg.text: shown:  \"{}\"", display);
+    Ok(())
+}
+
+unsafe fn mark(table: &str) -> Result<(), string_table::Error> {
+    let keys = string_table::keys(table)?;
+
+    for key in &keys {
+        let source = string_table::source_string(table, key)?;
+
+        if let Err(e) = string_table::set(table, key, &format!("[{}] {}", key, source)) {
+            common::log!("drg.text: {}: {:?}", key, e);
+        }
+    }
+
+    common::log!("drg.text: marked {} entries", keys.len());
+    Ok(())
+}
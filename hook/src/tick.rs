@@ -0,0 +1,40 @@
+use common::list::{self, Listeners};
+use common::queue::{self, Queue};
+use core::ptr;
+
+pub type Callback = unsafe fn(delta_seconds: f32);
+pub type Task = unsafe fn();
+
+static mut CALLBACKS: Listeners<Callback, 64> = Listeners::new();
+
+// Filled by any thread. Drained by the game thread at the start of each frame.
+static TASKS: Queue<Task, 256> = Queue::new();
+
+#[allow(dead_code)]
+pub unsafe fn register(callback: Callback) -> Result<(), list::Error> {
+    CALLBACKS.add(callback)
+}
+
+#[allow(dead_code)]
+pub unsafe fn unregister(callback: Callback) {
+    CALLBACKS.remove(|&c| c as usize == callback as usize);
+}
+
+#[allow(dead_code)]
+pub fn run_on_game_thread(task: Task) -> Result<(), queue::Error> {
+    TASKS.push(task)
+}
+
+pub unsafe fn dispatch(delta_seconds: f32) {
+    while let Some(task) = TASKS.pop() {
+        task();
+    }
+
+    crate::scheduler::tick(delta_seconds);
+    crate::hotkey::poll();
+    crate::script::tick(delta_seconds);
+
+    Listeners::for_each(ptr::addr_of_mut!(CALLBACKS), |callback| {
+        callback(delta_seconds)
+    });
+}
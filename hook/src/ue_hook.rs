@@ -0,0 +1,117 @@
+// Hooks declared with #[macros::ue_hook("FSD.PlayerCharacter:ReceiveTick")].
+//
+// Each hook puts an Entry in the .uehook$b section. The linker sorts grouped
+// sections by the text after the $, so every entry ends up between START in
+// .uehook$a and END in .uehook$c. That way we can find all the hooks without
+// having to list them anywhere.
+
+use common::{FFrame, FNativeFuncPtr, UObject};
+use core::cell::UnsafeCell;
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+
+pub struct UeHook {
+    // e.g. "Function /Script/FSD.PlayerCharacter.ReceiveTick"
+    pub path: &'static str,
+    pub hook: FNativeFuncPtr,
+    pub original: &'static Original,
+}
+
+// Where the function's original native implementation is kept while it's
+// hooked.
+pub struct Original(UnsafeCell<Option<FNativeFuncPtr>>);
+
+// SAFETY: Only written while installing hooks, before the game can call them.
+unsafe impl Sync for Original {}
+
+impl Original {
+    pub const fn new() -> Self {
+        Self(UnsafeCell::new(None))
+    }
+
+    pub fn as_mut_ptr(&self) -> *mut FNativeFuncPtr {
+        self.0.get().cast()
+    }
+
+    unsafe fn get(&self) -> Option<FNativeFuncPtr> {
+        *self.0.get()
+    }
+}
+
+// The linker may pad the section with zeros, which read as None.
+#[repr(transparent)]
+pub struct Entry(pub Option<&'static UeHook>);
+
+#[link_section = ".uehook$a"]
+#[used]
+static START: Entry = Entry(None);
+
+#[link_section = ".uehook$c"]
+#[used]
+static END: Entry = Entry(None);
+
+pub fn iter() -> impl Iterator<Item = &'static UeHook> {
+    let start: *const Entry = &START;
+    let end: *const Entry = &END;
+    let len = (end as usize - start as usize) / core::mem::size_of::<Entry>();
+
+    (1..len).filter_map(move |i| unsafe { ptr::read_volatile(start.add(i)).0 })
+}
+
+// What a hook gets called with. Derefs to the function's parameters.
+pub struct Call<P> {
+    context: *mut UObject,
+    stack: *mut FFrame,
+    result: *mut c_void,
+    original: &'static Original,
+    parameters: PhantomData<P>,
+}
+
+impl<P> Call<P> {
+    pub fn new(
+        context: *mut UObject,
+        stack: *mut FFrame,
+        result: *mut c_void,
+        original: &'static Original,
+    ) -> Self {
+        Self {
+            context,
+            stack,
+            result,
+            original,
+            parameters: PhantomData,
+        }
+    }
+
+    // The object the function was called on.
+    pub fn object<T>(&self) -> *mut T {
+        self.context.cast()
+    }
+
+    #[allow(dead_code)]
+    pub fn result<R>(&self) -> *mut R {
+        self.result.cast()
+    }
+
+    pub unsafe fn call_original(&mut self) {
+        if let Some(original) = self.original.get() {
+            original(self.context, self.stack, self.result);
+        }
+    }
+}
+
+impl<P> Deref for Call<P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        unsafe { &*(*self.stack).Locals.cast() }
+    }
+}
+
+impl<P> DerefMut for Call<P> {
+    fn deref_mut(&mut self) -> &mut P {
+        unsafe { &mut *(*self.stack).Locals.cast() }
+    }
+}
@@ -2,10 +2,19 @@
 #![allow(clippy::missing_panics_doc)]
 
 use proc_macro::TokenStream;
+use std::fmt::Write as _;
 
 mod enumeration;
 use enumeration::{Enum, Fields};
 
+mod ue_hook;
+
+mod uobject_cast;
+use uobject_cast::UObjectCast;
+
+mod uparams;
+use uparams::UParams;
+
 #[proc_macro_derive(NoPanicErrorDebug, attributes(from))]
 pub fn derive_no_panic_error_debug(input: TokenStream) -> TokenStream {
     // for token in input {
@@ -40,3 +49,120 @@ pub fn derive_no_panic_error_debug(input: TokenStream) -> TokenStream {
 
     implementation.parse().unwrap()
 }
+
+// Adds static_class() and from_object() for checked downcasts from UObject,
+// and implements common::Downcast with them for UObject::try_downcast, plus
+// the Deref chain to the `base` field.
+#[proc_macro_derive(UObjectCast, attributes(uclass, cast_flags))]
+pub fn derive_uobject_cast(input: TokenStream) -> TokenStream {
+    let UObjectCast {
+        name,
+        class,
+        cast_flags,
+        base,
+    } = UObjectCast::from(input);
+
+    let check = if let Some(cast_flags) = cast_flags {
+        format!("(*object).fast_is(common::EClassCastFlags::{cast_flags}).then(|| object.cast())")
+    } else {
+        String::from(
+            "let class = Self::static_class();\n\
+             (!class.is_null() && (*object).is(class)).then(|| object.cast())",
+        )
+    };
+
+    let mut implementation = format!(
+        include_str!("impl_uobject_cast"),
+        name = name,
+        class = class,
+        check = check,
+    );
+
+    if let Some(base) = base {
+        let _ = write!(
+            implementation,
+            "common::impl_deref! {{ {name} as {base}, no_display }}"
+        );
+    }
+
+    implementation.parse().unwrap()
+}
+
+// Adds from_raw() and as_raw() for the `parameters: *mut c_void` that hooks
+// get. #[offset(..)] on a field checks its offset at compile time.
+// #[ufunction("Function ...")] adds verify_layout(), which checks every field
+// against the function's reflected parameters.
+#[proc_macro_derive(UParams, attributes(ufunction, offset))]
+pub fn derive_uparams(input: TokenStream) -> TokenStream {
+    let UParams {
+        name,
+        function,
+        fields,
+    } = UParams::from(input);
+
+    let mut offset_checks = String::new();
+
+    for field in &fields {
+        if let Some(offset) = &field.offset {
+            let field = &field.name;
+
+            let _ = write!(
+                offset_checks,
+                "\n    assert!(core::mem::offset_of!({name}, {field}) == {offset}, \
+                 \"{name}.{field} is not at offset {offset}\");"
+            );
+        }
+    }
+
+    let verify_layout = function.map_or_else(String::new, |function| {
+        let mut param_fields = String::new();
+
+        for field in &fields {
+            let field = &field.name;
+
+            let _ = write!(
+                param_fields,
+                "\n                common::params::ParamField {{ \
+                 name: \"{field}\", \
+                 offset: core::mem::offset_of!(Self, {field}), \
+                 size: common::params::field_size(|p: &Self| &p.{field}) }},"
+            );
+        }
+
+        format!(
+            include_str!("impl_uparams_verify_layout"),
+            function = function,
+            fields = param_fields,
+        )
+    });
+
+    format!(
+        include_str!("impl_uparams"),
+        name = name,
+        verify_layout = verify_layout,
+        offset_checks = offset_checks,
+    )
+    .parse()
+    .unwrap()
+}
+
+// Registers a hook for a UFunction. The hook crate installs every registered
+// hook at attach.
+//
+// #[ue_hook("FSD.PlayerCharacter:ReceiveTick")]
+// unsafe fn on_receive_tick(call: &mut Call<ReceiveTickParameters>) {
+//     call.call_original();
+// }
+#[proc_macro_attribute]
+pub fn ue_hook(attribute: TokenStream, item: TokenStream) -> TokenStream {
+    let path = ue_hook::function_path(attribute);
+    let name = ue_hook::function_name(&item);
+
+    let glue: TokenStream = format!(include_str!("impl_ue_hook"), name = name, path = path)
+        .parse()
+        .unwrap();
+
+    let mut output = item;
+    output.extend(glue);
+    output
+}
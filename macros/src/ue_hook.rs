@@ -0,0 +1,49 @@
+use proc_macro::{Literal, TokenStream, TokenTree};
+
+// Turns "FSD.PlayerCharacter:ReceiveTick" into
+// "Function /Script/FSD.PlayerCharacter.ReceiveTick". Blueprint functions give
+// their full object path instead of a script package, e.g.
+// "/Game/Character/BP_PlayerCharacter.BP_PlayerCharacter_C:ReceiveTick".
+pub fn function_path(attribute: TokenStream) -> String {
+    let mut tokens = attribute.into_iter();
+
+    let Some(TokenTree::Literal(literal)) = tokens.next() else {
+        panic!("expected a path like #[ue_hook(\"FSD.PlayerCharacter:ReceiveTick\")]");
+    };
+
+    let path = unquote(&literal);
+
+    let Some((outer, function)) = path.rsplit_once(':') else {
+        panic!("expected a ':' between the class and the function in {path}");
+    };
+
+    if outer.starts_with('/') {
+        format!("Function {outer}.{function}")
+    } else {
+        format!("Function /Script/{outer}.{function}")
+    }
+}
+
+fn unquote(literal: &Literal) -> String {
+    let literal = literal.to_string();
+
+    literal
+        .strip_prefix('"')
+        .and_then(|l| l.strip_suffix('"'))
+        .unwrap_or_else(|| panic!("expected a string literal, got {literal}"))
+        .to_owned()
+}
+
+pub fn function_name(item: &TokenStream) -> String {
+    let mut tokens = item.clone().into_iter();
+
+    tokens
+        .by_ref()
+        .find(|t| matches!(t, TokenTree::Ident(ident) if ident.to_string() == "fn"))
+        .expect("#[ue_hook] only applies to functions");
+
+    match tokens.next() {
+        Some(TokenTree::Ident(name)) => name.to_string(),
+        token => panic!("expected a function name after fn, got {token:?}"),
+    }
+}
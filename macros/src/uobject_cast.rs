@@ -0,0 +1,119 @@
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+// #[derive(UObjectCast)]
+// #[uclass("Class /Script/FSD.PlayerCharacter")]
+// #[cast_flags(CASTCLASS_APawn)]
+// #[repr(C)]
+// pub struct PlayerCharacter {
+//     base: Character,
+//     ...
+// }
+pub struct UObjectCast {
+    pub name: String,
+
+    // The full name that GUObjectArray.find() takes.
+    pub class: String,
+
+    // Lets from_object() check the class cast flags instead of walking the
+    // class hierarchy.
+    pub cast_flags: Option<String>,
+
+    // The type of the `base` field, if there is one.
+    pub base: Option<String>,
+}
+
+impl From<TokenStream> for UObjectCast {
+    fn from(stream: TokenStream) -> Self {
+        let mut class = None;
+        let mut cast_flags = None;
+        let mut stream = stream.into_iter();
+
+        let name = loop {
+            match stream.next() {
+                Some(TokenTree::Group(attribute))
+                    if attribute.delimiter() == Delimiter::Bracket =>
+                {
+                    let mut attribute = attribute.stream().into_iter();
+
+                    let (Some(TokenTree::Ident(key)), Some(TokenTree::Group(value))) =
+                        (attribute.next(), attribute.next())
+                    else {
+                        continue;
+                    };
+
+                    match key.to_string().as_str() {
+                        "uclass" => class = Some(unquote(&value.stream().to_string())),
+                        "cast_flags" => cast_flags = Some(value.stream().to_string()),
+                        _ => {}
+                    }
+                }
+
+                Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => {
+                    let Some(TokenTree::Ident(name)) = stream.next() else {
+                        panic!("expected name after struct keyword");
+                    };
+
+                    break name.to_string();
+                }
+
+                Some(_) => {}
+                None => panic!("UObjectCast only applies to structs"),
+            }
+        };
+
+        let Some(TokenTree::Group(fields)) = stream.next() else {
+            panic!("expected fields after {name}");
+        };
+
+        Self {
+            class: class.unwrap_or_else(|| panic!("{name} needs a #[uclass(\"Class ...\")]")),
+            cast_flags,
+            base: find_base(fields.stream()),
+            name,
+        }
+    }
+}
+
+pub fn unquote(literal: &str) -> String {
+    literal
+        .strip_prefix('"')
+        .and_then(|l| l.strip_suffix('"'))
+        .unwrap_or_else(|| panic!("expected a string literal, got {literal}"))
+        .to_owned()
+}
+
+fn find_base(fields: TokenStream) -> Option<String> {
+    let mut tokens = fields.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        if matches!(&token, TokenTree::Ident(ident) if ident.to_string() == "base")
+            && matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ':')
+        {
+            tokens.next();
+            return Some(field_type(tokens));
+        }
+    }
+
+    None
+}
+
+// Collects the tokens up to the comma that ends the field.
+fn field_type(tokens: impl Iterator<Item = TokenTree>) -> String {
+    let mut depth = 0;
+    let mut typ = TokenStream::new();
+
+    for token in tokens {
+        if let TokenTree::Punct(p) = &token {
+            match p.as_char() {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                ',' if depth == 0 => break,
+                _ => {}
+            }
+        }
+
+        typ.extend([token]);
+    }
+
+    typ.to_string()
+}
@@ -0,0 +1,123 @@
+use crate::uobject_cast::unquote;
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
+
+// #[derive(UParams)]
+// #[ufunction("Function /Script/FSD.PlayerCharacter.ReceiveTick")]
+// #[repr(C)]
+// struct ReceiveTickParameters {
+//     #[offset(0x0)]
+//     DeltaSeconds: f32,
+// }
+pub struct UParams {
+    pub name: String,
+
+    // Checked against reflection at runtime.
+    pub function: Option<String>,
+
+    pub fields: Vec<Field>,
+}
+
+pub struct Field {
+    pub name: String,
+
+    // Checked at compile time.
+    pub offset: Option<String>,
+}
+
+impl From<TokenStream> for UParams {
+    fn from(stream: TokenStream) -> Self {
+        let mut function = None;
+        let mut stream = stream.into_iter();
+
+        let name = loop {
+            match stream.next() {
+                Some(TokenTree::Group(attribute))
+                    if attribute.delimiter() == Delimiter::Bracket =>
+                {
+                    if let Some(value) = attribute_value(&attribute, "ufunction") {
+                        function = Some(unquote(&value));
+                    }
+                }
+
+                Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => {
+                    let Some(TokenTree::Ident(name)) = stream.next() else {
+                        panic!("expected name after struct keyword");
+                    };
+
+                    break name.to_string();
+                }
+
+                Some(_) => {}
+                None => panic!("UParams only applies to structs"),
+            }
+        };
+
+        let Some(TokenTree::Group(fields)) = stream.next() else {
+            panic!("expected fields after {name}");
+        };
+
+        Self {
+            name,
+            function,
+            fields: parse_fields(fields.stream()),
+        }
+    }
+}
+
+// The text inside #[key(...)].
+fn attribute_value(attribute: &Group, key: &str) -> Option<String> {
+    let mut attribute = attribute.stream().into_iter();
+
+    match (attribute.next(), attribute.next()) {
+        (Some(TokenTree::Ident(k)), Some(TokenTree::Group(value))) if k.to_string() == key => {
+            Some(value.stream().to_string())
+        }
+        _ => None,
+    }
+}
+
+fn parse_fields(stream: TokenStream) -> Vec<Field> {
+    let mut fields = vec![];
+    let mut offset = None;
+    let mut tokens = stream.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Group(attribute) if attribute.delimiter() == Delimiter::Bracket => {
+                if let Some(value) = attribute_value(&attribute, "offset") {
+                    offset = Some(value);
+                }
+            }
+
+            TokenTree::Ident(ident) if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ':') =>
+            {
+                fields.push(Field {
+                    name: ident.to_string(),
+                    offset: offset.take(),
+                });
+
+                skip_type(&mut tokens);
+            }
+
+            _ => {}
+        }
+    }
+
+    fields
+}
+
+// Skips past the comma that ends the field.
+fn skip_type(tokens: impl Iterator<Item = TokenTree>) {
+    let mut depth = 0;
+
+    for token in tokens {
+        if let TokenTree::Punct(p) = &token {
+            match p.as_char() {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                ',' if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+// Limits which packages get generated. The full SDK is huge and most hooks
+// only need a few packages.
+//
+// Reads the SDK_GEN_PACKAGES environment variable if it's set, otherwise
+// packages.txt in the SDK directory. Either one is a list of package paths
+// separated by newlines or commas:
+//
+//     /Script/FSD
+//     /Script/Engine
+//     !/Game/WeaponsNTools/Cosmetics
+//
+// A package is generated if it starts with any allowed path (or there are
+// none) and doesn't start with any denied path, which begins with a `!`.
+// Generated code still names types from excluded packages, so the SDK only
+// builds if everything it references is included.
+
+use crate::sdk_file;
+use std::env;
+use std::fs;
+
+const FILTER_VARIABLE: &str = "SDK_GEN_PACKAGES";
+
+#[derive(Default)]
+pub struct PackageFilter {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl PackageFilter {
+    pub fn load() -> Self {
+        let Some(config) = env::var(FILTER_VARIABLE)
+            .ok()
+            .or_else(|| fs::read_to_string(sdk_file!("packages.txt")).ok())
+        else {
+            return Self::default();
+        };
+
+        let mut filter = Self::default();
+
+        for entry in config.split([',', '\n']).map(str::trim) {
+            if let Some(denied) = entry.strip_prefix('!') {
+                filter.deny.push(denied.trim().to_owned());
+            } else if !entry.is_empty() && !entry.starts_with('#') {
+                filter.allow.push(entry.to_owned());
+            }
+        }
+
+        println!(
+            "Package filter: {} allowed, {} denied.",
+            filter.allow.len(),
+            filter.deny.len()
+        );
+
+        filter
+    }
+
+    pub fn includes(&self, package: &str) -> bool {
+        let allowed = self.allow.is_empty() || self.allow.iter().any(|a| package.starts_with(a));
+        let denied = self.deny.iter().any(|d| package.starts_with(d));
+        allowed && !denied
+    }
+}
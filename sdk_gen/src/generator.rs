@@ -1,21 +1,48 @@
-use crate::game::{
-    self, EPropertyFlags, FBoolProperty, FProperty, PropertyDisplayable, TPair, UEnum,
-};
+use crate::filter::PackageFilter;
+use crate::game::{self, PropertyDisplayable};
+use crate::graph::DependencyGraph;
+use crate::mangle;
 use crate::{sdk_file, sdk_path};
 
+use common::{city_hash_64, Hex, List};
 use common::{
-    EClassCastFlags, FName, GUObjectArray, UClass, UFunction, UObject, UPackage, UStruct,
+    EClassCastFlags, EFunctionFlags, EPropertyFlags, FBoolProperty, FName, FProperty,
+    GUObjectArray, TPair, UClass, UEnum, UFunction, UObject, UPackage, UStruct,
 };
-use common::{Hex, List, SplitIterator};
 
 use core::cell::Cell;
 use core::cmp::Ordering;
 use core::fmt::{self, Display, Formatter};
 use core::str;
+use std::collections::HashMap;
 use std::fmt::Write as _;
-use std::fs::File;
-use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::fs::{self, File};
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+// Pairs each EClassCastFlags constant with its name.
+macro_rules! cast_flags {
+    ($($flag:ident),* $(,)?) => {
+        [$((EClassCastFlags::$flag, stringify!($flag))),*]
+    };
+}
+
+// The cast flags that classes in the SDK add.
+const OWN_CAST_FLAGS: [(EClassCastFlags, &str); 11] = cast_flags![
+    CASTCLASS_AActor,
+    CASTCLASS_APawn,
+    CASTCLASS_APlayerController,
+    CASTCLASS_ULevel,
+    CASTCLASS_USceneComponent,
+    CASTCLASS_UPrimitiveComponent,
+    CASTCLASS_USkinnedMeshComponent,
+    CASTCLASS_USkeletalMeshComponent,
+    CASTCLASS_UStaticMeshComponent,
+    CASTCLASS_UBlueprint,
+    CASTCLASS_UPackage,
+];
 
 #[derive(macros::NoPanicErrorDebug)]
 pub enum Error {
@@ -31,11 +58,15 @@ pub enum Error {
     BitfieldFull,
 
     MaxParameters,
+    WriterPanicked,
 }
 
+// Packages are generated into memory on the attach thread, which is the only
+// one that reads reflection data. Worker threads write the files at the end.
 struct Package {
     ptr: *mut UPackage,
-    file: File,
+    path: PathBuf,
+    contents: Vec<u8>,
 }
 
 impl Drop for Package {
@@ -49,39 +80,154 @@ impl Drop for Package {
 pub struct Generator {
     lib_rs: File,
     packages: List<Package, 256>,
-    blueprint_generated_package_file: BufWriter<File>,
+    blueprint_generated_package_file: Vec<u8>,
+    layouts_file: Vec<u8>,
+    filter: PackageFilter,
 }
 
+// The head of layouts.rs, which has each generated struct's size and field
+// offsets as compiled, so that the hook can check them against the game.
+const LAYOUTS_HEADER: &str = "\
+    use core::mem::{offset_of, size_of};\n\
+    \n\
+    pub struct Layout {\n\
+    \x20   // The struct's full name, like \"Class /Script/FSD.Item\".\n\
+    \x20   pub name: &'static str,\n\
+    \x20   pub size: usize,\n\
+    \x20   // Each field's property name and offset. Bitfields aren't here.\n\
+    \x20   pub fields: &'static [(&'static str, usize)],\n\
+    }\n\
+    \n\
+    pub static LAYOUTS: &[Layout] = &[\n";
+
 impl Generator {
     pub unsafe fn new() -> Result<Generator, Error> {
-        println!("SDK output: {}", sdk_path!());
-        std::fs::create_dir(Path::new(sdk_path!()).join("src")).ok();
+        println!("SDK output: {}", sdk_path!().display());
+        std::fs::create_dir(sdk_path!().join("src")).ok();
         let mut lib_rs = File::create(sdk_file!("src/lib.rs"))?;
         write!(lib_rs, "\
             #![allow(dead_code, non_camel_case_types, non_snake_case, non_upper_case_globals)]\n\
             #![allow(clippy::missing_safety_doc, clippy::too_many_arguments, clippy::type_complexity)]\n\
-            pub mod blueprint_generated;\n",
+            pub mod blueprint_generated;\n\
+            pub mod layouts;\n",
         )?;
 
         Ok(Generator {
             lib_rs,
             packages: List::new(),
-            blueprint_generated_package_file: BufWriter::new(File::create(sdk_file!(
-                "src/blueprint_generated.rs"
-            ))?),
+            blueprint_generated_package_file: Vec::new(),
+            layouts_file: LAYOUTS_HEADER.as_bytes().to_vec(),
+            filter: PackageFilter::load(),
         })
     }
 
     pub unsafe fn generate_sdk(&mut self) -> Result<(), Error> {
-        for object in (*GUObjectArray).iter().filter(|o| !o.is_null()) {
-            if (*object).fast_is(
-                EClassCastFlags::CASTCLASS_UClass | EClassCastFlags::CASTCLASS_UScriptStruct,
-            ) {
-                self.generate_structure(object.cast())?;
-            } else if (*object).fast_is(EClassCastFlags::CASTCLASS_UEnum) {
+        let mut objects = sorted_types(&self.filter);
+        mangle::assign_type_names(&objects);
+
+        // Packages are registered as their first type is generated, so this
+        // declares each package's module in lib.rs after the ones it uses.
+        // The sort is stable, so a package's types stay sorted by path.
+        let graph = DependencyGraph::build(&self.filter);
+        let order: HashMap<&str, usize> = graph
+            .package_order()
+            .into_iter()
+            .enumerate()
+            .map(|(i, package)| (package, i))
+            .collect();
+
+        objects.sort_by_key(|&(_, object)| {
+            order
+                .get(package_name(object))
+                .copied()
+                .unwrap_or(usize::MAX)
+        });
+
+        for (_, object) in objects {
+            if (*object).fast_is(EClassCastFlags::CASTCLASS_UEnum) {
                 self.generate_enum(object.cast())?;
+            } else {
+                self.generate_structure(object.cast())?;
             }
         }
+
+        writeln!(self.layouts_file, "];")?;
+        self.write_files()?;
+        mangle::write_renames()?;
+        Ok(())
+    }
+
+    // Splits the package files between a few threads to write. Files whose
+    // hash matches the last run are left alone. The generated text is a pure
+    // function of the package's reflection data, so an unchanged hash means
+    // the game didn't change the package.
+    fn write_files(&self) -> Result<(), Error> {
+        let blueprint_generated = sdk_file!("src/blueprint_generated.rs");
+        let layouts = sdk_file!("src/layouts.rs");
+
+        let files: Vec<(&Path, &[u8])> = self
+            .packages
+            .iter()
+            .map(|p| (p.path.as_path(), p.contents.as_slice()))
+            .chain([
+                (
+                    blueprint_generated.as_path(),
+                    self.blueprint_generated_package_file.as_slice(),
+                ),
+                (layouts.as_path(), self.layouts_file.as_slice()),
+            ])
+            .collect();
+
+        let previous_hashes = read_hashes();
+        let mut hashes = String::new();
+
+        let files: Vec<(&Path, &[u8])> = files
+            .into_iter()
+            .filter(|(path, contents)| {
+                let hash = city_hash_64(contents);
+                let _ = writeln!(hashes, "{:016x} {}", hash, path.display());
+
+                previous_hashes.get(*path) != Some(&hash) || !path.exists()
+            })
+            .collect();
+
+        println!(
+            "Writing {} changed packages. The rest are unchanged.",
+            files.len()
+        );
+
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let workers = thread::available_parallelism().map_or(4, NonZeroUsize::get);
+        let files_per_worker = files.len().div_ceil(workers);
+
+        thread::scope(|scope| {
+            let writers: Vec<_> = files
+                .chunks(files_per_worker)
+                .map(|files| {
+                    scope.spawn(move || -> Result<(), std::io::Error> {
+                        for (path, contents) in files {
+                            fs::write(path, contents)?;
+                        }
+
+                        Ok(())
+                    })
+                })
+                .collect();
+
+            for writer in writers {
+                writer.join().map_err(|_| Error::WriterPanicked)??;
+            }
+
+            Ok::<(), Error>(())
+        })?;
+
+        // Only record the hashes once every file is written, so that a failed
+        // run doesn't leave stale files looking up to date.
+        fs::write(sdk_file!("package_hashes.txt"), hashes)?;
+
         Ok(())
     }
 
@@ -97,22 +243,15 @@ impl Generator {
         Ok(self.packages.get_unchecked_mut(package))
     }
 
-    unsafe fn get_package_file(
-        &mut self,
-        object: *mut UObject,
-    ) -> Result<BufWriter<&mut File>, Error> {
-        Ok(BufWriter::new(&mut self.get_package(object)?.file))
+    unsafe fn get_package_file(&mut self, object: *mut UObject) -> Result<&mut Vec<u8>, Error> {
+        Ok(&mut self.get_package(object)?.contents)
     }
 
     unsafe fn register_package(&mut self, package: *mut UPackage) -> Result<(), Error> {
         let package_name = (*package).short_name();
 
-        // Create a Rust module file for this package.
-        let file = File::create(
-            Path::new(sdk_path!())
-                .join("src")
-                .join(format!("{}.rs", package_name)),
-        )?;
+        // The Rust module file for this package.
+        let path = sdk_path!().join("src").join(format!("{}.rs", package_name));
 
         // Declare the module in the SDK lib.rs.
         writeln!(&mut self.lib_rs, "pub mod {};", package_name)?;
@@ -120,7 +259,11 @@ impl Generator {
         // Register this package's index in our package cache.
         (*package).PIEInstanceID = self.packages.len() as i32;
 
-        let p = Package { ptr: package, file };
+        let p = Package {
+            ptr: package,
+            path,
+            contents: Vec::new(),
+        };
 
         // Save the package to our cache.
         self.packages.push(p).map_err(|_| Error::MaxPackages)?;
@@ -156,7 +299,7 @@ impl Generator {
             "// {}\n#[repr(transparent)]\n#[derive(Copy, Clone, PartialEq, Eq)]\npub struct {name}({});\n\nimpl {name} {{",
             *enumeration,
             representation,
-            name = (*enumeration).name(),
+            name = mangle::type_name(enumeration.cast()),
         )?;
 
         for variant in rest.iter() {
@@ -177,24 +320,133 @@ impl Generator {
             let class = structure.cast::<UClass>();
 
             if (*class).is_blueprint_generated() {
-                return StructGenerator::new(
+                StructGenerator::new(
                     structure,
                     (*class).package(),
                     &mut self.blueprint_generated_package_file,
                     true,
                 )
-                .generate();
+                .generate()?;
+
+                return self.write_layout(structure, "blueprint_generated", true);
             }
         }
 
         let package = self.get_package(structure.cast())?;
 
-        // TODO(perf): Don't need to create a new `BufWriter` if the previous object is from the same package.
-        // Reuse previous buffer to reduce total `WriteFile` calls.
-        let file = BufWriter::new(&mut package.file);
+        StructGenerator::new(structure, package.ptr, &mut package.contents, false).generate()?;
 
-        StructGenerator::new(structure, package.ptr, file, false).generate()
+        let module = (*(*structure).package()).short_name();
+        self.write_layout(structure, module, false)
     }
+
+    // Adds the struct's entry to layouts.rs, with the fields that
+    // StructGenerator gave it a name for.
+    unsafe fn write_layout(
+        &mut self,
+        structure: *const UStruct,
+        module: &str,
+        is_blueprint_generated: bool,
+    ) -> Result<(), Error> {
+        if (*structure).PropertiesSize == 0 {
+            // StructGenerator skipped it.
+            return Ok(());
+        }
+
+        let typ = format!("crate::{}::{}", module, mangle::type_name(structure.cast()));
+        let out = &mut self.layouts_file;
+
+        writeln!(
+            out,
+            "    Layout {{\n        name: {:?},\n        size: size_of::<{}>(),\n        fields: &[",
+            (*structure).to_string(),
+            typ,
+        )?;
+
+        let mut property = (*structure).ChildProperties.cast::<FProperty>();
+
+        while !property.is_null() {
+            let is_bitfield = (*property).is(EClassCastFlags::CASTCLASS_FBoolProperty)
+                && (*property.cast::<FBoolProperty>()).is_bitfield();
+
+            if !is_bitfield {
+                let name = (*property).base.NamePrivate;
+
+                if is_blueprint_generated {
+                    writeln!(
+                        out,
+                        "            ({:?}, offset_of!({}, {})),",
+                        name.text(),
+                        typ,
+                        CleanedName::new(name)
+                    )?;
+                } else {
+                    writeln!(
+                        out,
+                        "            ({:?}, offset_of!({}, {})),",
+                        name.text(),
+                        typ,
+                        name
+                    )?;
+                }
+            }
+
+            property = (*property).base.Next.cast();
+        }
+
+        writeln!(out, "        ],\n    }},")?;
+        Ok(())
+    }
+}
+
+// Each line is "{hash} {path}".
+fn read_hashes() -> HashMap<PathBuf, u64> {
+    let Ok(hashes) = fs::read_to_string(sdk_file!("package_hashes.txt")) else {
+        return HashMap::new();
+    };
+
+    hashes
+        .lines()
+        .filter_map(|line| {
+            let (hash, path) = line.split_once(' ')?;
+            let hash = u64::from_str_radix(hash, 16).ok()?;
+            Some((PathBuf::from(path), hash))
+        })
+        .collect()
+}
+
+// Every class, struct, and enum in the packages that `filter` includes, with
+// its path. GUObjectArray's order changes between runs, so sort by path
+// instead. Then SDK diffs only show what the game changed.
+pub unsafe fn sorted_types(filter: &PackageFilter) -> Vec<(String, *mut UObject)> {
+    let mut types: Vec<(String, *mut UObject)> = (*GUObjectArray)
+        .iter()
+        .filter(|o| {
+            !o.is_null()
+                && (**o).fast_is(
+                    EClassCastFlags::CASTCLASS_UClass
+                        | EClassCastFlags::CASTCLASS_UScriptStruct
+                        | EClassCastFlags::CASTCLASS_UEnum,
+                )
+                && filter.includes(package_name(*o))
+        })
+        .map(|o| (path(o), o))
+        .collect();
+
+    types.sort_by(|(a, _), (b, _)| a.cmp(b));
+    types
+}
+
+// "/Script/FSD"
+pub unsafe fn package_name(object: *const UObject) -> &'static str {
+    (*(*object).package().cast::<UObject>()).name()
+}
+
+// "/Script/FSD.PlayerCharacter" for "Class /Script/FSD.PlayerCharacter".
+pub unsafe fn path(object: *const UObject) -> String {
+    let mut path = String::new();
+    let _ = (*object).write_path(&mut path);
+    path
 }
 
 unsafe fn get_enum_representation(variants: &[TPair<FName, i64>]) -> &'static str {
@@ -256,7 +508,7 @@ struct StructGenerator<W: Write> {
     last_bitfield_offset: Option<i32>,
     is_blueprint_generated: bool,
     inherited_type: List<u8, 128>,
-    name: CleanedName,
+    name: String,
 }
 
 impl<W: Write> StructGenerator<W> {
@@ -275,7 +527,7 @@ impl<W: Write> StructGenerator<W> {
             last_bitfield_offset: None,
             is_blueprint_generated,
             inherited_type: List::new(),
-            name: CleanedName::new((*structure).NamePrivate),
+            name: mangle::type_name(structure.cast()),
         }
     }
 
@@ -293,6 +545,12 @@ impl<W: Write> StructGenerator<W> {
         }
 
         self.add_deref_impls()?;
+        self.add_upcasts()?;
+        self.add_debug_impl()?;
+
+        if cfg!(feature = "gen_accessors") {
+            self.add_accessors()?;
+        }
 
         self.add_functions()?;
 
@@ -305,12 +563,12 @@ impl<W: Write> StructGenerator<W> {
         if base.is_null() {
             writeln!(
                 self.out,
-                "// {} is {} bytes.\n#[repr(C, align({}))]\npub struct {} {{",
+                "// {} is {} bytes.",
                 *self.structure,
                 Hex((*self.structure).PropertiesSize),
-                (*self.structure).MinAlignment,
-                self.name,
             )?;
+
+            self.write_struct_declaration()?;
         } else {
             self.write_header_inherited(base)?;
         }
@@ -318,20 +576,71 @@ impl<W: Write> StructGenerator<W> {
         Ok(())
     }
 
+    unsafe fn write_struct_declaration(&mut self) -> Result<(), Error> {
+        if self.is_class() {
+            // The derive adds the Deref impls too.
+            writeln!(
+                self.out,
+                "#[derive(macros::UObjectCast)]\n#[uclass(\"{}\")]",
+                *self.structure,
+            )?;
+
+            if let Some(name) = self.own_cast_flag() {
+                writeln!(self.out, "#[cast_flags({})]", name)?;
+            }
+        }
+
+        writeln!(
+            self.out,
+            "#[repr(C, align({}))]\npub struct {} {{",
+            (*self.structure).MinAlignment,
+            self.name,
+        )?;
+
+        Ok(())
+    }
+
+    // The cast flag that this class adds to its base's, if it adds one of
+    // OWN_CAST_FLAGS. Every subclass inherits it, so checking an object's class
+    // for it is the same as walking the class hierarchy, only faster.
+    unsafe fn own_cast_flag(&self) -> Option<&'static str> {
+        let flags = (*self.structure.cast::<UClass>()).ClassCastFlags;
+        let base = (*self.structure).SuperStruct;
+
+        let inherited = if base.is_null() {
+            EClassCastFlags(0)
+        } else {
+            (*base.cast::<UClass>()).ClassCastFlags
+        };
+
+        OWN_CAST_FLAGS
+            .iter()
+            .find(|(flag, _)| flags.any(*flag) && !inherited.any(*flag))
+            .map(|&(_, name)| name)
+    }
+
+    unsafe fn is_class(&self) -> bool {
+        (*self.structure).fast_is(EClassCastFlags::CASTCLASS_UClass)
+    }
+
+    unsafe fn is_interface(&self) -> bool {
+        self.is_class() && (*self.structure.cast::<UClass>()).is_interface()
+    }
+
     unsafe fn write_header_inherited(&mut self, base: *mut UStruct) -> Result<(), Error> {
         self.offset = (*base).PropertiesSize;
 
         writeln!(
             self.out,
-            "// {} is {} bytes ({} inherited).\n#[repr(C, align({}))]\npub struct {} {{",
+            "// {} is {} bytes ({} inherited).",
             *self.structure,
             Hex((*self.structure).PropertiesSize),
             Hex(self.offset),
-            (*self.structure).MinAlignment,
-            self.name,
         )?;
 
-        let base_name = (*base).name();
+        self.write_struct_declaration()?;
+
+        let base_name = mangle::type_name(base.cast());
         let base_package = (*base).package();
 
         let is_base_blueprint_generated = self.is_blueprint_generated
@@ -396,9 +705,10 @@ impl<W: Write> StructGenerator<W> {
             } else {
                 writeln!(
                     self.out,
-                    "    // offset: {offset}, size: {size}\n    pub {name}: {typ},\n",
+                    "    // offset: {offset}, size: {size}\n    // {flags}\n    pub {name}: {typ},\n",
                     offset = Hex(self.offset),
                     size = Hex(size),
+                    flags = (*property).flags(),
                     name = (*property).base.NamePrivate,
                     typ = PropertyDisplayable::new(
                         property,
@@ -474,9 +784,10 @@ impl<W: Write> StructGenerator<W> {
     ) -> Result<(), Error> {
         write!(
             self.out,
-            "    // offset: {offset}, size: {size}\n    pub ",
+            "    // offset: {offset}, size: {size}\n    // {flags}\n    pub ",
             offset = Hex(self.offset),
             size = Hex(size),
+            flags = (*property).flags(),
         )?;
 
         let name = (*property).base.NamePrivate;
@@ -595,8 +906,74 @@ impl<W: Write> StructGenerator<W> {
         Ok(())
     }
 
+    // A getter and setter for each field that look its offset up by name the
+    // first time, so that they still work if the field has moved since.
+    unsafe fn add_accessors(&mut self) -> Result<(), Error> {
+        writeln!(self.out, "impl {} {{", self.name)?;
+
+        let mut property = (*self.structure).ChildProperties.cast::<FProperty>();
+
+        while !property.is_null() {
+            let is_bitfield = (*property).is(EClassCastFlags::CASTCLASS_FBoolProperty)
+                && (*property.cast::<FBoolProperty>()).is_bitfield();
+
+            if !is_bitfield {
+                let name = (*property).base.NamePrivate;
+
+                let name = if self.is_blueprint_generated {
+                    CleanedName::new(name).to_string()
+                } else {
+                    name.to_string()
+                };
+
+                writeln!(
+                    self.out,
+                    include_str!("accessor.fmt"),
+                    flags = (*property).flags(),
+                    name = name,
+                    typ = PropertyDisplayable::new(
+                        property,
+                        self.package,
+                        self.is_blueprint_generated
+                    ),
+                    structure = *self.structure,
+                    property = (*property).base.NamePrivate.text(),
+                    offset = Hex((*property).Offset),
+                )?;
+            }
+
+            property = (*property).base.Next.cast();
+        }
+
+        writeln!(self.out, "}}\n")?;
+
+        Ok(())
+    }
+
+    // Debug writes each property's live value through reflection, so it shows
+    // what the game has even where the generated fields are off.
+    unsafe fn add_debug_impl(&mut self) -> Result<(), Error> {
+        let fmt = if self.is_class() {
+            String::from("common::inspect::fmt_object((self as *const Self).cast(), f)")
+        } else {
+            format!(
+                "common::inspect::fmt_struct(\"{}\", (self as *const Self).cast(), f)",
+                *self.structure
+            )
+        };
+
+        writeln!(
+            self.out,
+            include_str!("debug.fmt"),
+            name = self.name,
+            fmt = fmt
+        )?;
+
+        Ok(())
+    }
+
     unsafe fn add_deref_impls(&mut self) -> Result<(), Error> {
-        if !self.inherited_type.is_empty() {
+        if !self.inherited_type.is_empty() && !self.is_class() {
             writeln!(
                 self.out,
                 include_str!("deref.fmt"),
@@ -608,39 +985,93 @@ impl<W: Write> StructGenerator<W> {
         Ok(())
     }
 
+    // From impls to a reference to each ancestor, so that `.into()` upcasts
+    // straight to any of them. The Deref chain gets there one base at a time.
+    unsafe fn add_upcasts(&mut self) -> Result<(), Error> {
+        let mut ancestor = (*self.structure).SuperStruct;
+
+        // Structs without properties aren't generated, and neither are their
+        // own bases.
+        while !ancestor.is_null() && (*ancestor).PropertiesSize != 0 {
+            let name = mangle::type_name(ancestor.cast());
+            let package = (*ancestor).package();
+
+            let is_ancestor_blueprint_generated = self.is_blueprint_generated
+                && (*ancestor).fast_is(EClassCastFlags::CASTCLASS_UClass)
+                && (*ancestor.cast::<UClass>()).is_blueprint_generated();
+
+            let ancestor_type = if is_ancestor_blueprint_generated || package == self.package {
+                name
+            } else {
+                format!("crate::{}::{}", (*package).short_name(), name)
+            };
+
+            writeln!(
+                self.out,
+                include_str!("upcast.fmt"),
+                child = self.name,
+                ancestor = ancestor_type,
+            )?;
+
+            ancestor = (*ancestor).SuperStruct;
+        }
+
+        Ok(())
+    }
+
     unsafe fn add_functions(&mut self) -> Result<(), Error> {
+        let mut functions = vec![];
         let mut property = (*self.structure).Children;
-        let mut has_at_least_one_function = false;
 
         while !property.is_null() {
             if (*property).fast_is(EClassCastFlags::CASTCLASS_UFunction) {
-                if !has_at_least_one_function {
-                    has_at_least_one_function = true;
-                    writeln!(self.out, "impl {} {{", self.name)?;
-                }
-
-                self.process_function(property.cast())?;
+                functions.push(property.cast::<UFunction>());
             }
 
             property = (*property).Next;
         }
 
-        if has_at_least_one_function {
-            writeln!(self.out, "}}\n")?;
+        if functions.is_empty() {
+            return Ok(());
+        }
+
+        // Sort by name so that the methods don't move around between runs.
+        functions.sort_by(|&a, &b| (*a).name().cmp((*b).name()));
+
+        writeln!(self.out, "impl {} {{", self.name)?;
+
+        let mut has_static_function = false;
+
+        for function in functions {
+            has_static_function |= self.process_function(function)?;
+        }
+
+        if has_static_function {
+            self.add_default_object()?;
         }
 
+        writeln!(self.out, "}}\n")?;
+
         Ok(())
     }
 
-    unsafe fn process_function(&mut self, function: *const UFunction) -> Result<(), Error> {
+    // Returns whether the function is static.
+    unsafe fn process_function(&mut self, function: *const UFunction) -> Result<bool, Error> {
         enum Kind {
             Input,
             Output,
+
+            // A non-const reference parameter. The function reads it and
+            // writes it back.
+            InOut,
         }
 
         struct Parameter {
             property: *const FProperty,
             kind: Kind,
+
+            // Bytes between the previous parameter and this one.
+            padding: i32,
         }
 
         struct Parameters {
@@ -648,6 +1079,7 @@ impl<W: Write> StructGenerator<W> {
             package: *const UPackage,
             is_struct_blueprint_generated: bool,
             num_outputs: u8,
+            offset: i32,
         }
 
         impl Parameters {
@@ -657,6 +1089,7 @@ impl<W: Write> StructGenerator<W> {
                     package,
                     is_struct_blueprint_generated,
                     num_outputs: 0,
+                    offset: 0,
                 }
             }
 
@@ -675,14 +1108,59 @@ impl<W: Write> StructGenerator<W> {
                         && !flags.contains(EPropertyFlags::CPF_ConstParm))
                 {
                     self.num_outputs += 1;
-                    Kind::Output
+
+                    if flags.contains(EPropertyFlags::CPF_ReferenceParm) {
+                        Kind::InOut
+                    } else {
+                        Kind::Output
+                    }
                 } else if flags.contains(EPropertyFlags::CPF_Parm) {
                     Kind::Input
                 } else {
                     return Ok(());
                 };
 
-                self.add(Parameter { property, kind })?;
+                let (offset, size) = unsafe {
+                    (
+                        (*property).Offset,
+                        (*property).ElementSize * (*property).ArrayDim,
+                    )
+                };
+
+                let padding = (offset - self.offset).max(0);
+                self.offset = self.offset.max(offset + size);
+
+                self.add(Parameter {
+                    property,
+                    kind,
+                    padding,
+                })?;
+
+                Ok(())
+            }
+        }
+
+        // ProcessEvent copies ParmsSize bytes in and out of the parameters,
+        // which may be more than the fields we declare.
+        struct EndPadding(i32);
+
+        impl Display for EndPadding {
+            fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+                if self.0 > 0 {
+                    write!(f, "\n            pad_at_end: [u8; {}], ", self.0)?;
+                }
+
+                Ok(())
+            }
+        }
+
+        struct InitEndPadding(i32);
+
+        impl Display for InitEndPadding {
+            fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+                if self.0 > 0 {
+                    write!(f, "\n            pad_at_end: [0; {}], ", self.0)?;
+                }
 
                 Ok(())
             }
@@ -693,7 +1171,7 @@ impl<W: Write> StructGenerator<W> {
         impl<'a> Display for Inputs<'a> {
             fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
                 for parameter in self.0.parameters.iter() {
-                    if let Kind::Input = parameter.kind {
+                    if let Kind::Input | Kind::InOut = parameter.kind {
                         let parameter = parameter.property;
                         let name = CleanedName::new(unsafe { (*parameter).base.NamePrivate });
                         let typ = PropertyDisplayable::new(
@@ -720,7 +1198,7 @@ impl<W: Write> StructGenerator<W> {
                 }
 
                 for parameter in self.0.parameters.iter() {
-                    if let Kind::Output = parameter.kind {
+                    if let Kind::Output | Kind::InOut = parameter.kind {
                         let typ = PropertyDisplayable::new(
                             parameter.property,
                             self.0.package,
@@ -757,6 +1235,15 @@ impl<W: Write> StructGenerator<W> {
                         self.0.is_struct_blueprint_generated,
                     );
 
+                    if parameter.padding > 0 {
+                        write!(
+                            f,
+                            "\n            pad_at_{}: [u8; {}], ",
+                            Hex(unsafe { (*property).Offset - parameter.padding }),
+                            parameter.padding
+                        )?;
+                    }
+
                     if let Kind::Input = parameter.kind {
                         write!(f, "\n            {}: {}, ", name, typ)?;
                     } else {
@@ -777,17 +1264,36 @@ impl<W: Write> StructGenerator<W> {
         impl<'a> Display for InitStructFields<'a> {
             fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
                 for parameter in self.0.parameters.iter() {
-                    let name = CleanedName::new(unsafe { (*parameter.property).base.NamePrivate });
+                    let property = parameter.property;
+                    let name = CleanedName::new(unsafe { (*property).base.NamePrivate });
 
-                    if let Kind::Input = parameter.kind {
-                        write!(f, "\n            {}, ", name)?;
-                    } else {
+                    if parameter.padding > 0 {
                         write!(
                             f,
-                            "\n            {}: core::mem::MaybeUninit::uninit(), ",
-                            name
+                            "\n            pad_at_{}: [0; {}], ",
+                            Hex(unsafe { (*property).Offset - parameter.padding }),
+                            parameter.padding
                         )?;
                     }
+
+                    match parameter.kind {
+                        Kind::Input => write!(f, "\n            {}, ", name)?,
+
+                        // Zeroed rather than uninitialized, since the function
+                        // may free what's already in an out parameter, e.g. a
+                        // TArray's allocation.
+                        Kind::Output => write!(
+                            f,
+                            "\n            {}: core::mem::MaybeUninit::zeroed(), ",
+                            name
+                        )?,
+
+                        Kind::InOut => write!(
+                            f,
+                            "\n            {}: core::mem::MaybeUninit::new({}), ",
+                            name, name
+                        )?,
+                    }
                 }
 
                 Ok(())
@@ -805,7 +1311,7 @@ impl<W: Write> StructGenerator<W> {
                 }
 
                 for parameter in self.0.parameters.iter() {
-                    if let Kind::Output = parameter.kind {
+                    if let Kind::Output | Kind::InOut = parameter.kind {
                         let name =
                             CleanedName::new(unsafe { (*parameter.property).base.NamePrivate });
 
@@ -835,20 +1341,55 @@ impl<W: Write> StructGenerator<W> {
         }
 
         let cleaned_name = CleanedName::new((*function).NamePrivate);
+        let end_padding = ((*function).parms_size() as i32 - parameters.offset).max(0);
+        let is_static = (*function)
+            .FunctionFlags
+            .contains(EFunctionFlags::FUNC_Static);
 
         writeln!(
             self.out,
             include_str!("function.fmt"),
             name = cleaned_name,
             full_name = *function,
+            receiver = if is_static { "" } else { "&mut self, " },
             inputs = Inputs(&parameters),
             outputs = Outputs(&parameters),
             declare_struct_fields = DeclareStructFields(&parameters),
+            end_padding = EndPadding(end_padding),
             init_struct_fields = InitStructFields(&parameters),
+            init_end_padding = InitEndPadding(end_padding),
+            // Interface methods are called on the implementing object.
+            process_event = if self.is_interface() {
+                "process_interface_event"
+            } else {
+                "process_event"
+            },
+            this = if is_static {
+                "Self::default_object()"
+            } else {
+                "(self as *mut Self).cast()"
+            },
             return_values = ReturnValues(&parameters),
             flags = (*function).FunctionFlags,
         )?;
 
+        Ok(is_static)
+    }
+
+    // Static functions run on the class default object.
+    unsafe fn add_default_object(&mut self) -> Result<(), Error> {
+        let class = (*self.structure).name();
+
+        writeln!(
+            self.out,
+            include_str!("default_object.fmt"),
+            full_name = format_args!(
+                "{class} {package}.Default__{class}",
+                class = class,
+                package = (*self.package.cast::<UObject>()).name(),
+            ),
+        )?;
+
         Ok(())
     }
 }
@@ -869,38 +1410,11 @@ impl CleanedName {
 
 impl Display for CleanedName {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        let mut num_pieces_added = 0;
-        let text = unsafe { self.name.text() };
-
-        if text.starts_with(|c: char| c.is_ascii_digit()) {
-            f.write_str("Func_")?;
-        }
-
-        for piece in
-            SplitIterator::new(text.as_bytes(), |c| !c.is_ascii_alphanumeric() && c != b'_')
-        {
-            if num_pieces_added > 0 {
-                f.write_char('_')?;
-            }
-
-            write!(f, "{}", unsafe { str::from_utf8_unchecked(piece) })?;
-
-            num_pieces_added += 1;
-        }
-
-        let number = self.name.number();
-
-        if number > 0 {
-            write!(f, "_{}", number - 1)?;
-        }
+        let identifier = unsafe { mangle::name_identifier(self.name) };
 
         self.num_invalid_characters_replaced
-            .set(num_pieces_added - 1);
+            .set(identifier.num_invalid_characters_replaced);
 
-        if self.num_invalid_characters_replaced.get() > 0 {
-            write!(f, "_replaced")?;
-        }
-
-        Ok(())
+        f.write_str(&identifier.text)
     }
 }
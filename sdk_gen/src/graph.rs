@@ -0,0 +1,247 @@
+// Exports which classes and structs reference which, and the same graph
+// collapsed to packages, as Graphviz DOT and GraphML. Handy for seeing which
+// FSD systems depend on each other.
+
+use crate::filter::PackageFilter;
+use crate::generator::{package_name, path, sorted_types};
+use common::{
+    EClassCastFlags, FArrayProperty, FByteProperty, FClassProperty, FEnumProperty,
+    FInterfaceProperty, FMapProperty, FObjectPropertyBase, FProperty, FSetProperty,
+    FSoftClassProperty, FStructProperty, UObject, UStruct,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+
+#[derive(Default)]
+pub struct DependencyGraph {
+    // Type path to package path.
+    types: BTreeMap<String, &'static str>,
+
+    // From type path to type path.
+    edges: BTreeSet<(String, String)>,
+}
+
+impl DependencyGraph {
+    pub unsafe fn build(filter: &PackageFilter) -> Self {
+        let mut graph = Self::default();
+
+        for (from, object) in sorted_types(filter) {
+            graph.types.insert(from.clone(), package_name(object));
+
+            if !(*object).fast_is(
+                EClassCastFlags::CASTCLASS_UClass | EClassCastFlags::CASTCLASS_UScriptStruct,
+            ) {
+                continue;
+            }
+
+            let structure = object.cast::<UStruct>();
+
+            if !(*structure).SuperStruct.is_null() {
+                graph.add_edge(&from, (*structure).SuperStruct.cast());
+            }
+
+            let mut property = (*structure).ChildProperties.cast::<FProperty>();
+
+            while !property.is_null() {
+                graph.add_property_edges(&from, property);
+                property = (*property).base.Next.cast();
+            }
+        }
+
+        graph
+    }
+
+    unsafe fn add_edge(&mut self, from: &str, to: *const UObject) {
+        if !to.is_null() {
+            let to = path(to);
+
+            if to != from {
+                self.edges.insert((from.to_owned(), to));
+            }
+        }
+    }
+
+    unsafe fn add_property_edges(&mut self, from: &str, property: *const FProperty) {
+        if (*property).is(EClassCastFlags::CASTCLASS_FStructProperty) {
+            let property = property.cast::<FStructProperty>();
+            self.add_edge(from, (*property).Structure.cast());
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FClassProperty) {
+            let property = property.cast::<FClassProperty>();
+            self.add_edge(from, (*property).MetaClass.cast());
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FSoftClassProperty) {
+            let property = property.cast::<FSoftClassProperty>();
+            self.add_edge(from, (*property).MetaClass.cast());
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FObjectPropertyBase) {
+            let property = property.cast::<FObjectPropertyBase>();
+            self.add_edge(from, (*property).PropertyClass.cast());
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FInterfaceProperty) {
+            let property = property.cast::<FInterfaceProperty>();
+            self.add_edge(from, (*property).InterfaceClass.cast());
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FEnumProperty) {
+            let property = property.cast::<FEnumProperty>();
+            self.add_edge(from, (*property).Enumeration.cast());
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FByteProperty) {
+            let property = property.cast::<FByteProperty>();
+            self.add_edge(from, (*property).Enumeration.cast());
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FArrayProperty) {
+            let property = property.cast::<FArrayProperty>();
+            self.add_property_edges(from, (*property).Inner);
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FSetProperty) {
+            let property = property.cast::<FSetProperty>();
+            self.add_property_edges(from, (*property).ElementProp);
+        } else if (*property).is(EClassCastFlags::CASTCLASS_FMapProperty) {
+            let property = property.cast::<FMapProperty>();
+            self.add_property_edges(from, (*property).KeyProp);
+            self.add_property_edges(from, (*property).ValueProp);
+        }
+    }
+
+    fn package_of<'a>(&'a self, path: &'a str) -> &'a str {
+        self.types.get(path).copied().unwrap_or_else(|| {
+            // A type from a package that was filtered out.
+            path.split_once('.').map_or(path, |(package, _)| package)
+        })
+    }
+
+    pub fn package_edges(&self) -> BTreeSet<(&str, &str)> {
+        self.edges
+            .iter()
+            .map(|(from, to)| (self.package_of(from), self.package_of(to)))
+            .filter(|(from, to)| from != to)
+            .collect()
+    }
+
+    pub fn write_dot(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "digraph dependencies {{")?;
+        writeln!(out, "    rankdir=LR;")?;
+
+        // Cluster the types by package.
+        let mut packages: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+        for (path, package) in &self.types {
+            packages.entry(package).or_default().push(path);
+        }
+
+        for (i, (package, types)) in packages.iter().enumerate() {
+            writeln!(out, "    subgraph cluster_{} {{", i)?;
+            writeln!(out, "        label=\"{}\";", escape_dot(package))?;
+
+            for path in types {
+                writeln!(out, "        \"{}\";", escape_dot(path))?;
+            }
+
+            writeln!(out, "    }}")?;
+        }
+
+        for (from, to) in &self.edges {
+            writeln!(
+                out,
+                "    \"{}\" -> \"{}\";",
+                escape_dot(from),
+                escape_dot(to)
+            )?;
+        }
+
+        writeln!(out, "}}")
+    }
+
+    pub fn write_package_dot(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "digraph packages {{")?;
+
+        for (from, to) in self.package_edges() {
+            writeln!(
+                out,
+                "    \"{}\" -> \"{}\";",
+                escape_dot(from),
+                escape_dot(to)
+            )?;
+        }
+
+        writeln!(out, "}}")
+    }
+
+    pub fn write_graphml(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            out,
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+        )?;
+        writeln!(
+            out,
+            "  <key id=\"package\" for=\"node\" attr.name=\"package\" attr.type=\"string\"/>"
+        )?;
+        writeln!(
+            out,
+            "  <graph id=\"dependencies\" edgedefault=\"directed\">"
+        )?;
+
+        for (path, package) in &self.types {
+            writeln!(
+                out,
+                "    <node id=\"{}\"><data key=\"package\">{}</data></node>",
+                escape_xml(path),
+                escape_xml(package)
+            )?;
+        }
+
+        for (from, to) in &self.edges {
+            // GraphML requires both ends to be declared nodes.
+            if self.types.contains_key(to) {
+                writeln!(
+                    out,
+                    "    <edge source=\"{}\" target=\"{}\"/>",
+                    escape_xml(from),
+                    escape_xml(to)
+                )?;
+            }
+        }
+
+        writeln!(out, "  </graph>")?;
+        writeln!(out, "</graphml>")
+    }
+
+    // Packages ordered so that each one comes after the packages it depends
+    // on. Packages in a dependency cycle are ordered by path.
+    pub fn package_order(&self) -> Vec<&str> {
+        let edges = self.package_edges();
+        let mut packages: BTreeSet<&str> = self.types.values().copied().collect();
+        let mut order = Vec::with_capacity(packages.len());
+
+        while !packages.is_empty() {
+            let ready: Vec<&str> = packages
+                .iter()
+                .copied()
+                .filter(|&package| {
+                    !edges
+                        .iter()
+                        .any(|&(from, to)| from == package && packages.contains(to))
+                })
+                .collect();
+
+            // Everything left is in a cycle. Break it at the first package.
+            let ready = if ready.is_empty() {
+                vec![*packages.iter().next().unwrap_or(&"")]
+            } else {
+                ready
+            };
+
+            for package in ready {
+                packages.remove(package);
+                order.push(package);
+            }
+        }
+
+        order
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
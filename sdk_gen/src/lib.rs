@@ -1,10 +1,20 @@
-use common::{list, win, GUObjectArray, Hex, NamePoolData, Timer};
+use common::{
+    asset, curve, gameplay_tags, list, ready, win, ClassHierarchy, ClassTree, EClassCastFlags,
+    FProperty, GUObjectArray, Hex, NamePoolData, Timer, UObject, UStruct,
+};
 use std::io::{BufWriter, Write};
 use windows::Win32::{Foundation::HMODULE, System::LibraryLoader::FreeLibraryAndExitThread};
 
+mod filter;
+use filter::PackageFilter;
+mod graph;
+use graph::DependencyGraph;
 mod game;
 mod generator;
 use generator::Generator;
+mod mangle;
+mod objects;
+mod signature;
 mod util;
 
 #[derive(macros::NoPanicErrorDebug)]
@@ -15,6 +25,7 @@ enum Error {
     Generator(#[from] generator::Error),
     Common(#[from] common::Error),
     Io(#[from] std::io::Error),
+    Objects(#[from] objects::Error),
 }
 
 #[no_mangle]
@@ -35,13 +46,38 @@ unsafe extern "system" fn on_attach(dll: HMODULE) -> u32 {
 unsafe fn on_detach() {}
 
 unsafe fn run() -> Result<(), Error> {
-    common::init_globals(&win::Module::current()?)?;
+    let module = win::Module::current()?;
+
+    // Dumps what there is even if the game never gets there.
+    ready::wait(|| {
+        if common::init_globals(&module).is_err() {
+            Some("the game's globals")
+        } else if !ready::world_exists() {
+            Some("a world")
+        } else {
+            None
+        }
+    });
+
+    common::init_globals(&module)?;
     dump_globals()?;
 
     if cfg!(feature = "gen_sdk") {
         generate_sdk()?;
     }
 
+    if cfg!(feature = "gen_graph") {
+        export_dependency_graph()?;
+    }
+
+    if cfg!(feature = "gen_signatures") {
+        generate_signatures(&module)?;
+    }
+
+    if cfg!(feature = "gen_curves") {
+        dump_curves()?;
+    }
+
     common::idle();
     Ok(())
 }
@@ -49,7 +85,11 @@ unsafe fn run() -> Result<(), Error> {
 unsafe fn dump_globals() -> Result<(), Error> {
     let timer = Timer::new("dump global names and objects");
     dump_names()?;
-    dump_objects()?;
+    objects::dump()?;
+    dump_properties()?;
+    dump_class_hierarchy()?;
+    dump_assets()?;
+    dump_gameplay_tags()?;
     timer.stop();
     Ok(())
 }
@@ -57,25 +97,97 @@ unsafe fn dump_globals() -> Result<(), Error> {
 unsafe fn dump_names() -> Result<(), Error> {
     let mut file = BufWriter::new(std::fs::File::create(sdk_file!("global_names.txt"))?);
 
+    // [id] block:offset ansi|wide hash text
     for (index, name) in (*NamePoolData).iter() {
-        let text = (*name).text();
-        writeln!(&mut file, "[{}] {}", index.value(), text)?;
+        writeln!(
+            &mut file,
+            "[{}] {}:{} {} {:016x} {}",
+            index.value(),
+            index.block(),
+            index.offset(),
+            if (*name).is_wide() { "wide" } else { "ansi" },
+            (*name).hash(),
+            (*name).text()
+        )?;
     }
 
     Ok(())
 }
 
-unsafe fn dump_objects() -> Result<(), Error> {
-    let mut file = BufWriter::new(std::fs::File::create(sdk_file!("global_objects.txt"))?);
+unsafe fn dump_properties() -> Result<(), Error> {
+    let mut file = BufWriter::new(std::fs::File::create(sdk_file!("global_properties.txt"))?);
 
     for object in (*GUObjectArray).iter().filter(|o| !o.is_null()) {
-        writeln!(
-            &mut file,
-            "[{}] {} {}",
-            (*object).InternalIndex,
-            *object,
-            Hex(object)
-        )?;
+        if !(*object).fast_is(
+            EClassCastFlags::CASTCLASS_UClass
+                | EClassCastFlags::CASTCLASS_UScriptStruct
+                | EClassCastFlags::CASTCLASS_UFunction,
+        ) {
+            continue;
+        }
+
+        writeln!(&mut file, "{}", *object)?;
+
+        let mut property = (*object.cast::<UStruct>())
+            .ChildProperties
+            .cast::<FProperty>();
+
+        while !property.is_null() {
+            writeln!(
+                &mut file,
+                "    {} {} {} {}",
+                Hex((*property).Offset),
+                (*property).name(),
+                Hex((*property).ElementSize * (*property).ArrayDim),
+                (*property).flags(),
+            )?;
+
+            property = (*property).base.Next.cast();
+        }
+    }
+
+    Ok(())
+}
+
+unsafe fn dump_class_hierarchy() -> Result<(), Error> {
+    let mut file = BufWriter::new(std::fs::File::create(sdk_file!("class_hierarchy.txt"))?);
+
+    for object in (*GUObjectArray).iter().filter(|o| !o.is_null()) {
+        if (*object).fast_is(EClassCastFlags::CASTCLASS_UClass) {
+            writeln!(&mut file, "{}", ClassHierarchy(object.cast()))?;
+        }
+    }
+
+    // Everything derives from Object, so its tree is every class.
+    let object = (*GUObjectArray)
+        .find("Class /Script/CoreUObject.Object")
+        .map_err(common::Error::from)?;
+    write!(&mut file, "\n{}", ClassTree(object.cast()))?;
+
+    Ok(())
+}
+
+unsafe fn dump_assets() -> Result<(), Error> {
+    let mut file = BufWriter::new(std::fs::File::create(sdk_file!("asset_manifest.txt"))?);
+
+    for asset in asset::all_assets(true) {
+        writeln!(&mut file, "{} {}", asset.AssetClass, asset.ObjectPath)?;
+    }
+
+    Ok(())
+}
+
+// The tag tree, one part per line, indented under its parent:
+// Enemy
+//   Bug
+//     Glyphid
+unsafe fn dump_gameplay_tags() -> Result<(), Error> {
+    let mut file = BufWriter::new(std::fs::File::create(sdk_file!("gameplay_tags.txt"))?);
+
+    for tag in gameplay_tags::collect() {
+        let depth = tag.matches('.').count();
+        let part = tag.rsplit('.').next().unwrap_or(&tag);
+        writeln!(&mut file, "{:indent$}{}", "", part, indent = depth * 2)?;
     }
 
     Ok(())
@@ -87,3 +199,95 @@ unsafe fn generate_sdk() -> Result<(), Error> {
     timer.stop();
     Ok(())
 }
+
+unsafe fn export_dependency_graph() -> Result<(), Error> {
+    let timer = Timer::new("export dependency graph");
+    let graph = DependencyGraph::build(&PackageFilter::load());
+
+    graph.write_dot(BufWriter::new(std::fs::File::create(sdk_file!(
+        "dependencies.dot"
+    ))?))?;
+
+    graph.write_package_dot(BufWriter::new(std::fs::File::create(sdk_file!(
+        "package_dependencies.dot"
+    ))?))?;
+
+    graph.write_graphml(BufWriter::new(std::fs::File::create(sdk_file!(
+        "dependencies.graphml"
+    ))?))?;
+
+    let mut order = BufWriter::new(std::fs::File::create(sdk_file!("package_order.txt"))?);
+
+    for package in graph.package_order() {
+        writeln!(&mut order, "{}", package)?;
+    }
+
+    timer.stop();
+    Ok(())
+}
+
+unsafe fn generate_signatures(module: &win::Module) -> Result<(), Error> {
+    let timer = Timer::new("generate signatures");
+    let mut file = BufWriter::new(std::fs::File::create(sdk_file!("signatures.txt"))?);
+
+    for signature in signature::generate_all(module) {
+        writeln!(&mut file, "{}", signature.format(module))?;
+    }
+
+    timer.stop();
+    Ok(())
+}
+
+// Every loaded curve's keys, and its value at evenly spaced times across them,
+// since cubic curves are hard to picture from their keys alone.
+unsafe fn dump_curves() -> Result<(), Error> {
+    const SAMPLES: usize = 8;
+
+    let timer = Timer::new("dump curves");
+    let mut file = BufWriter::new(std::fs::File::create(sdk_file!("curves.txt"))?);
+
+    let write_curve = |file: &mut BufWriter<std::fs::File>, curve: curve::Curve| {
+        let base = curve.base();
+        let keys = curve.keys();
+
+        writeln!(
+            file,
+            "    default {}, before {}, after {}",
+            base.DefaultValue,
+            base.PreInfinityExtrap.name(),
+            base.PostInfinityExtrap.name()
+        )?;
+
+        for (time, value, interp) in &keys {
+            writeln!(file, "    key {} = {} ({})", time, value, interp.name())?;
+        }
+
+        if let (Some(first), Some(last)) = (keys.first(), keys.last()) {
+            for i in 0..=SAMPLES {
+                let time = first.0 + (last.0 - first.0) * i as f32 / SAMPLES as f32;
+                writeln!(file, "    at {} = {}", time, curve.eval(time))?;
+            }
+        }
+
+        Ok::<(), std::io::Error>(())
+    };
+
+    for object in curve::float_curves() {
+        if let Some(float_curve) = curve::float_curve(object) {
+            writeln!(&mut file, "{}", *object)?;
+            write_curve(&mut file, curve::Curve::Rich(&*float_curve))?;
+        }
+    }
+
+    for table in curve::tables() {
+        writeln!(&mut file, "{}", *table.cast::<UObject>())?;
+
+        for (name, row) in (*table).rows() {
+            writeln!(&mut file, "  {}", name)?;
+            write_curve(&mut file, row)?;
+        }
+    }
+
+    timer.stop();
+    Ok(())
+}
@@ -0,0 +1,212 @@
+// Turns Unreal names into Rust identifiers.
+//
+// Every identifier goes through `identifier()`, which is a pure function of
+// the name. Type names also have to be unique within their module, which they
+// often aren't: blueprint classes from every package share one module. So
+// before generating, `assign_type_names()` gives each type a name, adding
+// "_2", "_3", ... to later duplicates. Names from the previous run's
+// renames.json are reused first, so that a new type never takes over an old
+// type's name.
+
+use crate::sdk_file;
+use common::{EClassCastFlags, FName, UClass, UObject};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+
+const KEYWORDS: [&str; 52] = [
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+pub struct Identifier {
+    pub text: String,
+
+    // How many invalid characters were dropped.
+    pub num_invalid_characters_replaced: u8,
+}
+
+// Replaces runs of characters that can't appear in an identifier with '_',
+// then makes sure the result doesn't start with a digit or clash with a
+// keyword. Names with a number get it appended, like "Name_0" for "Name_1" in
+// Unreal, which counts from 1.
+pub fn identifier(text: &str, number: u32) -> Identifier {
+    let mut identifier = String::with_capacity(text.len() + 8);
+
+    if text.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.push_str("Func_");
+    }
+
+    for (i, piece) in text
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|piece| !piece.is_empty())
+        .enumerate()
+    {
+        if i > 0 {
+            identifier.push('_');
+        }
+
+        identifier.push_str(piece);
+    }
+
+    if number > 0 {
+        let _ = write!(identifier, "_{}", number - 1);
+    }
+
+    let num_invalid_characters_replaced = text
+        .chars()
+        .filter(|&c| !c.is_ascii_alphanumeric() && c != '_')
+        .count()
+        .try_into()
+        .unwrap_or(u8::MAX);
+
+    if num_invalid_characters_replaced > 0 {
+        identifier.push_str("_replaced");
+    }
+
+    if identifier.is_empty() {
+        identifier.push_str("Unnamed");
+    } else if KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+
+    Identifier {
+        text: identifier,
+        num_invalid_characters_replaced,
+    }
+}
+
+pub unsafe fn name_identifier(name: FName) -> Identifier {
+    identifier(name.text(), name.number())
+}
+
+static mut TYPE_NAMES: Option<HashMap<usize, String>> = None;
+static mut RENAMES: BTreeMap<String, String> = BTreeMap::new();
+
+// The module a type's generated code goes in.
+pub unsafe fn module(object: *const UObject) -> &'static str {
+    if (*object).fast_is(EClassCastFlags::CASTCLASS_UClass)
+        && (*object.cast::<UClass>()).is_blueprint_generated()
+    {
+        "blueprint_generated"
+    } else {
+        (*(*object).package()).short_name()
+    }
+}
+
+// `types` holds each type with its path, in generation order.
+pub unsafe fn assign_type_names(types: &[(String, *mut UObject)]) {
+    let previous = read_renames();
+    let mut names = HashMap::with_capacity(types.len());
+    let mut taken: HashSet<(&str, String)> = HashSet::new();
+
+    // Keep last run's names for types that are still around.
+    for (path, object) in types {
+        let module = module(*object);
+
+        if let Some(name) = previous
+            .get(path)
+            .and_then(|rust_path| rust_path.rsplit("::").next())
+        {
+            if taken.insert((module, name.to_owned())) {
+                names.insert(*object as usize, name.to_owned());
+            }
+        }
+    }
+
+    for (_, object) in types {
+        if names.contains_key(&(*object as usize)) {
+            continue;
+        }
+
+        let module = module(*object);
+        let base = name_identifier((**object).NamePrivate).text;
+        let mut name = base.clone();
+        let mut suffix = 2;
+
+        while !taken.insert((module, name.clone())) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+
+        names.insert(*object as usize, name);
+    }
+
+    let renames = &mut *core::ptr::addr_of_mut!(RENAMES);
+
+    for (path, object) in types {
+        let rust_path = format!("crate::{}::{}", module(*object), names[&(*object as usize)]);
+        renames.insert(path.clone(), rust_path);
+    }
+
+    TYPE_NAMES = Some(names);
+}
+
+// The Rust name of a class, struct, or enum. Types that weren't assigned a
+// name, such as ones from filtered out packages, get their plain identifier.
+pub unsafe fn type_name(object: *const UObject) -> String {
+    (*core::ptr::addr_of!(TYPE_NAMES))
+        .as_ref()
+        .and_then(|names| names.get(&(object as usize)))
+        .cloned()
+        .unwrap_or_else(|| name_identifier((*object).NamePrivate).text)
+}
+
+// Writes renames.json, which maps each type's path to its Rust path.
+pub unsafe fn write_renames() -> Result<(), std::io::Error> {
+    let renames = &*core::ptr::addr_of!(RENAMES);
+    let mut json = String::from("{\n");
+
+    for (i, (path, rust_path)) in renames.iter().enumerate() {
+        let separator = if i + 1 == renames.len() { "" } else { "," };
+        let _ = writeln!(
+            json,
+            "    \"{}\": \"{}\"{}",
+            escape(path),
+            escape(rust_path),
+            separator
+        );
+    }
+
+    json.push_str("}\n");
+    fs::write(sdk_file!("renames.json"), json)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Reads back what write_renames() wrote: one "key": "value" pair per line.
+fn read_renames() -> HashMap<String, String> {
+    let Ok(json) = fs::read_to_string(sdk_file!("renames.json")) else {
+        return HashMap::new();
+    };
+
+    json.lines()
+        .filter_map(|line| {
+            let (key, rest) = read_string(line)?;
+            let (value, _) = read_string(rest)?;
+            Some((key, value))
+        })
+        .collect()
+}
+
+// Reads the first JSON string in `text`. Returns it and the text after it.
+fn read_string(text: &str) -> Option<(String, &str)> {
+    let start = text.find('"')? + 1;
+    let mut string = String::new();
+    let mut chars = text[start..].char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((string, &text[start + i + 1..])),
+            '\\' => string.push(chars.next()?.1),
+            c => string.push(c),
+        }
+    }
+
+    None
+}
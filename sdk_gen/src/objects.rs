@@ -0,0 +1,197 @@
+// Every object in GUObjectArray, one per line, with its index, full name,
+// address, serial number, cluster root index, object flags, class cast flags
+// and outers, innermost first.
+//
+// SDK_GEN_OBJECTS_FORMAT picks the format. `text` (the default) writes
+// global_objects.txt, `jsonl` writes global_objects.jsonl with a JSON object
+// per line, and `csv` writes global_objects.csv with a header row.
+//
+// There are hundreds of thousands of objects, so each of GUObjectArray's
+// chunks is formatted on its own thread, then they're written in order.
+
+use crate::sdk_file;
+use common::{GUObjectArray, Hex, JsonString, ObjectIterator, UObject};
+use std::env;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::thread;
+
+const FORMAT_VARIABLE: &str = "SDK_GEN_OBJECTS_FORMAT";
+
+const CSV_HEADER: &str = "index,name,address,serial,cluster,flags,cast_flags,outers\n";
+
+#[derive(macros::NoPanicErrorDebug)]
+pub enum Error {
+    Io(#[from] io::Error),
+    FormatterPanicked,
+    UnknownFormat(String),
+}
+
+#[derive(Copy, Clone)]
+enum Format {
+    Text,
+    Jsonl,
+    Csv,
+}
+
+impl Format {
+    fn load() -> Result<Self, Error> {
+        match env::var(FORMAT_VARIABLE).as_deref() {
+            Err(_) | Ok("text") => Ok(Self::Text),
+            Ok("jsonl") => Ok(Self::Jsonl),
+            Ok("csv") => Ok(Self::Csv),
+            Ok(other) => Err(Error::UnknownFormat(other.to_owned())),
+        }
+    }
+
+    fn file(self) -> PathBuf {
+        match self {
+            Self::Text => sdk_file!("global_objects.txt"),
+            Self::Jsonl => sdk_file!("global_objects.jsonl"),
+            Self::Csv => sdk_file!("global_objects.csv"),
+        }
+    }
+}
+
+pub unsafe fn dump() -> Result<(), Error> {
+    let format = Format::load()?;
+
+    let chunks = thread::scope(|scope| {
+        let formatters: Vec<_> = (*GUObjectArray)
+            .chunks()
+            .map(|objects| scope.spawn(move || format_objects(objects, format)))
+            .collect();
+
+        formatters
+            .into_iter()
+            .map(|formatter| formatter.join().map_err(|_| Error::FormatterPanicked))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    let mut file = File::create(format.file())?;
+
+    if let Format::Csv = format {
+        file.write_all(CSV_HEADER.as_bytes())?;
+    }
+
+    for chunk in chunks {
+        file.write_all(&chunk?)?;
+    }
+
+    Ok(())
+}
+
+unsafe fn format_objects(objects: ObjectIterator, format: Format) -> Result<Vec<u8>, io::Error> {
+    let mut buffer = Vec::new();
+
+    for object in objects.filter(|o| !o.is_null()) {
+        let index = (*object).InternalIndex;
+        let item = (*GUObjectArray).index_to_object(index);
+
+        let (serial, cluster) = if item.is_null() {
+            (0, 0)
+        } else {
+            ((*item).SerialNumber, (*item).cluster_root_index())
+        };
+
+        let flags = Flags(object);
+        let cast_flags = (*(*object).class()).ClassCastFlags.0;
+        let outers = Outers(object);
+
+        match format {
+            Format::Text => writeln!(
+                &mut buffer,
+                "[{}] {} {} serial={} cluster={} flags={} cast={:#x} outers={}",
+                index,
+                *object,
+                Hex(object),
+                serial,
+                cluster,
+                flags,
+                cast_flags,
+                outers,
+            )?,
+            Format::Jsonl => writeln!(
+                &mut buffer,
+                "{{\"index\":{},\"name\":\"{}\",\"address\":\"{}\",\"serial\":{},\"cluster\":{},\
+                 \"flags\":\"{}\",\"cast_flags\":\"{:#x}\",\"outers\":\"{}\"}}",
+                index,
+                JsonString(&*object),
+                Hex(object),
+                serial,
+                cluster,
+                flags,
+                cast_flags,
+                JsonString(outers),
+            )?,
+            Format::Csv => writeln!(
+                &mut buffer,
+                "{},\"{}\",{},{},{},{},{:#x},\"{}\"",
+                index,
+                Csv(&*object),
+                Hex(object),
+                serial,
+                cluster,
+                flags,
+                cast_flags,
+                Csv(outers),
+            )?,
+        }
+    }
+
+    Ok(buffer)
+}
+
+// The object's flags by name, separated by `|`.
+struct Flags(*const UObject);
+
+impl Display for Flags {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let flags = unsafe { (*self.0).ObjectFlags }.to_string();
+
+        for (i, flag) in flags.split(", ").filter(|f| !f.is_empty()).enumerate() {
+            if i > 0 {
+                f.write_str("|")?;
+            }
+
+            f.write_str(flag)?;
+        }
+
+        Ok(())
+    }
+}
+
+// The names of the object's outers, innermost first, separated by `<`.
+struct Outers(*const UObject);
+
+impl Display for Outers {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        unsafe {
+            let mut outer = (*self.0).outer();
+            let mut first = true;
+
+            while !outer.is_null() {
+                if !first {
+                    f.write_str("<")?;
+                }
+
+                f.write_str((*outer).name())?;
+                outer = (*outer).outer();
+                first = false;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// A value for inside a quoted CSV field, with its quotes doubled.
+struct Csv<T>(T);
+
+impl<T: Display> Display for Csv<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        f.write_str(&self.0.to_string().replace('"', "\"\""))
+    }
+}
@@ -0,0 +1,256 @@
+// Derives signatures for addresses we already know, so that they don't have
+// to be rebuilt by hand after every patch. A signature is the shortest run of
+// bytes from an address that matches nowhere else in the executable's code,
+// with the operands that move between builds masked out: call and jump
+// targets, and RIP-relative displacements.
+//
+// We don't decode instructions. Operands are recognized by the opcodes that
+// usually carry them, so a signature can mask a byte it didn't need to, or
+// keep one that will move. Check a signature against the listing before
+// pasting it in.
+
+use common::{memory, win, GUObjectArray, Hex, NamePoolData};
+use core::fmt::Write as _;
+use core::slice;
+
+// Shorter patterns match nearly everywhere.
+const MIN_LEN: usize = 8;
+const MAX_LEN: usize = 64;
+
+// References to a global that we try, looking for the shortest signature.
+const MAX_XREFS: usize = 32;
+
+const REL32_SIZE: usize = 4;
+
+// REX.W and REX.WR, as in `48 8B 05` and `4C 8D 05`.
+const REX_PREFIXES: [u8; 4] = [0x48, 0x49, 0x4C, 0x4D];
+
+// mov, lea, cmp, add and sub with a register and a memory operand.
+const MEMORY_OPCODES: [u8; 7] = [0x8B, 0x8D, 0x89, 0x3B, 0x39, 0x03, 0x2B];
+
+pub struct Signature {
+    pub name: &'static str,
+    // Of the pattern's first byte.
+    pub address: usize,
+    pub pattern: Vec<Option<u8>>,
+    // Where the displacement to the global is, for a global's signature.
+    pub displacement: Option<usize>,
+}
+
+// Signatures for everything common::init_globals() finds by pattern.
+pub unsafe fn generate_all(module: &win::Module) -> Vec<Signature> {
+    let mut signatures = Vec::new();
+
+    // Every UObject has the same ProcessEvent, so any will do.
+    let process_event = (*GUObjectArray)
+        .iter()
+        .find(|object| !object.is_null())
+        .map(|object| (*object).process_event_address() as usize);
+
+    if let Some(address) = process_event {
+        signatures.extend(function("ProcessEvent", module, address));
+    }
+
+    // The code reads the chunk table at +0x10 rather than the array itself.
+    let globals = [
+        ("GUObjectArray", GUObjectArray as usize + 0x10),
+        ("NamePoolData", NamePoolData as usize),
+        ("GMalloc", memory::GMalloc as usize),
+    ];
+
+    for (name, address) in globals {
+        match global(name, module, address) {
+            Some(signature) => signatures.push(signature),
+            None => common::log!("signature: nothing unique refers to {}", name),
+        }
+    }
+
+    signatures
+}
+
+// A signature for the function at `address`.
+pub unsafe fn function(
+    name: &'static str,
+    module: &win::Module,
+    address: usize,
+) -> Option<Signature> {
+    Some(Signature {
+        name,
+        address,
+        pattern: shortest_unique(module, address)?,
+        displacement: None,
+    })
+}
+
+// A signature for an instruction that refers to the global at `address`,
+// whichever is shortest.
+pub unsafe fn global(
+    name: &'static str,
+    module: &win::Module,
+    address: usize,
+) -> Option<Signature> {
+    module
+        .xrefs(address)
+        .take(MAX_XREFS)
+        .filter_map(|displacement| {
+            let start = instruction_start(module, displacement)?;
+
+            Some(Signature {
+                name,
+                address: start,
+                pattern: shortest_unique(module, start)?,
+                displacement: Some(displacement - start),
+            })
+        })
+        .min_by_key(|signature| signature.pattern.len())
+}
+
+impl Signature {
+    // In the form the finders declare their patterns in.
+    pub fn format(&self, module: &win::Module) -> String {
+        let mut text = String::new();
+
+        let _ = write!(
+            text,
+            "// {} at exe+{}",
+            self.name,
+            Hex(self.address - module.base())
+        );
+
+        if let Some(displacement) = self.displacement {
+            let _ = write!(text, ", displacement at +{}", displacement);
+        }
+
+        let _ = writeln!(
+            text,
+            "\nconst PATTERN: [Option<u8>; {}] = [",
+            self.pattern.len()
+        );
+
+        for byte in &self.pattern {
+            let _ = match byte {
+                Some(byte) => writeln!(text, "    Some({:#04X}),", byte),
+                None => writeln!(text, "    None,"),
+            };
+        }
+
+        text.push_str("];\n");
+        text
+    }
+}
+
+// Where the instruction with the RIP-relative operand at `displacement`
+// starts, if it's one of the usual loads or stores.
+unsafe fn instruction_start(module: &win::Module, displacement: usize) -> Option<usize> {
+    let start = displacement.checked_sub(3)?;
+
+    if start < module.start() {
+        return None;
+    }
+
+    let bytes = slice::from_raw_parts(start as *const u8, 3);
+
+    match *bytes {
+        [rex, opcode, modrm]
+            if REX_PREFIXES.contains(&rex)
+                && MEMORY_OPCODES.contains(&opcode)
+                && is_rip_relative(modrm) =>
+        {
+            Some(start)
+        }
+        _ => None,
+    }
+}
+
+// The shortest masked pattern from `address` that matches only there.
+unsafe fn shortest_unique(module: &win::Module, address: usize) -> Option<Vec<Option<u8>>> {
+    let end = module.start() + module.size();
+    let len = MAX_LEN.min(end.checked_sub(address)?);
+    let code = slice::from_raw_parts(module.start() as *const u8, module.size());
+    let pattern = mask(slice::from_raw_parts(address as *const u8, len));
+
+    let is_unique = |len: usize| {
+        let Some(pattern) = pattern.get(..len) else {
+            return false;
+        };
+
+        let mut matches = code.windows(len).filter(|w| {
+            w.iter()
+                .zip(pattern)
+                .all(|(&w, &p)| p.is_none_or(|p| w == p))
+        });
+
+        matches.next().map(|w| w.as_ptr() as usize) == Some(address) && matches.next().is_none()
+    };
+
+    if len < MIN_LEN || !is_unique(len) {
+        return None;
+    }
+
+    // A longer prefix matches in fewer places, so search for the shortest.
+    let (mut low, mut high) = (MIN_LEN, len);
+
+    while low < high {
+        let middle = (low + high) / 2;
+
+        if is_unique(middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    let mut pattern = pattern.get(..high)?.to_vec();
+
+    // Trailing wildcards don't narrow anything down.
+    while pattern.last() == Some(&None) {
+        pattern.pop();
+    }
+
+    Some(pattern)
+}
+
+fn mask(bytes: &[u8]) -> Vec<Option<u8>> {
+    let mut pattern: Vec<Option<u8>> = bytes.iter().copied().map(Some).collect();
+    let mut i = 0;
+
+    while let Some(rest) = bytes.get(i..).filter(|rest| !rest.is_empty()) {
+        let operand = match *rest {
+            // call rel32 and jmp rel32.
+            [0xE8 | 0xE9, ..] => Some(1),
+            // jcc rel32.
+            [0x0F, 0x80..=0x8F, ..] => Some(2),
+            // call and jmp through [rip + disp32].
+            [0xFF, modrm, ..] if is_rip_relative(modrm) => Some(2),
+            [rex, opcode, modrm, ..]
+                if REX_PREFIXES.contains(&rex)
+                    && MEMORY_OPCODES.contains(&opcode)
+                    && is_rip_relative(modrm) =>
+            {
+                Some(3)
+            }
+            [opcode, modrm, ..] if MEMORY_OPCODES.contains(&opcode) && is_rip_relative(modrm) => {
+                Some(2)
+            }
+            _ => None,
+        };
+
+        match operand {
+            Some(operand) => {
+                for byte in pattern.iter_mut().skip(i + operand).take(REL32_SIZE) {
+                    *byte = None;
+                }
+
+                i += operand + REL32_SIZE;
+            }
+            None => i += 1,
+        }
+    }
+
+    pattern
+}
+
+// A ModRM byte with mod 00 and r/m 101 addresses [rip + disp32].
+fn is_rip_relative(modrm: u8) -> bool {
+    modrm & 0b1100_0111 == 0b0000_0101
+}
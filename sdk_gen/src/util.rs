@@ -1,13 +1,39 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// Overrides where the SDK and the dumps go, so that one build of sdk_gen
+// works for any machine or game install.
+const OUTPUT_VARIABLE: &str = "SDK_GEN_OUTPUT";
+
+// The sdk folder next to sdk_gen, as found by build.rs.
+const DEFAULT_SDK_PATH: &str = include_str!(concat!(env!("OUT_DIR"), "/sdk_path"));
+
 #[macro_export]
 macro_rules! sdk_file {
     ($filename:literal) => {{
-        concat!(sdk_path!(), '/', $filename)
+        $crate::util::sdk_path().join($filename)
     }};
 }
 
 #[macro_export]
 macro_rules! sdk_path {
     () => {
-        include_str!(concat!(env!("OUT_DIR"), "/sdk_path"))
+        $crate::util::sdk_path()
     };
 }
+
+// SDK_GEN_OUTPUT if it's set, otherwise the folder chosen at build time.
+// Created if it doesn't exist yet.
+pub fn sdk_path() -> &'static Path {
+    static PATH: OnceLock<PathBuf> = OnceLock::new();
+
+    PATH.get_or_init(|| {
+        let path = env::var_os(OUTPUT_VARIABLE)
+            .map_or_else(|| PathBuf::from(DEFAULT_SDK_PATH), PathBuf::from);
+
+        fs::create_dir_all(&path).ok();
+        path
+    })
+}